@@ -1,23 +1,59 @@
-pub mod paren;
+// Records an event with the `trace` feature, and does nothing without it.
+macro_rules! trace {
+    ($($event:tt)*) => {
+        #[cfg(feature = "trace")]
+        {
+            if trace::is_recording() {
+                trace::record(trace::Event::$($event)*);
+            }
+        }
+    };
+}
+
+mod memory;
+mod paren;
 pub mod validate;
 
-use std::{
-    cmp::Ordering,
-    fmt::Debug,
-    mem,
-    ops::{Deref, DerefMut},
+use {
+    node_pool::NodePool,
+    std::{
+        cmp::Ordering,
+        fmt::Debug,
+        mem,
+        ops::{Deref, DerefMut},
+    },
 };
 
-pub struct AVLTree<K, V>(BoxedNode<K, V>);
+pub struct AVLTree<K, V>(BoxedNode<K, V>, NodePool<Node<K, V>>);
 impl<K: Ord + Debug, V: Debug> AVLTree<K, V> {
     pub fn new() -> Self {
-        Self(BoxedNode::nil())
+        Self(BoxedNode::nil(), NodePool::disabled())
+    }
+    // Recycles the deleted nodes through `NodePool::with_capacity(capacity)`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(BoxedNode::nil(), NodePool::with_capacity(capacity))
     }
     pub fn insert(&mut self, k: K, v: V) {
-        self.0.insert(k, v)
+        trace!(Begin {
+            op: "insert".to_owned(),
+            key: trace::key(&k),
+        });
+        let node = self.1.alloc(Node::new(k, v));
+        self.0.insert(node)
     }
     pub fn delete(&mut self, k: &K) -> Option<(K, V)> {
-        self.0.delete(k).0.map(|node| (node.key, node.value))
+        trace!(Begin {
+            op: "delete".to_owned(),
+            key: trace::key(k),
+        });
+        let pool = &mut self.1;
+        self.0.delete(k).0.map(|node| {
+            let node = pool.free(node);
+            (node.key, node.value)
+        })
+    }
+    pub fn get(&self, k: &K) -> Option<&V> {
+        self.0.get(k)
     }
     pub fn collect_vec(&self) -> Vec<(K, V)>
     where
@@ -38,12 +74,15 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
     fn is_nil(&self) -> bool {
         self.0.is_none()
     }
-    fn insert(&mut self, k: K, v: V) {
+    fn insert(&mut self, node: Box<Node<K, V>>) {
         if let Some(internal) = self.0.as_mut() {
-            internal.child[if k <= internal.key { 0 } else { 1 }].insert(k, v);
+            internal.child[if node.key <= internal.key { 0 } else { 1 }].insert(node);
             self.update_balance();
         } else {
-            *self = Self(Some(Box::new(Node::new(k, v))));
+            trace!(Attach {
+                label: trace::key(&node.key),
+            });
+            *self = Self(Some(node));
         }
     }
     fn delete(&mut self, k: &K) -> Self {
@@ -56,6 +95,10 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
                         let mut rem = self.child_mut(1).delete_first();
                         rem.assert_isolated();
                         (0..2).for_each(|i| rem.replace_empty_child(i, self.take_child(i)));
+                        trace!(Replace {
+                            key: trace::key(&self.unwrap().key),
+                            by: trace::key(&rem.unwrap().key),
+                        });
                         rem.update_balance();
                         mem::replace(self, rem)
                     } else {
@@ -81,6 +124,14 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
             self.replace_by_child(1)
         }
     }
+    fn get(&self, k: &K) -> Option<&V> {
+        let internal = self.0.as_ref()?;
+        match k.cmp(&internal.key) {
+            Ordering::Less => internal.child[0].get(k),
+            Ordering::Greater => internal.child[1].get(k),
+            Ordering::Equal => Some(&internal.value),
+        }
+    }
     fn collect_vec(&self, vec: &mut Vec<(K, V)>)
     where
         K: Clone,
@@ -128,6 +179,15 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
         }
     }
     fn rotate(&mut self, i: usize) {
+        if i == 1 {
+            trace!(RotateLeft {
+                key: trace::key(&self.unwrap().key),
+            });
+        } else {
+            trace!(RotateRight {
+                key: trace::key(&self.unwrap().key),
+            });
+        }
         let mut x = self.take();
         let mut y = x.take_child(i);
         let z = y.take_child(1 - i);
@@ -159,6 +219,9 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
     }
     fn replace_by_child(&mut self, i: usize) -> Self {
         assert!(self.child(1 - i).is_nil());
+        trace!(Splice {
+            key: trace::key(&self.unwrap().key),
+        });
         let x = self.take_child(i);
         mem::replace(self, x)
     }
@@ -183,8 +246,176 @@ impl<K: Ord + Debug, V: Debug> Node<K, V> {
 
 #[cfg(test)]
 mod tests {
-    use super::{validate, AVLTree};
+    use super::{validate, AVLTree, Node};
+    use counting_allocator::{count, CountingAllocator, Counts};
+    use invariant::Property;
+    use memory_usage::MemoryUsage;
+    use paren::{FromParen, Paren, Visualize};
     use rand::prelude::*;
+    use std::mem::size_of;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_paren() {
+        let mut avl = AVLTree::new();
+        (0..7).for_each(|i| avl.insert(i, ()));
+        let expected = "(((0)1(2))3((4)5(6)))";
+        assert_eq!(avl.to_paren(), expected);
+        let mut parsed = AVLTree::<u32, ()>::from_paren("((0)1((2)3))").unwrap();
+        validate::all(&parsed);
+        parsed.insert(4, ());
+        parsed.insert(5, ());
+        parsed.insert(6, ());
+        validate::all(&parsed);
+        assert_eq!(parsed.to_paren(), expected);
+    }
+
+    #[test]
+    fn test_pretty() {
+        let avl = AVLTree::<u32, ()>::from_paren("((0)1((2)3))").unwrap();
+        let expected = ["/-- 3 h=2", "|   \\-- 2 h=1", "1 h=3", "\\-- 0 h=1", ""].join("\n");
+        assert_eq!(avl.pretty(), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "AVL balance is violated")]
+    fn test_validate_unbalanced() {
+        validate::all(&AVLTree::<u32, ()>::from_paren("(0(1(2)))").unwrap());
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let check = |s: &str| {
+            AVLTree::<u32, ()>::from_paren(s)
+                .unwrap()
+                .check_invariants()
+                .map_err(|e| (e.property, e.path))
+        };
+        assert_eq!(check("((0)1((2)3))"), Ok(()));
+        assert_eq!(
+            check("((0(1(2)))3((4)5(6)))"),
+            Err((Property::AvlBalance, vec![0]))
+        );
+        assert_eq!(check("((0)1((3)2))"), Err((Property::BstOrder, vec![1])));
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let node = size_of::<Node<u32, ()>>();
+        let mut avl = AVLTree::<u32, ()>::new();
+        assert_eq!(avl.heap_size_bytes(), 0);
+        for k in 0..10 {
+            avl.insert(k, ());
+        }
+        assert_eq!(avl.heap_size_bytes(), 10 * node);
+        for k in 0..4 {
+            avl.delete(&k);
+        }
+        assert_eq!(avl.heap_size_bytes(), 6 * node);
+
+        let mut avl = AVLTree::<u32, ()>::with_capacity(10);
+        assert_eq!(
+            avl.heap_size_bytes(),
+            10 * (node + size_of::<Box<Node<u32, ()>>>())
+        );
+        avl.insert(0, ());
+        avl.delete(&0);
+        assert_eq!(
+            avl.heap_size_bytes(),
+            10 * (node + size_of::<Box<Node<u32, ()>>>())
+        );
+    }
+
+    #[test]
+    fn test_allocations() {
+        // a box for every node
+        let mut avl = AVLTree::<u32, ()>::new();
+        let ((), counts) = count(|| avl.insert(0, ()));
+        assert_eq!(
+            counts,
+            Counts {
+                allocs: 1,
+                ..Counts::default()
+            }
+        );
+        let (_, counts) = count(|| avl.get(&0).is_some());
+        assert_eq!(counts, Counts::default());
+        let (_, counts) = count(|| avl.delete(&0));
+        assert_eq!(
+            counts,
+            Counts {
+                deallocs: 1,
+                ..Counts::default()
+            }
+        );
+
+        // none in the churn, with a pool
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut avl = AVLTree::<u32, ()>::with_capacity(100);
+        let ((), counts) = count(|| {
+            (0..100).for_each(|k| avl.insert(k, ()));
+            for _ in 0..1000 {
+                if avl.delete(&rng.gen_range(0, 200)).is_some() {
+                    avl.insert(rng.gen_range(0, 200), ());
+                }
+            }
+        });
+        assert_eq!(counts, Counts::default());
+        validate::all(&avl);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_trace() {
+        use trace::Event;
+        let mut avl = AVLTree::<u32, ()>::from_paren("((1)3)").unwrap();
+        let ((), events) = trace::capture(|| avl.insert(2, ()));
+        // the double rotation
+        let expected = vec![
+            Event::Begin {
+                op: "insert".to_owned(),
+                key: "2".to_owned(),
+            },
+            Event::Attach {
+                label: "2".to_owned(),
+            },
+            Event::RotateLeft {
+                key: "1".to_owned(),
+            },
+            Event::RotateRight {
+                key: "3".to_owned(),
+            },
+        ];
+        assert_eq!(events, expected);
+        assert_eq!(
+            trace::replay::<u32>("((1)3)", &events).unwrap(),
+            vec!["((1)3)", "((1(2))3)", "(((1)2)3)", "((1)2(3))"]
+        );
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_trace_replay() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut avl = AVLTree::<u32, ()>::new();
+        let mut keys = std::collections::BTreeSet::new();
+        for _ in 0..1000 {
+            let key = rng.gen_range(0, 40);
+            let before = avl.to_paren();
+            let ((), events) = if keys.insert(key) {
+                trace::capture(|| avl.insert(key, ()))
+            } else {
+                keys.remove(&key);
+                trace::capture(|| {
+                    avl.delete(&key);
+                })
+            };
+            let snapshots = trace::replay::<u32>(&before, &events).unwrap();
+            assert_eq!(snapshots.last().unwrap(), &avl.to_paren());
+        }
+    }
 
     #[test]
     fn test_hand() {
@@ -277,6 +508,9 @@ mod tests {
                     .collect::<Vec<_>>(),
                 &self.vec
             );
+            for x in 0..30 {
+                assert_eq!(self.avl.get(&x).is_some(), self.vec.contains(&x));
+            }
         }
     }
 }
@@ -0,0 +1,24 @@
+use {
+    super::{AVLTree, BoxedNode, Node},
+    memory_usage::MemoryUsage,
+};
+
+// The spare nodes of the pool are counted as well.
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for AVLTree<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes() + self.1.heap_size_bytes()
+    }
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for BoxedNode<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes()
+    }
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Node<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.key.heap_size_bytes()
+            + self.value.heap_size_bytes()
+            + self.child[0].heap_size_bytes()
+            + self.child[1].heap_size_bytes()
+    }
+}
@@ -1,9 +1,13 @@
-use super::{AVLTree, BoxedNode, Node};
-use std::fmt::{self, Debug};
+use {
+    super::{AVLTree, BoxedNode, Node},
+    node_pool::NodePool,
+    paren::{FromParen, Paren, ParseError, Shape, Visualize, Wrapper},
+    std::{
+        fmt::{self, Debug},
+        str::FromStr,
+    },
+};
 
-pub trait Paren {
-    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result;
-}
 impl<K: Ord + Debug, V: Debug> Paren for AVLTree<K, V> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
         self.0.paren(w)
@@ -23,26 +27,60 @@ impl<K: Ord + Debug, V: Debug> Paren for Node<K, V> {
         write!(w, ")")
     }
 }
-struct ParenWrapper<'a, T>(&'a T);
-impl<'a, T: Paren> Debug for ParenWrapper<'a, T> {
-    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        self.0.paren(w)
+
+impl<K: Ord + Debug, V: Debug> Visualize for AVLTree<K, V> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.0.shape().into_iter().collect()
+    }
+}
+impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
+    fn shape(&self) -> Option<Shape> {
+        self.0.as_ref().map(|x| {
+            Shape::new(&x.key)
+                .attr("h", x.ht)
+                .child(x.child[0].shape())
+                .child(x.child[1].shape())
+        })
     }
 }
+
+// The values are left default, and the heights are computed from the shape.
+impl<K: Ord + Debug + FromStr, V: Debug + Default> FromParen for AVLTree<K, V> {
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        BoxedNode::from_parsed(paren::parse(s)?).map(|root| Self(root, NodePool::disabled()))
+    }
+}
+impl<K: Ord + Debug + FromStr, V: Debug + Default> BoxedNode<K, V> {
+    fn from_parsed(node: Option<Box<paren::Node>>) -> Result<Self, ParseError> {
+        match node {
+            None => Ok(Self::nil()),
+            Some(node) => {
+                let paren::Node {
+                    label,
+                    child: [left, right],
+                } = *node;
+                let mut node = Node::new(label.key()?, V::default());
+                node.child = [Self::from_parsed(left)?, Self::from_parsed(right)?];
+                let mut x = Self(Some(Box::new(node)));
+                x.update();
+                Ok(x)
+            }
+        }
+    }
+}
+
 impl<K: Ord + Debug, V: Debug> Debug for AVLTree<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("AVLTree").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("AVLTree").field(&Wrapper(self)).finish()
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for BoxedNode<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("BoxedNode")
-            .field(&ParenWrapper(self))
-            .finish()
+        w.debug_tuple("BoxedNode").field(&Wrapper(self)).finish()
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for Node<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("Node").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("Node").field(&Wrapper(self)).finish()
     }
 }
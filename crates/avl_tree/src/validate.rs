@@ -1,30 +1,52 @@
-use super::{AVLTree, BoxedNode, Node};
+use super::{AVLTree, BoxedNode};
+use invariant::{ensure, InvariantViolation, Property};
 use std::fmt::Debug;
 
 pub fn all<T: Validate>(x: &T) {
     x.balanced()
 }
 
+// `check_invariants` that panics.
 pub trait Validate {
     fn balanced(&self);
 }
 impl<K: Ord + Debug, V: Debug> Validate for AVLTree<K, V> {
     fn balanced(&self) {
-        self.0.balanced()
+        invariant::assert_holds(self.check_invariants())
     }
 }
-impl<K: Ord + Debug, V: Debug> Validate for BoxedNode<K, V> {
-    fn balanced(&self) {
-        self.0.as_ref().iter().for_each(|x| x.balanced())
+
+impl<K: Ord + Debug, V: Debug> AVLTree<K, V> {
+    // the BST order, the cached heights and the balance
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        check(&self.0, &mut Vec::new(), &mut None).map(|_| ())
     }
 }
-impl<K: Ord + Debug, V: Debug> Validate for Node<K, V> {
-    fn balanced(&self) {
-        assert!(
-            (self.child[0].ht() as i32 - self.child[1].ht() as i32).abs() <= 1,
-            "Unbalanced: self = {:?}",
-            self
-        );
-        self.child.iter().for_each(|x| x.balanced());
+// Returns the height, with `prev` the last key in the in-order.
+fn check<'a, K: Ord, V>(
+    x: &'a BoxedNode<K, V>,
+    path: &mut Vec<usize>,
+    prev: &mut Option<&'a K>,
+) -> Result<u32, InvariantViolation> {
+    let node = match &x.0 {
+        None => return Ok(0),
+        Some(node) => node,
+    };
+    path.push(0);
+    let left = check(&node.child[0], path, prev)?;
+    path.pop();
+    if let Some(prev) = prev {
+        ensure(*prev <= &node.key, Property::BstOrder, path)?;
     }
+    *prev = Some(&node.key);
+    path.push(1);
+    let right = check(&node.child[1], path, prev)?;
+    path.pop();
+    ensure(node.ht == 1 + left.max(right), Property::HeightCache, path)?;
+    ensure(
+        (left as i32 - right as i32).abs() <= 1,
+        Property::AvlBalance,
+        path,
+    )?;
+    Ok(node.ht)
 }
@@ -0,0 +1,147 @@
+// The min-heaps of `n` keys in `0..n`. Every workload runs `n` operations, on the keys drawn from
+// a distribution:
+//
+// - insert: pushes the keys into an empty heap,
+// - delete: pops all the keys,
+// - mixed: pops the minimum and pushes a key, keeping `n` keys.
+//
+// The heaps have neither lookups nor ordered iteration.
+//
+// All the sizes take hours. Filter them with a regex, e.g. `cargo bench -p benchmarks --bench
+// heaps -- '/(1000|10000)$'`, and export the results with `cargo run -p benchmarks --bin report`.
+use {
+    benchmarks::{Distribution, Indexed, MinHeap, Pooled, StdBinaryHeap, SIZES},
+    criterion::{
+        black_box, criterion_group, criterion_main, BatchSize, Bencher, BenchmarkId, Criterion,
+        SamplingMode, Throughput,
+    },
+    heap_sort::{BinaryHeap, DArrayHeap, MinComparator},
+    rand::prelude::*,
+};
+
+struct Input {
+    n: usize,
+    build: Vec<u32>,
+    keys: Vec<u32>,
+}
+
+type Bench = fn(&mut Bencher, &Input);
+
+macro_rules! heaps {
+    ($bench:ident) => {
+        heaps!(
+            @ $bench;
+            BinaryHeap<u32, MinComparator>,
+            DArrayHeap<u32, MinComparator>,
+            Indexed,
+            fibonacci_heap::FibonacciHeap<u32, ()>,
+            Pooled<fibonacci_heap::arena::FibonacciHeap<u32, ()>>,
+            Pooled<mergeable_heaps::BinomialHeap<u32, ()>>,
+            Pooled<mergeable_heaps::LeftistHeap<u32, ()>>,
+            Pooled<mergeable_heaps::PairingHeap<u32, ()>>,
+            StdBinaryHeap,
+        )
+    };
+    (@ $bench:ident; $($heap:ty),* $(,)?) => {
+        [$((<$heap as MinHeap>::NAME, $bench::<$heap> as Bench)),*]
+    };
+}
+
+fn build<H: MinHeap>(input: &Input) -> H {
+    let mut heap = H::new(input.n);
+    for &key in &input.build {
+        heap.push(key);
+    }
+    heap
+}
+
+// The heaps are returned, so that they are dropped out of the measurement.
+fn insert<H: MinHeap>(b: &mut Bencher, input: &Input) {
+    b.iter_batched(
+        || H::new(input.n),
+        |mut heap| {
+            for &key in &input.keys {
+                heap.push(key);
+            }
+            heap
+        },
+        BatchSize::PerIteration,
+    )
+}
+fn delete<H: MinHeap>(b: &mut Bencher, input: &Input) {
+    b.iter_batched(
+        || build::<H>(input),
+        |mut heap| {
+            while let Some(key) = heap.pop() {
+                black_box(key);
+            }
+            heap
+        },
+        BatchSize::PerIteration,
+    )
+}
+fn mixed<H: MinHeap>(b: &mut Bencher, input: &Input) {
+    b.iter_batched(
+        || build::<H>(input),
+        |mut heap| {
+            for &key in &input.keys {
+                black_box(heap.pop());
+                heap.push(key);
+            }
+            heap
+        },
+        BatchSize::PerIteration,
+    )
+}
+
+fn run(
+    c: &mut Criterion,
+    group: &str,
+    benches: &[(&str, Bench)],
+    mut input: impl FnMut(usize) -> Input,
+) {
+    let mut group = c.benchmark_group(group);
+    // An iteration takes up to seconds at the largest size.
+    group.sample_size(10).sampling_mode(SamplingMode::Flat);
+    for &n in SIZES.iter() {
+        let input = input(n);
+        group.throughput(Throughput::Elements(n as u64));
+        for &(name, bench) in benches {
+            group.bench_with_input(BenchmarkId::new(name, n), &input, |b, input| {
+                bench(b, input)
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_heaps(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(42);
+    for &distribution in Distribution::ALL.iter() {
+        let name = distribution.name();
+        run(c, &format!("heaps/insert/{}", name), &heaps!(insert), |n| {
+            Input {
+                n,
+                build: Vec::new(),
+                keys: distribution.keys(n, n, &mut rng),
+            }
+        });
+        run(c, &format!("heaps/delete/{}", name), &heaps!(delete), |n| {
+            Input {
+                n,
+                build: distribution.keys(n, n, &mut rng),
+                keys: Vec::new(),
+            }
+        });
+        run(c, &format!("heaps/mixed/{}", name), &heaps!(mixed), |n| {
+            Input {
+                n,
+                build: Distribution::Random.keys(n, n, &mut rng),
+                keys: distribution.keys(n, n, &mut rng),
+            }
+        });
+    }
+}
+
+criterion_group!(benches, bench_heaps);
+criterion_main!(benches);
@@ -0,0 +1,186 @@
+// The ordered sets on the keys `0..n`, built by inserting them in a random order. Every workload
+// runs `n` operations, on the keys in the order of a distribution:
+//
+// - insert: inserts the keys into an empty set,
+// - delete: deletes all the keys,
+// - lookup: looks up the keys,
+// - mixed: deletes and inserts back every fourth key, and looks up the others,
+// - iterate: visits all the keys in order, which does not depend on a distribution.
+//
+// A Zipfian distribution is not a permutation, so it is only for `lookup` and `mixed`.
+//
+// Left out are `binary_search_trees`, which is not balanced, `merge_based_rbtree`, which does not
+// search by keys, and `mergible_rbtree`, which has no lookup.
+//
+// All the sizes take hours. Filter them with a regex, e.g. `cargo bench -p benchmarks --bench
+// trees -- '/(1000|10000)$'`, and export the results with `cargo run -p benchmarks --bin report`.
+use {
+    benchmarks::{Distribution, OrderedSet, Persistent, SIZES},
+    criterion::{
+        black_box, criterion_group, criterion_main, BatchSize, Bencher, BenchmarkId, Criterion,
+        SamplingMode, Throughput,
+    },
+    rand::prelude::*,
+    std::collections::BTreeSet,
+};
+
+struct Input {
+    n: usize,
+    build: Vec<u32>,
+    keys: Vec<u32>,
+}
+
+type Bench = fn(&mut Bencher, &Input);
+
+macro_rules! sets {
+    ($bench:ident) => {
+        sets!(
+            @ $bench;
+            avl_tree::AVLTree<u32, ()>,
+            btree::BTree<u32>,
+            foldable_llrb_tree::LLRB<u32, u64>,
+            llrb::LLRB<u32, ()>,
+            nilless_parentless_rbtree::RBTree<u32, ()>,
+            order_statistic_llrb_tree::LLRB<u32, ()>,
+            parentless_rbtree::RBTree<u32, ()>,
+            Persistent,
+            red_black_tree::RBTree<u32, ()>,
+            red_black_tree::arena::RBTree<u32, ()>,
+            treap::Treap<u32, (), StdRng>,
+            van_emde_boas::Veb,
+            reduced_space_van_emde_boas::Veb,
+            BTreeSet<u32>,
+        )
+    };
+    (@ $bench:ident; $($set:ty),* $(,)?) => {
+        [$((<$set as OrderedSet>::NAME, <$set as OrderedSet>::MAX_LEN, $bench::<$set> as Bench)),*]
+    };
+}
+
+fn build<S: OrderedSet>(input: &Input) -> S {
+    let mut set = S::new(input.n);
+    for &key in &input.build {
+        set.insert(key);
+    }
+    set
+}
+
+// The sets are returned, so that they are dropped out of the measurement.
+fn insert<S: OrderedSet>(b: &mut Bencher, input: &Input) {
+    b.iter_batched(
+        || S::new(input.n),
+        |mut set| {
+            for &key in &input.keys {
+                set.insert(key);
+            }
+            set
+        },
+        BatchSize::PerIteration,
+    )
+}
+fn delete<S: OrderedSet>(b: &mut Bencher, input: &Input) {
+    b.iter_batched(
+        || build::<S>(input),
+        |mut set| {
+            for &key in &input.keys {
+                set.delete(key);
+            }
+            set
+        },
+        BatchSize::PerIteration,
+    )
+}
+fn lookup<S: OrderedSet>(b: &mut Bencher, input: &Input) {
+    let set = build::<S>(input);
+    b.iter(|| input.keys.iter().filter(|&&key| set.contains(key)).count())
+}
+fn mixed<S: OrderedSet>(b: &mut Bencher, input: &Input) {
+    b.iter_batched(
+        || build::<S>(input),
+        |mut set| {
+            for (i, &key) in input.keys.iter().enumerate() {
+                if i % 4 == 0 {
+                    set.delete(key);
+                    set.insert(key);
+                } else {
+                    black_box(set.contains(key));
+                }
+            }
+            set
+        },
+        BatchSize::PerIteration,
+    )
+}
+fn iterate<S: OrderedSet>(b: &mut Bencher, input: &Input) {
+    let set = build::<S>(input);
+    b.iter(|| set.iterate())
+}
+
+fn run(
+    c: &mut Criterion,
+    group: &str,
+    benches: &[(&str, usize, Bench)],
+    mut input: impl FnMut(usize) -> Input,
+) {
+    let mut group = c.benchmark_group(group);
+    // An iteration takes up to seconds at the largest size.
+    group.sample_size(10).sampling_mode(SamplingMode::Flat);
+    for &n in SIZES.iter() {
+        let input = input(n);
+        group.throughput(Throughput::Elements(n as u64));
+        for &(name, max_len, bench) in benches {
+            if n <= max_len {
+                group.bench_with_input(BenchmarkId::new(name, n), &input, |b, input| {
+                    bench(b, input)
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
+fn bench_trees(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(42);
+    for &distribution in Distribution::ALL.iter() {
+        let name = distribution.name();
+        if distribution != Distribution::Zipfian {
+            run(c, &format!("trees/insert/{}", name), &sets!(insert), |n| {
+                let keys = distribution.permutation(n, &mut rng).unwrap();
+                Input {
+                    n,
+                    build: Vec::new(),
+                    keys,
+                }
+            });
+            run(c, &format!("trees/delete/{}", name), &sets!(delete), |n| {
+                Input {
+                    n,
+                    build: Distribution::Random.permutation(n, &mut rng).unwrap(),
+                    keys: distribution.permutation(n, &mut rng).unwrap(),
+                }
+            });
+        }
+        run(c, &format!("trees/lookup/{}", name), &sets!(lookup), |n| {
+            Input {
+                n,
+                build: Distribution::Random.permutation(n, &mut rng).unwrap(),
+                keys: distribution.keys(n, n, &mut rng),
+            }
+        });
+        run(c, &format!("trees/mixed/{}", name), &sets!(mixed), |n| {
+            Input {
+                n,
+                build: Distribution::Random.permutation(n, &mut rng).unwrap(),
+                keys: distribution.keys(n, n, &mut rng),
+            }
+        });
+    }
+    run(c, "trees/iterate", &sets!(iterate), |n| Input {
+        n,
+        build: Distribution::Random.permutation(n, &mut rng).unwrap(),
+        keys: Vec::new(),
+    });
+}
+
+criterion_group!(benches, bench_trees);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+// Prints the memory of the structures as CSV, or as JSON with `--json`.
+//
+// cargo run --release -p benchmarks --bin memory -- [--json] [--lg LG] [N...]
+//
+// `N` defaults to the sizes of the benchmarks up to a million. The van Emde Boas trees are also
+// measured on sparse keys out of `0..2^LG`, which defaults to 32; the plain one only up to 24.
+use {
+    benchmarks::{memory, SIZES},
+    rand::prelude::*,
+    std::{env, process},
+};
+
+const PLAIN_MAX_LG: u32 = 24;
+
+fn usage() -> ! {
+    eprintln!("usage: memory [--json] [--lg LG] [N...]");
+    process::exit(2);
+}
+
+fn main() {
+    let mut json = false;
+    let mut lg = 32;
+    let mut sizes = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--lg" => match args.next().and_then(|x| x.parse().ok()) {
+                Some(x) if x <= 32 => lg = x,
+                _ => usage(),
+            },
+            _ => match arg.parse() {
+                Ok(n) if n != 0 => sizes.push(n),
+                _ => usage(),
+            },
+        }
+    }
+    if sizes.is_empty() {
+        sizes = SIZES.iter().copied().filter(|&n| n <= 1_000_000).collect();
+    }
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut usages = memory::measure(&sizes, &mut rng);
+    let sparse = sizes
+        .iter()
+        .copied()
+        .filter(|&n| n <= 1 << lg)
+        .collect::<Vec<_>>();
+    usages.extend(memory::measure_sparse(lg, PLAIN_MAX_LG, &sparse, &mut rng));
+    if json {
+        println!("{}", memory::to_json(&usages));
+    } else {
+        print!("{}", memory::to_csv(&usages));
+    }
+}
@@ -0,0 +1,38 @@
+// Prints the results of `cargo bench` as CSV, or as JSON with `--json`.
+//
+// cargo run -p benchmarks --bin report -- [--json] [DIR]
+//
+// `DIR` defaults to the directory `criterion` in `$CARGO_TARGET_DIR`, or in `target`.
+use {
+    benchmarks::report,
+    std::{env, path::PathBuf, process},
+};
+
+fn main() {
+    let mut json = false;
+    let mut dir = None;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ if dir.is_none() && !arg.starts_with('-') => dir = Some(PathBuf::from(arg)),
+            _ => {
+                eprintln!("usage: report [--json] [DIR]");
+                process::exit(2);
+            }
+        }
+    }
+    let dir = dir.unwrap_or_else(|| {
+        env::var_os("CARGO_TARGET_DIR")
+            .map_or_else(|| PathBuf::from("target"), PathBuf::from)
+            .join("criterion")
+    });
+    let records = report::collect(&dir).unwrap_or_else(|e| {
+        eprintln!("cannot read {}: {}", dir.display(), e);
+        process::exit(1);
+    });
+    if json {
+        println!("{}", report::to_json(&records));
+    } else {
+        print!("{}", report::to_csv(&records));
+    }
+}
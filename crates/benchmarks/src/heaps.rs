@@ -0,0 +1,139 @@
+use {
+    super::MinHeap,
+    heap_sort::{BinaryHeap, DArrayHeap, IndexedHeap, MinComparator},
+    memory_usage::MemoryUsage,
+    mergeable_heaps::AddressableHeap,
+    std::{cmp::Reverse, mem::size_of},
+};
+
+impl MinHeap for BinaryHeap<u32, MinComparator> {
+    const NAME: &'static str = "heap_sort::BinaryHeap";
+    fn new(_n: usize) -> Self {
+        Self::new_min()
+    }
+    fn push(&mut self, key: u32) {
+        self.push(key)
+    }
+    fn pop(&mut self) -> Option<u32> {
+        self.pop()
+    }
+}
+
+impl MinHeap for DArrayHeap<u32, MinComparator> {
+    const NAME: &'static str = "heap_sort::DArrayHeap";
+    fn new(_n: usize) -> Self {
+        Self::new_min(4)
+    }
+    fn push(&mut self, key: u32) {
+        self.push(key)
+    }
+    fn pop(&mut self) -> Option<u32> {
+        self.pop()
+    }
+}
+
+// The ids are recycled, which costs a little more than the other heaps.
+pub struct Indexed {
+    heap: IndexedHeap<u32, MinComparator>,
+    free: Vec<usize>,
+}
+impl MinHeap for Indexed {
+    const NAME: &'static str = "heap_sort::IndexedHeap";
+    fn new(n: usize) -> Self {
+        Self {
+            heap: IndexedHeap::new_min(n, 4),
+            free: (0..n).rev().collect(),
+        }
+    }
+    fn push(&mut self, key: u32) {
+        let id = self.free.pop().unwrap();
+        self.heap.push(id, key);
+    }
+    fn pop(&mut self) -> Option<u32> {
+        let (id, key) = self.heap.pop()?;
+        self.free.push(id);
+        Some(key)
+    }
+}
+impl MemoryUsage for Indexed {
+    fn heap_size_bytes(&self) -> usize {
+        self.heap.heap_size_bytes() + self.free.heap_size_bytes()
+    }
+}
+
+// The handles are dropped, so that the ones counted by references free their nodes.
+impl MinHeap for fibonacci_heap::FibonacciHeap<u32, ()> {
+    const NAME: &'static str = "fibonacci_heap";
+    fn new(_n: usize) -> Self {
+        Self::new()
+    }
+    fn push(&mut self, key: u32) {
+        self.push(key, ());
+    }
+    fn pop(&mut self) -> Option<u32> {
+        self.pop().map(|(key, ())| key)
+    }
+}
+
+// The addressable heaps of `mergeable_heaps`, including the arena-based Fibonacci heap, each with a
+// pool of its own.
+pub struct Pooled<H: AddressableHeap<u32, ()>> {
+    heap: H,
+    pool: H::Pool,
+}
+macro_rules! addressable {
+    ($($name:expr => $heap:ty),* $(,)?) => {
+        $(
+            impl MinHeap for Pooled<$heap> {
+                const NAME: &'static str = $name;
+                fn new(_n: usize) -> Self {
+                    Self {
+                        heap: Default::default(),
+                        pool: Default::default(),
+                    }
+                }
+                fn push(&mut self, key: u32) {
+                    self.heap.push(&mut self.pool, key, ());
+                }
+                fn pop(&mut self) -> Option<u32> {
+                    self.heap.pop(&mut self.pool).map(|(key, ())| key)
+                }
+            }
+        )*
+    };
+}
+addressable!(
+    "fibonacci_heap::arena" => fibonacci_heap::arena::FibonacciHeap<u32, ()>,
+    "mergeable_heaps::BinomialHeap" => mergeable_heaps::BinomialHeap<u32, ()>,
+    "mergeable_heaps::LeftistHeap" => mergeable_heaps::LeftistHeap<u32, ()>,
+    "mergeable_heaps::PairingHeap" => mergeable_heaps::PairingHeap<u32, ()>,
+);
+impl<H> MemoryUsage for Pooled<H>
+where
+    H: AddressableHeap<u32, ()>,
+    H::Pool: MemoryUsage,
+{
+    fn heap_size_bytes(&self) -> usize {
+        self.pool.heap_size_bytes()
+    }
+}
+
+// the baseline
+pub struct StdBinaryHeap(std::collections::BinaryHeap<Reverse<u32>>);
+impl MinHeap for StdBinaryHeap {
+    const NAME: &'static str = "std::collections::BinaryHeap";
+    fn new(n: usize) -> Self {
+        Self(std::collections::BinaryHeap::with_capacity(n))
+    }
+    fn push(&mut self, key: u32) {
+        self.0.push(Reverse(key))
+    }
+    fn pop(&mut self) -> Option<u32> {
+        self.0.pop().map(|Reverse(key)| key)
+    }
+}
+impl MemoryUsage for StdBinaryHeap {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.capacity() * size_of::<Reverse<u32>>()
+    }
+}
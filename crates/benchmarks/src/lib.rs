@@ -0,0 +1,248 @@
+mod heaps;
+pub mod memory;
+pub mod report;
+mod sets;
+
+pub use {
+    heaps::{Indexed, Pooled, StdBinaryHeap},
+    sets::Persistent,
+};
+
+use {rand::prelude::*, std::iter::repeat_with};
+
+pub const SIZES: [usize; 5] = [1_000, 10_000, 100_000, 1_000_000, 10_000_000];
+
+// An ordered set of the keys in `0..n`. The multisets are given distinct keys only, so that they
+// hold as many keys as the sets.
+pub trait OrderedSet {
+    const NAME: &'static str;
+    // the largest `n` to run, for the ones which do not fit in memory at the largest size
+    const MAX_LEN: usize = usize::MAX;
+    fn new(n: usize) -> Self;
+    fn insert(&mut self, key: u32);
+    fn delete(&mut self, key: u32);
+    fn contains(&self, key: u32) -> bool;
+    // Visits all the keys in order, and returns how many. The ones without iterators collect the
+    // keys into a `Vec`.
+    fn iterate(&self) -> usize;
+}
+
+// A min-heap of at most `n` keys.
+pub trait MinHeap {
+    const NAME: &'static str;
+    fn new(n: usize) -> Self;
+    fn push(&mut self, key: u32);
+    fn pop(&mut self) -> Option<u32>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Distribution {
+    Sequential,
+    Random,
+    Zipfian,
+}
+impl Distribution {
+    pub const ALL: [Distribution; 3] = [
+        Distribution::Sequential,
+        Distribution::Random,
+        Distribution::Zipfian,
+    ];
+    pub fn name(self) -> &'static str {
+        match self {
+            Distribution::Sequential => "sequential",
+            Distribution::Random => "random",
+            Distribution::Zipfian => "zipfian",
+        }
+    }
+    // The keys `0..n` in the order of this distribution. A Zipfian one is skewed towards some of
+    // the keys, and is not a permutation, so there is none.
+    pub fn permutation(self, n: usize, rng: &mut StdRng) -> Option<Vec<u32>> {
+        let mut keys = (0..n as u32).collect::<Vec<_>>();
+        match self {
+            Distribution::Sequential => Some(keys),
+            Distribution::Random => {
+                keys.shuffle(rng);
+                Some(keys)
+            }
+            Distribution::Zipfian => None,
+        }
+    }
+    // `len` keys in `0..n`
+    pub fn keys(self, n: usize, len: usize, rng: &mut StdRng) -> Vec<u32> {
+        match self {
+            Distribution::Sequential => (0..len).map(|i| (i % n) as u32).collect(),
+            Distribution::Random => repeat_with(|| rng.gen_range(0, n as u32))
+                .take(len)
+                .collect(),
+            Distribution::Zipfian => {
+                // The popular keys are scattered, rather than being the smallest ones.
+                let scatter = Distribution::Random.permutation(n, rng).unwrap();
+                let zipf = Zipf::new(n, ZIPF_THETA);
+                repeat_with(|| scatter[zipf.sample(rng)])
+                    .take(len)
+                    .collect()
+            }
+        }
+    }
+}
+
+// the skew of YCSB
+pub const ZIPF_THETA: f64 = 0.99;
+
+// The ranks in `0..n` whose probabilities are proportional to `1 / (rank + 1)^theta`, sampled in
+// O(1) after an O(n) preprocess, following Gray et al., "Quickly Generating Billion-Record
+// Synthetic Databases".
+pub struct Zipf {
+    n: usize,
+    theta: f64,
+    alpha: f64,
+    zetan: f64,
+    eta: f64,
+}
+impl Zipf {
+    pub fn new(n: usize, theta: f64) -> Self {
+        assert!(n != 0 && 0.0 < theta && theta < 1.0);
+        let zeta = |n: usize| (1..=n).map(|i| (i as f64).powf(-theta)).sum::<f64>();
+        let zetan = zeta(n);
+        Self {
+            n,
+            theta,
+            alpha: 1.0 / (1.0 - theta),
+            zetan,
+            eta: (1.0 - (2.0 / n as f64).powf(1.0 - theta)) / (1.0 - zeta(2) / zetan),
+        }
+    }
+    pub fn sample(&self, rng: &mut impl Rng) -> usize {
+        let u = rng.gen::<f64>();
+        let uz = u * self.zetan;
+        if uz < 1.0 {
+            0
+        } else if uz < 1.0 + 0.5f64.powf(self.theta) {
+            1.min(self.n - 1)
+        } else {
+            let rank = self.n as f64 * (self.eta * u - self.eta + 1.0).powf(self.alpha);
+            (rank as usize).min(self.n - 1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Distribution, MinHeap, OrderedSet, Zipf, ZIPF_THETA},
+        rand::prelude::*,
+        std::collections::BTreeSet,
+    };
+
+    #[test]
+    fn test_distributions() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for &distribution in Distribution::ALL.iter() {
+            for &n in [1, 2, 10, 1000].iter() {
+                if let Some(mut keys) = distribution.permutation(n, &mut rng) {
+                    keys.sort();
+                    assert_eq!(keys, (0..n as u32).collect::<Vec<_>>());
+                }
+                let keys = distribution.keys(n, 3 * n, &mut rng);
+                assert_eq!(keys.len(), 3 * n);
+                assert!(keys.iter().all(|&key| (key as usize) < n));
+            }
+        }
+    }
+
+    #[test]
+    fn test_zipf() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let zipf = Zipf::new(1000, ZIPF_THETA);
+        let mut count = vec![0; 1000];
+        for _ in 0..100_000 {
+            count[zipf.sample(&mut rng)] += 1;
+        }
+        // about 13% are the most popular, and then about half of it
+        assert!(12_000 < count[0] && count[0] < 14_000, "{}", count[0]);
+        assert!(5_500 < count[1] && count[1] < 7_500, "{}", count[1]);
+        assert!(count[..10].iter().sum::<u32>() > count[10..].iter().sum::<u32>() / 2);
+    }
+
+    #[test]
+    fn test_sets() {
+        macro_rules! test {
+            ($($set:ty),* $(,)?) => {
+                $(test_set::<$set>();)*
+            };
+        }
+        test!(
+            avl_tree::AVLTree<u32, ()>,
+            btree::BTree<u32>,
+            foldable_llrb_tree::LLRB<u32, u64>,
+            llrb::LLRB<u32, ()>,
+            nilless_parentless_rbtree::RBTree<u32, ()>,
+            order_statistic_llrb_tree::LLRB<u32, ()>,
+            parentless_rbtree::RBTree<u32, ()>,
+            super::Persistent,
+            red_black_tree::RBTree<u32, ()>,
+            red_black_tree::arena::RBTree<u32, ()>,
+            reduced_space_van_emde_boas::Veb,
+            treap::Treap<u32, (), StdRng>,
+            van_emde_boas::Veb,
+            BTreeSet<u32>,
+        );
+    }
+    fn test_set<S: OrderedSet>() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for &n in [1, 2, 100].iter() {
+            let mut set = S::new(n);
+            let mut expected = BTreeSet::new();
+            for _ in 0..4 * n {
+                let key = rng.gen_range(0, n as u32);
+                if rng.gen_bool(0.5) {
+                    if expected.insert(key) {
+                        set.insert(key);
+                    }
+                } else if expected.remove(&key) {
+                    set.delete(key);
+                }
+                let key = rng.gen_range(0, n as u32);
+                assert_eq!(set.contains(key), expected.contains(&key), "{}", S::NAME);
+                assert_eq!(set.iterate(), expected.len(), "{}", S::NAME);
+            }
+        }
+    }
+
+    #[test]
+    fn test_heaps() {
+        macro_rules! test {
+            ($($heap:ty),* $(,)?) => {
+                $(test_heap::<$heap>();)*
+            };
+        }
+        test!(
+            fibonacci_heap::FibonacciHeap<u32, ()>,
+            super::Pooled<fibonacci_heap::arena::FibonacciHeap<u32, ()>>,
+            heap_sort::BinaryHeap<u32, heap_sort::MinComparator>,
+            heap_sort::DArrayHeap<u32, heap_sort::MinComparator>,
+            super::Indexed,
+            super::Pooled<mergeable_heaps::BinomialHeap<u32, ()>>,
+            super::Pooled<mergeable_heaps::LeftistHeap<u32, ()>>,
+            super::Pooled<mergeable_heaps::PairingHeap<u32, ()>>,
+            super::StdBinaryHeap,
+        );
+    }
+    fn test_heap<H: MinHeap>() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for &n in [1, 2, 100].iter() {
+            let mut heap = H::new(n);
+            let mut expected = Vec::new();
+            for _ in 0..4 * n {
+                if expected.len() < n && rng.gen_bool(0.5) {
+                    let key = rng.gen_range(0, 10);
+                    heap.push(key);
+                    expected.push(key);
+                } else {
+                    expected.sort_by(|x, y| y.cmp(x));
+                    assert_eq!(heap.pop(), expected.pop(), "{}", H::NAME);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,250 @@
+// The bytes which the structures hold on the heap, so that they can be compared by their space as
+// well as by their time. The sets hold the keys `0..n` inserted in a random order, and the heaps
+// hold `n` random keys in `0..n`. `BTreeSet` is left out, since its nodes cannot be seen from
+// outside.
+//
+// The van Emde Boas trees on `0..n` take the space of the universe, so they are measured again on
+// `n` random keys out of a universe of `2^lg`, where only the reduced-space one takes O(n).
+use {
+    super::{Distribution, Indexed, MinHeap, OrderedSet, Persistent, Pooled, StdBinaryHeap},
+    heap_sort::{BinaryHeap, DArrayHeap, MinComparator},
+    memory_usage::MemoryUsage,
+    rand::{prelude::*, seq::index},
+    serde_json::json,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Usage {
+    pub structure: &'static str,
+    // the universe of the sparse keys, if not `0..n`
+    pub lg: Option<u32>,
+    pub n: usize,
+    pub bytes: usize,
+}
+impl Usage {
+    pub fn per_element(&self) -> f64 {
+        self.bytes as f64 / self.n as f64
+    }
+}
+
+const COLUMNS: [&str; 5] = ["structure", "lg", "n", "bytes", "bytes_per_element"];
+
+type Measure = fn(&[u32]) -> usize;
+
+macro_rules! sets {
+    ($($set:ty),* $(,)?) => {
+        [$((<$set as OrderedSet>::NAME, <$set as OrderedSet>::MAX_LEN, set::<$set> as Measure)),*]
+    };
+}
+macro_rules! heaps {
+    ($($heap:ty),* $(,)?) => {
+        [$((<$heap as MinHeap>::NAME, heap::<$heap> as Measure)),*]
+    };
+}
+
+fn set<S: OrderedSet + MemoryUsage>(keys: &[u32]) -> usize {
+    let mut set = S::new(keys.len());
+    for &key in keys {
+        set.insert(key);
+    }
+    set.heap_size_bytes()
+}
+fn heap<H: MinHeap + MemoryUsage>(keys: &[u32]) -> usize {
+    let mut heap = H::new(keys.len());
+    for &key in keys {
+        heap.push(key);
+    }
+    heap.heap_size_bytes()
+}
+
+// every structure at every size in `sizes`
+pub fn measure(sizes: &[usize], rng: &mut StdRng) -> Vec<Usage> {
+    let sets = sets!(
+        avl_tree::AVLTree<u32, ()>,
+        btree::BTree<u32>,
+        foldable_llrb_tree::LLRB<u32, u64>,
+        llrb::LLRB<u32, ()>,
+        nilless_parentless_rbtree::RBTree<u32, ()>,
+        order_statistic_llrb_tree::LLRB<u32, ()>,
+        parentless_rbtree::RBTree<u32, ()>,
+        Persistent,
+        red_black_tree::RBTree<u32, ()>,
+        red_black_tree::arena::RBTree<u32, ()>,
+        treap::Treap<u32, (), StdRng>,
+        van_emde_boas::Veb,
+        reduced_space_van_emde_boas::Veb,
+    );
+    let heaps = heaps!(
+        BinaryHeap<u32, MinComparator>,
+        DArrayHeap<u32, MinComparator>,
+        Indexed,
+        fibonacci_heap::FibonacciHeap<u32, ()>,
+        Pooled<fibonacci_heap::arena::FibonacciHeap<u32, ()>>,
+        Pooled<mergeable_heaps::BinomialHeap<u32, ()>>,
+        Pooled<mergeable_heaps::LeftistHeap<u32, ()>>,
+        Pooled<mergeable_heaps::PairingHeap<u32, ()>>,
+        StdBinaryHeap,
+    );
+    let mut usages = Vec::new();
+    for &n in sizes {
+        let keys = Distribution::Random.permutation(n, rng).unwrap();
+        for &(structure, max_len, measure) in sets.iter() {
+            if n <= max_len {
+                usages.push(Usage {
+                    structure,
+                    lg: None,
+                    n,
+                    bytes: measure(&keys),
+                });
+            }
+        }
+        let keys = Distribution::Random.keys(n, n, rng);
+        for &(structure, measure) in heaps.iter() {
+            usages.push(Usage {
+                structure,
+                lg: None,
+                n,
+                bytes: measure(&keys),
+            });
+        }
+    }
+    usages
+}
+
+// The van Emde Boas trees on `n` distinct random keys out of `0..2^lg`, for every `n` in `sizes`.
+// The plain one takes O(2^lg) even if empty, so it is measured only if `lg <= plain_max_lg`.
+pub fn measure_sparse(lg: u32, plain_max_lg: u32, sizes: &[usize], rng: &mut StdRng) -> Vec<Usage> {
+    assert!(lg <= 32, "the keys are u32");
+    let mut usages = Vec::new();
+    for &n in sizes {
+        let keys = index::sample(rng, 1 << lg, n);
+        let mut veb = reduced_space_van_emde_boas::Veb::new(lg);
+        for key in keys.iter() {
+            veb.insert(key);
+        }
+        usages.push(Usage {
+            structure: <reduced_space_van_emde_boas::Veb as OrderedSet>::NAME,
+            lg: Some(lg),
+            n,
+            bytes: veb.heap_size_bytes(),
+        });
+        if lg <= plain_max_lg {
+            let mut veb = van_emde_boas::Veb::new(lg);
+            for key in keys.iter() {
+                veb.insert(key);
+            }
+            usages.push(Usage {
+                structure: <van_emde_boas::Veb as OrderedSet>::NAME,
+                lg: Some(lg),
+                n,
+                bytes: veb.heap_size_bytes(),
+            });
+        }
+    }
+    usages
+}
+
+pub fn to_csv(usages: &[Usage]) -> String {
+    let mut csv = COLUMNS.join(",");
+    csv.push('\n');
+    for usage in usages {
+        let row = [
+            usage.structure.to_owned(),
+            usage.lg.map_or_else(String::new, |x| x.to_string()),
+            usage.n.to_string(),
+            usage.bytes.to_string(),
+            usage.per_element().to_string(),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+pub fn to_json(usages: &[Usage]) -> String {
+    let usages = usages
+        .iter()
+        .map(|usage| {
+            json!({
+                "structure": usage.structure,
+                "lg": usage.lg,
+                "n": usage.n,
+                "bytes": usage.bytes,
+                "bytes_per_element": usage.per_element(),
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&usages).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{measure, measure_sparse, to_csv, to_json, Usage},
+        rand::prelude::*,
+        std::mem::size_of,
+    };
+
+    #[test]
+    fn test_measure() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let usages = measure(&[10, 1000], &mut rng);
+        assert_eq!(usages.len(), 2 * (13 + 9));
+        for usage in &usages {
+            // Every tree and heap holds the keys on the heap, but a small van Emde Boas tree is
+            // inline.
+            if !usage.structure.contains("van_emde_boas") {
+                assert!(usage.bytes >= usage.n * size_of::<u32>(), "{:?}", usage);
+            }
+        }
+        // The nodes are larger than the slots of an array.
+        let bytes = |structure: &str| {
+            usages
+                .iter()
+                .find(|usage| usage.structure == structure && usage.n == 1000)
+                .unwrap()
+                .bytes
+        };
+        assert!(bytes("avl_tree") > bytes("heap_sort::BinaryHeap"));
+
+        let csv = to_csv(&usages[..1]);
+        assert_eq!(
+            csv,
+            format!(
+                "structure,lg,n,bytes,bytes_per_element\navl_tree,,10,{},{}\n",
+                usages[0].bytes,
+                usages[0].bytes as f64 / 10.0
+            )
+        );
+        let json = serde_json::from_str::<serde_json::Value>(&to_json(&usages)).unwrap();
+        assert_eq!(json[0]["lg"], serde_json::Value::Null);
+        assert_eq!(json[0]["structure"], "avl_tree");
+    }
+
+    // The reduced-space one grows linearly in `n`, while the plain one stays at its universe.
+    #[test]
+    fn test_reduced_space_van_emde_boas() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let sizes = (0..6).map(|k| 1 << (2 * k)).collect::<Vec<usize>>();
+        let usages = measure_sparse(20, 20, &sizes, &mut rng);
+        let (reduced, plain): (Vec<&Usage>, Vec<&Usage>) = usages
+            .iter()
+            .partition(|usage| usage.structure == "reduced_space_van_emde_boas");
+        assert_eq!(reduced.len(), sizes.len());
+        assert_eq!(plain.len(), sizes.len());
+        for usage in &reduced {
+            assert!(usage.per_element() <= 1024.0, "{:?}", usage);
+        }
+        for (reduced, plain) in reduced.iter().zip(&plain) {
+            assert!(reduced.bytes < plain.bytes);
+        }
+        for usage in &plain {
+            assert_eq!(usage.bytes, plain[0].bytes);
+        }
+
+        // too large a universe for the plain one
+        let usages = measure_sparse(32, 20, &[1000], &mut rng);
+        assert_eq!(usages.len(), 1);
+        assert!(usages[0].per_element() <= 1024.0, "{:?}", usages[0]);
+    }
+}
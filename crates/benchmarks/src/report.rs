@@ -0,0 +1,255 @@
+// Gathers the results which criterion leaves under `target/criterion` into a table, so that they
+// can be compared in a spreadsheet or a script rather than in its HTML reports.
+use {
+    serde_json::{json, Value},
+    std::{
+        ffi::OsStr,
+        fs, io,
+        path::{Path, PathBuf},
+    },
+};
+
+// One benchmark, e.g. the group `trees/insert/random`, the function `avl_tree` and the parameter
+// `1000`. The times are in nanoseconds per iteration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub group: String,
+    pub function: String,
+    pub parameter: String,
+    // the operations in an iteration, if it has been given as the throughput
+    pub elements: Option<u64>,
+    pub mean: f64,
+    pub mean_lower: f64,
+    pub mean_upper: f64,
+    pub median: f64,
+}
+impl Record {
+    pub fn per_element(&self) -> Option<f64> {
+        self.elements.map(|elements| self.mean / elements as f64)
+    }
+}
+
+const COLUMNS: [&str; 9] = [
+    "group",
+    "function",
+    "parameter",
+    "elements",
+    "mean_ns",
+    "mean_lower_ns",
+    "mean_upper_ns",
+    "median_ns",
+    "mean_ns_per_element",
+];
+
+// The latest results of all the benchmarks under `dir`, sorted by their names.
+pub fn collect(dir: &Path) -> io::Result<Vec<Record>> {
+    let mut records = Vec::new();
+    for new in find_new_dirs(dir)? {
+        records.push(read(&new)?);
+    }
+    records.sort_by(|x, y| {
+        (&x.group, &x.function, x.elements, &x.parameter).cmp(&(
+            &y.group,
+            &y.function,
+            y.elements,
+            &y.parameter,
+        ))
+    });
+    Ok(records)
+}
+
+// The directories `new`, which criterion makes for the latest run of each benchmark, aside from
+// `base` and the ones named after the saved baselines.
+fn find_new_dirs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if path.file_name() == Some(OsStr::new("new")) && path.join("benchmark.json").is_file()
+            {
+                dirs.push(path);
+            } else {
+                dirs.extend(find_new_dirs(&path)?);
+            }
+        }
+    }
+    Ok(dirs)
+}
+
+fn read(new: &Path) -> io::Result<Record> {
+    let benchmark = read_json(&new.join("benchmark.json"))?;
+    let estimates = read_json(&new.join("estimates.json"))?;
+    let string = |key: &str| benchmark[key].as_str().unwrap_or("").to_owned();
+    let estimate = |pointer: &str| {
+        estimates
+            .pointer(pointer)
+            .and_then(Value::as_f64)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("no {} in {}", pointer, new.display()),
+                )
+            })
+    };
+    Ok(Record {
+        group: string("group_id"),
+        function: string("function_id"),
+        parameter: string("value_str"),
+        elements: benchmark["throughput"]["Elements"].as_u64(),
+        mean: estimate("/mean/point_estimate")?,
+        mean_lower: estimate("/mean/confidence_interval/lower_bound")?,
+        mean_upper: estimate("/mean/confidence_interval/upper_bound")?,
+        median: estimate("/median/point_estimate")?,
+    })
+}
+fn read_json(path: &Path) -> io::Result<Value> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn to_csv(records: &[Record]) -> String {
+    let mut csv = COLUMNS.join(",");
+    csv.push('\n');
+    for record in records {
+        let row = [
+            escape(&record.group),
+            escape(&record.function),
+            escape(&record.parameter),
+            record.elements.map_or_else(String::new, |x| x.to_string()),
+            record.mean.to_string(),
+            record.mean_lower.to_string(),
+            record.mean_upper.to_string(),
+            record.median.to_string(),
+            record
+                .per_element()
+                .map_or_else(String::new, |x| x.to_string()),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+// quoted only if needed, as RFC 4180
+fn escape(field: &str) -> String {
+    if field.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+pub fn to_json(records: &[Record]) -> String {
+    let records = records
+        .iter()
+        .map(|record| {
+            json!({
+                "group": record.group,
+                "function": record.function,
+                "parameter": record.parameter,
+                "elements": record.elements,
+                "mean_ns": record.mean,
+                "mean_lower_ns": record.mean_lower,
+                "mean_upper_ns": record.mean_upper,
+                "median_ns": record.median,
+                "mean_ns_per_element": record.per_element(),
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&records).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{collect, to_csv, to_json, Record},
+        std::{env, fs},
+    };
+
+    #[test]
+    fn test_collect() {
+        let dir = env::temp_dir().join(format!("benchmarks-report-{}", std::process::id()));
+        let write = |path: &str, content: &str| {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        let estimates = |mean: f64| {
+            format!(
+                r#"{{"mean":{{"confidence_interval":{{"confidence_level":0.95,"lower_bound":{},"upper_bound":{}}},"point_estimate":{},"standard_error":1.0}},"median":{{"confidence_interval":{{"confidence_level":0.95,"lower_bound":0.0,"upper_bound":0.0}},"point_estimate":{},"standard_error":1.0}}}}"#,
+                mean - 1.0,
+                mean + 1.0,
+                mean,
+                mean
+            )
+        };
+        for &(n, mean) in [(10_000, 2e5), (1_000, 1e4)].iter() {
+            let new = format!("trees_insert_random/avl_tree/{}/new", n);
+            write(
+                &format!("{}/benchmark.json", new),
+                &format!(
+                    r#"{{"group_id":"trees/insert/random","function_id":"avl_tree","value_str":"{}","throughput":{{"Elements":{}}}}}"#,
+                    n, n
+                ),
+            );
+            write(&format!("{}/estimates.json", new), &estimates(mean));
+            // the previous run, which is to be ignored
+            let base = format!("trees_insert_random/avl_tree/{}/base", n);
+            write(&format!("{}/benchmark.json", base), "{}");
+            write(&format!("{}/estimates.json", base), "{}");
+        }
+        write(
+            "Build/Binary Heap/new/benchmark.json",
+            r#"{"group_id":"Build","function_id":"Binary, Heap","value_str":null,"throughput":null}"#,
+        );
+        write("Build/Binary Heap/new/estimates.json", &estimates(3.0));
+        let records = collect(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let records = records.unwrap();
+        assert_eq!(
+            records,
+            vec![
+                Record {
+                    group: "Build".to_owned(),
+                    function: "Binary, Heap".to_owned(),
+                    parameter: "".to_owned(),
+                    elements: None,
+                    mean: 3.0,
+                    mean_lower: 2.0,
+                    mean_upper: 4.0,
+                    median: 3.0,
+                },
+                Record {
+                    group: "trees/insert/random".to_owned(),
+                    function: "avl_tree".to_owned(),
+                    parameter: "1000".to_owned(),
+                    elements: Some(1000),
+                    mean: 1e4,
+                    mean_lower: 1e4 - 1.0,
+                    mean_upper: 1e4 + 1.0,
+                    median: 1e4,
+                },
+                Record {
+                    group: "trees/insert/random".to_owned(),
+                    function: "avl_tree".to_owned(),
+                    parameter: "10000".to_owned(),
+                    elements: Some(10_000),
+                    mean: 2e5,
+                    mean_lower: 2e5 - 1.0,
+                    mean_upper: 2e5 + 1.0,
+                    median: 2e5,
+                },
+            ]
+        );
+        assert_eq!(
+            to_csv(&records),
+            "group,function,parameter,elements,mean_ns,mean_lower_ns,mean_upper_ns,median_ns,mean_ns_per_element
+Build,\"Binary, Heap\",,,3,2,4,3,
+trees/insert/random,avl_tree,1000,1000,10000,9999,10001,10000,10
+trees/insert/random,avl_tree,10000,10000,200000,199999,200001,200000,20
+"
+        );
+        let json = serde_json::from_str::<serde_json::Value>(&to_json(&records)).unwrap();
+        assert_eq!(json[0]["elements"], serde_json::Value::Null);
+        assert_eq!(json[2]["mean_ns_per_element"], 20.0);
+    }
+}
@@ -0,0 +1,280 @@
+use {super::OrderedSet, memory_usage::MemoryUsage, rand::prelude::*, std::collections::BTreeSet};
+
+impl OrderedSet for avl_tree::AVLTree<u32, ()> {
+    const NAME: &'static str = "avl_tree";
+    fn new(_n: usize) -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ());
+    }
+    fn delete(&mut self, key: u32) {
+        self.delete(&key);
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.get(&key).is_some()
+    }
+    fn iterate(&self) -> usize {
+        self.collect_vec().len()
+    }
+}
+
+impl OrderedSet for llrb::LLRB<u32, ()> {
+    const NAME: &'static str = "llrb";
+    fn new(_n: usize) -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ());
+    }
+    fn delete(&mut self, key: u32) {
+        self.delete(&key);
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.get(&key).is_some()
+    }
+    fn iterate(&self) -> usize {
+        self.collect_vec().len()
+    }
+}
+
+impl OrderedSet for nilless_parentless_rbtree::RBTree<u32, ()> {
+    const NAME: &'static str = "nilless_parentless_rbtree";
+    fn new(_n: usize) -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ());
+    }
+    fn delete(&mut self, key: u32) {
+        self.delete(key);
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.get(&key).is_some()
+    }
+    fn iterate(&self) -> usize {
+        self.collect_vec().len()
+    }
+}
+
+impl OrderedSet for order_statistic_llrb_tree::LLRB<u32, ()> {
+    const NAME: &'static str = "order_statistic_llrb_tree";
+    fn new(_n: usize) -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ());
+    }
+    fn delete(&mut self, key: u32) {
+        self.delete(&key);
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.find(&key).is_some()
+    }
+    fn iterate(&self) -> usize {
+        self.collect_vec().len()
+    }
+}
+
+impl OrderedSet for red_black_tree::RBTree<u32, ()> {
+    const NAME: &'static str = "red_black_tree";
+    fn new(_n: usize) -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ());
+    }
+    fn delete(&mut self, key: u32) {
+        self.delete(key);
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.get(&key).is_some()
+    }
+    fn iterate(&self) -> usize {
+        self.collect_vec().len()
+    }
+}
+
+impl OrderedSet for red_black_tree::arena::RBTree<u32, ()> {
+    const NAME: &'static str = "red_black_tree::arena";
+    fn new(_n: usize) -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ());
+    }
+    fn delete(&mut self, key: u32) {
+        self.delete(&key);
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.get(&key).is_some()
+    }
+    fn iterate(&self) -> usize {
+        self.collect_vec().len()
+    }
+}
+
+impl OrderedSet for treap::Treap<u32, (), StdRng> {
+    const NAME: &'static str = "treap";
+    fn new(_n: usize) -> Self {
+        Self::new(StdRng::seed_from_u64(42))
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ());
+    }
+    fn delete(&mut self, key: u32) {
+        self.delete(&key);
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.get(&key).is_some()
+    }
+    fn iterate(&self) -> usize {
+        self.collect_vec().len()
+    }
+}
+
+// `remove` and `collect` rather than `delete` and `collect_vec`
+impl OrderedSet for parentless_rbtree::RBTree<u32, ()> {
+    const NAME: &'static str = "parentless_rbtree";
+    fn new(_n: usize) -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ());
+    }
+    fn delete(&mut self, key: u32) {
+        self.remove(key);
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.get(&key).is_some()
+    }
+    fn iterate(&self) -> usize {
+        self.collect().len()
+    }
+}
+
+// The values are the keys, so that the folds are maintained as well.
+impl OrderedSet for foldable_llrb_tree::LLRB<u32, u64> {
+    const NAME: &'static str = "foldable_llrb_tree";
+    fn new(_n: usize) -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, u64::from(key));
+    }
+    fn delete(&mut self, key: u32) {
+        self.delete(&key);
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.find(&key).is_some()
+    }
+    fn iterate(&self) -> usize {
+        self.collect_vec().len()
+    }
+}
+
+impl OrderedSet for btree::BTree<u32> {
+    const NAME: &'static str = "btree";
+    fn new(_n: usize) -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key);
+    }
+    fn delete(&mut self, key: u32) {
+        self.delete(key);
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.get(&key).is_some()
+    }
+    fn iterate(&self) -> usize {
+        self.collect_vec().len()
+    }
+}
+
+// the latest version of a persistent tree
+pub struct Persistent {
+    rbt: persistent_rbtree::PersistentRBTree<u32, ()>,
+    version: usize,
+}
+impl OrderedSet for Persistent {
+    const NAME: &'static str = "persistent_rbtree";
+    // Every version is kept, which takes O(n lg n) nodes in all.
+    const MAX_LEN: usize = 100_000;
+    fn new(_n: usize) -> Self {
+        Self {
+            rbt: persistent_rbtree::PersistentRBTree::new(),
+            version: 0,
+        }
+    }
+    fn insert(&mut self, key: u32) {
+        self.rbt.insert(key, ());
+        self.version += 1;
+    }
+    fn delete(&mut self, key: u32) {
+        self.rbt.delete(key);
+        self.version += 1;
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.rbt.get(self.version, &key).is_some()
+    }
+    fn iterate(&self) -> usize {
+        self.rbt.collect_vec(self.version).len()
+    }
+}
+// all the versions
+impl MemoryUsage for Persistent {
+    fn heap_size_bytes(&self) -> usize {
+        self.rbt.heap_size_bytes()
+    }
+}
+
+// the smallest universe holding `0..n`
+fn lg(n: usize) -> u32 {
+    n.next_power_of_two().trailing_zeros().max(1)
+}
+// The two van Emde Boas trees have the same interface.
+macro_rules! veb {
+    ($veb:ident) => {
+        impl OrderedSet for $veb::Veb {
+            const NAME: &'static str = stringify!($veb);
+            fn new(n: usize) -> Self {
+                Self::new(lg(n))
+            }
+            fn insert(&mut self, key: u32) {
+                self.insert(key as usize);
+            }
+            fn delete(&mut self, key: u32) {
+                self.delete(key as usize);
+            }
+            fn contains(&self, key: u32) -> bool {
+                self.contains(key as usize)
+            }
+            fn iterate(&self) -> usize {
+                self.collect_vec().len()
+            }
+        }
+    };
+}
+veb!(van_emde_boas);
+veb!(reduced_space_van_emde_boas);
+
+// the baseline
+impl OrderedSet for BTreeSet<u32> {
+    const NAME: &'static str = "std::collections::BTreeSet";
+    fn new(_n: usize) -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key);
+    }
+    fn delete(&mut self, key: u32) {
+        self.remove(&key);
+    }
+    fn contains(&self, key: u32) -> bool {
+        self.contains(&key)
+    }
+    fn iterate(&self) -> usize {
+        self.iter().count()
+    }
+}
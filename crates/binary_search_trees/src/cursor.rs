@@ -0,0 +1,334 @@
+use super::{rc_ref_cell, BinarySearchBree, Hook, RcRefCell};
+use std::{mem::replace, rc::Rc};
+
+/// 中順の位置を指すカーソルです。ノードか、最後と最初の間のゴーストを指します。
+///
+/// 親ポインタをたどって動くので、`n` 個のノードをすべて歩いても O(n) です。
+pub struct Cursor<'a> {
+    tree: &'a BinarySearchBree,
+    /// ゴーストのとき `None`
+    current: Option<RcRefCell<Hook>>,
+}
+impl<'a> Cursor<'a> {
+    /// 次に動きます。ゴーストからは最初に動きます。
+    pub fn move_next(&mut self) {
+        self.current = step(self.tree, self.current.take(), 1);
+    }
+    /// 前に動きます。ゴーストからは最後に動きます。
+    pub fn move_prev(&mut self) {
+        self.current = step(self.tree, self.current.take(), 0);
+    }
+    /// ゴーストのとき `None` です。
+    pub fn key(&self) -> Option<u32> {
+        self.current.as_ref().map(|x| x.borrow().key)
+    }
+}
+
+/// 木を変更できるカーソルです。
+///
+/// キーしか持たない木なので、`value_mut` はありません。両端のほかに次のノードも覚えておくので、
+/// `insert_after` は探さずに挿入できます。次のノードは一歩先を歩いて求めるので、`Cursor`
+/// と同じく歩き通せばならし O(1) です。
+pub struct CursorMut<'a> {
+    tree: &'a mut BinarySearchBree,
+    /// ゴーストのとき `None`
+    current: Option<RcRefCell<Hook>>,
+    /// `current` の次のノード。ゴーストのときは最初のノードで、次がゴーストのときは `None`
+    next: Option<RcRefCell<Hook>>,
+    /// ゴーストの隣の、最初と最後のノード
+    ends: [Option<RcRefCell<Hook>>; 2],
+}
+impl<'a> CursorMut<'a> {
+    /// 次に動きます。ゴーストからは最初に動きます。
+    pub fn move_next(&mut self) {
+        self.current = self.next.take();
+        self.next = self.after(&self.current);
+    }
+    /// 前に動きます。ゴーストからは最後に動きます。
+    pub fn move_prev(&mut self) {
+        let current = match &self.current {
+            None => self.ends[1].clone(),
+            Some(x) => Hook::neighbor(x, 0),
+        };
+        self.next = replace(&mut self.current, current);
+    }
+    /// ゴーストのとき `None` です。
+    pub fn key(&self) -> Option<u32> {
+        self.current.as_ref().map(|x| x.borrow().key)
+    }
+    /// いまのノードを消して、そのキーを返し、次に動きます。
+    pub fn remove_current(&mut self) -> Option<u32> {
+        let x = self.current.take()?;
+        self.current = self.next.take();
+        if is(&self.ends[0], &x) {
+            self.ends[0] = self.current.clone();
+        }
+        if is(&self.ends[1], &x) {
+            self.ends[1] = Hook::neighbor(&x, 0);
+        }
+        self.tree.delete_hook(&x);
+        self.next = self.after(&self.current);
+        let key = x.borrow().key;
+        Some(key)
+    }
+    /// いまのノードの直後（ゴーストのときは先頭）に挿入します。カーソルは動きません。
+    ///
+    /// いまのノードの右が空ならそこに、そうでなければ次のノードの左に挿入します。このとき次のノードの左は
+    /// 空です。
+    ///
+    /// ### Panics
+    ///
+    /// `key` が前後のキーの間にないとき、パニックします。
+    pub fn insert_after(&mut self, key: u32) {
+        if let Some(x) = &self.current {
+            assert!(x.borrow().key <= key, "the order of the keys is broken");
+        }
+        if let Some(x) = &self.next {
+            assert!(key <= x.borrow().key, "the order of the keys is broken");
+        }
+        let hook = rc_ref_cell(Hook::new(key));
+        match (&self.current, &self.next) {
+            (Some(x), _) if x.borrow().children[1].is_none() => {
+                Hook::connect(x, 1, Some(Rc::clone(&hook)))
+            }
+            (_, Some(y)) => Hook::connect(y, 0, Some(Rc::clone(&hook))),
+            (_, None) => self.tree.root = Some(Rc::clone(&hook)),
+        }
+        if self.current.is_none() {
+            self.ends[0] = Some(Rc::clone(&hook));
+        }
+        if self.next.is_none() {
+            self.ends[1] = Some(Rc::clone(&hook));
+        }
+        self.next = Some(hook);
+    }
+    /// `x` の次です。ゴーストの次は最初のノードです。
+    fn after(&self, x: &Option<RcRefCell<Hook>>) -> Option<RcRefCell<Hook>> {
+        match x {
+            None => self.ends[0].clone(),
+            Some(x) => Hook::neighbor(x, 1),
+        }
+    }
+}
+
+impl BinarySearchBree {
+    /// 最初のノードを指すカーソルです。空ならばゴーストを指します。
+    pub fn cursor_first(&self) -> Cursor<'_> {
+        let mut cursor = Cursor {
+            tree: self,
+            current: None,
+        };
+        cursor.move_next();
+        cursor
+    }
+    /// 最後のノードを指すカーソルです。空ならばゴーストを指します。
+    pub fn cursor_last(&self) -> Cursor<'_> {
+        let mut cursor = Cursor {
+            tree: self,
+            current: None,
+        };
+        cursor.move_prev();
+        cursor
+    }
+    /// `key` 以上で最初のノードを指すカーソルです。なければゴーストを指します。
+    pub fn cursor_at(&self, key: u32) -> Cursor<'_> {
+        Cursor {
+            current: lower_bound(self, key),
+            tree: self,
+        }
+    }
+    /// 最初のノードを指すカーソルです。空ならばゴーストを指します。
+    pub fn cursor_first_mut(&mut self) -> CursorMut<'_> {
+        let mut cursor = self.cursor_ghost_mut();
+        cursor.move_next();
+        cursor
+    }
+    /// 最後のノードを指すカーソルです。空ならばゴーストを指します。
+    pub fn cursor_last_mut(&mut self) -> CursorMut<'_> {
+        let mut cursor = self.cursor_ghost_mut();
+        cursor.move_prev();
+        cursor
+    }
+    /// `key` 以上で最初のノードを指すカーソルです。なければゴーストを指します。
+    pub fn cursor_at_mut(&mut self, key: u32) -> CursorMut<'_> {
+        let mut cursor = self.cursor_ghost_mut();
+        cursor.current = lower_bound(cursor.tree, key);
+        cursor.next = cursor.after(&cursor.current);
+        cursor
+    }
+    fn cursor_ghost_mut(&mut self) -> CursorMut<'_> {
+        let first = step(self, None, 1);
+        CursorMut {
+            next: first.clone(),
+            ends: [first, step(self, None, 0)],
+            tree: self,
+            current: None,
+        }
+    }
+}
+
+/// `i` 側の隣です。ゴーストは両端の隣です。
+fn step(
+    tree: &BinarySearchBree,
+    current: Option<RcRefCell<Hook>>,
+    i: usize,
+) -> Option<RcRefCell<Hook>> {
+    match current {
+        None => tree
+            .root
+            .as_ref()
+            .map(|root| Hook::tree_extremum(Rc::clone(root), 1 - i)),
+        Some(x) => Hook::neighbor(&x, i),
+    }
+}
+/// `x` が `y` を指すかどうかです。
+fn is(x: &Option<RcRefCell<Hook>>, y: &RcRefCell<Hook>) -> bool {
+    x.as_ref().is_some_and(|x| Rc::ptr_eq(x, y))
+}
+/// `key` 以上で最初のノードです。
+fn lower_bound(tree: &BinarySearchBree, key: u32) -> Option<RcRefCell<Hook>> {
+    let mut x = tree.root.as_ref().map(Rc::clone);
+    let mut found = None;
+    while let Some(y) = x {
+        let i = if key <= y.borrow().key {
+            found = Some(Rc::clone(&y));
+            0
+        } else {
+            1
+        };
+        x = y.borrow().children[i].as_ref().map(Rc::clone);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::BinarySearchBree;
+    use paren::FromParen;
+    use rand::prelude::*;
+
+    #[test]
+    fn test_move() {
+        // CLRS Figure 12.4 (d)
+        let bst = BinarySearchBree::from_paren("((2)5(((6)7)12(13)))").unwrap();
+        let mut cursor = bst.cursor_first();
+        let mut forward = Vec::new();
+        while let Some(key) = cursor.key() {
+            forward.push(key);
+            cursor.move_next();
+        }
+        assert_eq!(forward, bst.collect_vec());
+        // ゴーストを越えます。
+        cursor.move_next();
+        assert_eq!(cursor.key(), Some(2));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.key(), Some(13));
+
+        let mut cursor = bst.cursor_last();
+        let mut backward = Vec::new();
+        while let Some(key) = cursor.key() {
+            backward.push(key);
+            cursor.move_prev();
+        }
+        backward.reverse();
+        assert_eq!(backward, forward);
+
+        assert_eq!(bst.cursor_at(0).key(), Some(2));
+        assert_eq!(bst.cursor_at(7).key(), Some(7));
+        assert_eq!(bst.cursor_at(8).key(), Some(12));
+        assert_eq!(bst.cursor_at(14).key(), None);
+        assert_eq!(BinarySearchBree::new().cursor_first().key(), None);
+    }
+
+    #[test]
+    fn test_cursor_mut() {
+        let mut bst = BinarySearchBree::new();
+        let mut cursor = bst.cursor_first_mut();
+        cursor.insert_after(4);
+        cursor.insert_after(0);
+        cursor.move_next();
+        assert_eq!(cursor.key(), Some(0));
+        for key in 1..4 {
+            cursor.insert_after(key);
+            cursor.move_next();
+        }
+        assert_eq!(bst.collect_vec(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(bst.check_invariants(), Ok(()));
+
+        let mut cursor = bst.cursor_at_mut(1);
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.key(), Some(2));
+        let mut cursor = bst.cursor_last_mut();
+        assert_eq!(cursor.remove_current(), Some(4));
+        assert_eq!(cursor.key(), None);
+        assert_eq!(bst.collect_vec(), vec![0, 2, 3]);
+        assert_eq!(bst.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "the order of the keys is broken")]
+    fn test_insert_after_unordered() {
+        let mut bst = BinarySearchBree::from_paren("((1)3)").unwrap();
+        bst.cursor_first_mut().insert_after(4);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..20 {
+            let mut bst = BinarySearchBree::new();
+            let mut vec = Vec::new();
+            let mut cursor = bst.cursor_first_mut();
+            // ゴーストのとき `None`
+            let mut i = None;
+            for _ in 0..200 {
+                match rng.gen_range(0, 4) {
+                    0 => {
+                        cursor.move_next();
+                        i = match i {
+                            None if vec.is_empty() => None,
+                            None => Some(0),
+                            Some(i) if i + 1 == vec.len() => None,
+                            Some(i) => Some(i + 1),
+                        };
+                    }
+                    1 => {
+                        cursor.move_prev();
+                        i = match i {
+                            None => vec.len().checked_sub(1),
+                            Some(0) => None,
+                            Some(i) => Some(i - 1),
+                        };
+                    }
+                    2 => {
+                        let j = i.map_or(0, |i| i + 1);
+                        let lo = i.map_or(0, |i| vec[i]);
+                        let hi = vec.get(j).copied().unwrap_or(100);
+                        let key = rng.gen_range(lo, hi + 1);
+                        cursor.insert_after(key);
+                        vec.insert(j, key);
+                    }
+                    3 => {
+                        assert_eq!(cursor.remove_current(), i.map(|i| vec[i]));
+                        if let Some(j) = i {
+                            vec.remove(j);
+                            if j == vec.len() {
+                                i = None;
+                            }
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+                assert_eq!(cursor.key(), i.map(|i| vec[i]));
+                assert_eq!(
+                    cursor.next.as_ref().map(|x| x.borrow().key),
+                    vec.get(i.map_or(0, |i| i + 1)).copied()
+                );
+            }
+            drop(cursor);
+            assert_eq!(bst.collect_vec(), vec);
+            assert_eq!(bst.check_invariants(), Ok(()));
+        }
+    }
+}
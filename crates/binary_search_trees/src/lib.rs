@@ -1,3 +1,5 @@
+use invariant::{ensure, InvariantViolation, Property};
+use memory_usage::MemoryUsage;
 #[allow(unused_imports)]
 use std::{
     cell::{Ref, RefCell},
@@ -9,6 +11,9 @@ use std::{
     rc::{Rc, Weak},
 };
 
+mod cursor;
+pub use cursor::{Cursor, CursorMut};
+
 /// 二分探索木
 #[derive(Clone)]
 pub struct BinarySearchBree {
@@ -40,26 +45,27 @@ impl BinarySearchBree {
     }
     /// キーで検索して、あればひとつ（もっとも上にあるもの）を消します
     pub fn delete(&mut self, key: u32) -> Option<u32> {
-        if let Some(x) = self.search(key) {
-            if let Some(right) = x.borrow().children[1].as_ref() {
-                if x.borrow().children[0].is_none() {
-                    self.transplant(&x, x.borrow().children[1].as_ref().map(Rc::clone));
-                } else {
-                    let y = Hook::tree_extremum(Rc::clone(&right), 0);
-                    if !Rc::ptr_eq(&right, &y) {
-                        self.transplant(&y, y.borrow().children[1].as_ref().map(Rc::clone));
-                        Hook::connect(&y, 1, x.borrow().children[1].as_ref().map(Rc::clone));
-                    }
-                    self.transplant(&x, Some(Rc::clone(&y)));
-                    Hook::connect(&y, 0, x.borrow().children[0].as_ref().map(Rc::clone));
-                }
+        let x = self.search(key)?;
+        self.delete_hook(&x);
+        let key = x.borrow().key;
+        Some(key)
+    }
+    /// ノード `x` を木から外します。
+    fn delete_hook(&mut self, x: &RcRefCell<Hook>) {
+        if let Some(right) = x.borrow().children[1].as_ref() {
+            if x.borrow().children[0].is_none() {
+                self.transplant(x, x.borrow().children[1].as_ref().map(Rc::clone));
             } else {
-                self.transplant(&x, x.borrow().children[0].as_ref().map(Rc::clone));
+                let y = Hook::tree_extremum(Rc::clone(right), 0);
+                if !Rc::ptr_eq(right, &y) {
+                    self.transplant(&y, y.borrow().children[1].as_ref().map(Rc::clone));
+                    Hook::connect(&y, 1, x.borrow().children[1].as_ref().map(Rc::clone));
+                }
+                self.transplant(x, Some(Rc::clone(&y)));
+                Hook::connect(&y, 0, x.borrow().children[0].as_ref().map(Rc::clone));
             }
-            self.validate();
-            Some(x.borrow().key)
         } else {
-            None
+            self.transplant(x, x.borrow().children[0].as_ref().map(Rc::clone));
         }
     }
     pub fn get(&self, key: u32) -> Option<u32> {
@@ -138,54 +144,73 @@ impl BinarySearchBree {
             self.root = y;
         }
     }
-    /// 各ノードの親ポインタが正しく逆になっていなければ、パニックします。
-    fn validate(&self) {
-        if let Some(root) = self.root.as_ref() {
-            assert!(root.borrow().parent.is_none(), "{:?}", VWrapper(self));
-            fn dfs(me: &BinarySearchBree, x: &RcRefCell<Hook>) {
-                if let Some(child) = x.borrow().children[0].as_ref() {
-                    assert!(
-                        Rc::ptr_eq(&child.borrow().parent().unwrap(), &x),
-                        "x = {:?}, child = {:?}, {:?}",
-                        x,
-                        child.borrow().deref(),
-                        VWrapper(me)
-                    );
-                    dfs(me, child);
-                }
-                if let Some(child) = x.borrow().children[1].as_ref() {
-                    assert!(
-                        Rc::ptr_eq(&child.borrow().parent().unwrap(), &x),
-                        "x = {:?}, child = {:?}, {:?}",
-                        x,
-                        child.borrow().deref(),
-                        VWrapper(me)
-                    );
-                    dfs(me, child);
+    /// 親ポインタとキーの順序を確かめて、壊れていれば、その性質と根からのパスを返します。
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        fn dfs(
+            x: &RcRefCell<Hook>,
+            path: &mut Vec<usize>,
+            prev: &mut Option<u32>,
+        ) -> Result<(), InvariantViolation> {
+            let x_ref = x.borrow();
+            for (i, child) in x_ref.children.iter().enumerate() {
+                if let Some(child) = child {
+                    path.push(i);
+                    ensure(
+                        matches!(&child.borrow().parent, Some(p) if Weak::ptr_eq(p, &Rc::downgrade(x))),
+                        Property::ParentPointer,
+                        path,
+                    )?;
+                    path.pop();
                 }
-            };
-            dfs(self, root);
+            }
+            if let Some(child) = &x_ref.children[0] {
+                path.push(0);
+                dfs(child, path, prev)?;
+                path.pop();
+            }
+            if let Some(prev) = *prev {
+                ensure(prev <= x_ref.key, Property::BstOrder, path)?;
+            }
+            *prev = Some(x_ref.key);
+            if let Some(child) = &x_ref.children[1] {
+                path.push(1);
+                dfs(child, path, prev)?;
+                path.pop();
+            }
+            Ok(())
         }
+        if let Some(root) = self.root.as_ref() {
+            ensure(root.borrow().parent.is_none(), Property::ParentPointer, &[])?;
+            dfs(root, &mut Vec::new(), &mut None)?;
+        }
+        Ok(())
     }
 }
-impl Verbose for BinarySearchBree {
-    fn verbose(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+
+/// 括弧列 `((1)2(3))` の形で書きます。
+impl paren::Paren for BinarySearchBree {
+    fn paren(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         self.root
             .iter()
-            .map(|root| root.borrow().verbose(fmt))
-            .collect()
+            .try_for_each(|root| root.borrow().print(fmt))
     }
 }
-
-trait Verbose {
-    fn verbose(&self, fmt: &mut fmt::Formatter) -> fmt::Result;
-}
-
-/// デバッグ
-struct VWrapper<'a, T>(&'a T);
-impl<'a, T: Verbose> fmt::Debug for VWrapper<'a, T> {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        self.0.verbose(fmt)
+/// 括弧列から形をそのまま復元します。
+impl paren::FromParen for BinarySearchBree {
+    fn from_paren(s: &str) -> Result<Self, paren::ParseError> {
+        fn dfs(node: paren::Node) -> Result<RcRefCell<Hook>, paren::ParseError> {
+            let paren::Node {
+                label,
+                child: [left, right],
+            } = node;
+            let x = rc_ref_cell(Hook::new(label.key()?));
+            Hook::connect(&x, 0, left.map(|y| dfs(*y)).transpose()?);
+            Hook::connect(&x, 1, right.map(|y| dfs(*y)).transpose()?);
+            Ok(x)
+        }
+        Ok(Self {
+            root: paren::parse(s)?.map(|root| dfs(*root)).transpose()?,
+        })
     }
 }
 
@@ -229,6 +254,24 @@ impl Hook {
         } {}
         root
     }
+    /// 中順で `i` 側の隣（`i = 1` なら次）のノードです。なければ `None` です。
+    fn neighbor(x: &RcRefCell<Self>, i: usize) -> Option<RcRefCell<Self>> {
+        let child = x.borrow().children[i].as_ref().map(Rc::clone);
+        if let Some(child) = child {
+            return Some(Self::tree_extremum(child, 1 - i));
+        }
+        let mut x = Rc::clone(x);
+        loop {
+            let p = x.borrow().parent()?;
+            let is_i = p.borrow().children[i]
+                .as_ref()
+                .is_some_and(|child| Rc::ptr_eq(child, &x));
+            if !is_i {
+                return Some(p);
+            }
+            x = p;
+        }
+    }
     /// x の i 番目の子を y にして、y の親を i にします。
     fn connect(x: &RcRefCell<Self>, i: usize, y: Option<RcRefCell<Self>>) {
         x.borrow_mut().children[i] = y.as_ref().map(Rc::clone);
@@ -263,21 +306,18 @@ impl Hook {
         write!(fmt, ")")
     }
 }
-impl Verbose for Hook {
-    fn verbose(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        if let Some(child) = &self.children[0] {
-            child.borrow().verbose(fmt)?;
-        }
-        writeln!(fmt)?;
-        write!(fmt, "  - ")?;
-        fmt.debug_struct("Node")
-            .field("key", &self.key)
-            .field("parent", &self.parent.as_ref().map(Weak::upgrade))
-            .finish()?;
-        if let Some(child) = &self.children[1] {
-            child.borrow().verbose(fmt)?;
-        }
-        Ok(())
+
+/// ノードの `Rc` の確保を数えます。親は `Weak` なので、各ノードは親から一度だけ数えます。
+///
+/// `Clone` した木とはノードを共有しますが、それぞれの木で数えます。
+impl MemoryUsage for BinarySearchBree {
+    fn heap_size_bytes(&self) -> usize {
+        self.root.heap_size_bytes()
+    }
+}
+impl MemoryUsage for Hook {
+    fn heap_size_bytes(&self) -> usize {
+        self.children[0].heap_size_bytes() + self.children[1].heap_size_bytes()
     }
 }
 
@@ -292,8 +332,84 @@ fn rc_ref_cell<T>(x: T) -> RcRefCell<T> {
 #[cfg(test)]
 mod tests {
     use super::span::Span;
-    use super::BinarySearchBree;
+    use super::{BinarySearchBree, Hook};
+    use counting_allocator::{count, CountingAllocator, Counts};
+    use invariant::Property;
+    use memory_usage::{rc_bytes, MemoryUsage};
+    use paren::{FromParen, Paren};
     use rand::prelude::*;
+    use std::cell::RefCell;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_paren() {
+        // CLRS Figure 12.4 (d): the successor is not the right child.
+        let mut bst = BinarySearchBree::from_paren("((2)5(((6)7)12(13)))").unwrap();
+        assert_eq!(bst.check_invariants(), Ok(()));
+        bst.delete(5);
+        assert_eq!(bst.to_paren(), "((2)6((7)12(13)))");
+        assert_eq!(bst.collect_vec(), vec![2, 6, 7, 12, 13]);
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let check =
+            |bst: &BinarySearchBree| bst.check_invariants().map_err(|e| (e.property, e.path));
+        let bst = BinarySearchBree::from_paren("((2)5(((6)7)12(13)))").unwrap();
+        assert_eq!(check(&bst), Ok(()));
+        let root = bst.root.as_ref().unwrap().borrow();
+        root.children[1].as_ref().unwrap().borrow_mut().parent = None;
+        assert_eq!(check(&bst), Err((Property::ParentPointer, vec![1])));
+        let bst = BinarySearchBree::from_paren("((2)5(((8)7)12(13)))").unwrap();
+        assert_eq!(check(&bst), Err((Property::BstOrder, vec![1, 0])));
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let node = rc_bytes::<RefCell<Hook>>();
+        let mut bst = BinarySearchBree::new();
+        assert_eq!(bst.heap_size_bytes(), 0);
+        for key in [5, 2, 12, 6, 13, 7].iter() {
+            bst.insert(*key);
+        }
+        assert_eq!(bst.heap_size_bytes(), 6 * node);
+        bst.delete(5);
+        assert_eq!(bst.heap_size_bytes(), 5 * node);
+    }
+
+    #[test]
+    fn test_allocations() {
+        // 節点ひとつにつき一回です。
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut bst = BinarySearchBree::new();
+        let mut keys = (0..100).collect::<Vec<_>>();
+        keys.shuffle(&mut rng);
+        for &key in &keys {
+            let ((), counts) = count(|| bst.insert(key));
+            assert_eq!(
+                counts,
+                Counts {
+                    allocs: 1,
+                    ..Counts::default()
+                }
+            );
+        }
+        let (_, counts) = count(|| bst.get(42));
+        assert_eq!(counts, Counts::default());
+        keys.shuffle(&mut rng);
+        for &key in &keys {
+            let (_, counts) = count(|| bst.delete(key));
+            assert_eq!(
+                counts,
+                Counts {
+                    deallocs: 1,
+                    ..Counts::default()
+                }
+            );
+        }
+    }
 
     #[test]
     fn test_hand() {
@@ -331,6 +447,7 @@ mod tests {
                 assert_eq!(&bst.collect_vec(), &vec);
                 assert_eq!(bst.first(), vec.first().copied());
                 assert_eq!(bst.last(), vec.last().copied());
+                assert_eq!(bst.check_invariants(), Ok(()));
                 println!();
             }
         }
@@ -1,6 +1,12 @@
-pub mod paren;
+mod memory;
+mod multiset;
+mod paren;
+mod validate;
+
+pub use multiset::BTreeMultiset;
 
 use std::{
+    borrow::Borrow,
     cmp::Ordering,
     collections::VecDeque,
     fmt::Debug,
@@ -42,6 +48,26 @@ impl<K: Ord + Debug> BTree<K> {
         }
         res
     }
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        self.0.get(key)
+    }
+    // the smallest key greater than `key`
+    pub fn succ<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        self.0.succ(key)
+    }
+    // the largest key less than `key`
+    pub fn prev<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        self.0.prev(key)
+    }
     pub fn collect_vec(&self) -> Vec<K>
     where
         K: Clone,
@@ -50,6 +76,13 @@ impl<K: Ord + Debug> BTree<K> {
         self.0.collect_vec(&mut vec);
         vec
     }
+    // must not change the order of the key
+    fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut K>
+    where
+        K: Borrow<Q>,
+    {
+        self.0.get_mut(key)
+    }
 }
 impl<K: Ord + Debug> Default for BTree<K> {
     fn default() -> Self {
@@ -129,6 +162,57 @@ impl<K: Ord + Debug> Node<K> {
             }
         }
     }
+    fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        match linear_search(&self.keys, key) {
+            Ok(pos) => Some(&self.keys[pos]),
+            Err(_) if self.is_leaf() => None,
+            Err(pos) => self.child[pos].get(key),
+        }
+    }
+    fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut K>
+    where
+        K: Borrow<Q>,
+    {
+        match linear_search(&self.keys, key) {
+            Ok(pos) => Some(&mut self.keys[pos]),
+            Err(_) if self.is_leaf() => None,
+            Err(pos) => self.child[pos].get_mut(key),
+        }
+    }
+    fn succ<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        let pos = self
+            .keys
+            .iter()
+            .position(|k| key < k.borrow())
+            .unwrap_or(self.keys.len());
+        if self.is_leaf() {
+            self.keys.get(pos)
+        } else {
+            self.child[pos].succ(key).or_else(|| self.keys.get(pos))
+        }
+    }
+    fn prev<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        let pos = self
+            .keys
+            .iter()
+            .position(|k| key <= k.borrow())
+            .unwrap_or(self.keys.len());
+        let here = pos.checked_sub(1).map(|i| &self.keys[i]);
+        if self.is_leaf() {
+            here
+        } else {
+            self.child[pos].prev(key).or(here)
+        }
+    }
     fn delete_first(&mut self) -> K {
         if self.is_leaf() {
             self.keys.pop_front().unwrap()
@@ -230,9 +314,9 @@ impl<K: Ord + Debug> Node<K> {
         (mid, Self { keys, child })
     }
 }
-fn linear_search<K: Ord>(v: &VecDeque<K>, key: &K) -> Result<usize, usize> {
-    if let Some(i) = v.iter().position(|k| key <= k) {
-        if v[i] == *key {
+fn linear_search<K: Borrow<Q>, Q: Ord + ?Sized>(v: &VecDeque<K>, key: &Q) -> Result<usize, usize> {
+    if let Some(i) = v.iter().position(|k| key <= k.borrow()) {
+        if v[i].borrow() == key {
             Ok(i)
         } else {
             Err(i)
@@ -244,7 +328,117 @@ fn linear_search<K: Ord>(v: &VecDeque<K>, key: &K) -> Result<usize, usize> {
 
 #[cfg(test)]
 mod tests {
-    use {super::BTree, rand::prelude::*, yansi::Paint};
+    use {
+        super::{BTree, BTreeMultiset, Node},
+        counting_allocator::{count, CountingAllocator, Counts},
+        invariant::Property,
+        memory_usage::MemoryUsage,
+        paren::{FromParen, Paren, Wrapper},
+        rand::prelude::*,
+        std::mem::size_of,
+        yansi::Paint,
+    };
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_paren() {
+        let mut bt = BTree::new();
+        (0..10).for_each(|i| {
+            bt.insert(i);
+        });
+        let expected = "[[0,1,2]3[4,5,6,7,8,9]]";
+        assert_eq!(bt.to_paren(), expected);
+
+        let mut test = Test {
+            bt: BTree::from_paren("[[0,1,2]3[4,5,6]7[8,9,10]]").unwrap(),
+            vec: (0..11).collect(),
+        };
+        test.postprocess();
+        test.delete(4);
+        assert_eq!(test.bt.to_paren(), "[[0,1,2]3[5,6,7,8,9,10]]");
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let check = |s: &str| {
+            BTree::<u32>::from_paren(s)
+                .unwrap()
+                .check_invariants()
+                .map_err(|e| (e.property, e.path))
+        };
+        assert_eq!(check("[[0,1,2]3[4,5,6]7[8,9,10]]"), Ok(()));
+        assert_eq!(
+            check("[[0,1,2]3[4,5]7[8,9,10]]"),
+            Err((Property::BTreeFill, vec![1]))
+        );
+        assert_eq!(
+            check("[[0,1,2]3[4,5,6]2[8,9,10]]"),
+            Err((Property::BstOrder, vec![]))
+        );
+        assert_eq!(
+            check("[[0,1,2]3[4,5,6]7[[8,9,10]11[12,13,14]15[16,17,18]19[20,21,22]]]"),
+            Err((Property::BTreeShape, vec![2, 0]))
+        );
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let mut bt = BTree::new();
+        assert_eq!(bt.heap_size_bytes(), 0);
+        (0..10).for_each(|i| {
+            bt.insert(i);
+        });
+        // [[0,1,2]3[4,5,6,7,8,9]]
+        let bytes = bt.heap_size_bytes();
+        assert!(bytes >= 2 * size_of::<Box<Node<u32>>>() + 2 * size_of::<Node<u32>>() + 10 * 4);
+
+        // A count is kept in the entry, and takes no more space.
+        let mut ms = BTreeMultiset::new();
+        ms.insert("x".repeat(100));
+        let bytes = ms.heap_size_bytes();
+        assert!(bytes >= 100);
+        ms.insert("x".repeat(100));
+        assert_eq!(ms.heap_size_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_allocations() {
+        fn nodes<K>(node: &Node<K>) -> usize {
+            1 + node.child.iter().map(|child| nodes(child)).sum::<usize>()
+        }
+        let mut bt = BTree::new();
+        (0..10).for_each(|i| {
+            bt.insert(i);
+        });
+        // [[0,1,2]3[4,5,6,7,8,9]]
+        // A leaf with room may grow its keys, but allocates nothing new.
+        let (_, counts) = count(|| bt.insert(10).is_some());
+        assert_eq!(counts.allocs + counts.deallocs, 0);
+        // A leaf with more than the fewest keys does not merge.
+        let (_, counts) = count(|| bt.delete(10));
+        assert_eq!(counts, Counts::default());
+        let (_, counts) = count(|| bt.get(&5).is_some());
+        assert_eq!(counts, Counts::default());
+
+        // A split allocates a node with its keys and children, and a merge frees them.
+        let mut bt = BTree::new();
+        let ((), counts) = count(|| {
+            (0..1000).for_each(|i| {
+                bt.insert(i);
+            })
+        });
+        let n = nodes(&bt.0);
+        assert!(counts.allocs <= 3 * n, "{:?} for {} nodes", counts, n);
+        let ((), counts) = count(|| {
+            (0..1000).for_each(|i| {
+                bt.delete(i);
+            })
+        });
+        assert!(counts.deallocs <= 3 * n, "{:?} for {} nodes", counts, n);
+        assert_eq!(counts.allocs, 0);
+    }
 
     // -- unittest delete
 
@@ -341,8 +535,9 @@ mod tests {
             self.postprocess();
         }
         fn postprocess(&self) {
-            println!("paren = {:?}", super::paren::Wrapper(&self.bt));
+            println!("paren = {:?}", Wrapper(&self.bt));
             println!("bt = {:?}", &self.bt);
+            self.bt.check_invariants().unwrap();
             assert_eq!(
                 &self.bt.collect_vec().iter().copied().collect::<Vec<_>>(),
                 &self.vec
@@ -0,0 +1,16 @@
+use {
+    super::{BTree, Node},
+    memory_usage::MemoryUsage,
+};
+
+impl<K: MemoryUsage> MemoryUsage for BTree<K> {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes()
+    }
+}
+// The `VecDeque`s are counted by their capacities, which are mostly more than `MAX_KEYS`.
+impl<K: MemoryUsage> MemoryUsage for Node<K> {
+    fn heap_size_bytes(&self) -> usize {
+        self.keys.heap_size_bytes() + self.child.heap_size_bytes()
+    }
+}
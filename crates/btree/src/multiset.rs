@@ -0,0 +1,251 @@
+use {
+    super::BTree,
+    invariant::InvariantViolation,
+    memory_usage::MemoryUsage,
+    std::{
+        borrow::Borrow,
+        cmp::Ordering,
+        fmt::{self, Debug},
+        iter::repeat_n,
+    },
+};
+
+#[derive(Debug)]
+pub struct BTreeMultiset<K> {
+    tree: BTree<Entry<K>>,
+    len: usize,
+}
+impl<K: Ord + Debug> BTreeMultiset<K> {
+    pub fn new() -> Self {
+        Self {
+            tree: BTree::new(),
+            len: 0,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn count(&self, key: &K) -> usize {
+        self.tree.get(key).map_or(0, |entry| entry.count)
+    }
+    pub fn contains(&self, key: &K) -> bool {
+        self.tree.get(key).is_some()
+    }
+    // keys whose count reaches zero are deleted from the tree, so they are never visited.
+    pub fn succ(&self, key: &K) -> Option<&K> {
+        self.tree.succ(key).map(|entry| &entry.key)
+    }
+    pub fn prev(&self, key: &K) -> Option<&K> {
+        self.tree.prev(key).map(|entry| &entry.key)
+    }
+    pub fn insert(&mut self, key: K) {
+        if let Some(entry) = self.tree.get_mut(&key) {
+            entry.count += 1;
+        } else {
+            self.tree.insert(Entry { key, count: 1 });
+        }
+        self.len += 1;
+    }
+    pub fn remove_one(&mut self, key: K) -> bool {
+        match self.tree.get_mut(&key) {
+            None => false,
+            Some(entry) if entry.count == 1 => {
+                self.remove_all(key);
+                true
+            }
+            Some(entry) => {
+                entry.count -= 1;
+                self.len -= 1;
+                true
+            }
+        }
+    }
+    pub fn remove_all(&mut self, key: K) -> usize {
+        let count = self
+            .tree
+            .delete(Entry { key, count: 0 })
+            .map_or(0, |entry| entry.count);
+        self.len -= count;
+        count
+    }
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.tree.check_invariants()
+    }
+    pub fn collect_vec(&self) -> Vec<K>
+    where
+        K: Clone,
+    {
+        self.tree
+            .collect_vec()
+            .into_iter()
+            .flat_map(|entry| repeat_n(entry.key, entry.count))
+            .collect()
+    }
+}
+impl<K: Ord + Debug> Default for BTreeMultiset<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// compared only by `key` so that `count` can be rewritten in place
+#[derive(Clone)]
+struct Entry<K> {
+    key: K,
+    count: usize,
+}
+impl<K: Debug> Debug for Entry<K> {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        write!(w, "{:?}x{}", &self.key, self.count)
+    }
+}
+impl<K> Borrow<K> for Entry<K> {
+    fn borrow(&self) -> &K {
+        &self.key
+    }
+}
+impl<K: Ord> PartialEq for Entry<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+impl<K: Ord> Eq for Entry<K> {}
+impl<K: Ord> PartialOrd for Entry<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<K: Ord> Ord for Entry<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl<K: MemoryUsage> MemoryUsage for BTreeMultiset<K> {
+    fn heap_size_bytes(&self) -> usize {
+        self.tree.heap_size_bytes()
+    }
+}
+impl<K: MemoryUsage> MemoryUsage for Entry<K> {
+    fn heap_size_bytes(&self) -> usize {
+        self.key.heap_size_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::BTreeMultiset,
+        rand::prelude::*,
+        std::{collections::BTreeMap, iter::repeat_n},
+        yansi::Paint,
+    };
+
+    #[test]
+    fn test_remove_one_until_empty() {
+        let mut test = Test::new();
+        (0..3).for_each(|_| test.insert(5));
+        test.insert(4);
+        (0..4).for_each(|_| test.remove_one(5));
+        test.remove_all(4);
+    }
+
+    #[test]
+    fn test_rand_small() {
+        test_rand(100, 20, 42);
+    }
+    #[test]
+    fn test_rand_large() {
+        test_rand(10, 1000, 91);
+    }
+
+    fn test_rand(t: u32, q: u32, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for _ in 0..t {
+            let mut test = Test::new();
+            for _ in 0..q {
+                match rng.gen_range(0, 6) {
+                    0 | 1 => test.insert(rng.gen_range(0, 30)),
+                    2 => test.remove_one(rng.gen_range(0, 30)),
+                    3 => test.remove_all(rng.gen_range(0, 30)),
+                    4 => test.succ(rng.gen_range(0, 30)),
+                    5 => test.prev(rng.gen_range(0, 30)),
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
+    struct Test {
+        ms: BTreeMultiset<u32>,
+        map: BTreeMap<u32, usize>,
+    }
+    impl Test {
+        fn new() -> Self {
+            Self {
+                ms: BTreeMultiset::new(),
+                map: BTreeMap::new(),
+            }
+        }
+        fn insert(&mut self, x: u32) {
+            println!("{}", Paint::red(format!("Insert {:?}", &x)).bold());
+            self.ms.insert(x);
+            *self.map.entry(x).or_insert(0) += 1;
+            self.postprocess();
+        }
+        fn remove_one(&mut self, x: u32) {
+            println!("{}", Paint::blue(format!("Remove one {:?}", &x)).bold());
+            let result = self.ms.remove_one(x);
+            let expected = match self.map.get_mut(&x) {
+                Some(c) => {
+                    *c -= 1;
+                    if *c == 0 {
+                        self.map.remove(&x);
+                    }
+                    true
+                }
+                None => false,
+            };
+            assert_eq!(result, expected);
+            self.postprocess();
+        }
+        fn remove_all(&mut self, x: u32) {
+            println!("{}", Paint::blue(format!("Remove all {:?}", &x)).bold());
+            let result = self.ms.remove_all(x);
+            let expected = self.map.remove(&x).unwrap_or(0);
+            assert_eq!(result, expected);
+            self.postprocess();
+        }
+        fn succ(&self, x: u32) {
+            println!("{}", Paint::yellow(format!("Succ {:?}", &x)).bold());
+            assert_eq!(
+                self.ms.succ(&x),
+                self.map.range(x + 1..).next().map(|(x, _)| x)
+            );
+        }
+        fn prev(&self, x: u32) {
+            println!("{}", Paint::yellow(format!("Prev {:?}", &x)).bold());
+            assert_eq!(
+                self.ms.prev(&x),
+                self.map.range(..x).next_back().map(|(x, _)| x)
+            );
+        }
+        fn postprocess(&self) {
+            println!("paren = {:?}", paren::Wrapper(&self.ms.tree));
+            self.ms.check_invariants().unwrap();
+            for (x, &c) in &self.map {
+                assert_eq!(self.ms.count(x), c);
+            }
+            let expected = self
+                .map
+                .iter()
+                .flat_map(|(&x, &c)| repeat_n(x, c))
+                .collect::<Vec<_>>();
+            assert_eq!(&self.ms.collect_vec(), &expected);
+            assert_eq!(self.ms.len(), expected.len());
+        }
+    }
+}
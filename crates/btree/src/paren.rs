@@ -1,28 +1,12 @@
 use {
     super::{BTree, Node},
-    std::fmt::{self, Debug},
+    paren::{FromParen, Paren, ParseError, Shape, Visualize},
+    std::{
+        fmt::{self, Debug},
+        str::FromStr,
+    },
 };
 
-// impl<K: Ord + Debug> Debug for BTree<K> {
-//     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-//         w.debug_tuple("BTree").field(&Wrapper(self)).finish()
-//     }
-// }
-// impl<K: Ord + Debug> Debug for Node<K> {
-//     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-//         w.debug_tuple("Node").field(&Wrapper(self)).finish()
-//     }
-// }
-pub struct Wrapper<'a, T>(pub &'a T);
-impl<'a, T: Paren> Debug for Wrapper<'a, T> {
-    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        self.0.paren(w)
-    }
-}
-
-pub trait Paren {
-    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result;
-}
 impl<K: Ord + Debug> Paren for BTree<K> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
         self.0.paren(w)
@@ -48,3 +32,40 @@ impl<K: Ord + Debug> Paren for Node<K> {
         write!(w, "]")
     }
 }
+
+impl<K: Ord + Debug> Visualize for BTree<K> {
+    fn shapes(&self) -> Vec<Shape> {
+        vec![self.0.shape()]
+    }
+}
+impl<K: Ord + Debug> Node<K> {
+    fn shape(&self) -> Shape {
+        self.child
+            .iter()
+            .fold(Shape::new(&self.keys), |shape, child| {
+                shape.child(Some(child.shape()))
+            })
+    }
+}
+
+impl<K: Ord + Debug + FromStr> FromParen for BTree<K> {
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        Node::from_parsed(paren::parse_btree(s)?).map(Self)
+    }
+}
+impl<K: Ord + Debug + FromStr> Node<K> {
+    fn from_parsed(node: paren::BNode) -> Result<Self, ParseError> {
+        Ok(Self {
+            keys: node
+                .keys
+                .iter()
+                .map(|label| label.key())
+                .collect::<Result<_, _>>()?,
+            child: node
+                .child
+                .into_iter()
+                .map(|child| Self::from_parsed(child).map(Box::new))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use {
+    super::{BTree, Node, MAX_KEYS, MIN_KEYS},
+    invariant::{ensure, InvariantViolation, Property},
+    std::fmt::Debug,
+};
+
+impl<K: Ord + Debug> BTree<K> {
+    // the order of the keys, the number of the keys and the children, and the depths of the
+    // leaves
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        let mut check = Check {
+            path: Vec::new(),
+            leaf_depth: None,
+            prev: None,
+        };
+        check.node(&self.0)
+    }
+}
+
+struct Check<'a, K> {
+    path: Vec<usize>,
+    leaf_depth: Option<usize>,
+    // the last key in the in-order
+    prev: Option<&'a K>,
+}
+impl<'a, K: Ord + Debug> Check<'a, K> {
+    fn node(&mut self, x: &'a Node<K>) -> Result<(), InvariantViolation> {
+        let path = &self.path;
+        let min = if path.is_empty() { 0 } else { MIN_KEYS };
+        ensure(
+            min <= x.keys.len() && x.keys.len() <= MAX_KEYS,
+            Property::BTreeFill,
+            path,
+        )?;
+        if x.is_leaf() {
+            let depth = *self.leaf_depth.get_or_insert(path.len());
+            ensure(depth == path.len(), Property::BTreeShape, path)?;
+            for key in &x.keys {
+                self.key(key)?;
+            }
+        } else {
+            ensure(
+                x.child.len() == x.keys.len() + 1,
+                Property::BTreeShape,
+                path,
+            )?;
+            for (i, child) in x.child.iter().enumerate() {
+                self.path.push(i);
+                self.node(child)?;
+                self.path.pop();
+                if let Some(key) = x.keys.get(i) {
+                    self.key(key)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    fn key(&mut self, key: &'a K) -> Result<(), InvariantViolation> {
+        if let Some(prev) = self.prev {
+            ensure(prev < key, Property::BstOrder, &self.path)?;
+        }
+        self.prev = Some(key);
+        Ok(())
+    }
+}
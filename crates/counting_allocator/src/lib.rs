@@ -0,0 +1,154 @@
+// Counts the calls to the allocator, so that a test can assert how many an operation makes. A test
+// binary installs it by
+//
+//     #[global_allocator]
+//     static ALLOCATOR: CountingAllocator = CountingAllocator;
+//
+// The counts are of each thread, so that the tests running in parallel do not disturb each other.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    ops::Sub,
+};
+
+pub struct CountingAllocator;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub allocs: usize,
+    pub deallocs: usize,
+    pub reallocs: usize,
+}
+impl Counts {
+    pub fn total(&self) -> usize {
+        self.allocs + self.deallocs + self.reallocs
+    }
+}
+impl Sub for Counts {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            allocs: self.allocs - rhs.allocs,
+            deallocs: self.deallocs - rhs.deallocs,
+            reallocs: self.reallocs - rhs.reallocs,
+        }
+    }
+}
+
+thread_local! {
+    // A `const` one is never lazily initialized, which could allocate on its own.
+    static COUNTS: Cell<Counts> = const {
+        Cell::new(Counts {
+            allocs: 0,
+            deallocs: 0,
+            reallocs: 0,
+        })
+    };
+}
+
+// Nothing is counted while the thread is being torn down.
+fn bump(f: impl FnOnce(&mut Counts)) {
+    let _ = COUNTS.try_with(|counts| {
+        let mut x = counts.get();
+        f(&mut x);
+        counts.set(x);
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        bump(|counts| counts.allocs += 1);
+        System.alloc(layout)
+    }
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        bump(|counts| counts.allocs += 1);
+        System.alloc_zeroed(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        bump(|counts| counts.deallocs += 1);
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        bump(|counts| counts.reallocs += 1);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+// the calls on this thread so far
+pub fn counts() -> Counts {
+    COUNTS.with(Cell::get)
+}
+
+// Runs `f`, and returns the calls it has made. The result is dropped by the caller, out of the
+// counts.
+pub fn count<T>(f: impl FnOnce() -> T) -> (T, Counts) {
+    let before = counts();
+    let x = f();
+    (x, counts() - before)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{count, CountingAllocator, Counts},
+        std::{hint::black_box, thread},
+    };
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_count() {
+        let (x, counts) = count(|| Box::new(42));
+        assert_eq!(
+            counts,
+            Counts {
+                allocs: 1,
+                ..Counts::default()
+            }
+        );
+        let ((), counts) = count(|| drop(x));
+        assert_eq!(
+            counts,
+            Counts {
+                deallocs: 1,
+                ..Counts::default()
+            }
+        );
+
+        let (vec, counts) = count(|| {
+            let mut vec = Vec::with_capacity(1);
+            vec.extend(0..2);
+            vec
+        });
+        assert_eq!(vec, vec![0, 1]);
+        assert_eq!(
+            counts,
+            Counts {
+                allocs: 1,
+                reallocs: 1,
+                ..Counts::default()
+            }
+        );
+        assert_eq!(counts.total(), 2);
+
+        let (_, counts) = count(|| 1 + 1);
+        assert_eq!(counts.total(), 0);
+    }
+
+    // The other threads are not counted.
+    #[test]
+    fn test_threads() {
+        let ((), counts) = count(|| {
+            thread::spawn(|| {
+                let ((), counts) =
+                    count(|| (0..100).map(|x| black_box(Box::new(x))).for_each(drop));
+                assert_eq!(counts.allocs, 100);
+                assert_eq!(counts.deallocs, 100);
+            })
+            .join()
+            .unwrap()
+        });
+        assert!(counts.allocs < 100, "{:?}", counts);
+    }
+}
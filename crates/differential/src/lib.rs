@@ -0,0 +1,418 @@
+mod subjects;
+
+pub use subjects::Persistent;
+
+use {
+    invariant::InvariantViolation,
+    rand::prelude::*,
+    std::{
+        any::Any,
+        cell::Cell,
+        collections::BTreeMap,
+        fmt::{self, Debug, Display, Formatter},
+        panic::{catch_unwind, AssertUnwindSafe},
+    },
+};
+
+// The keys are less than this, so that the van Emde Boas trees hold them all.
+pub const KEYS: u32 = 1 << subjects::LG;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Insert(u32),
+    // one of the keys if any
+    Delete(u32),
+    Get(u32),
+    // the keys in `l..r`
+    Range(u32, u32),
+    // Splits off the keys at least `key`, and merges them back.
+    Split(u32),
+}
+impl Op {
+    // the same operations with smaller keys, to shrink a failing sequence
+    fn simpler(self) -> Vec<Op> {
+        fn smaller(key: u32) -> Vec<u32> {
+            let mut vec = vec![0, key / 2, key.saturating_sub(1)];
+            vec.dedup();
+            vec.retain(|&x| x < key);
+            vec
+        }
+        match self {
+            Op::Insert(key) => smaller(key).into_iter().map(Op::Insert).collect(),
+            Op::Delete(key) => smaller(key).into_iter().map(Op::Delete).collect(),
+            Op::Get(key) => smaller(key).into_iter().map(Op::Get).collect(),
+            Op::Range(l, r) => smaller(l)
+                .into_iter()
+                .map(|l| Op::Range(l, r))
+                .chain(smaller(r).into_iter().map(|r| Op::Range(l, r)))
+                .collect(),
+            Op::Split(key) => smaller(key).into_iter().map(Op::Split).collect(),
+        }
+    }
+}
+
+// An ordered set or multiset of `u32` under test. The operations returning `None` are not
+// supported, and are skipped.
+pub trait Subject: Sized {
+    const NAME: &'static str;
+    // whether `insert` keeps the duplicates
+    const MULTISET: bool;
+    fn new() -> Self;
+    fn insert(&mut self, key: u32);
+    // whether a key is deleted
+    fn delete(&mut self, key: u32) -> Option<bool>;
+    fn to_vec(&self) -> Vec<u32>;
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        Ok(())
+    }
+    fn get(&self, _key: u32) -> Option<bool> {
+        None
+    }
+    fn range(&self, _l: u32, _r: u32) -> Option<Vec<u32>> {
+        None
+    }
+    fn split_off(&mut self, _key: u32) -> Option<Self> {
+        None
+    }
+    // called only after `split_off` succeeds
+    fn append(&mut self, _other: Self) {
+        unreachable!()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub subject: &'static str,
+    // the index of the operation
+    pub step: usize,
+    pub message: String,
+}
+impl Display for Failure {
+    fn fmt(&self, w: &mut Formatter) -> fmt::Result {
+        write!(
+            w,
+            "{} at step {}: {}",
+            self.subject, self.step, self.message
+        )
+    }
+}
+
+pub type Check = fn(&[Op]) -> Result<(), Failure>;
+
+macro_rules! subjects {
+    ($($subject:ty),* $(,)?) => {
+        &[$((<$subject as Subject>::NAME, check::<$subject> as Check)),*]
+    };
+}
+pub const SUBJECTS: &[(&str, Check)] = subjects![
+    avl_tree::AVLTree<u32, ()>,
+    binary_search_trees::BinarySearchBree,
+    btree::BTree<u32>,
+    btree::BTreeMultiset<u32>,
+    foldable_llrb_tree::LLRB<u32, u64>,
+    llrb::LLRB<u32, ()>,
+    merge_based_rbtree::RBTree<u32>,
+    mergible_rbtree::RBTree<u32, ()>,
+    nilless_parentless_rbtree::RBTree<u32, ()>,
+    order_statistic_llrb_tree::LLRB<u32, ()>,
+    parentless_rbtree::RBTree<u32, ()>,
+    Persistent,
+    red_black_tree::RBTree<u32, ()>,
+    red_black_tree::arena::RBTree<u32, ()>,
+    reduced_space_van_emde_boas::Veb,
+    reduced_space_van_emde_boas::VebMultiset,
+    treap::Treap<u32, (), StdRng>,
+    van_emde_boas::Veb,
+    van_emde_boas::VebMultiset,
+];
+
+// Runs `ops` on `S` and on a `BTreeMap`, and compares them after every step. A panic is caught
+// and reported as a failure.
+pub fn check<S: Subject>(ops: &[Op]) -> Result<(), Failure> {
+    let step = Cell::new(0);
+    let res = catch_unwind(AssertUnwindSafe(|| run::<S>(ops, &step)))
+        .unwrap_or_else(|payload| Err(format!("panicked: {}", panic_message(&*payload))));
+    res.map_err(|message| Failure {
+        subject: S::NAME,
+        step: step.get(),
+        message,
+    })
+}
+pub fn check_all(ops: &[Op]) -> Result<(), Failure> {
+    SUBJECTS.iter().try_for_each(|(_, check)| check(ops))
+}
+
+// the first failure, and the operations shrunk to still fail the same implementation
+pub fn reproduce(ops: &[Op]) -> Result<(), (Failure, Vec<Op>)> {
+    for (_, check) in SUBJECTS {
+        if check(ops).is_err() {
+            let ops = shrink(ops.to_vec(), |ops| check(ops).is_err());
+            return Err((check(&ops).unwrap_err(), ops));
+        }
+    }
+    Ok(())
+}
+
+// Shrinks `ops` while `fails` holds: drops chunks of the operations, from the whole to single
+// ones, and then makes the keys smaller one by one.
+pub fn shrink(mut ops: Vec<Op>, mut fails: impl FnMut(&[Op]) -> bool) -> Vec<Op> {
+    let mut chunk = ops.len();
+    while chunk != 0 {
+        let mut i = 0;
+        while i < ops.len() {
+            let mut candidate = ops.clone();
+            candidate.drain(i..ops.len().min(i + chunk));
+            if fails(&candidate) {
+                ops = candidate;
+            } else {
+                i += chunk;
+            }
+        }
+        chunk /= 2;
+    }
+    for i in 0..ops.len() {
+        while let Some(op) = ops[i].simpler().into_iter().find(|&op| {
+            let mut candidate = ops.clone();
+            candidate[i] = op;
+            fails(&candidate)
+        }) {
+            ops[i] = op;
+        }
+    }
+    ops
+}
+
+pub fn random_ops(rng: &mut impl Rng, len: usize, keys: u32) -> Vec<Op> {
+    assert!(keys <= KEYS);
+    (0..len)
+        .map(|_| match rng.gen_range(0, 10) {
+            0..=3 => Op::Insert(rng.gen_range(0, keys)),
+            4..=6 => Op::Delete(rng.gen_range(0, keys)),
+            7 => Op::Get(rng.gen_range(0, keys)),
+            8 => Op::Range(rng.gen_range(0, keys), rng.gen_range(0, keys)),
+            9 => Op::Split(rng.gen_range(0, keys)),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+// three bytes for each operation: the kind, and the keys
+pub fn ops_from_bytes(data: &[u8]) -> Vec<Op> {
+    data.chunks_exact(3)
+        .map(|b| {
+            let (x, y) = (u32::from(b[1]), u32::from(b[2]));
+            match b[0] % 10 {
+                0..=3 => Op::Insert(x),
+                4..=6 => Op::Delete(x),
+                7 => Op::Get(x),
+                8 => Op::Range(x, y),
+                9 => Op::Split(x),
+                _ => unreachable!(),
+            }
+        })
+        .collect()
+}
+
+// the body of the `cargo fuzz` target
+pub fn fuzz(data: &[u8]) {
+    if let Err((failure, ops)) = reproduce(&ops_from_bytes(data)) {
+        panic!("{}\nminimal reproduction: {:?}", failure, ops);
+    }
+}
+
+fn run<S: Subject>(ops: &[Op], step: &Cell<usize>) -> Result<(), String> {
+    let mut subject = S::new();
+    let mut model = Model(BTreeMap::new());
+    for (i, &op) in ops.iter().enumerate() {
+        step.set(i);
+        match op {
+            Op::Insert(key) => {
+                subject.insert(key);
+                model.insert(key, S::MULTISET);
+            }
+            Op::Delete(key) => {
+                let expected = model.delete(key);
+                if let Some(deleted) = subject.delete(key) {
+                    compare("delete", deleted, expected)?;
+                }
+            }
+            Op::Get(key) => {
+                if let Some(found) = subject.get(key) {
+                    compare("get", found, model.0.contains_key(&key))?;
+                }
+            }
+            Op::Range(l, r) => {
+                if let Some(vec) = subject.range(l, r) {
+                    compare("range", vec, model.range(l, r))?;
+                }
+            }
+            Op::Split(key) => {
+                if let Some(right) = subject.split_off(key) {
+                    subject.check_invariants().map_err(|e| e.to_string())?;
+                    right.check_invariants().map_err(|e| e.to_string())?;
+                    compare("the left half", subject.to_vec(), model.range(0, key))?;
+                    compare("the right half", right.to_vec(), model.range(key, KEYS))?;
+                    subject.append(right);
+                }
+            }
+        }
+        subject.check_invariants().map_err(|e| e.to_string())?;
+        compare("the keys", subject.to_vec(), model.range(0, KEYS))?;
+    }
+    Ok(())
+}
+
+fn compare<T: PartialEq + Debug>(what: &str, result: T, expected: T) -> Result<(), String> {
+    if result == expected {
+        Ok(())
+    } else {
+        Err(format!("{} = {:?}, expected {:?}", what, result, expected))
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown".to_owned()
+    }
+}
+
+// the counts of the keys
+struct Model(BTreeMap<u32, usize>);
+impl Model {
+    fn insert(&mut self, key: u32, multiset: bool) {
+        let count = self.0.entry(key).or_insert(0);
+        if multiset || *count == 0 {
+            *count += 1;
+        }
+    }
+    fn delete(&mut self, key: u32) -> bool {
+        match self.0.get_mut(&key) {
+            None => false,
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.0.remove(&key);
+                }
+                true
+            }
+        }
+    }
+    fn range(&self, l: u32, r: u32) -> Vec<u32> {
+        if l < r {
+            self.0
+                .range(l..r)
+                .flat_map(|(&key, &count)| vec![key; count])
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            check, check_all, random_ops, reproduce, shrink, Failure, Op, Subject, KEYS, SUBJECTS,
+        },
+        rand::prelude::*,
+    };
+
+    #[test]
+    fn test_rand() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for &(_, check) in SUBJECTS {
+            for _ in 0..20 {
+                let keys = *[4, 16, KEYS].choose(&mut rng).unwrap();
+                let ops = random_ops(&mut rng, 200, keys);
+                if check(&ops).is_err() {
+                    let ops = shrink(ops, |ops| check(ops).is_err());
+                    let failure = check(&ops).unwrap_err();
+                    panic!("{}\nminimal reproduction: {:?}", failure, ops);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_shrink() {
+        // It fails when 3 is deleted after inserted twice.
+        let fails = |ops: &[Op]| {
+            let mut count = 0;
+            ops.iter().any(|&op| {
+                match op {
+                    Op::Insert(3) => count += 1,
+                    Op::Delete(3) => return count >= 2,
+                    _ => (),
+                }
+                false
+            })
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let ops = [
+            random_ops(&mut rng, 50, 8),
+            vec![Op::Insert(3), Op::Insert(3), Op::Delete(3)],
+        ]
+        .concat();
+        assert!(fails(&ops));
+        assert_eq!(
+            shrink(ops, fails),
+            vec![Op::Insert(3), Op::Insert(3), Op::Delete(3)]
+        );
+    }
+
+    // A set that forgets the keys greater than 5.
+    struct Forgetful(Vec<u32>);
+    impl Subject for Forgetful {
+        const NAME: &'static str = "forgetful";
+        const MULTISET: bool = false;
+        fn new() -> Self {
+            Self(Vec::new())
+        }
+        fn insert(&mut self, key: u32) {
+            if key <= 5 && !self.0.contains(&key) {
+                self.0.push(key);
+                self.0.sort();
+            }
+        }
+        fn delete(&mut self, key: u32) -> Option<bool> {
+            let len = self.0.len();
+            self.0.retain(|&x| x != key);
+            Some(self.0.len() != len)
+        }
+        fn to_vec(&self) -> Vec<u32> {
+            self.0.clone()
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let ops = [Op::Insert(1), Op::Delete(1), Op::Insert(2), Op::Insert(9)];
+        assert_eq!(
+            check::<Forgetful>(&ops),
+            Err(Failure {
+                subject: "forgetful",
+                step: 3,
+                message: "the keys = [2], expected [2, 9]".to_owned(),
+            })
+        );
+        let ops = shrink(ops.to_vec(), |ops| check::<Forgetful>(ops).is_err());
+        assert_eq!(ops, vec![Op::Insert(6)]);
+        assert_eq!(reproduce(&[Op::Insert(3), Op::Split(2)]), Ok(()));
+    }
+
+    #[test]
+    fn test_check_all() {
+        // inserts and deletes enough to rebalance, and splits and merges back
+        let ops = (0..16)
+            .map(Op::Insert)
+            .chain((0..16).step_by(3).map(Op::Split))
+            .chain((0..16).map(Op::Delete))
+            .collect::<Vec<_>>();
+        assert_eq!(check_all(&ops), Ok(()));
+    }
+}
@@ -0,0 +1,475 @@
+use {super::Subject, invariant::InvariantViolation, rand::prelude::*};
+
+// the universe of the van Emde Boas trees
+pub const LG: u32 = 8;
+
+// the keys in `l..r`, visited by `succ`
+fn range_by_succ(
+    l: u32,
+    r: u32,
+    count: impl Fn(u32) -> usize,
+    succ: impl Fn(u32) -> Option<u32>,
+) -> Vec<u32> {
+    let mut vec = Vec::new();
+    let mut x = if count(l) != 0 { Some(l) } else { succ(l) };
+    while let Some(y) = x.filter(|&y| y < r) {
+        vec.resize(vec.len() + count(y), y);
+        x = succ(y);
+    }
+    vec
+}
+
+impl Subject for avl_tree::AVLTree<u32, ()> {
+    const NAME: &'static str = "avl_tree";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ())
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.delete(&key).is_some())
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec().into_iter().map(|(k, ())| k).collect()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.get(&key).is_some())
+    }
+}
+
+impl Subject for binary_search_trees::BinarySearchBree {
+    const NAME: &'static str = "binary_search_trees";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key)
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.delete(key).is_some())
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.contains(key))
+    }
+}
+
+impl Subject for btree::BTree<u32> {
+    const NAME: &'static str = "btree";
+    const MULTISET: bool = false;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key);
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.delete(key).is_some())
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.get(&key).is_some())
+    }
+    fn range(&self, l: u32, r: u32) -> Option<Vec<u32>> {
+        Some(range_by_succ(
+            l,
+            r,
+            |x| self.get(&x).map_or(0, |_| 1),
+            |x| self.succ(&x).copied(),
+        ))
+    }
+}
+
+impl Subject for btree::BTreeMultiset<u32> {
+    const NAME: &'static str = "btree::BTreeMultiset";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key)
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.remove_one(key))
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.contains(&key))
+    }
+    fn range(&self, l: u32, r: u32) -> Option<Vec<u32>> {
+        Some(range_by_succ(
+            l,
+            r,
+            |x| self.count(&x),
+            |x| self.succ(&x).copied(),
+        ))
+    }
+}
+
+// The values are the keys, so that the folds are checked as well.
+impl Subject for foldable_llrb_tree::LLRB<u32, u64> {
+    const NAME: &'static str = "foldable_llrb_tree";
+    const MULTISET: bool = false;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, u64::from(key))
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.delete(&key).is_some())
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec().into_iter().map(|(k, _)| k).collect()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.find(&key).is_some())
+    }
+}
+
+impl Subject for llrb::LLRB<u32, ()> {
+    const NAME: &'static str = "llrb";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ())
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.delete(&key).is_some())
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec().into_iter().map(|(k, ())| k).collect()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.get(&key).is_some())
+    }
+}
+
+// A sequence rather than a search tree: the positions are found from the keys, and everything
+// else is done by splitting and merging.
+impl Subject for merge_based_rbtree::RBTree<u32> {
+    const NAME: &'static str = "merge_based_rbtree";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        let mut right = self.split_off(lower_bound(self, key));
+        self.append(&mut Self::from_slice(&[key]));
+        self.append(&mut right);
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        let mut right = self.split_off(lower_bound(self, key));
+        let deleted = right.collect_vec().first() == Some(&key);
+        if deleted {
+            right = right.split_off(1);
+        }
+        self.append(&mut right);
+        Some(deleted)
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn split_off(&mut self, key: u32) -> Option<Self> {
+        Some(self.split_off(lower_bound(self, key)))
+    }
+    fn append(&mut self, mut other: Self) {
+        self.append(&mut other)
+    }
+}
+fn lower_bound(rbt: &merge_based_rbtree::RBTree<u32>, key: u32) -> usize {
+    rbt.collect_vec().iter().filter(|&&x| x < key).count()
+}
+
+impl Subject for mergible_rbtree::RBTree<u32, ()> {
+    const NAME: &'static str = "mergible_rbtree";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ())
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.delete(key))
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec().into_iter().map(|(k, ())| k).collect()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn split_off(&mut self, key: u32) -> Option<Self> {
+        Some(self.split_off(&key))
+    }
+    fn append(&mut self, mut other: Self) {
+        self.append(&mut other)
+    }
+}
+
+impl Subject for nilless_parentless_rbtree::RBTree<u32, ()> {
+    const NAME: &'static str = "nilless_parentless_rbtree";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ())
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.delete(key))
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec().into_iter().map(|(k, ())| k).collect()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.get(&key).is_some())
+    }
+}
+
+impl Subject for order_statistic_llrb_tree::LLRB<u32, ()> {
+    const NAME: &'static str = "order_statistic_llrb_tree";
+    const MULTISET: bool = false;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ())
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.delete(&key).is_some())
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec().into_iter().map(|(k, ())| k).collect()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.find(&key).is_some())
+    }
+}
+
+impl Subject for parentless_rbtree::RBTree<u32, ()> {
+    const NAME: &'static str = "parentless_rbtree";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ())
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.remove(key))
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect().into_iter().map(|(k, ())| k).collect()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.get(&key).is_some())
+    }
+}
+
+// the latest version of a persistent tree; the older ones are checked by `check_invariants`
+pub struct Persistent {
+    rbt: persistent_rbtree::PersistentRBTree<u32, ()>,
+    version: usize,
+}
+impl Subject for Persistent {
+    const NAME: &'static str = "persistent_rbtree";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self {
+            rbt: persistent_rbtree::PersistentRBTree::new(),
+            version: 0,
+        }
+    }
+    fn insert(&mut self, key: u32) {
+        self.rbt.insert(key, ());
+        self.version += 1;
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        let deleted = self.rbt.delete(key).is_some();
+        self.version += 1;
+        Some(deleted)
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        let vec = self.rbt.collect_vec(self.version);
+        vec.into_iter().map(|(k, ())| k).collect()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.rbt.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.rbt.get(self.version, &key).is_some())
+    }
+}
+
+impl Subject for red_black_tree::RBTree<u32, ()> {
+    const NAME: &'static str = "red_black_tree";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ())
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.delete(key).is_some())
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec().into_iter().map(|(k, ())| k).collect()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.get(&key).is_some())
+    }
+}
+
+impl Subject for red_black_tree::arena::RBTree<u32, ()> {
+    const NAME: &'static str = "red_black_tree::arena";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ())
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.delete(&key).is_some())
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec().into_iter().map(|(k, ())| k).collect()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.get(&key).is_some())
+    }
+}
+
+impl Subject for treap::Treap<u32, (), StdRng> {
+    const NAME: &'static str = "treap";
+    const MULTISET: bool = true;
+    fn new() -> Self {
+        Self::new(StdRng::seed_from_u64(42))
+    }
+    fn insert(&mut self, key: u32) {
+        self.insert(key, ())
+    }
+    fn delete(&mut self, key: u32) -> Option<bool> {
+        Some(self.delete(&key).is_some())
+    }
+    fn to_vec(&self) -> Vec<u32> {
+        self.collect_vec().into_iter().map(|(k, ())| k).collect()
+    }
+    fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_invariants()
+    }
+    fn get(&self, key: u32) -> Option<bool> {
+        Some(self.get(&key).is_some())
+    }
+}
+
+// The two van Emde Boas trees have the same interface.
+macro_rules! veb {
+    ($veb:ident) => {
+        impl Subject for $veb::Veb {
+            const NAME: &'static str = stringify!($veb);
+            const MULTISET: bool = false;
+            fn new() -> Self {
+                Self::new(LG)
+            }
+            fn insert(&mut self, key: u32) {
+                self.insert(key as usize)
+            }
+            fn delete(&mut self, key: u32) -> Option<bool> {
+                Some(self.delete(key as usize))
+            }
+            fn to_vec(&self) -> Vec<u32> {
+                self.collect_vec().into_iter().map(|x| x as u32).collect()
+            }
+            fn get(&self, key: u32) -> Option<bool> {
+                Some(self.contains(key as usize))
+            }
+            fn range(&self, l: u32, r: u32) -> Option<Vec<u32>> {
+                Some(range_by_succ(
+                    l,
+                    r,
+                    |x| self.contains(x as usize) as usize,
+                    |x| self.succ(x as usize).map(|y| y as u32),
+                ))
+            }
+        }
+        impl Subject for $veb::VebMultiset {
+            const NAME: &'static str = concat!(stringify!($veb), "::VebMultiset");
+            const MULTISET: bool = true;
+            fn new() -> Self {
+                Self::new(LG)
+            }
+            fn insert(&mut self, key: u32) {
+                self.insert(key as usize)
+            }
+            fn delete(&mut self, key: u32) -> Option<bool> {
+                Some(self.remove_one(key as usize))
+            }
+            fn to_vec(&self) -> Vec<u32> {
+                self.collect_vec().into_iter().map(|x| x as u32).collect()
+            }
+            fn get(&self, key: u32) -> Option<bool> {
+                Some(self.contains(key as usize))
+            }
+            fn range(&self, l: u32, r: u32) -> Option<Vec<u32>> {
+                Some(range_by_succ(
+                    l,
+                    r,
+                    |x| self.count(x as usize),
+                    |x| self.succ(x as usize).map(|y| y as u32),
+                ))
+            }
+        }
+    };
+}
+veb!(van_emde_boas);
+veb!(reduced_space_van_emde_boas);
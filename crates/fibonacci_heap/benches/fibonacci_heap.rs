@@ -0,0 +1,102 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use fibonacci_heap::FibonacciHeap;
+use rand::prelude::*;
+use std::iter::repeat_with;
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+fn gen_heap(n: usize, rng: &mut StdRng) -> FibonacciHeap<u32, ()> {
+    let mut fib = FibonacciHeap::new();
+    repeat_with(|| rng.gen_range(0, u32::MAX))
+        .take(n)
+        .for_each(|x| {
+            fib.push(x, ());
+        });
+    fib
+}
+
+// O(1): apart from cache misses, the time should not grow with the sizes.
+fn bench_append(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Append");
+    let mut rng = StdRng::seed_from_u64(42);
+    for &n in SIZES.iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter_batched_ref(
+                || (gen_heap(n, &mut rng), gen_heap(n, &mut rng)),
+                |(fib, other)| fib.append(other),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+// O(1): apart from cache misses, the time should not grow with the sizes.
+fn bench_push(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Push");
+    let mut rng = StdRng::seed_from_u64(42);
+    for &n in SIZES.iter() {
+        let mut fib = gen_heap(n, &mut rng);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter_batched(
+                || rng.gen_range(0, u32::MAX),
+                |x| fib.push(x, ()),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+// amortized O(lg n): the time per element should grow logarithmically.
+fn bench_pop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Push and pop all");
+    let mut rng = StdRng::seed_from_u64(42);
+    for &n in SIZES.iter() {
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter_batched_ref(
+                || gen_heap(n, &mut rng),
+                |fib| while fib.pop().is_some() {},
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+// amortized O(1): apart from cache misses, the time per element should not grow with the sizes.
+fn bench_decrease_key(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Decrease key");
+    let mut rng = StdRng::seed_from_u64(42);
+    for &n in SIZES.iter() {
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter_batched_ref(
+                || {
+                    let mut fib = FibonacciHeap::new();
+                    let mut handles = (0..n as u32)
+                        .map(|i| fib.push(u32::MAX / 2 + i, ()))
+                        .collect::<Vec<_>>();
+                    // Consolidate the root list into trees.
+                    fib.pop();
+                    handles.remove(0);
+                    handles.shuffle(&mut rng);
+                    (fib, handles)
+                },
+                |(fib, handles)| {
+                    for (i, h) in handles.iter().enumerate() {
+                        fib.decrease_key(h, i as u32).unwrap();
+                    }
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+criterion_group!(
+    benches,
+    bench_append,
+    bench_push,
+    bench_pop,
+    bench_decrease_key
+);
+criterion_main!(benches);
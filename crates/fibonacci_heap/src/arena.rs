@@ -0,0 +1,598 @@
+use {
+    super::HandleError,
+    heap_arena::{Arena, HeapId, Heaps},
+    memory_usage::MemoryUsage,
+    paren::Shape,
+    std::{
+        fmt::Debug,
+        marker::PhantomData,
+        mem::{replace, swap},
+    },
+};
+
+// A reference to an element of a `FibonacciHeap`, returned by `FibonacciHeap::push`.
+pub use heap_arena::Handle;
+
+// The same as `super::FibonacciHeap` except that the nodes are stored in a `Pool` and linked by
+// their indices.
+//
+// The heaps that may be appended to each other share a pool, so `append` splices the root lists
+// in O(1) as the `Rc` version does, besides linking the ids of the heaps in O(lg h) for the h heaps
+// appended so far (see `heap_arena`). The other operations have the same costs.
+//
+// The signatures differ from the `Rc` version in three ways:
+//
+// - Every method takes the pool, `&Pool` to read and `&mut Pool` to write.
+// - `Handle` is not generic, since it is an index into the pool rather than a `Weak` to a node.
+//   It is `Copy` and `Send`, as is the heap.
+// - `peek` returns `&K` borrowed from the pool where the `Rc` version returns `Ref<K>`, since
+//   there is no `RefCell` to borrow; both dereference to `K`.
+#[derive(Debug)]
+pub struct FibonacciHeap<K, V> {
+    id: HeapId,
+    len: usize,
+    min: Option<usize>,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+impl<K: Ord + Debug, V: Debug> FibonacciHeap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            id: HeapId::new(),
+            len: 0,
+            min: None,
+            marker: PhantomData,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    // O(1)
+    pub fn push(&mut self, pool: &mut Pool<K, V>, key: K, value: V) -> Handle {
+        let node = Node {
+            mark: false,
+            degree: 0,
+            key,
+            value,
+            heap: self.id,
+            child: None,
+            parent: None,
+            left: 0,
+            right: 0,
+        };
+        let x = pool.nodes.insert(node);
+        pool.nodes[x].left = x;
+        pool.nodes[x].right = x;
+        pool.heaps.join(self.id);
+        self.add_root(pool, x);
+        self.len += 1;
+        pool.nodes.handle(self.id, x)
+    }
+    // O(lg h) for the h heaps appended so far, which only link their ids
+    pub fn append(&mut self, pool: &mut Pool<K, V>, other: &mut Self) {
+        if let Some(root) = other.min.take() {
+            self.add_root(pool, root);
+        }
+        self.id = pool.heaps.union(self.id, other.id);
+        other.id = HeapId::new();
+        self.len += other.len;
+        other.len = 0;
+    }
+    pub fn peek<'a>(&self, pool: &'a Pool<K, V>) -> Option<&'a K> {
+        self.min.map(|x| &pool.nodes[x].key)
+    }
+    // amortized O(lg n)
+    pub fn pop(&mut self, pool: &mut Pool<K, V>) -> Option<(K, V)> {
+        let z = self.min.take()?;
+        self.len -= 1;
+        if let Some(child) = pool.nodes[z].child.take() {
+            for x in pool.ring(child) {
+                pool.nodes[x].parent = None;
+            }
+            pool.splice(z, child);
+        }
+        self.min = pool.unlink(z);
+        let Node {
+            key, value, heap, ..
+        } = pool.nodes.remove(z);
+        pool.heaps.leave(heap);
+        if self.min.is_some() {
+            self.consolidate(pool);
+        }
+        Some((key, value))
+    }
+    pub fn is_live(&self, pool: &Pool<K, V>, handle: &Handle) -> bool {
+        self.resolve(pool, handle).is_ok()
+    }
+    pub fn get(&self, pool: &Pool<K, V>, handle: &Handle) -> Result<(K, V), HandleError>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let node = &pool.nodes[self.resolve(pool, handle)?];
+        Ok((node.key.clone(), node.value.clone()))
+    }
+    // amortized O(1)
+    pub fn decrease_key(
+        &mut self,
+        pool: &mut Pool<K, V>,
+        handle: &Handle,
+        key: K,
+    ) -> Result<(), HandleError> {
+        let x = self.resolve(pool, handle)?;
+        if key > pool.nodes[x].key {
+            return Err(HandleError::KeyIncreased);
+        }
+        pool.nodes[x].key = key;
+        if let Some(p) = pool.nodes[x].parent {
+            if pool.nodes[x].key < pool.nodes[p].key {
+                self.cut(pool, x, p);
+                self.cascading_cut(pool, p);
+            }
+        }
+        if pool.nodes[x].key < pool.nodes[self.min.unwrap()].key {
+            self.min = Some(x);
+        }
+        Ok(())
+    }
+    // Cut the children to the root list and reinsert the node as a root.
+    pub fn increase_key(
+        &mut self,
+        pool: &mut Pool<K, V>,
+        handle: &Handle,
+        key: K,
+    ) -> Result<(), HandleError> {
+        let x = self.resolve(pool, handle)?;
+        if key < pool.nodes[x].key {
+            return Err(HandleError::KeyDecreased);
+        }
+        pool.nodes[x].key = key;
+        if let Some(p) = pool.nodes[x].parent {
+            self.cut(pool, x, p);
+            self.cascading_cut(pool, p);
+        }
+        if let Some(child) = pool.nodes[x].child.take() {
+            for y in pool.ring(child) {
+                pool.nodes[y].parent = None;
+                pool.nodes[y].mark = false;
+            }
+            pool.splice(self.min.unwrap(), child);
+        }
+        pool.nodes[x].degree = 0;
+        pool.nodes[x].mark = false;
+        if self.min == Some(x) {
+            self.consolidate(pool);
+        }
+        Ok(())
+    }
+    // Decrease the key to minus infinity and extract it.
+    pub fn delete(
+        &mut self,
+        pool: &mut Pool<K, V>,
+        handle: &Handle,
+    ) -> Result<(K, V), HandleError> {
+        let x = self.resolve(pool, handle)?;
+        if let Some(p) = pool.nodes[x].parent {
+            self.cut(pool, x, p);
+            self.cascading_cut(pool, p);
+        }
+        self.min = Some(x);
+        Ok(self.pop(pool).unwrap())
+    }
+    pub fn shapes(&self, pool: &Pool<K, V>) -> Vec<Shape> {
+        self.min
+            .map_or_else(Vec::new, |x| pool.ring(x))
+            .into_iter()
+            .map(|x| pool.shape(x))
+            .collect()
+    }
+    fn resolve(&self, pool: &Pool<K, V>, handle: &Handle) -> Result<usize, HandleError> {
+        pool.nodes.resolve(&pool.heaps, self.id, handle)
+    }
+    // Merge a circular list whose minimum is `x` into the root list.
+    fn add_root(&mut self, pool: &mut Pool<K, V>, x: usize) {
+        if let Some(min) = self.min {
+            pool.splice(min, x);
+            if pool.nodes[min].key <= pool.nodes[x].key {
+                return;
+            }
+        }
+        self.min = Some(x);
+    }
+    // Move `x` from the child list of `p` to the root list.
+    fn cut(&mut self, pool: &mut Pool<K, V>, x: usize, p: usize) {
+        let rest = pool.unlink(x);
+        if pool.nodes[p].child == Some(x) {
+            pool.nodes[p].child = rest;
+        }
+        pool.nodes[p].degree -= 1;
+        pool.nodes[x].parent = None;
+        pool.nodes[x].mark = false;
+        pool.splice(self.min.unwrap(), x);
+    }
+    fn cascading_cut(&mut self, pool: &mut Pool<K, V>, mut y: usize) {
+        while let Some(z) = pool.nodes[y].parent {
+            if !replace(&mut pool.nodes[y].mark, true) {
+                break;
+            }
+            self.cut(pool, y, z);
+            y = z;
+        }
+    }
+    fn consolidate(&mut self, pool: &mut Pool<K, V>) {
+        let n = (self.len.next_power_of_two().trailing_zeros() + 2) as usize * 2;
+        let mut a = vec![None::<usize>; n];
+        let min = self.min.take().unwrap();
+        let roots = pool.ring(min);
+        for mut x in roots.into_iter() {
+            pool.nodes[x].left = x;
+            pool.nodes[x].right = x;
+            loop {
+                let deg = pool.nodes[x].degree;
+                if let Some(mut y) = a[deg].take() {
+                    if pool.nodes[x].key > pool.nodes[y].key {
+                        swap(&mut x, &mut y);
+                    }
+                    pool.link(y, x);
+                } else {
+                    break;
+                }
+            }
+            let deg = pool.nodes[x].degree;
+            a[deg] = Some(x);
+        }
+        a.into_iter().flatten().for_each(|x| self.add_root(pool, x));
+    }
+}
+impl<K: Ord + Debug, V: Debug> Default for FibonacciHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The nodes of the Fibonacci heaps, and the ids of the heaps that hold them.
+#[derive(Debug)]
+pub struct Pool<K, V> {
+    nodes: Arena<Node<K, V>>,
+    heaps: Heaps,
+}
+impl<K, V> Pool<K, V> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Arena::new(),
+            heaps: Heaps::new(),
+        }
+    }
+    // the nodes of the circular list from `x`
+    fn ring(&self, x: usize) -> Vec<usize> {
+        let mut vec = vec![x];
+        loop {
+            let y = self.nodes[*vec.last().unwrap()].right;
+            if y == x {
+                return vec;
+            }
+            vec.push(y);
+        }
+    }
+    // Concatenate two circular lists: `a` -> `b` -> ... -> (the left of `b`) -> (the right of `a`) -> ...
+    fn splice(&mut self, a: usize, b: usize) {
+        let a_right = self.nodes[a].right;
+        let b_left = self.nodes[b].left;
+        self.nodes[a].right = b;
+        self.nodes[b].left = a;
+        self.nodes[a_right].left = b_left;
+        self.nodes[b_left].right = a_right;
+    }
+    // Remove `x` from its circular list and return the rest if any.
+    fn unlink(&mut self, x: usize) -> Option<usize> {
+        let left = self.nodes[x].left;
+        let right = self.nodes[x].right;
+        if right == x {
+            None
+        } else {
+            self.nodes[right].left = left;
+            self.nodes[left].right = right;
+            self.nodes[x].left = x;
+            self.nodes[x].right = x;
+            Some(right)
+        }
+    }
+    // Make a root `y` a child of a root `x`.
+    fn link(&mut self, y: usize, x: usize) {
+        self.nodes[y].parent = Some(x);
+        self.nodes[y].mark = false;
+        match self.nodes[x].child {
+            Some(child) => self.splice(child, y),
+            None => self.nodes[x].child = Some(y),
+        }
+        self.nodes[x].degree += 1;
+    }
+    fn shape(&self, x: usize) -> Shape
+    where
+        K: Debug,
+    {
+        let node = &self.nodes[x];
+        let mut shape = Shape::new(&node.key).attr("degree", node.degree);
+        if node.mark {
+            shape = shape.attr("mark", true);
+        }
+        node.child
+            .map_or_else(Vec::new, |y| self.ring(y))
+            .into_iter()
+            .fold(shape, |shape, y| shape.child(Some(self.shape(y))))
+    }
+}
+impl<K, V> Default for Pool<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+// The slots of the removed nodes are kept for reuse.
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Pool<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.nodes.heap_size_bytes() + self.heaps.heap_size_bytes()
+    }
+}
+
+#[derive(Debug)]
+struct Node<K, V> {
+    mark: bool,
+    degree: usize,
+    key: K,
+    value: V,
+    // the heap which the node was pushed to
+    heap: HeapId,
+    child: Option<usize>,
+    parent: Option<usize>,
+    left: usize,
+    right: usize,
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Node<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.key.heap_size_bytes() + self.value.heap_size_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{FibonacciHeap, Handle, Pool},
+        crate::HandleError,
+        paren::Visualize,
+        rand::prelude::*,
+        std::fmt::Debug,
+    };
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<FibonacciHeap<u32, String>>();
+        assert_send::<Pool<u32, String>>();
+        assert_send::<Handle>();
+    }
+
+    #[test]
+    fn test_handle_misuse() {
+        let mut pool = Pool::new();
+        let mut fib = FibonacciHeap::new();
+        let mut other = FibonacciHeap::new();
+        let h = fib.push(&mut pool, 10, "a");
+        let g = other.push(&mut pool, 20, "b");
+        assert_eq!(fib.get(&pool, &g), Err(HandleError::ForeignHeap));
+        assert_eq!(
+            fib.decrease_key(&mut pool, &h, 11),
+            Err(HandleError::KeyIncreased)
+        );
+        assert_eq!(
+            fib.increase_key(&mut pool, &h, 9),
+            Err(HandleError::KeyDecreased)
+        );
+
+        fib.append(&mut pool, &mut other);
+        assert!(!other.is_live(&pool, &g));
+        assert_eq!(fib.get(&pool, &g), Ok((20, "b")));
+        assert_eq!(fib.delete(&mut pool, &h), Ok((10, "a")));
+        assert_eq!(fib.get(&pool, &h), Err(HandleError::Removed));
+
+        // The slot of `h` is reused, but `h` is still dead.
+        let k = fib.push(&mut pool, 30, "c");
+        assert!(!fib.is_live(&pool, &h));
+        assert_eq!(fib.pop(&mut pool), Some((20, "b")));
+        assert_eq!(fib.pop(&mut pool), Some((30, "c")));
+        assert!(!fib.is_live(&pool, &k));
+
+        // A handle of another pool is foreign too.
+        let mut another = Pool::new();
+        let l = FibonacciHeap::new().push(&mut another, 40, "d");
+        assert_eq!(fib.get(&pool, &l), Err(HandleError::ForeignHeap));
+    }
+
+    // `append` leaves the nodes of `other` where they are.
+    #[test]
+    fn test_append_in_place() {
+        let mut pool = Pool::new();
+        let mut fib = FibonacciHeap::new();
+        let mut other = FibonacciHeap::new();
+        fib.push(&mut pool, 10, "a");
+        let handles = (0..10)
+            .map(|i| other.push(&mut pool, i, "b"))
+            .collect::<Vec<_>>();
+        let before = handles
+            .iter()
+            .map(|h| other.resolve(&pool, h).unwrap())
+            .collect::<Vec<_>>();
+        fib.append(&mut pool, &mut other);
+        let after = handles
+            .iter()
+            .map(|h| fib.resolve(&pool, h).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(before, after);
+        assert_eq!(fib.peek(&pool), Some(&0));
+    }
+
+    // Run the same operations on this and the `Rc` version. They have the same structures, so even
+    // the ties are broken in the same way.
+    #[test]
+    fn test_rand_against_rc() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let mut test = Test::new();
+            for _ in 0..200 {
+                match rng.gen_range(0, 10) {
+                    0..=2 => test.push(rng.gen_range(0, 100)),
+                    3 => test.pop(),
+                    4 => {
+                        let mut other = test.other();
+                        for _ in 0..rng.gen_range(0, 10) {
+                            other.push(&mut test.pool, rng.gen_range(0, 100));
+                        }
+                        other.pop(&mut test.pool);
+                        test.append(other);
+                    }
+                    5..=9 => {
+                        if test.handles.is_empty() {
+                            continue;
+                        }
+                        let i = rng.gen_range(0, test.handles.len());
+                        if let Ok((key, _)) = test.arena.get(&test.pool, &test.handles[i].0) {
+                            match rng.gen_range(0, 3) {
+                                0 => test.decrease_key(i, rng.gen_range(0, key + 1)),
+                                1 => test.increase_key(i, rng.gen_range(key, 100)),
+                                2 => test.delete(i),
+                                _ => unreachable!(),
+                            }
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
+    struct Test {
+        pool: Pool<u32, usize>,
+        arena: FibonacciHeap<u32, usize>,
+        rc: crate::FibonacciHeap<u32, usize>,
+        handles: Vec<(Handle, crate::Handle<u32, usize>)>,
+    }
+    // A heap to be appended to `Test`, which borrows its pool.
+    struct Other {
+        arena: FibonacciHeap<u32, usize>,
+        rc: crate::FibonacciHeap<u32, usize>,
+        handles: Vec<(Handle, crate::Handle<u32, usize>)>,
+        offset: usize,
+    }
+    impl Other {
+        fn push(&mut self, pool: &mut Pool<u32, usize>, key: u32) {
+            let value = self.offset + self.handles.len();
+            let h = self.arena.push(pool, key, value);
+            let g = self.rc.push(key, value);
+            self.handles.push((h, g));
+        }
+        fn pop(&mut self, pool: &mut Pool<u32, usize>) {
+            assert_eq!(self.arena.pop(pool), self.rc.pop());
+        }
+    }
+    impl Test {
+        fn new() -> Self {
+            Self {
+                pool: Pool::new(),
+                arena: FibonacciHeap::new(),
+                rc: crate::FibonacciHeap::new(),
+                handles: Vec::new(),
+            }
+        }
+        fn other(&self) -> Other {
+            Other {
+                arena: FibonacciHeap::new(),
+                rc: crate::FibonacciHeap::new(),
+                handles: Vec::new(),
+                offset: self.handles.len(),
+            }
+        }
+        fn push(&mut self, key: u32) {
+            let value = self.handles.len();
+            let h = self.arena.push(&mut self.pool, key, value);
+            let g = self.rc.push(key, value);
+            self.handles.push((h, g));
+            self.postprocess();
+        }
+        fn append(&mut self, mut other: Other) {
+            self.arena.append(&mut self.pool, &mut other.arena);
+            self.rc.append(&mut other.rc);
+            self.handles.append(&mut other.handles);
+            self.postprocess();
+        }
+        fn pop(&mut self) {
+            assert_eq!(self.arena.pop(&mut self.pool), self.rc.pop());
+            self.postprocess();
+        }
+        fn decrease_key(&mut self, i: usize, key: u32) {
+            let (h, g) = &self.handles[i];
+            assert_eq!(
+                self.arena.decrease_key(&mut self.pool, h, key),
+                self.rc.decrease_key(g, key)
+            );
+            self.postprocess();
+        }
+        fn increase_key(&mut self, i: usize, key: u32) {
+            let (h, g) = &self.handles[i];
+            assert_eq!(
+                self.arena.increase_key(&mut self.pool, h, key),
+                self.rc.increase_key(g, key)
+            );
+            self.postprocess();
+        }
+        fn delete(&mut self, i: usize) {
+            let (h, g) = &self.handles[i];
+            assert_eq!(self.arena.delete(&mut self.pool, h), self.rc.delete(g));
+            self.postprocess();
+        }
+        fn postprocess(&self) {
+            assert_eq!(self.arena.len(), self.rc.len());
+            assert_eq!(
+                self.arena.peek(&self.pool).copied(),
+                self.rc.peek().map(|x| *x)
+            );
+            for (h, g) in self.handles.iter() {
+                assert_eq!(self.arena.get(&self.pool, h), self.rc.get(g));
+            }
+            assert_eq!(self.arena.shapes(&self.pool), self.rc.shapes());
+            self.arena.validate(&self.pool);
+        }
+    }
+
+    impl<K: Ord + Debug, V: Debug> FibonacciHeap<K, V> {
+        fn validate(&self, pool: &Pool<K, V>) {
+            let roots = self.min.map_or_else(Vec::new, |x| pool.ring(x));
+            for &x in roots.iter() {
+                assert!(pool.nodes[x].parent.is_none());
+                assert!(pool.nodes[self.min.unwrap()].key <= pool.nodes[x].key);
+            }
+            assert_eq!(
+                roots
+                    .iter()
+                    .map(|&x| self.validate_node(pool, x))
+                    .sum::<usize>(),
+                self.len
+            );
+        }
+        // Return the size of the subtree.
+        fn validate_node(&self, pool: &Pool<K, V>, x: usize) -> usize {
+            let child = pool.nodes[x].child.map_or_else(Vec::new, |y| pool.ring(y));
+            assert_eq!(pool.nodes[x].degree, child.len());
+            assert_eq!(pool.heaps.find(pool.nodes[x].heap), self.id);
+            for (i, &y) in child.iter().enumerate() {
+                assert_eq!(pool.nodes[y].parent, Some(x));
+                assert_eq!(pool.nodes[child[(i + 1) % child.len()]].left, y);
+                assert!(pool.nodes[x].key <= pool.nodes[y].key);
+            }
+            1 + child
+                .iter()
+                .map(|&y| self.validate_node(pool, y))
+                .sum::<usize>()
+        }
+    }
+}
@@ -1,203 +1,440 @@
-use std::{
-    cell::{Ref, RefCell},
-    convert::identity,
-    fmt::Debug,
-    mem::{replace, swap, take},
-    rc::{Rc, Weak},
+pub mod arena;
+
+pub use heap_arena::HandleError;
+
+use {
+    memory_usage::{rc_bytes, MemoryUsage},
+    paren::{Shape, Visualize},
+    std::{
+        cell::{Ref, RefCell},
+        fmt::{self, Debug, Formatter},
+        mem::{replace, swap},
+        rc::{Rc, Weak},
+    },
 };
 
-#[derive(Debug)]
+type NodeRef<K, V> = Rc<RefCell<Node<K, V>>>;
+
 pub struct FibonacciHeap<K, V> {
     len: usize,
-    chain: Vec<Rc<RefCell<Node<K, V>>>>,
+    min: Option<NodeRef<K, V>>,
+    id: Rc<HeapId>,
 }
 impl<K: Ord + Debug, V: Debug> FibonacciHeap<K, V> {
     pub fn new() -> Self {
         Self {
             len: 0,
-            chain: Vec::new(),
+            min: None,
+            id: Rc::new(HeapId::default()),
         }
     }
     pub fn len(&self) -> usize {
         self.len
     }
-    pub fn push(&mut self, key: K, value: V) -> Weak<RefCell<Node<K, V>>> {
-        let handle = Rc::new(RefCell::new(Node::new(key, value, self.chain.len())));
-        self.chain.push(Rc::clone(&handle));
-        if self.chain.first().unwrap().borrow().key > self.chain.last().unwrap().borrow().key {
-            let len = self.chain.len();
-            self.swap_heaps(0, len - 1);
-        }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    // O(1)
+    pub fn push(&mut self, key: K, value: V) -> Handle<K, V> {
+        let node = Node::new(key, value);
+        let handle = Handle {
+            id: Rc::clone(&self.id),
+            node: Rc::downgrade(&node),
+        };
+        self.add_root(node);
         self.len += 1;
-        Rc::downgrade(&handle)
+        handle
     }
-    // TODO: make this O(1)
+    // O(1)
     pub fn append(&mut self, other: &mut Self) {
-        other
-            .chain
-            .iter_mut()
-            .for_each(|root| root.borrow_mut().position += self.chain.len());
-        self.len += other.len;
-        if let Some(me) = self.chain.first_mut() {
-            if let Some(other) = other.chain.first_mut() {
-                if me.borrow().key > other.borrow().key {
-                    swap(&mut *me, &mut *other);
-                    me.borrow_mut().position = 0;
-                    other.borrow_mut().position = self.chain.len();
-                }
-            }
+        if let Some(root) = other.min.take() {
+            self.add_root(root);
         }
-        self.chain.append(&mut other.chain)
+        self.len += other.len;
+        other.len = 0;
+        // Handles issued by `other` now point to nodes of `self`.
+        *other.id.forward.borrow_mut() = Some(Rc::clone(&self.id));
+        other.id = Rc::new(HeapId::default());
     }
     pub fn peek(&self) -> Option<Ref<K>> {
-        self.chain
-            .first()
+        self.min
+            .as_ref()
             .map(|node| Ref::map(node.borrow(), |node| &node.key))
     }
+    // amortized O(lg n)
     pub fn pop(&mut self) -> Option<(K, V)> {
-        if self.chain.is_empty() {
-            None
-        } else {
-            self.len -= 1;
-            let Node {
-                mark: _mark,
-                key,
-                value,
-                position: _position,
-                mut child,
-                parent,
-            } = Rc::try_unwrap(self.chain.swap_remove(0))
-                .unwrap()
-                .into_inner();
-            assert!(parent.upgrade().is_none());
-            self.chain.append(&mut child);
+        let z = self.min.take()?;
+        self.len -= 1;
+        let child = z.borrow_mut().child.take();
+        if let Some(child) = child {
+            ring(&child)
+                .iter()
+                .for_each(|x| x.borrow_mut().parent = Weak::new());
+            splice(&z, &child);
+        }
+        self.min = unlink(&z);
+        // `z` is a singleton now, and the self-loop is the last strong reference except for `z`.
+        z.borrow_mut().right = None;
+        let Node { key, value, .. } = Rc::try_unwrap(z).ok().unwrap().into_inner();
+        if self.min.is_some() {
             self.consolidate();
-            self.fix_top();
-            Some((key, value))
         }
+        Some((key, value))
     }
-    pub fn decrease_key(&mut self, x: Rc<RefCell<Node<K, V>>>, key: K) {
-        assert!(
-            key <= x.borrow().key,
-            "A new key is greater than an old one: {:?} vs {:?}",
-            &key,
-            x.borrow().key
-        );
+    pub fn is_live(&self, handle: &Handle<K, V>) -> bool {
+        self.upgrade(handle).is_ok()
+    }
+    pub fn get(&self, handle: &Handle<K, V>) -> Result<(K, V), HandleError>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let node = self.upgrade(handle)?;
+        let node = node.borrow();
+        Ok((node.key.clone(), node.value.clone()))
+    }
+    // amortized O(1)
+    pub fn decrease_key(&mut self, handle: &Handle<K, V>, key: K) -> Result<(), HandleError> {
+        let x = self.upgrade(handle)?;
+        if key > x.borrow().key {
+            return Err(HandleError::KeyIncreased);
+        }
         x.borrow_mut().key = key;
-        let p = Weak::upgrade(&x.borrow().parent);
+        let p = x.borrow().parent.upgrade();
         if let Some(p) = p {
             if x.borrow().key < p.borrow().key {
-                let x_pos = self.chain.len();
-                self.cut(&p, x);
-                let mut p = p;
-                while replace(&mut p.borrow_mut().mark, true) {
-                    let pp = Weak::upgrade(&p.borrow().parent);
-                    p = if let Some(pp) = pp {
-                        self.cut(&pp, p);
-                        pp
-                    } else {
-                        break;
-                    }
-                }
-                if self.chain[0].borrow().key > self.chain[x_pos].borrow().key {
-                    self.swap_heaps(0, x_pos);
-                }
-            }
-        } else {
-            let x_pos = x.borrow().position;
-            if self.chain[0].borrow().key > self.chain[x_pos].borrow().key {
-                self.swap_heaps(0, x_pos);
+                self.cut(&x, &p);
+                self.cascading_cut(p);
             }
         }
+        if x.borrow().key < self.min.as_ref().unwrap().borrow().key {
+            self.min = Some(x);
+        }
+        Ok(())
+    }
+    // Cut the children to the root list and reinsert the node as a root.
+    pub fn increase_key(&mut self, handle: &Handle<K, V>, key: K) -> Result<(), HandleError> {
+        let x = self.upgrade(handle)?;
+        if key < x.borrow().key {
+            return Err(HandleError::KeyDecreased);
+        }
+        x.borrow_mut().key = key;
+        let p = x.borrow().parent.upgrade();
+        if let Some(p) = p {
+            self.cut(&x, &p);
+            self.cascading_cut(p);
+        }
+        let child = x.borrow_mut().child.take();
+        if let Some(child) = child {
+            ring(&child).iter().for_each(|y| {
+                let mut y = y.borrow_mut();
+                y.parent = Weak::new();
+                y.mark = false;
+            });
+            splice(self.min.as_ref().unwrap(), &child);
+        }
+        x.borrow_mut().degree = 0;
+        x.borrow_mut().mark = false;
+        if Rc::ptr_eq(self.min.as_ref().unwrap(), &x) {
+            self.consolidate();
+        }
+        Ok(())
+    }
+    // Decrease the key to minus infinity and extract it.
+    pub fn delete(&mut self, handle: &Handle<K, V>) -> Result<(K, V), HandleError> {
+        let x = self.upgrade(handle)?;
+        let p = x.borrow().parent.upgrade();
+        if let Some(p) = p {
+            self.cut(&x, &p);
+            self.cascading_cut(p);
+        }
+        self.min = Some(x);
+        Ok(self.pop().unwrap())
+    }
+    fn upgrade(&self, handle: &Handle<K, V>) -> Result<NodeRef<K, V>, HandleError> {
+        if !Rc::ptr_eq(&handle.id.resolve(), &self.id) {
+            return Err(HandleError::ForeignHeap);
+        }
+        handle.node.upgrade().ok_or(HandleError::Removed)
     }
-    fn swap_heaps(&mut self, i: usize, j: usize) {
-        self.chain.swap(i, j);
-        self.chain[i].borrow_mut().position = i;
-        self.chain[j].borrow_mut().position = j;
+    // Merge a circular list whose minimum is `x` into the root list.
+    fn add_root(&mut self, x: NodeRef<K, V>) {
+        if let Some(min) = self.min.as_ref() {
+            splice(min, &x);
+            if min.borrow().key <= x.borrow().key {
+                return;
+            }
+        }
+        self.min = Some(x);
     }
-    fn cut(&mut self, p: &Rc<RefCell<Node<K, V>>>, x: Rc<RefCell<Node<K, V>>>) {
-        let i = x.borrow().position;
-        let x = p.borrow_mut().child.swap_remove(i);
-        if let Some(y) = p.borrow_mut().child.get_mut(i) {
-            y.borrow_mut().position = i;
+    // Move `x` from the child list of `p` to the root list.
+    fn cut(&mut self, x: &NodeRef<K, V>, p: &NodeRef<K, V>) {
+        let rest = unlink(x);
+        let mut p = p.borrow_mut();
+        if p.child.as_ref().is_some_and(|child| Rc::ptr_eq(child, x)) {
+            p.child = rest;
         }
+        p.degree -= 1;
+        drop(p);
         x.borrow_mut().parent = Weak::new();
-        x.borrow_mut().position = self.chain.len();
         x.borrow_mut().mark = false;
-        self.chain.push(x);
+        splice(self.min.as_ref().unwrap(), x);
+    }
+    fn cascading_cut(&mut self, mut y: NodeRef<K, V>) {
+        loop {
+            let z = y.borrow().parent.upgrade();
+            match z {
+                Some(z) if replace(&mut y.borrow_mut().mark, true) => {
+                    self.cut(&y, &z);
+                    y = z;
+                }
+                _ => break,
+            }
+        }
     }
     fn consolidate(&mut self) {
         let n = (self.len.next_power_of_two().trailing_zeros() + 2) as usize * 2;
-        let mut a = vec![None::<Rc<RefCell<Node<K, V>>>>; n];
-        let roots = take(&mut self.chain);
-        for mut node in roots.into_iter() {
+        let mut a = vec![None::<NodeRef<K, V>>; n];
+        let roots = ring(self.min.as_ref().unwrap());
+        self.min = None;
+        for mut x in roots.into_iter() {
+            x.borrow_mut().right = Some(Rc::clone(&x));
+            x.borrow_mut().left = Rc::downgrade(&x);
             loop {
-                let deg = node.borrow().child.len();
-                if let Some(mut other) = a[deg].take() {
-                    let need_swap = node.borrow().key > other.borrow().key;
-                    if need_swap {
-                        swap(&mut node, &mut other);
+                let deg = x.borrow().degree;
+                if let Some(mut y) = a[deg].take() {
+                    if x.borrow().key > y.borrow().key {
+                        swap(&mut x, &mut y);
                     }
-                    other.borrow_mut().parent = Rc::downgrade(&node);
-                    other.borrow_mut().position = node.borrow().child.len();
-                    node.borrow_mut().child.push(other);
+                    link(&y, &x);
                 } else {
                     break;
                 }
             }
-            let len = node.borrow().child.len();
-            a[len] = Some(node);
-        }
-        self.chain = a
-            .into_iter()
-            .filter_map(identity)
-            .enumerate()
-            .map(|(i, child)| {
-                child.borrow_mut().position = i;
-                child
-            })
-            .collect();
-    }
-    fn fix_top(&mut self) {
-        if let Some((i, _)) = self
-            .chain
+            let deg = x.borrow().degree;
+            a[deg] = Some(x);
+        }
+        a.into_iter().flatten().for_each(|x| self.add_root(x));
+    }
+}
+impl<K, V> Drop for FibonacciHeap<K, V> {
+    // Break the cycles of the circular lists, or the nodes leak.
+    fn drop(&mut self) {
+        let mut stack = self.min.take().into_iter().collect::<Vec<_>>();
+        while let Some(mut x) = stack.pop() {
+            loop {
+                let mut x_mut = x.borrow_mut();
+                stack.extend(x_mut.child.take());
+                let right = x_mut.right.take();
+                drop(x_mut);
+                match right {
+                    Some(right) => x = right,
+                    None => break,
+                }
+            }
+        }
+    }
+}
+impl<K: Debug, V: Debug> Debug for FibonacciHeap<K, V> {
+    fn fmt(&self, w: &mut Formatter) -> fmt::Result {
+        w.debug_struct("FibonacciHeap")
+            .field("len", &self.len)
+            .field("roots", &self.min.as_ref().map_or_else(Vec::new, ring))
+            .finish()
+    }
+}
+
+// The root list is drawn as a forest from the minimum.
+impl<K: Ord + Debug, V: Debug> Visualize for FibonacciHeap<K, V> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.min
+            .as_ref()
+            .map_or_else(Vec::new, ring)
             .iter()
-            .enumerate()
-            .min_by(|(_, x), (_, y)| x.borrow().key.cmp(&y.borrow().key))
-        {
-            self.swap_heaps(0, i);
+            .map(shape)
+            .collect()
+    }
+}
+fn shape<K: Debug, V>(x: &NodeRef<K, V>) -> Shape {
+    let x = x.borrow();
+    let mut shape = Shape::new(&x.key).attr("degree", x.degree);
+    if x.mark {
+        shape = shape.attr("mark", true);
+    }
+    x.child
+        .as_ref()
+        .map_or_else(Vec::new, ring)
+        .iter()
+        .fold(shape, |shape, y| shape.child(Some(self::shape(y))))
+}
+
+impl<K: Ord + Debug, V: Debug> Default for FibonacciHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The rings are cycles of `Rc`s, so the nodes are counted by walking them. The identity is shared
+// with the handles, but counted here.
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for FibonacciHeap<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        rc_bytes::<HeapId>() + self.min.as_ref().map_or(0, ring_bytes)
+    }
+}
+fn ring_bytes<K: MemoryUsage, V: MemoryUsage>(x: &NodeRef<K, V>) -> usize {
+    ring(x)
+        .iter()
+        .map(|x| {
+            let x = x.borrow();
+            rc_bytes::<RefCell<Node<K, V>>>()
+                + x.key.heap_size_bytes()
+                + x.value.heap_size_bytes()
+                + x.child.as_ref().map_or(0, ring_bytes)
+        })
+        .sum()
+}
+
+/// A reference to an element of a [`FibonacciHeap`], returned by [`FibonacciHeap::push`].
+///
+/// It remembers the heap that issued it, so passing it to another heap is an error rather than
+/// silent corruption. It stays valid after the heap is appended to another one.
+pub struct Handle<K, V> {
+    id: Rc<HeapId>,
+    node: Weak<RefCell<Node<K, V>>>,
+}
+impl<K, V> Clone for Handle<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            id: Rc::clone(&self.id),
+            node: Weak::clone(&self.node),
+        }
+    }
+}
+impl<K, V> Debug for Handle<K, V> {
+    fn fmt(&self, w: &mut Formatter) -> fmt::Result {
+        w.debug_struct("Handle")
+            .field("id", &Rc::as_ptr(&self.id))
+            .field("node", &self.node.as_ptr())
+            .finish()
+    }
+}
+
+// The identity of a heap. When a heap is appended to another, its identity is forwarded to the
+// other one.
+#[derive(Debug, Default)]
+struct HeapId {
+    forward: RefCell<Option<Rc<HeapId>>>,
+}
+impl HeapId {
+    fn resolve(self: &Rc<Self>) -> Rc<Self> {
+        let mut id = Rc::clone(self);
+        loop {
+            let next = id.forward.borrow().as_ref().map(Rc::clone);
+            match next {
+                Some(next) => id = next,
+                None => return id,
+            }
         }
     }
 }
 
-#[derive(Debug)]
-pub struct Node<K, V> {
+struct Node<K, V> {
     mark: bool,
-    position: usize,
+    degree: usize,
     key: K,
     value: V,
-    child: Vec<Rc<RefCell<Node<K, V>>>>,
+    child: Option<NodeRef<K, V>>,
     parent: Weak<RefCell<Node<K, V>>>,
+    left: Weak<RefCell<Node<K, V>>>,
+    right: Option<NodeRef<K, V>>,
 }
-impl<K: Ord + Debug, V: Debug> Node<K, V> {
-    pub fn new(key: K, value: V, position: usize) -> Self {
-        Self {
+impl<K, V> Node<K, V> {
+    // a singleton circular list
+    fn new(key: K, value: V) -> NodeRef<K, V> {
+        let node = Rc::new(RefCell::new(Self {
             mark: false,
+            degree: 0,
             key,
-            position,
             value,
-            child: Vec::new(),
+            child: None,
             parent: Weak::new(),
+            left: Weak::new(),
+            right: None,
+        }));
+        node.borrow_mut().left = Rc::downgrade(&node);
+        node.borrow_mut().right = Some(Rc::clone(&node));
+        node
+    }
+}
+impl<K: Debug, V: Debug> Debug for Node<K, V> {
+    fn fmt(&self, w: &mut Formatter) -> fmt::Result {
+        w.debug_struct("Node")
+            .field("mark", &self.mark)
+            .field("key", &self.key)
+            .field("value", &self.value)
+            .field("child", &self.child.as_ref().map_or_else(Vec::new, ring))
+            .finish()
+    }
+}
+
+// the nodes of the circular list from `x`
+fn ring<K, V>(x: &NodeRef<K, V>) -> Vec<NodeRef<K, V>> {
+    let mut vec = vec![Rc::clone(x)];
+    loop {
+        let y = Rc::clone(vec.last().unwrap().borrow().right.as_ref().unwrap());
+        if Rc::ptr_eq(&y, x) {
+            return vec;
         }
+        vec.push(y);
+    }
+}
+// Concatenate two circular lists: `a` -> `b` -> ... -> (the left of `b`) -> (the right of `a`) -> ...
+fn splice<K, V>(a: &NodeRef<K, V>, b: &NodeRef<K, V>) {
+    let a_right = a.borrow_mut().right.take().unwrap();
+    let b_left = b.borrow().left.upgrade().unwrap();
+    a.borrow_mut().right = Some(Rc::clone(b));
+    b.borrow_mut().left = Rc::downgrade(a);
+    a_right.borrow_mut().left = Rc::downgrade(&b_left);
+    b_left.borrow_mut().right = Some(a_right);
+}
+// Remove `x` from its circular list and return the rest if any.
+fn unlink<K, V>(x: &NodeRef<K, V>) -> Option<NodeRef<K, V>> {
+    let right = x.borrow_mut().right.take().unwrap();
+    if Rc::ptr_eq(&right, x) {
+        x.borrow_mut().right = Some(right);
+        None
+    } else {
+        let left = x.borrow().left.upgrade().unwrap();
+        right.borrow_mut().left = Rc::downgrade(&left);
+        left.borrow_mut().right = Some(Rc::clone(&right));
+        x.borrow_mut().left = Rc::downgrade(x);
+        x.borrow_mut().right = Some(Rc::clone(x));
+        Some(right)
     }
 }
+// Make a root `y` a child of a root `x`.
+fn link<K, V>(y: &NodeRef<K, V>, x: &NodeRef<K, V>) {
+    y.borrow_mut().parent = Rc::downgrade(x);
+    y.borrow_mut().mark = false;
+    let child = x.borrow_mut().child.take();
+    let child = match child {
+        Some(child) => {
+            splice(&child, y);
+            child
+        }
+        None => Rc::clone(y),
+    };
+    x.borrow_mut().child = Some(child);
+    x.borrow_mut().degree += 1;
+}
 
 #[cfg(test)]
 mod tests {
     use {
-        super::FibonacciHeap,
+        super::{ring, FibonacciHeap, Handle, HandleError, HeapId, Node},
         itertools::Itertools,
-        paren::Paren,
+        memory_usage::{rc_bytes, MemoryUsage},
+        paren::{Paren, Visualize},
         rand::prelude::*,
         std::{
             cell::RefCell,
@@ -209,6 +446,21 @@ mod tests {
         yansi::Paint,
     };
 
+    #[test]
+    fn test_heap_size_bytes() {
+        let node = rc_bytes::<RefCell<Node<u32, String>>>();
+        let mut fib = FibonacciHeap::new();
+        let empty = fib.heap_size_bytes();
+        assert_eq!(empty, rc_bytes::<HeapId>());
+        for key in 0..10 {
+            fib.push(key, "x".repeat(key as usize));
+        }
+        assert_eq!(fib.heap_size_bytes(), empty + 10 * node + 45);
+        // The rest are consolidated into trees.
+        fib.pop();
+        assert_eq!(fib.heap_size_bytes(), empty + 9 * node + 45);
+    }
+
     #[test]
     fn test_push_append() {
         let mut test = Test::new();
@@ -250,22 +502,111 @@ mod tests {
         test.pop();
     }
 
+    #[test]
+    fn test_pretty() {
+        let mut fib = FibonacciHeap::new();
+        let handles = (0..6).map(|key| fib.push(key, ())).collect::<Vec<_>>();
+        fib.pop();
+        fib.decrease_key(&handles[5], 0).unwrap();
+        // 5 has been cut from 4, which is marked now.
+        let expected = [
+            "0 degree=0",
+            "/-- 4 degree=0 mark=true",
+            "2 degree=2",
+            "\\-- 3 degree=0",
+            "1 degree=0",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(fib.pretty(), expected);
+    }
+
     #[test]
     fn test_decrease_key() {
         let mut test = Test::new();
         let h0 = test.push(20);
-        test.decrease_key(Weak::upgrade(&h0).unwrap(), 18);
+        test.decrease_key(&h0, 18);
         let h1 = test.push(21);
         let h2 = test.push(22);
         let h3 = test.push(23);
-        test.decrease_key(Weak::upgrade(&h1).unwrap(), 10);
-        test.decrease_key(Weak::upgrade(&h2).unwrap(), 14);
-        test.decrease_key(Weak::upgrade(&h3).unwrap(), 8);
+        test.decrease_key(&h1, 10);
+        test.decrease_key(&h2, 14);
+        test.decrease_key(&h3, 8);
         let h4 = test.push(24);
         test.pop();
-        test.decrease_key(Weak::upgrade(&h1).unwrap(), 7);
-        test.decrease_key(Weak::upgrade(&h2).unwrap(), 2);
-        test.decrease_key(Weak::upgrade(&h4).unwrap(), 9);
+        test.decrease_key(&h1, 7);
+        test.decrease_key(&h2, 2);
+        test.decrease_key(&h4, 9);
+    }
+
+    #[test]
+    fn test_delete() {
+        let mut test = Test::new();
+        let h = (0..8).map(|i| test.push(20 + i)).collect::<Vec<_>>();
+        test.pop();
+        test.delete(&h[5]);
+        test.decrease_key(&h[7], 10);
+        test.delete(&h[7]);
+        test.delete(&h[1]);
+        test.delete(&h[3]);
+        test.pop();
+    }
+
+    #[test]
+    fn test_increase_key() {
+        let mut test = Test::new();
+        let h = (0..8).map(|i| test.push(20 + i)).collect::<Vec<_>>();
+        test.pop();
+        test.increase_key(&h[2], 40);
+        test.increase_key(&h[1], 41);
+        test.increase_key(&h[7], 30);
+        test.decrease_key(&h[2], 10);
+        test.increase_key(&h[2], 50);
+        test.pop();
+        test.pop();
+    }
+
+    #[test]
+    fn test_handle_misuse() {
+        let mut fib = FibonacciHeap::new();
+        let mut other = FibonacciHeap::new();
+        let h = fib.push(10, "a");
+        let g = other.push(20, "b");
+        assert_eq!(fib.get(&h), Ok((10, "a")));
+        assert!(!fib.is_live(&g));
+        assert_eq!(fib.get(&g), Err(HandleError::ForeignHeap));
+        assert_eq!(fib.decrease_key(&g, 0), Err(HandleError::ForeignHeap));
+        assert_eq!(fib.delete(&g), Err(HandleError::ForeignHeap));
+        assert_eq!(fib.decrease_key(&h, 11), Err(HandleError::KeyIncreased));
+        assert_eq!(fib.increase_key(&h, 9), Err(HandleError::KeyDecreased));
+        assert_eq!(fib.get(&h), Ok((10, "a")));
+
+        // Handles of `other` are handed over to `fib`.
+        fib.append(&mut other);
+        assert!(fib.is_live(&g));
+        assert!(!other.is_live(&g));
+        assert_eq!(fib.decrease_key(&g, 5), Ok(()));
+        assert_eq!(fib.pop(), Some((5, "b")));
+        assert!(!fib.is_live(&g));
+        assert_eq!(fib.get(&g), Err(HandleError::Removed));
+        assert_eq!(fib.delete(&h), Ok((10, "a")));
+        assert_eq!(fib.delete(&h), Err(HandleError::Removed));
+        assert!(fib.is_empty());
+
+        // `other` gets a new identity after being appended.
+        let k = other.push(30, "c");
+        assert!(other.is_live(&k));
+        assert!(!fib.is_live(&k));
+    }
+
+    #[test]
+    fn test_drop() {
+        let mut fib = FibonacciHeap::new();
+        let h = (0..20).map(|i| fib.push(i, ())).collect::<Vec<_>>();
+        fib.pop();
+        fib.decrease_key(&h[10], 0).unwrap();
+        drop(fib);
+        assert!(h.iter().all(|h| h.node.upgrade().is_none()));
     }
 
     #[test]
@@ -286,9 +627,8 @@ mod tests {
                         if vec.is_empty() {
                             continue;
                         }
-                        let h = Weak::upgrade(&vec[rng.gen_range(0, vec.len())]);
-                        if let Some(h) = h {
-                            let key = h.borrow().key;
+                        let h = &vec[rng.gen_range(0, vec.len())];
+                        if let Ok((key, ())) = test.fib.get(h) {
                             if key == 0 {
                                 continue;
                             }
@@ -301,6 +641,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rand_delete_increase_key() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let mut test = Test::new();
+            let mut vec = Vec::new();
+            for _ in 0..200 {
+                match rng.gen_range(0, 8) {
+                    0..=2 => vec.push(test.push(rng.gen_range(0, 100))),
+                    3 => test.pop(),
+                    4..=7 => {
+                        if vec.is_empty() {
+                            continue;
+                        }
+                        let h = &vec[rng.gen_range(0, vec.len())];
+                        if let Ok((key, ())) = test.fib.get(h) {
+                            match rng.gen_range(0, 3) {
+                                0 => test.decrease_key(h, rng.gen_range(0, key + 1)),
+                                1 => test.increase_key(h, rng.gen_range(key, 100)),
+                                2 => test.delete(h),
+                                _ => unreachable!(),
+                            }
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_rand_append() {
         rand_append(100, 100);
@@ -320,9 +690,8 @@ mod tests {
                         if vec.is_empty() {
                             continue;
                         }
-                        let h = Weak::upgrade(&vec[rng.gen_range(0, vec.len())]);
-                        if let Some(h) = h {
-                            let key = h.borrow().key;
+                        let h = &vec[rng.gen_range(0, vec.len())];
+                        if let Ok((key, ())) = test.fib.get(h) {
                             if key == 0 {
                                 continue;
                             }
@@ -346,9 +715,8 @@ mod tests {
                         if vec.is_empty() {
                             continue;
                         }
-                        let h = Weak::upgrade(&vec[rng.gen_range(0, vec.len())]);
-                        if let Some(h) = h {
-                            let key = h.borrow().key;
+                        let h = &vec[rng.gen_range(0, vec.len())];
+                        if let Ok((key, ())) = test.fib.get(h) {
                             if key == 0 {
                                 continue;
                             }
@@ -376,7 +744,7 @@ mod tests {
                 bin: BinaryHeap::new(),
             }
         }
-        fn push(&mut self, key: u32) -> Weak<RefCell<super::Node<u32, ()>>> {
+        fn push(&mut self, key: u32) -> Handle<u32, ()> {
             println!(
                 "{} {} to {}",
                 Paint::red("Push").bold(),
@@ -384,7 +752,7 @@ mod tests {
                 self.fib.to_paren()
             );
             let res = self.fib.push(key, ());
-            assert_eq!(Weak::upgrade(&res).unwrap().borrow().key, key);
+            assert_eq!(self.fib.get(&res), Ok((key, ())));
             self.bin.push(Reverse(key));
             self.postprocess();
             res
@@ -407,22 +775,52 @@ mod tests {
             assert_eq!(res, exp);
             self.postprocess();
         }
-        fn decrease_key(&mut self, x: Rc<RefCell<super::Node<u32, ()>>>, key: u32) {
+        fn decrease_key(&mut self, x: &Handle<u32, ()>, key: u32) {
+            let (old, ()) = self.fib.get(x).unwrap();
             println!(
                 "{} {} in {} down to {}",
                 Paint::blue("Decrease a key").bold(),
+                old,
                 self.fib.to_paren(),
-                &x.borrow().key,
                 key,
             );
             let mut vec = self.bin.drain().collect::<Vec<_>>();
-            let i = vec
-                .iter()
-                .position(|&Reverse(item)| item == x.borrow().key)
-                .unwrap();
+            let i = vec.iter().position(|&Reverse(item)| item == old).unwrap();
+            vec[i] = Reverse(key);
+            self.bin = vec.iter().copied().collect::<BinaryHeap<_>>();
+            self.fib.decrease_key(x, key).unwrap();
+            self.postprocess();
+        }
+        fn increase_key(&mut self, x: &Handle<u32, ()>, key: u32) {
+            let (old, ()) = self.fib.get(x).unwrap();
+            println!(
+                "{} {} in {} up to {}",
+                Paint::blue("Increase a key").bold(),
+                old,
+                self.fib.to_paren(),
+                key,
+            );
+            let mut vec = self.bin.drain().collect::<Vec<_>>();
+            let i = vec.iter().position(|&Reverse(item)| item == old).unwrap();
             vec[i] = Reverse(key);
             self.bin = vec.iter().copied().collect::<BinaryHeap<_>>();
-            self.fib.decrease_key(x, key);
+            self.fib.increase_key(x, key).unwrap();
+            self.postprocess();
+        }
+        fn delete(&mut self, x: &Handle<u32, ()>) {
+            let (old, ()) = self.fib.get(x).unwrap();
+            println!(
+                "{} {} from {}",
+                Paint::blue("Delete").bold(),
+                old,
+                self.fib.to_paren(),
+            );
+            let mut vec = self.bin.drain().collect::<Vec<_>>();
+            let i = vec.iter().position(|&Reverse(item)| item == old).unwrap();
+            vec.swap_remove(i);
+            self.bin = vec.iter().copied().collect::<BinaryHeap<_>>();
+            assert_eq!(self.fib.delete(x), Ok((old, ())));
+            assert!(!self.fib.is_live(x));
             self.postprocess();
         }
         fn postprocess(&self) {
@@ -443,35 +841,74 @@ mod tests {
     }
     impl<K: Ord + Debug, V: Debug> Validate for FibonacciHeap<K, V> {
         fn validate(&self) {
-            self.chain.iter().enumerate().for_each(|(i, node)| {
-                node.validate();
-                assert_eq!(i, node.borrow().position);
-            })
+            let roots = self.min.as_ref().map_or_else(Vec::new, ring);
+            validate_ring(&roots);
+            for root in roots.iter() {
+                root.validate();
+                assert!(root.borrow().parent.upgrade().is_none());
+                assert!(
+                    self.min.as_ref().unwrap().borrow().key <= root.borrow().key,
+                    "`min` is not the minimum."
+                );
+            }
+            assert_eq!(roots.iter().map(size).sum::<usize>(), self.len);
         }
     }
+    // the links of a circular list
+    fn validate_ring<K, V>(ring: &[Rc<RefCell<super::Node<K, V>>>]) {
+        for (i, x) in ring.iter().enumerate() {
+            let y = &ring[(i + 1) % ring.len()];
+            assert!(Rc::ptr_eq(x.borrow().right.as_ref().unwrap(), y));
+            assert!(Weak::ptr_eq(&y.borrow().left, &Rc::downgrade(x)));
+        }
+    }
+    // The number of the nodes in the subtree, asserting that it is at least F_{degree + 2}, which
+    // holds as long as the marks are maintained correctly.
+    fn size<K: Ord + Debug, V: Debug>(node: &Rc<RefCell<super::Node<K, V>>>) -> usize {
+        let child = node.borrow().child.as_ref().map_or_else(Vec::new, ring);
+        let size = 1 + child.iter().map(size).sum::<usize>();
+        let deg = node.borrow().degree;
+        let (mut f0, mut f1) = (0, 1);
+        (0..deg + 2).for_each(|_| {
+            f1 += f0;
+            f0 = f1 - f0;
+        });
+        assert!(
+            f0 <= size,
+            "Too few nodes: degree = {}, size = {}",
+            deg,
+            size
+        );
+        size
+    }
     impl<K: Ord + Debug, V: Debug> Validate for Rc<RefCell<super::Node<K, V>>> {
         fn validate(&self) {
-            for child in self.borrow().child.iter() {
+            let child = self.borrow().child.as_ref().map_or_else(Vec::new, ring);
+            assert_eq!(self.borrow().degree, child.len());
+            validate_ring(&child);
+            for child in child.iter() {
+                child.validate();
+                assert!(
+                    self.borrow().key <= child.borrow().key,
+                    "Heap order is violated."
+                );
                 assert!(
                     Weak::ptr_eq(&child.borrow().parent, &Rc::downgrade(self)),
                     "Parent of a child is not me."
                 );
-                assert!(Rc::ptr_eq(
-                    &self.borrow().child[child.borrow().position],
-                    &child
-                ));
             }
         }
     }
     impl<K: Ord + Debug, V: Debug> Paren for FibonacciHeap<K, V> {
         fn paren(&self, w: &mut Formatter) -> fmt::Result {
             write!(w, "FibonacciHeap {{ len: {}, paren:", self.len())?;
-            self.chain
+            self.min
+                .as_ref()
+                .map_or_else(Vec::new, ring)
                 .iter()
                 .map(|node| format!("{:?}", paren::Wrapper(&*node.borrow())))
                 .intersperse(",".to_owned())
-                .map(|s| write!(w, "{}", s))
-                .collect::<fmt::Result>()?;
+                .try_for_each(|s| write!(w, "{}", s))?;
             write!(w, "}}")
         }
     }
@@ -480,6 +917,8 @@ mod tests {
             write!(w, "(")?;
             write!(w, "{:?}", &self.key)?;
             self.child
+                .as_ref()
+                .map_or_else(Vec::new, ring)
                 .iter()
                 .map(|node| node.borrow().paren(w))
                 .collect::<fmt::Result>()?;
@@ -1,11 +1,9 @@
+mod memory;
 mod paren;
 mod validate;
 
+use std::{cmp::Ordering, fmt::Debug, iter::once, mem::replace, ops::Add};
 pub use validate::Validate;
-use {
-    std::{cmp::Ordering, fmt::Debug, iter::once, mem::replace, ops::Add},
-    yansi::Paint,
-};
 
 pub struct LLRB<K, V>(BoxNode<K, V>);
 impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> LLRB<K, V> {
@@ -19,6 +17,9 @@ impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> LLRB<K, V> {
     pub fn get(&self, i: usize) -> Option<&Node<K, V>> {
         self.0.get(i)
     }
+    pub fn find(&self, key: &K) -> Option<&Node<K, V>> {
+        self.0.find(key)
+    }
     pub fn fold(&self, l: usize, r: usize) -> Option<V> {
         assert!(l <= r && r <= self.len());
         self.0.fold(l, r)
@@ -66,7 +67,6 @@ impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> BoxNode<K, V> {
         self.0.as_ref().map(|node| &node.acc)
     }
     fn update(&mut self) {
-        println!("update {:?}", &self);
         if let Some(me) = self.0.as_mut() {
             me.size = 1 + me.child.iter().map(|child| child.size()).sum::<usize>();
             me.acc = me
@@ -77,6 +77,14 @@ impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> BoxNode<K, V> {
                 .fold(me.value.clone(), |x, y| x + y.clone())
         }
     }
+    fn find(&self, key: &K) -> Option<&Node<K, V>> {
+        let me = self.0.as_ref()?;
+        match key.cmp(&me.key) {
+            Ordering::Less => me.child[0].find(key),
+            Ordering::Greater => me.child[1].find(key),
+            Ordering::Equal => Some(me),
+        }
+    }
     fn get(&self, i: usize) -> Option<&Node<K, V>> {
         let me = self.0.as_ref()?;
         let lsize = me.child[0].size();
@@ -322,22 +330,17 @@ enum Color {
     Red,
     Black,
 }
-impl Color {
-    fn paint<T>(&self, x: T) -> Paint<T> {
-        match self {
-            Self::Red => Paint::red(x),
-            Self::Black => Paint::blue(x),
-        }
-        .bold()
-    }
-}
 
 #[cfg(test)]
 mod tests {
     use super::Validate;
-    use super::LLRB;
+    use super::{Node, LLRB};
+    use invariant::Property;
+    use memory_usage::MemoryUsage;
+    use paren::{FromParen, Paren};
     use rand::prelude::*;
     use randtools;
+    use std::mem::size_of;
 
     #[test]
     fn test_hand_insert() {
@@ -374,6 +377,51 @@ mod tests {
         (0..10).for_each(|i| test.get(i));
     }
 
+    #[test]
+    fn test_paren() {
+        let mut llrb = LLRB::<u32, u32>::from_paren("((1:B:10)2:B:20(3:B:30))").unwrap();
+        assert_eq!(llrb.validate(), 2);
+        assert_eq!(llrb.fold(0, 3), Some(60));
+        llrb.insert(4, 40);
+        let expected = "((1:B:10:10)2:B:20:100((3:R:30:30)4:B:40:70))";
+        assert_eq!(llrb.to_paren(), expected);
+        assert_eq!(llrb.validate(), 2);
+        let parsed = LLRB::<u32, u32>::from_paren(expected).unwrap();
+        assert_eq!(parsed.to_paren(), expected);
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut llrb = LLRB::new();
+        (0..4).for_each(|i| llrb.insert(i, 10));
+        assert_eq!(llrb.check_invariants(), Ok(()));
+        let root = llrb.0 .0.as_mut().unwrap();
+        root.child[0].0.as_mut().unwrap().size += 1;
+        let e = llrb.check_invariants().unwrap_err();
+        assert_eq!((e.property, e.path), (Property::SizeCache, vec![0]));
+
+        let mut llrb = LLRB::new();
+        (0..4).for_each(|i| llrb.insert(i, 10));
+        llrb.0 .0.as_mut().unwrap().acc = 0;
+        let e = llrb.check_invariants().unwrap_err();
+        assert_eq!((e.property, e.path), (Property::AccCache, vec![]));
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let node = size_of::<Node<u32, u32>>();
+        let mut llrb = LLRB::<u32, u32>::new();
+        assert_eq!(llrb.heap_size_bytes(), 0);
+        for k in 0..10 {
+            llrb.insert(k, k);
+        }
+        assert_eq!(llrb.heap_size_bytes(), 10 * node);
+        for k in 0..4 {
+            llrb.delete(&k);
+        }
+        assert_eq!(llrb.heap_size_bytes(), 6 * node);
+    }
+
     #[test]
     fn test_rand_small() {
         test_rand(10, 50, 42);
@@ -482,6 +530,11 @@ mod tests {
             Validate::validate(&self.llrb);
             assert_eq!(&self.llrb.collect_vec(), &self.vec);
             assert_eq!(self.llrb.len(), self.vec.len());
+            for x in 0..30 {
+                let result = self.llrb.find(&x).map(|node| (node.key, node.value));
+                let expected = self.vec.iter().find(|&&(key, _)| key == x).copied();
+                assert_eq!(result, expected, "Failed in `find`");
+            }
         }
     }
 }
@@ -1,15 +1,13 @@
 use {
-    super::{BoxNode, Node, LLRB},
+    super::{BoxNode, Color, Node, LLRB},
+    paren::{FromParen, Painted, Paren, ParseError, Shape, Visualize},
     std::{
         fmt::{self, Debug},
         ops::Add,
+        str::FromStr,
     },
 };
 
-pub trait Paren {
-    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result;
-}
-
 impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> Paren for LLRB<K, V> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
         self.0.paren(w)
@@ -26,8 +24,9 @@ impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> Paren for Node<K, V> {
         self.child[0].paren(w)?;
         write!(
             w,
-            "{:?}:{:?}:{:?}",
-            self.color.paint(&self.key),
+            "{:?}:{}:{:?}:{:?}",
+            paren::paint(w, self.color.into(), &self.key),
+            paren::Color::from(self.color),
             &self.value,
             &self.acc
         )?;
@@ -36,25 +35,81 @@ impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> Paren for Node<K, V> {
     }
 }
 
-struct ParenWrapper<'a, T>(&'a T);
-impl<'a, T: Paren> Debug for ParenWrapper<'a, T> {
-    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        self.0.paren(w)
+impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> Visualize for LLRB<K, V> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.0.shape().into_iter().collect()
+    }
+}
+impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> BoxNode<K, V> {
+    fn shape(&self) -> Option<Shape> {
+        self.0.as_ref().map(|x| {
+            Shape::new(&x.key)
+                .color(x.color)
+                .attr("value", &x.value)
+                .attr("acc", &x.acc)
+                .child(x.child[0].shape())
+                .child(x.child[1].shape())
+        })
+    }
+}
+
+// The value is the third field of a label, and the sizes and the folds are computed, so the fourth
+// field may be left out, e.g. `((1:R:10)2:B:20)`.
+impl<K: Ord + Debug + FromStr, V: Clone + Add<Output = V> + Debug + FromStr> FromParen
+    for LLRB<K, V>
+{
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        BoxNode::from_parsed(paren::parse(s)?).map(Self)
+    }
+}
+impl<K: Ord + Debug + FromStr, V: Clone + Add<Output = V> + Debug + FromStr> BoxNode<K, V> {
+    fn from_parsed(node: Option<Box<paren::Node>>) -> Result<Self, ParseError> {
+        match node {
+            None => Ok(Self::nil()),
+            Some(node) => {
+                let paren::Node {
+                    label,
+                    child: [left, right],
+                } = *node;
+                let mut node = Node::new(label.key()?, label.field(2)?, label.color()?.into());
+                node.child = [Self::from_parsed(left)?, Self::from_parsed(right)?];
+                let mut x = node.boxed();
+                x.update();
+                Ok(x)
+            }
+        }
+    }
+}
+
+impl From<Color> for paren::Color {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Red => paren::Color::Red,
+            Color::Black => paren::Color::Black,
+        }
+    }
+}
+impl From<paren::Color> for Color {
+    fn from(color: paren::Color) -> Self {
+        match color {
+            paren::Color::Red => Color::Red,
+            paren::Color::Black => Color::Black,
+        }
     }
 }
 
 impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> Debug for LLRB<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("LLRB").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("LLRB").field(&Painted(self)).finish()
     }
 }
 impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> Debug for BoxNode<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("BoxNode").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("BoxNode").field(&Painted(self)).finish()
     }
 }
 impl<K: Ord + Debug, V: Clone + Add<Output = V> + Debug> Debug for Node<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("Node").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("Node").field(&Painted(self)).finish()
     }
 }
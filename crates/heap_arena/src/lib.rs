@@ -0,0 +1,327 @@
+// The slots shared by the addressable heaps, so that a heap can be appended to another one
+// without touching the nodes of either.
+//
+// A heap holds only its roots and borrows a pool of an `Arena` of its nodes and `Heaps` in every
+// operation. All the heaps which may be appended to each other must share one pool, and using a
+// heap with another pool is a logic error: it does not cause undefined behavior, but it may panic
+// or return meaningless results.
+use {
+    memory_usage::MemoryUsage,
+    std::{
+        collections::HashMap,
+        error::Error,
+        fmt::{self, Display, Formatter},
+        ops::{Index, IndexMut},
+        sync::atomic::{self, AtomicUsize},
+    },
+};
+
+static HEAP_COUNT: AtomicUsize = AtomicUsize::new(0);
+static ARENA_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// The identity of a heap, unique in the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HeapId(usize);
+impl HeapId {
+    pub fn new() -> Self {
+        Self(HEAP_COUNT.fetch_add(1, atomic::Ordering::Relaxed))
+    }
+}
+impl Default for HeapId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl MemoryUsage for HeapId {
+    fn heap_size_bytes(&self) -> usize {
+        0
+    }
+}
+
+// A reference to an element of a heap, returned by `push`.
+//
+// It remembers the arena and the heap that issued it and the generation of the slot, so passing it
+// to another heap or after removing the element is an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    arena: usize,
+    heap: HeapId,
+    index: usize,
+    generation: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleError {
+    /// The handle was issued by another heap.
+    ForeignHeap,
+    /// The element has already been popped or deleted.
+    Removed,
+    /// `decrease_key` was called with a key greater than the current one.
+    KeyIncreased,
+    /// `increase_key` was called with a key less than the current one.
+    KeyDecreased,
+}
+impl Display for HandleError {
+    fn fmt(&self, w: &mut Formatter) -> fmt::Result {
+        match self {
+            HandleError::ForeignHeap => write!(w, "the handle belongs to another heap"),
+            HandleError::Removed => write!(w, "the element has already been removed"),
+            HandleError::KeyIncreased => write!(w, "a new key is greater than an old one"),
+            HandleError::KeyDecreased => write!(w, "a new key is less than an old one"),
+        }
+    }
+}
+impl Error for HandleError {}
+
+// A `Vec` of reusable slots whose generations are counted, so that a stale handle is detected.
+#[derive(Debug)]
+pub struct Arena<T> {
+    id: usize,
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+}
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self {
+            id: ARENA_COUNT.fetch_add(1, atomic::Ordering::Relaxed),
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+    pub fn insert(&mut self, value: T) -> usize {
+        match self.free.pop() {
+            Some(x) => {
+                self.slots[x].value = Some(value);
+                x
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: Some(value),
+                });
+                self.slots.len() - 1
+            }
+        }
+    }
+    pub fn remove(&mut self, x: usize) -> T {
+        let slot = &mut self.slots[x];
+        slot.generation += 1;
+        self.free.push(x);
+        slot.value.take().unwrap()
+    }
+    pub fn pair_mut(&mut self, x: usize, y: usize) -> (&mut T, &mut T) {
+        assert_ne!(x, y);
+        if x < y {
+            let (left, right) = self.slots.split_at_mut(y);
+            (
+                left[x].value.as_mut().unwrap(),
+                right[0].value.as_mut().unwrap(),
+            )
+        } else {
+            let (y, x) = self.pair_mut(y, x);
+            (x, y)
+        }
+    }
+    pub fn handle(&self, heap: HeapId, x: usize) -> Handle {
+        Handle {
+            arena: self.id,
+            heap,
+            index: x,
+            generation: self.slots[x].generation,
+        }
+    }
+    // The slot of `handle` if its element is live and held by `heap`. The generation is checked
+    // before the heap, since the id of an element that has been removed may be forgotten by `heaps`.
+    pub fn resolve(
+        &self,
+        heaps: &Heaps,
+        heap: HeapId,
+        handle: &Handle,
+    ) -> Result<usize, HandleError> {
+        if handle.arena != self.id {
+            return Err(HandleError::ForeignHeap);
+        }
+        if self.slots[handle.index].generation != handle.generation {
+            return Err(HandleError::Removed);
+        }
+        if heaps.find(handle.heap) == heap {
+            Ok(handle.index)
+        } else {
+            Err(HandleError::ForeignHeap)
+        }
+    }
+}
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> Index<usize> for Arena<T> {
+    type Output = T;
+    fn index(&self, x: usize) -> &T {
+        self.slots[x].value.as_ref().unwrap()
+    }
+}
+impl<T> IndexMut<usize> for Arena<T> {
+    fn index_mut(&mut self, x: usize) -> &mut T {
+        self.slots[x].value.as_mut().unwrap()
+    }
+}
+
+// The slots of the removed values are kept for reuse.
+impl<T: MemoryUsage> MemoryUsage for Arena<T> {
+    fn heap_size_bytes(&self) -> usize {
+        self.slots.heap_size_bytes() + self.free.heap_size_bytes()
+    }
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+    generation: usize,
+    value: Option<T>,
+}
+impl<T: MemoryUsage> MemoryUsage for Slot<T> {
+    fn heap_size_bytes(&self) -> usize {
+        self.value.heap_size_bytes()
+    }
+}
+
+// The heaps sharing a pool, as a union-find of their ids.
+//
+// An element joins the heap under its id at `push`, and leaves it when removed. Appending a heap
+// to another links their ids, so the id in a handle finds the heap that holds the element now.
+// An id is forgotten once no element nor id is under it, so there are at most as many ids as the
+// live elements, however many heaps have been appended.
+#[derive(Debug, Default)]
+pub struct Heaps {
+    ids: HashMap<HeapId, Entry>,
+}
+impl Heaps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn join(&mut self, heap: HeapId) {
+        self.ids.entry(heap).or_default().count += 1;
+    }
+    pub fn leave(&mut self, mut heap: HeapId) {
+        loop {
+            let entry = self.ids.get_mut(&heap).unwrap();
+            entry.count -= 1;
+            if entry.count != 0 {
+                break;
+            }
+            match self.ids.remove(&heap).unwrap().parent {
+                Some(parent) => heap = parent,
+                None => break,
+            }
+        }
+    }
+    // The heap that holds the elements pushed under `heap`, in O(lg h) time for the h heaps
+    // appended, since the union is by rank.
+    pub fn find(&self, mut heap: HeapId) -> HeapId {
+        while let Some(parent) = self.ids.get(&heap).and_then(|entry| entry.parent) {
+            heap = parent;
+        }
+        heap
+    }
+    // Link the ids of two heaps, and return the id of the merged one.
+    pub fn union(&mut self, x: HeapId, y: HeapId) -> HeapId {
+        assert_ne!(x, y);
+        debug_assert_eq!(self.find(x), x);
+        debug_assert_eq!(self.find(y), y);
+        let (x_rank, y_rank) = match (self.ids.get(&x), self.ids.get(&y)) {
+            (_, None) => return x,
+            (None, Some(_)) => return y,
+            (Some(x), Some(y)) => (x.rank, y.rank),
+        };
+        let (root, child) = if x_rank < y_rank { (y, x) } else { (x, y) };
+        self.ids.get_mut(&child).unwrap().parent = Some(root);
+        let entry = self.ids.get_mut(&root).unwrap();
+        entry.count += 1;
+        if x_rank == y_rank {
+            entry.rank += 1;
+        }
+        root
+    }
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+impl MemoryUsage for Heaps {
+    fn heap_size_bytes(&self) -> usize {
+        self.ids.heap_size_bytes()
+    }
+}
+
+// `count` is the number of the elements and the ids directly under the id.
+#[derive(Debug, Default)]
+struct Entry {
+    parent: Option<HeapId>,
+    rank: u32,
+    count: usize,
+}
+impl MemoryUsage for Entry {
+    fn heap_size_bytes(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Arena, HandleError, HeapId, Heaps};
+
+    #[test]
+    fn test_resolve() {
+        let mut arena = Arena::new();
+        let mut heaps = Heaps::new();
+        let (x, y) = (HeapId::new(), HeapId::new());
+        let a = arena.insert("a");
+        heaps.join(x);
+        let a = arena.handle(x, a);
+        let b = arena.insert("b");
+        heaps.join(y);
+        let b = arena.handle(y, b);
+        assert_eq!(arena.resolve(&heaps, x, &a), Ok(0));
+        assert_eq!(arena.resolve(&heaps, x, &b), Err(HandleError::ForeignHeap));
+        let x = heaps.union(x, y);
+        assert_eq!(arena.resolve(&heaps, x, &b), Ok(1));
+        assert_eq!(arena.remove(1), "b");
+        heaps.leave(y);
+        assert_eq!(arena.resolve(&heaps, x, &b), Err(HandleError::Removed));
+        assert_eq!(arena.insert("c"), 1);
+        assert_eq!(arena.resolve(&heaps, x, &b), Err(HandleError::Removed));
+        let other = Arena::<()>::new();
+        assert_eq!(other.resolve(&heaps, x, &a), Err(HandleError::ForeignHeap));
+    }
+
+    // Appending many heaps leaves no ids behind once their elements are removed.
+    #[test]
+    fn test_forget() {
+        let mut heaps = Heaps::new();
+        let mut root = HeapId::new();
+        let mut ids = Vec::new();
+        for _ in 0..100 {
+            let id = HeapId::new();
+            heaps.join(id);
+            ids.push(id);
+            root = heaps.union(root, id);
+        }
+        assert!(ids.iter().all(|&id| heaps.find(id) == root));
+        let depth = |heaps: &Heaps, mut id| {
+            let mut depth = 0;
+            while let Some(parent) = heaps.ids[&id].parent {
+                id = parent;
+                depth += 1;
+            }
+            depth
+        };
+        assert!(ids.iter().all(|&id| depth(&heaps, id) <= 7));
+        for id in ids {
+            heaps.leave(id);
+        }
+        assert!(heaps.is_empty());
+    }
+}
@@ -7,7 +7,7 @@ fn bench_build(c: &mut Criterion) {
     let mut rng = StdRng::seed_from_u64(42);
 
     group.bench_function("Binary Heap", |b| {
-        b.iter_batched_ref(
+        b.iter_batched(
             || {
                 let n = rng.gen_range(90_000, 100_000);
                 let binary_heap = repeat_with(|| rng.gen_range(0, std::u32::MAX))
@@ -15,13 +15,13 @@ fn bench_build(c: &mut Criterion) {
                     .collect::<Vec<u32>>();
                 binary_heap
             },
-            |a| heap_sort::BinaryHeap::build(a),
+            heap_sort::BinaryHeap::from,
             BatchSize::SmallInput,
         )
     });
 
     group.bench_function("Tertiary Heap", |b| {
-        b.iter_batched_ref(
+        b.iter_batched(
             || {
                 let n = rng.gen_range(90_000, 100_000);
                 let a = repeat_with(|| rng.gen_range(0, std::u32::MAX))
@@ -29,7 +29,7 @@ fn bench_build(c: &mut Criterion) {
                     .collect::<Vec<u32>>();
                 a
             },
-            |a| heap_sort::DArrayHeap::build(a, 3),
+            |a| heap_sort::DArrayHeap::from_vec(a, 3),
             BatchSize::SmallInput,
         )
     });
@@ -44,13 +44,13 @@ fn bench_insert(c: &mut Criterion) {
         let a = repeat_with(|| rng.gen_range(0, std::u32::MAX))
             .take(n)
             .collect::<Vec<u32>>();
-        let mut binary_heap = heap_sort::BinaryHeap::build(&a);
+        let mut binary_heap = heap_sort::BinaryHeap::from(a);
         b.iter_batched_ref(
             || {
                 let x = rng.gen_range(0, std::u32::MAX);
                 x
             },
-            |x| binary_heap.insert(*x),
+            |x| binary_heap.push(*x),
             BatchSize::SmallInput,
         )
     });
@@ -60,14 +60,14 @@ fn bench_insert(c: &mut Criterion) {
         let a = repeat_with(|| rng.gen_range(0, std::u32::MAX))
             .take(n)
             .collect::<Vec<u32>>();
-        let mut tertiary_heap = heap_sort::DArrayHeap::build(&a, 3);
+        let mut tertiary_heap = heap_sort::DArrayHeap::from_vec(a, 3);
 
         b.iter_batched_ref(
             || {
                 let x = rng.gen_range(0, std::u32::MAX);
                 x
             },
-            |x| tertiary_heap.insert(*x),
+            |x| tertiary_heap.push(*x),
             BatchSize::SmallInput,
         )
     });
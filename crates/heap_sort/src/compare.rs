@@ -0,0 +1,38 @@
+use std::cmp::Ordering;
+
+// The order of a heap: the greatest element with respect to `compare` comes to the top.
+pub trait Compare<T> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MaxComparator;
+impl<T: Ord> Compare<T> for MaxComparator {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MinComparator;
+impl<T: Ord> Compare<T> for MinComparator {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        b.cmp(a)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FnComparator<F>(pub F);
+impl<T, F: Fn(&T, &T) -> Ordering> Compare<T> for FnComparator<F> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a, b)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyComparator<F>(pub F);
+impl<T, K: Ord, F: Fn(&T) -> K> Compare<T> for KeyComparator<F> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a).cmp(&(self.0)(b))
+    }
+}
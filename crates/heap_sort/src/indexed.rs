@@ -0,0 +1,230 @@
+use {
+    super::{Compare, MaxComparator, MinComparator},
+    memory_usage::MemoryUsage,
+    std::{cmp::Ordering, mem::replace},
+};
+
+// A d-ary heap of the ids `0..n` with priorities, which remembers the position of each id so that
+// its priority can be changed or removed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedHeap<P, C = MaxComparator> {
+    d: usize,
+    // the ids in the heap order
+    heap: Vec<usize>,
+    // the index of each id in `heap`
+    pos: Vec<Option<usize>>,
+    prio: Vec<Option<P>>,
+    cmp: C,
+}
+
+impl<P: Ord> IndexedHeap<P> {
+    pub fn new(n: usize, d: usize) -> Self {
+        Self::with_comparator(n, d, MaxComparator)
+    }
+}
+impl<P: Ord> IndexedHeap<P, MinComparator> {
+    pub fn new_min(n: usize, d: usize) -> Self {
+        Self::with_comparator(n, d, MinComparator)
+    }
+}
+impl<P, C: Compare<P>> IndexedHeap<P, C> {
+    pub fn with_comparator(n: usize, d: usize, cmp: C) -> Self {
+        assert!(d >= 2);
+        Self {
+            d,
+            heap: Vec::new(),
+            pos: vec![None; n],
+            prio: std::iter::repeat_with(|| None).take(n).collect(),
+            cmp,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+    pub fn contains(&self, id: usize) -> bool {
+        self.pos[id].is_some()
+    }
+    pub fn priority(&self, id: usize) -> Option<&P> {
+        self.prio[id].as_ref()
+    }
+    pub fn peek(&self) -> Option<(usize, &P)> {
+        self.heap
+            .first()
+            .map(|&id| (id, self.prio[id].as_ref().unwrap()))
+    }
+    // O(log_d n)
+    pub fn push(&mut self, id: usize, prio: P) {
+        assert!(!self.contains(id), "{} is already in the heap", id);
+        self.prio[id] = Some(prio);
+        self.pos[id] = Some(self.heap.len());
+        self.heap.push(id);
+        self.sift_up(self.heap.len() - 1);
+    }
+    // O(d log_d n)
+    pub fn pop(&mut self) -> Option<(usize, P)> {
+        let id = *self.heap.first()?;
+        self.remove(id).map(|prio| (id, prio))
+    }
+    // Set the priority of `id` to `prio` in either direction, and return the old one.
+    // O(d log_d n)
+    pub fn change_priority(&mut self, id: usize, prio: P) -> P {
+        let i = self.pos[id].expect("not in the heap");
+        let old = replace(self.prio[id].as_mut().unwrap(), prio);
+        match self.cmp.compare(self.prio[id].as_ref().unwrap(), &old) {
+            Ordering::Greater => self.sift_up(i),
+            Ordering::Less => self.heapify(i),
+            Ordering::Equal => (),
+        }
+        old
+    }
+    // O(d log_d n)
+    pub fn remove(&mut self, id: usize) -> Option<P> {
+        let i = self.pos[id]?;
+        let last = self.heap.len() - 1;
+        self.swap(i, last);
+        self.heap.pop();
+        self.pos[id] = None;
+        if i != last {
+            self.heapify(i);
+            self.sift_up(i);
+        }
+        self.prio[id].take()
+    }
+
+    fn parent(&self, i: usize) -> usize {
+        (i - 1) / self.d
+    }
+    fn compare(&self, i: usize, j: usize) -> Ordering {
+        self.cmp.compare(
+            self.prio[self.heap[i]].as_ref().unwrap(),
+            self.prio[self.heap[j]].as_ref().unwrap(),
+        )
+    }
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        self.pos[self.heap[i]] = Some(i);
+        self.pos[self.heap[j]] = Some(j);
+    }
+    fn heapify(&mut self, i: usize) {
+        let mut largest = i;
+        for j in (self.d * i + 1..=self.d * (i + 1)).take_while(|&j| j < self.heap.len()) {
+            if self.compare(largest, j) == Ordering::Less {
+                largest = j;
+            }
+        }
+        if largest != i {
+            self.swap(i, largest);
+            self.heapify(largest);
+        }
+    }
+    fn sift_up(&mut self, mut i: usize) {
+        while i != 0 && self.compare(self.parent(i), i) == Ordering::Less {
+            let p = self.parent(i);
+            self.swap(i, p);
+            i = p;
+        }
+    }
+}
+
+// `pos` and `prio` are allocated for all the ids by `new`.
+impl<P: MemoryUsage, C> MemoryUsage for IndexedHeap<P, C> {
+    fn heap_size_bytes(&self) -> usize {
+        self.heap.heap_size_bytes() + self.pos.heap_size_bytes() + self.prio.heap_size_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::IndexedHeap, memory_usage::MemoryUsage, rand::prelude::*, std::mem::size_of};
+
+    #[test]
+    fn test_dijkstra() {
+        // CLRS Figure 24.6 with s, t, x, z, y numbered 0, 1, 2, 3, 4.
+        let g = [
+            vec![(1, 10), (4, 5)],
+            vec![(2, 1), (4, 2)],
+            vec![(3, 4)],
+            vec![(0, 7), (2, 6)],
+            vec![(1, 3), (2, 9), (3, 2)],
+        ];
+        let mut dist = vec![u32::MAX; g.len()];
+        let mut heap = IndexedHeap::new_min(g.len(), 2);
+        heap.push(0, 0);
+        while let Some((x, d)) = heap.pop() {
+            dist[x] = d;
+            for &(y, w) in &g[x] {
+                if dist[y] != u32::MAX {
+                    continue;
+                }
+                match heap.priority(y) {
+                    None => heap.push(y, d + w),
+                    Some(&e) if d + w < e => {
+                        heap.change_priority(y, d + w);
+                    }
+                    Some(_) => (),
+                }
+            }
+        }
+        assert_eq!(dist, vec![0, 8, 9, 7, 5]);
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let mut heap = IndexedHeap::<u32>::new(10, 2);
+        let ids = 10 * (size_of::<Option<usize>>() + size_of::<Option<u32>>());
+        assert_eq!(heap.heap_size_bytes(), ids);
+        heap.push(3, 42);
+        assert_eq!(
+            heap.heap_size_bytes(),
+            ids + heap.heap.capacity() * size_of::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_rand() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let n = rng.gen_range(1, 20);
+            let d = rng.gen_range(2, 5);
+            let mut heap = IndexedHeap::new(n, d);
+            let mut prio = vec![None; n];
+            for _ in 0..200 {
+                let id = rng.gen_range(0, n);
+                match rng.gen_range(0, 4) {
+                    0 => {
+                        let p = rng.gen_range(0, 30);
+                        if prio[id].is_some() {
+                            assert_eq!(Some(heap.change_priority(id, p)), prio[id]);
+                        } else {
+                            heap.push(id, p);
+                        }
+                        prio[id] = Some(p);
+                    }
+                    1 => assert_eq!(heap.remove(id), prio[id].take()),
+                    2 => {
+                        let result = heap.pop();
+                        // Ties may be broken in any way.
+                        let expected = prio.iter().filter_map(|&p| p).max();
+                        assert_eq!(result.map(|(_, p)| p), expected);
+                        if let Some((id, p)) = result {
+                            assert_eq!(prio[id].take(), Some(p));
+                        }
+                    }
+                    3 => assert_eq!(heap.contains(id), prio[id].is_some()),
+                    _ => unreachable!(),
+                }
+                assert_eq!(heap.len(), prio.iter().filter(|p| p.is_some()).count());
+                assert_eq!(
+                    heap.peek().map(|(_, &p)| p),
+                    prio.iter().filter_map(|&p| p).max()
+                );
+                for (id, p) in prio.iter().enumerate() {
+                    assert_eq!(heap.priority(id), p.as_ref());
+                }
+            }
+        }
+    }
+}
@@ -1,213 +1,511 @@
+mod compare;
+mod indexed;
+
+pub use {
+    compare::{Compare, FnComparator, KeyComparator, MaxComparator, MinComparator},
+    indexed::IndexedHeap,
+};
+
+use {memory_usage::MemoryUsage, std::cmp::Ordering};
+
+// A binary heap whose top is the greatest element with respect to `C`.
 #[derive(Debug, Clone, PartialEq)]
-pub struct BinaryHeap {
-    heap_size: usize,
-    a: Vec<u32>,
+pub struct BinaryHeap<T, C = MaxComparator> {
+    a: Vec<T>,
+    cmp: C,
 }
 
-impl BinaryHeap {
+impl<T: Ord> BinaryHeap<T> {
+    pub fn new() -> Self {
+        Self::with_comparator(MaxComparator)
+    }
+}
+impl<T: Ord> BinaryHeap<T, MinComparator> {
+    pub fn new_min() -> Self {
+        Self::with_comparator(MinComparator)
+    }
+}
+impl<T, F: Fn(&T, &T) -> Ordering> BinaryHeap<T, FnComparator<F>> {
+    pub fn new_by(f: F) -> Self {
+        Self::with_comparator(FnComparator(f))
+    }
+}
+impl<T, K: Ord, F: Fn(&T) -> K> BinaryHeap<T, KeyComparator<F>> {
+    pub fn new_by_key(f: F) -> Self {
+        Self::with_comparator(KeyComparator(f))
+    }
+}
+impl<T, C: Compare<T>> BinaryHeap<T, C> {
     pub fn parent(i: usize) -> usize {
-        i / 2
+        (i - 1) / 2
     }
     pub fn left(i: usize) -> usize {
-        2 * i
+        2 * i + 1
     }
     pub fn right(i: usize) -> usize {
-        2 * i + 1
+        2 * i + 2
     }
-    pub fn max_heapify(&mut self, i: usize) {
-        let l = Self::left(i);
-        let r = Self::right(i);
-        let mut largest = if l <= self.heap_size && self.a[l] > self.a[i] {
-            l
-        } else {
-            i
-        };
-        if r <= self.heap_size && self.a[r] > self.a[largest] {
-            largest = r;
+    pub fn with_comparator(cmp: C) -> Self {
+        Self { a: Vec::new(), cmp }
+    }
+    // O(n)
+    pub fn from_vec_cmp(a: Vec<T>, cmp: C) -> Self {
+        let mut me = Self { a, cmp };
+        for i in (0..me.a.len() / 2).rev() {
+            me.heapify(i, me.a.len());
         }
-        if largest != i {
-            self.a.swap(i, largest);
-            self.max_heapify(largest);
+        me
+    }
+    pub fn len(&self) -> usize {
+        self.a.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.a.is_empty()
+    }
+    pub fn peek(&self) -> Option<&T> {
+        self.a.first()
+    }
+    // O(lg n)
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.a.pop()?;
+        if self.a.is_empty() {
+            return Some(last);
         }
+        let top = std::mem::replace(&mut self.a[0], last);
+        self.heapify(0, self.a.len());
+        Some(top)
     }
-    pub fn build(a: &[u32]) -> Self {
-        let mut me = Self {
-            heap_size: a.len(),
-            a: std::iter::once(0).chain(a.iter().copied()).collect(),
-        };
-        me.build_max();
-        me
+    // O(lg n)
+    pub fn push(&mut self, key: T) {
+        self.a.push(key);
+        self.sift_up(self.a.len() - 1);
     }
-    pub fn build_max(&mut self) {
-        self.heap_size = self.a.len() - 1;
-        for i in (1..=self.a.len() / 2).rev() {
-            self.max_heapify(i);
+    // Replace the element at the index `i` with a greater or equal one.
+    pub fn increase_key(&mut self, i: usize, key: T) {
+        assert_ne!(self.cmp.compare(&self.a[i], &key), Ordering::Greater);
+        self.a[i] = key;
+        self.sift_up(i);
+    }
+    // The elements in the increasing order with respect to `C`.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        for i in (1..self.a.len()).rev() {
+            self.a.swap(0, i);
+            self.heapify(0, i);
         }
+        self.a
     }
-    pub fn maximum(&self) -> u32 {
-        self.a[1]
+    pub fn into_vec(self) -> Vec<T> {
+        self.a
     }
-    pub fn extract_max(&mut self) -> u32 {
-        assert!(self.heap_size != 0);
-        let max = self.a[1];
-        self.a[1] = self.a[self.heap_size];
-        self.heap_size -= 1;
-        self.max_heapify(1);
-        return max;
+
+    // The subtrees of `i` in `a[..n]` are heaps.
+    fn heapify(&mut self, i: usize, n: usize) {
+        let cmp = &self.cmp;
+        max_heapify(&mut self.a[..n], i, &mut |x, y| cmp.compare(x, y));
     }
-    pub fn increase_key(&mut self, mut i: usize, key: u32) {
-        assert!(self.a[i] <= key);
-        self.a[i] = key;
-        while i > 1 && self.a[Self::parent(i)] < self.a[i] {
+    fn sift_up(&mut self, mut i: usize) {
+        while i != 0 && self.cmp.compare(&self.a[Self::parent(i)], &self.a[i]) == Ordering::Less {
             self.a.swap(i, Self::parent(i));
             i = Self::parent(i);
         }
     }
-    pub fn insert(&mut self, key: u32) {
-        self.heap_size += 1;
-        if self.heap_size == self.a.len() {
-            self.a.push(0);
-        }
-        self.a[self.heap_size] = std::u32::MIN;
-        self.increase_key(self.heap_size, key);
+}
+impl<T: Ord> Default for BinaryHeap<T> {
+    fn default() -> Self {
+        Self::new()
     }
-    pub fn sort(&mut self) {
-        self.build_max();
-        for i in (2..self.a.len()).rev() {
-            self.a.swap(1, i);
-            self.heap_size -= 1;
-            self.max_heapify(1);
-        }
+}
+impl<T: Ord> From<Vec<T>> for BinaryHeap<T> {
+    fn from(a: Vec<T>) -> Self {
+        Self::from_vec_cmp(a, MaxComparator)
+    }
+}
+// The comparators are inline, even the closures.
+impl<T: MemoryUsage, C> MemoryUsage for BinaryHeap<T, C> {
+    fn heap_size_bytes(&self) -> usize {
+        self.a.heap_size_bytes()
+    }
+}
+
+// Sort `a` in place with a binary heap. It is not stable.
+pub fn heap_sort<T: Ord>(a: &mut [T]) {
+    heap_sort_by(a, T::cmp);
+}
+pub fn heap_sort_by_key<T, K: Ord>(a: &mut [T], mut f: impl FnMut(&T) -> K) {
+    heap_sort_by(a, |x, y| f(x).cmp(&f(y)));
+}
+pub fn heap_sort_by<T>(a: &mut [T], mut compare: impl FnMut(&T, &T) -> Ordering) {
+    for i in (0..a.len() / 2).rev() {
+        max_heapify(a, i, &mut compare);
+    }
+    for i in (1..a.len()).rev() {
+        a.swap(0, i);
+        max_heapify(&mut a[..i], 0, &mut compare);
+    }
+}
+
+// The subtrees of `i` are heaps.
+fn max_heapify<T>(a: &mut [T], i: usize, compare: &mut impl FnMut(&T, &T) -> Ordering) {
+    let l = 2 * i + 1;
+    let r = 2 * i + 2;
+    let mut largest = if l < a.len() && compare(&a[l], &a[i]) == Ordering::Greater {
+        l
+    } else {
+        i
+    };
+    if r < a.len() && compare(&a[r], &a[largest]) == Ordering::Greater {
+        largest = r;
+    }
+    if largest != i {
+        a.swap(i, largest);
+        max_heapify(a, largest, compare);
     }
 }
 
+// A heap whose nodes have `d` children.
 #[derive(Debug, Clone, PartialEq)]
-pub struct DArrayHeap {
-    a: Vec<u32>,
+pub struct DArrayHeap<T, C = MaxComparator> {
+    a: Vec<T>,
     d: usize,
-    heap_size: usize,
+    cmp: C,
 }
-impl DArrayHeap {
-    pub fn build(a: &[u32], d: usize) -> Self {
-        let mut me = Self {
-            heap_size: a.len(),
-            a: a.to_vec(),
+
+impl<T: Ord> DArrayHeap<T> {
+    pub fn new(d: usize) -> Self {
+        Self::with_comparator(d, MaxComparator)
+    }
+    pub fn from_vec(a: Vec<T>, d: usize) -> Self {
+        Self::from_vec_cmp(a, d, MaxComparator)
+    }
+}
+impl<T: Ord> DArrayHeap<T, MinComparator> {
+    pub fn new_min(d: usize) -> Self {
+        Self::with_comparator(d, MinComparator)
+    }
+}
+impl<T, F: Fn(&T, &T) -> Ordering> DArrayHeap<T, FnComparator<F>> {
+    pub fn new_by(d: usize, f: F) -> Self {
+        Self::with_comparator(d, FnComparator(f))
+    }
+}
+impl<T, K: Ord, F: Fn(&T) -> K> DArrayHeap<T, KeyComparator<F>> {
+    pub fn new_by_key(d: usize, f: F) -> Self {
+        Self::with_comparator(d, KeyComparator(f))
+    }
+}
+impl<T, C: Compare<T>> DArrayHeap<T, C> {
+    pub fn with_comparator(d: usize, cmp: C) -> Self {
+        assert!(d >= 2);
+        Self {
+            a: Vec::new(),
             d,
-        };
-        me.build_max_heap();
+            cmp,
+        }
+    }
+    // O(n)
+    pub fn from_vec_cmp(a: Vec<T>, d: usize, cmp: C) -> Self {
+        let mut me = Self::with_comparator(d, cmp);
+        me.a = a;
+        (0..me.a.len().div_ceil(d))
+            .rev()
+            .for_each(|i| me.heapify(i, me.a.len()));
         me
     }
-    pub fn max_heapify(&mut self, i: usize) {
+    pub fn len(&self) -> usize {
+        self.a.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.a.is_empty()
+    }
+    pub fn peek(&self) -> Option<&T> {
+        self.a.first()
+    }
+    // O(d log_d n)
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.a.pop()?;
+        if self.a.is_empty() {
+            return Some(last);
+        }
+        let top = std::mem::replace(&mut self.a[0], last);
+        self.heapify(0, self.a.len());
+        Some(top)
+    }
+    // O(log_d n)
+    pub fn push(&mut self, key: T) {
+        self.a.push(key);
+        self.sift_up(self.a.len() - 1);
+    }
+    // Replace the element at the index `i` with a greater or equal one.
+    pub fn increase_key(&mut self, i: usize, key: T) {
+        assert_ne!(self.cmp.compare(&self.a[i], &key), Ordering::Greater);
+        self.a[i] = key;
+        self.sift_up(i);
+    }
+    // The elements in the increasing order with respect to `C`.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        for i in (1..self.a.len()).rev() {
+            self.a.swap(0, i);
+            self.heapify(0, i);
+        }
+        self.a
+    }
+    pub fn into_vec(self) -> Vec<T> {
+        self.a
+    }
+
+    fn parent(&self, i: usize) -> usize {
+        (i - 1) / self.d
+    }
+    // The subtrees of `i` in `a[..n]` are heaps.
+    fn heapify(&mut self, i: usize, n: usize) {
         let mut largest = i;
-        for j in (self.d * i + 1..=self.d * (i + 1)).take_while(|&j| j < self.heap_size) {
-            if self.a[largest] < self.a[j] {
+        for j in (self.d * i + 1..=self.d * (i + 1)).take_while(|&j| j < n) {
+            if self.cmp.compare(&self.a[largest], &self.a[j]) == Ordering::Less {
                 largest = j;
             }
         }
         if largest != i {
             self.a.swap(i, largest);
-            self.max_heapify(largest);
+            self.heapify(largest, n);
         }
     }
-    pub fn build_max_heap(&mut self) {
-        self.heap_size = self.a.len();
-        (0..(self.a.len() + self.d - 1) / self.d)
-            .rev()
-            .for_each(|i| self.max_heapify(i));
-    }
-    pub fn sort(&mut self) {
-        self.build_max_heap();
-        for i in (1..self.a.len()).rev() {
-            self.heap_size -= 1;
-            self.a.swap(0, i);
-            self.max_heapify(0);
-        }
-    }
-    pub fn increase_key(&mut self, mut i: usize, key: u32) {
-        assert!(self.a[i] <= key);
-        self.a[i] = key;
-        while i != 0 && self.a[self.parent(i)] < self.a[i] {
+    fn sift_up(&mut self, mut i: usize) {
+        while i != 0 && self.cmp.compare(&self.a[self.parent(i)], &self.a[i]) == Ordering::Less {
             let p = self.parent(i);
             self.a.swap(i, p);
             i = p;
         }
     }
-    pub fn insert(&mut self, key: u32) {
-        self.heap_size += 1;
-        if self.a.len() < self.heap_size {
-            self.a.push(0);
-        }
-        self.a[self.heap_size - 1] = std::u32::MIN;
-        self.increase_key(self.heap_size - 1, key);
+}
+// 4-ary heaps are known to be faster than binary ones in practice.
+impl<T: Ord> From<Vec<T>> for DArrayHeap<T> {
+    fn from(a: Vec<T>) -> Self {
+        Self::from_vec(a, 4)
     }
-    fn parent(&self, i: usize) -> usize {
-        (i - 1) / self.d
+}
+impl<T: MemoryUsage, C> MemoryUsage for DArrayHeap<T, C> {
+    fn heap_size_bytes(&self) -> usize {
+        self.a.heap_size_bytes()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{BinaryHeap, DArrayHeap};
+    use {
+        super::{
+            heap_sort, heap_sort_by, heap_sort_by_key, BinaryHeap, Compare, DArrayHeap,
+            FnComparator, KeyComparator, MaxComparator, MinComparator,
+        },
+        memory_usage::MemoryUsage,
+        rand::prelude::*,
+        std::{cmp::Reverse, collections, fmt::Debug, iter::repeat_with},
+    };
 
     #[test]
     fn test_binary_heap() {
-        let heap = vec![std::u32::MIN, 15, 13, 9, 5, 12, 8, 7, 4, 0, 6, 2, 1];
         let heap = BinaryHeap {
-            heap_size: heap.len() - 1,
-            a: heap,
+            a: vec![15, 13, 9, 5, 12, 8, 7, 4, 0, 6, 2, 1],
+            cmp: MaxComparator,
         };
         {
             let mut heap = heap.clone();
-            let result = heap.extract_max();
-            assert_eq!(result, 15);
-            let result = [std::u32::MIN, 13, 12, 9, 5, 6, 8, 7, 4, 0, 1, 2, 1];
+            let result = heap.pop();
+            assert_eq!(result, Some(15));
+            let result = [13, 12, 9, 5, 6, 8, 7, 4, 0, 1, 2];
             assert_eq!(&result, heap.a.as_slice());
         }
         {
             let mut heap = heap.clone();
-            heap.insert(10);
-            let result = [std::u32::MIN, 15, 13, 10, 5, 12, 9, 7, 4, 0, 6, 2, 1, 8];
+            heap.push(10);
+            let result = [15, 13, 10, 5, 12, 9, 7, 4, 0, 6, 2, 1, 8];
             assert_eq!(&result, heap.a.as_slice());
         }
     }
 
     #[test]
     fn test_heap_sort() {
-        let heap = vec![std::u32::MIN, 5, 13, 2, 25, 7, 17, 20, 8, 4];
-        let mut heap = BinaryHeap {
-            heap_size: heap.len() - 1,
-            a: heap,
-        };
-        heap.sort();
-        let result = [std::u32::MIN, 2, 4, 5, 7, 8, 13, 17, 20, 25];
-        assert_eq!(&result, heap.a.as_slice());
+        let heap = BinaryHeap::from(vec![5, 13, 2, 25, 7, 17, 20, 8, 4]);
+        let result = [2, 4, 5, 7, 8, 13, 17, 20, 25];
+        assert_eq!(&result, heap.into_sorted_vec().as_slice());
+    }
+
+    #[test]
+    fn test_heap_sort_by() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let n = rng.gen_range(0, 50);
+            let a = repeat_with(|| (rng.gen_range(0, 10), rng.gen_range(0, 10)))
+                .take(n)
+                .collect::<Vec<(u32, u32)>>();
+            let mut expected = a.clone();
+            expected.sort();
+            let mut result = a.clone();
+            heap_sort(&mut result);
+            assert_eq!(&result, &expected);
+
+            // It is unstable, so only the keys are compared.
+            let keys = |a: &[(u32, u32)]| a.iter().map(|&(x, _)| Reverse(x)).collect::<Vec<_>>();
+            let mut expected = keys(&a);
+            expected.sort();
+            let mut result = a.clone();
+            heap_sort_by(&mut result, |x, y| y.0.cmp(&x.0));
+            assert_eq!(&keys(&result), &expected);
+            let mut result = a.clone();
+            heap_sort_by_key(&mut result, |&(x, _)| Reverse(x));
+            assert_eq!(&keys(&result), &expected);
+        }
     }
 
     #[test]
     fn test_tertiary_max_heap() {
         let heap = vec![5, 13, 2, 25, 7, 17, 20, 8, 4, 24, 3, 2, 14, 12, 9, 10, 1];
-        let heap = DArrayHeap {
-            heap_size: heap.len() - 1,
-            a: heap,
-            d: 3,
-        };
         {
-            let mut heap = heap.clone();
-            heap.build_max_heap();
+            let mut heap = DArrayHeap::from_vec(heap.clone(), 3);
             let result = [25, 20, 24, 14, 12, 17, 13, 8, 4, 2, 3, 2, 5, 7, 9, 10, 1];
             assert_eq!(&result, heap.a.as_slice());
 
-            heap.insert(24);
+            heap.push(24);
             let result = [
                 25, 24, 24, 14, 12, 20, 13, 8, 4, 2, 3, 2, 5, 7, 9, 10, 1, 17,
             ];
             assert_eq!(&result, heap.a.as_slice());
         }
         {
-            let mut heap = heap.clone();
-            heap.sort();
+            let heap = DArrayHeap::from_vec(heap, 3);
             let result = [1, 2, 2, 3, 4, 5, 7, 8, 9, 10, 12, 13, 14, 17, 20, 24, 25];
-            assert_eq!(&result, heap.a.as_slice());
+            assert_eq!(&result, heap.into_sorted_vec().as_slice());
+        }
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let mut heap = BinaryHeap::new_by(|x: &String, y: &String| x.len().cmp(&y.len()));
+        assert_eq!(heap.heap_size_bytes(), 0);
+        (0..10).for_each(|i| heap.push("x".repeat(i)));
+        let vec = heap.a.capacity() * std::mem::size_of::<String>();
+        assert_eq!(heap.heap_size_bytes(), vec + 45);
+        // The capacity is kept.
+        (0..4).for_each(|_| {
+            heap.pop();
+        });
+        assert_eq!(heap.heap_size_bytes(), vec + 45 - (9 + 8 + 7 + 6));
+
+        let heap = DArrayHeap::from_vec(Vec::<u32>::with_capacity(20), 3);
+        assert_eq!(heap.heap_size_bytes(), 80);
+    }
+
+    #[test]
+    fn test_rand_comparators() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let n = rng.gen_range(0, 50);
+            let d = rng.gen_range(2, 5);
+            let a = repeat_with(|| rng.gen_range(0, 30))
+                .take(n)
+                .collect::<Vec<u32>>();
+            let b = repeat_with(|| rng.gen_range(0, 30))
+                .take(100)
+                .collect::<Vec<u32>>();
+
+            let expected = collections::BinaryHeap::from(a.clone());
+            check(BinaryHeap::from(a.clone()), expected.clone(), &b, |x| x);
+            check(DArrayHeap::from_vec(a.clone(), d), expected, &b, |x| x);
+
+            // Tag the elements with their indices so that the ties cannot be broken differently.
+            let a = a
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| (x, i))
+                .collect::<Vec<_>>();
+            let b = b
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| (x, n + i))
+                .collect::<Vec<_>>();
+            let expected = a
+                .iter()
+                .map(|&x| Reverse(x))
+                .collect::<collections::BinaryHeap<_>>();
+            let key = |&(x, i): &(u32, usize)| Reverse((x, i));
+            let cmp = |x: &(u32, usize), y: &(u32, usize)| y.cmp(x);
+            check(
+                BinaryHeap::from_vec_cmp(a.clone(), MinComparator),
+                expected.clone(),
+                &b,
+                Reverse,
+            );
+            check(
+                DArrayHeap::from_vec_cmp(a.clone(), d, MinComparator),
+                expected.clone(),
+                &b,
+                Reverse,
+            );
+            check(
+                BinaryHeap::from_vec_cmp(a.clone(), KeyComparator(key)),
+                expected.clone(),
+                &b,
+                Reverse,
+            );
+            check(
+                DArrayHeap::from_vec_cmp(a.clone(), d, FnComparator(cmp)),
+                expected,
+                &b,
+                Reverse,
+            );
+        }
+    }
+
+    trait Heap<T> {
+        fn push(&mut self, x: T);
+        fn pop(&mut self) -> Option<T>;
+        fn peek(&self) -> Option<&T>;
+        fn len(&self) -> usize;
+    }
+    impl<T, C: Compare<T>> Heap<T> for BinaryHeap<T, C> {
+        fn push(&mut self, x: T) {
+            self.push(x)
+        }
+        fn pop(&mut self) -> Option<T> {
+            self.pop()
+        }
+        fn peek(&self) -> Option<&T> {
+            self.peek()
+        }
+        fn len(&self) -> usize {
+            self.len()
+        }
+    }
+    impl<T, C: Compare<T>> Heap<T> for DArrayHeap<T, C> {
+        fn push(&mut self, x: T) {
+            self.push(x)
+        }
+        fn pop(&mut self) -> Option<T> {
+            self.pop()
+        }
+        fn peek(&self) -> Option<&T> {
+            self.peek()
+        }
+        fn len(&self) -> usize {
+            self.len()
+        }
+    }
+
+    // Push the elements of `b` and pop all, comparing `heap` with `expected` through `f`.
+    fn check<T: Copy, U: Ord + Copy + Debug>(
+        mut heap: impl Heap<T>,
+        mut expected: collections::BinaryHeap<U>,
+        b: &[T],
+        f: impl Fn(T) -> U,
+    ) {
+        for &x in b {
+            heap.push(x);
+            expected.push(f(x));
+            assert_eq!(heap.peek().map(|&x| f(x)), expected.peek().copied());
+            assert_eq!(heap.len(), expected.len());
+        }
+        while let Some(x) = expected.pop() {
+            assert_eq!(heap.pop().map(&f), Some(x));
         }
+        assert_eq!(heap.pop().map(&f), None);
     }
 }
@@ -0,0 +1,119 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+// A property of a tree checked by `check_invariants`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Property {
+    // The keys are sorted in the in-order.
+    BstOrder,
+    // Every path from a node to the nils has the same number of black nodes.
+    BlackHeight,
+    // A red node has no red child.
+    RedRed,
+    // The root is black.
+    BlackRoot,
+    // A right child is not red.
+    LeftLeaning,
+    // The heights of the children differ by at most one.
+    AvlBalance,
+    // A node has at most the priorities of its children.
+    HeapPriority,
+    // the cached height, black height, size or fold of a subtree
+    HeightCache,
+    BlackHeightCache,
+    SizeCache,
+    AccCache,
+    // Every node but the root has at least `t - 1` and at most `2t - 1` keys.
+    BTreeFill,
+    // An internal node has one more children than the keys, and all the leaves have the same
+    // depth.
+    BTreeShape,
+    // A child points back to its parent.
+    ParentPointer,
+}
+impl Display for Property {
+    fn fmt(&self, w: &mut Formatter) -> fmt::Result {
+        let s = match self {
+            Property::BstOrder => "BST order",
+            Property::BlackHeight => "black height",
+            Property::RedRed => "no red-red",
+            Property::BlackRoot => "black root",
+            Property::LeftLeaning => "left-leaning",
+            Property::AvlBalance => "AVL balance",
+            Property::HeapPriority => "heap priority",
+            Property::HeightCache => "height cache",
+            Property::BlackHeightCache => "black height cache",
+            Property::SizeCache => "size cache",
+            Property::AccCache => "acc cache",
+            Property::BTreeFill => "B-tree fill factor",
+            Property::BTreeShape => "B-tree shape",
+            Property::ParentPointer => "parent pointer",
+        };
+        w.write_str(s)
+    }
+}
+
+// The property broken first, and the path to the node from the root: the indices of the
+// children, i.e. 0 for left and 1 for right in a binary tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InvariantViolation {
+    pub property: Property,
+    pub path: Vec<usize>,
+}
+impl InvariantViolation {
+    pub fn new(property: Property, path: &[usize]) -> Self {
+        Self {
+            property,
+            path: path.to_vec(),
+        }
+    }
+}
+impl Display for InvariantViolation {
+    fn fmt(&self, w: &mut Formatter) -> fmt::Result {
+        write!(w, "{} is violated at ", self.property)?;
+        if self.path.is_empty() {
+            write!(w, "the root")
+        } else {
+            write!(w, "the path {:?}", self.path)
+        }
+    }
+}
+impl Error for InvariantViolation {}
+
+pub fn ensure(cond: bool, property: Property, path: &[usize]) -> Result<(), InvariantViolation> {
+    if cond {
+        Ok(())
+    } else {
+        Err(InvariantViolation::new(property, path))
+    }
+}
+
+// For the panicking `validate` entry points: the value, or a panic with the message of the
+// violation.
+pub fn assert_holds<T>(result: Result<T, InvariantViolation>) -> T {
+    result.unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_holds, ensure, InvariantViolation, Property};
+
+    #[test]
+    fn test_display() {
+        let e = ensure(false, Property::RedRed, &[0, 1]).unwrap_err();
+        assert_eq!(e, InvariantViolation::new(Property::RedRed, &[0, 1]));
+        assert_eq!(e.to_string(), "no red-red is violated at the path [0, 1]");
+        let e = InvariantViolation::new(Property::BlackRoot, &[]);
+        assert_eq!(e.to_string(), "black root is violated at the root");
+        assert_eq!(ensure(true, Property::RedRed, &[]), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "no red-red is violated at the path [0, 1]")]
+    fn test_assert_holds() {
+        assert_eq!(assert_holds(Ok(42)), 42);
+        assert_holds::<()>(ensure(false, Property::RedRed, &[0, 1]));
+    }
+}
@@ -0,0 +1,162 @@
+use super::{insertion_sort_by, radix::permute};
+
+// Buckets up to this length are left to the insertion sort.
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+// CLRS bucket sort with `n` buckets, which takes expected O(n) if the values are uniformly
+// distributed, e.g. in [0, 1).
+pub fn bucket_sort(a: &mut [f64]) {
+    let n = a.len();
+    bucket_sort_by_key(a, n, |&x| x);
+}
+
+// Sort the records by their keys, distributing them into `buckets` buckets of equal widths between
+// the minimum and maximum finite keys. Infinite keys go to the first or last bucket.
+//
+// A skewed bucket is sorted by a comparison sort, so it takes O(n lg n) in the worst case. It is
+// stable, and panics on NaN.
+pub fn bucket_sort_by_key<T>(a: &mut [T], buckets: usize, mut f: impl FnMut(&T) -> f64) {
+    assert!(buckets >= 1 || a.is_empty());
+    let keys = a.iter().map(&mut f).collect::<Vec<_>>();
+    assert!(keys.iter().all(|x| !x.is_nan()), "NaN cannot be sorted");
+    let min = keys
+        .iter()
+        .copied()
+        .filter(|x| x.is_finite())
+        .fold(f64::INFINITY, f64::min);
+    let max = keys
+        .iter()
+        .copied()
+        .filter(|x| x.is_finite())
+        .fold(f64::NEG_INFINITY, f64::max);
+    // Halve the values so that `max - min` does not overflow.
+    let width = max / 2.0 - min / 2.0;
+    // `as` saturates negative values to zero.
+    let bucket = |x: f64| {
+        if width > 0.0 {
+            (((x / 2.0 - min / 2.0) / width * buckets as f64) as usize).min(buckets - 1)
+        } else {
+            0
+        }
+    };
+
+    // a stable counting sort of the indices by the buckets
+    let mut c = vec![0; buckets + 1];
+    keys.iter().for_each(|&x| c[bucket(x) + 1] += 1);
+    for k in 1..=buckets {
+        c[k] += c[k - 1];
+    }
+    let start = c.clone();
+    let mut idx = vec![0; a.len()];
+    for (i, &x) in keys.iter().enumerate() {
+        idx[c[bucket(x)]] = i;
+        c[bucket(x)] += 1;
+    }
+
+    let compare = |&i: &usize, &j: &usize| keys[i].total_cmp(&keys[j]);
+    for k in 0..buckets {
+        let b = &mut idx[start[k]..start[k + 1]];
+        if b.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort_by(b, compare);
+        } else {
+            b.sort_by(compare);
+        }
+    }
+    permute(a, idx);
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{bucket_sort, bucket_sort_by_key},
+        rand::prelude::*,
+        std::{f64, iter::repeat_with},
+    };
+
+    #[test]
+    fn test_clrs() {
+        let mut a = [0.78, 0.17, 0.39, 0.26, 0.72, 0.94, 0.21, 0.12, 0.23, 0.68];
+        bucket_sort(&mut a);
+        assert_eq!(
+            &a,
+            &[0.12, 0.17, 0.21, 0.23, 0.26, 0.39, 0.68, 0.72, 0.78, 0.94]
+        );
+    }
+
+    #[test]
+    fn test_rand() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let specials = [
+            0.0,
+            -0.0,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::MIN,
+            f64::EPSILON,
+        ];
+        let gens: [fn(&mut StdRng, &[f64]) -> f64; 6] = [
+            |rng, _| rng.gen(),
+            |rng, _| rng.gen_range(-1e9, 1e9),
+            // skewed: most of the values are in a tiny range
+            |rng, _| {
+                if rng.gen_ratio(1, 20) {
+                    rng.gen_range(0.0, 1e300)
+                } else {
+                    rng.gen_range(0.0, 1e-300)
+                }
+            },
+            |rng, specials| *specials.choose(rng).unwrap(),
+            |rng, _| rng.gen_range(0, 3) as f64,
+            |rng, _| loop {
+                let x = f64::from_bits(rng.gen());
+                if !x.is_nan() {
+                    return x;
+                }
+            },
+        ];
+        for _ in 0..300 {
+            let n = rng.gen_range(0, 100);
+            let gen = *gens.choose(&mut rng).unwrap();
+            let a = repeat_with(|| gen(&mut rng, &specials))
+                .take(n)
+                .collect::<Vec<f64>>();
+            let mut expected = a.clone();
+            expected.sort_by(f64::total_cmp);
+            let bits = |a: &[f64]| a.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+
+            let mut result = a.clone();
+            bucket_sort(&mut result);
+            assert_eq!(bits(&result), bits(&expected));
+            for &buckets in [1, 2, 7, 1000].iter() {
+                let mut result = a.clone();
+                bucket_sort_by_key(&mut result, buckets, |&x| x);
+                assert_eq!(bits(&result), bits(&expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_by_key_is_stable() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let n = rng.gen_range(0, 200);
+            // (key, the original index)
+            let a = (0..n)
+                .map(|i| (rng.gen_range(-5, 5), i))
+                .collect::<Vec<(i32, usize)>>();
+            let mut expected = a.clone();
+            expected.sort_by_key(|&(x, _)| x);
+            let mut result = a.clone();
+            bucket_sort_by_key(&mut result, 3, |&(x, _)| x as f64);
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_nan() {
+        bucket_sort(&mut [0.5, f64::NAN, 0.2]);
+    }
+}
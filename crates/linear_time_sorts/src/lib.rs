@@ -1,3 +1,15 @@
+mod bucket;
+mod parallel;
+mod radix;
+
+pub use {
+    bucket::{bucket_sort, bucket_sort_by_key},
+    parallel::par_radix_sort,
+    radix::{msd_radix_sort, radix_sort, radix_sort_by_key, RadixKey},
+};
+
+use std::cmp::Ordering;
+
 pub fn counting_sort(a: &mut [u32], lim: u32) {
     let mut c = vec![0; lim as usize];
     a.iter().for_each(|&x| c[x as usize] += 1);
@@ -11,58 +23,66 @@ pub fn counting_sort(a: &mut [u32], lim: u32) {
     );
 }
 
-pub fn radix_sort(a: &mut [u32], r: usize) {
-    let b = 32;
-    for i in 0..(b + r - 1) / r {
-        let mut c = vec![0; 1 << r];
-        a.iter()
-            .map(|&x| extract_key(x, i, r))
-            .for_each(|key| c[key] += 1);
-        for i in 1..1 << r {
-            c[i] += c[i - 1];
-        }
-
-        let mut swp = vec![0; a.len()];
-        for &x in a.iter().rev() {
-            let key = extract_key(x, i, r);
-            c[key] -= 1;
-            swp[c[key]] = x;
-        }
-        a.copy_from_slice(&swp);
+// Sort the records by their keys in `0..=max_key` stably, and return the offsets: the records with
+// the key `k` end up in `a[offsets[k]..offsets[k + 1]]`.
+pub fn counting_sort_by_key<T>(
+    a: &mut [T],
+    mut f: impl FnMut(&T) -> usize,
+    max_key: usize,
+) -> Vec<usize> {
+    let keys = a.iter().map(&mut f).collect::<Vec<_>>();
+    let mut c = vec![0; max_key + 2];
+    keys.iter().for_each(|&k| {
+        assert!(k <= max_key, "the key {} exceeds {}", k, max_key);
+        c[k + 1] += 1
+    });
+    for k in 1..c.len() {
+        c[k] += c[k - 1];
     }
-
-    fn extract_key(x: u32, i: usize, r: usize) -> usize {
-        ((x >> i * r) & (1 << r) - 1) as usize
+    let offsets = c.clone();
+    let mut idx = vec![0; a.len()];
+    for (i, &k) in keys.iter().enumerate() {
+        idx[c[k]] = i;
+        c[k] += 1;
     }
+    radix::permute(a, idx);
+    offsets
 }
 
 pub fn backet_sort(a: &mut [u32], lim: u32) {
     let n = a.len();
     let mut b = vec![Vec::new(); n];
-    a.iter()
-        .for_each(|&x| b[x as usize * n / lim as usize].push(x));
+    // The values at least `lim` go to the last bucket.
+    a.iter().for_each(|&x| {
+        let i = (x as u64 * n as u64 / lim.max(1) as u64).min(n as u64 - 1);
+        b[i as usize].push(x)
+    });
     b.iter_mut().for_each(|list| insertion_sort(list));
     a.copy_from_slice(&b.iter().flatten().copied().collect::<Vec<_>>());
 }
 
-pub fn insertion_sort(a: &mut [u32]) {
+pub fn insertion_sort<T: Ord>(a: &mut [T]) {
+    insertion_sort_by(a, T::cmp);
+}
+pub fn insertion_sort_by_key<T, K: Ord>(a: &mut [T], mut f: impl FnMut(&T) -> K) {
+    insertion_sort_by(a, |x, y| f(x).cmp(&f(y)));
+}
+// stable
+pub fn insertion_sort_by<T>(a: &mut [T], mut compare: impl FnMut(&T, &T) -> Ordering) {
     for j in 1..a.len() {
-        let key = a[j];
-        for i in (0..=j).rev() {
-            if i != j {
-                a[i + 1] = a[i];
-            }
-            if i == 0 || a[i - 1] <= key {
-                a[i] = key;
-                break;
-            }
+        let mut i = j;
+        while i != 0 && compare(&a[i - 1], &a[j]) == Ordering::Greater {
+            i -= 1;
         }
+        a[i..=j].rotate_right(1);
     }
 }
-
 #[cfg(test)]
 mod tests {
-    use super::{backet_sort, counting_sort, insertion_sort, radix_sort};
+    use super::{
+        backet_sort, counting_sort, counting_sort_by_key, insertion_sort, insertion_sort_by,
+        insertion_sort_by_key, radix_sort,
+    };
     use rand::prelude::*;
 
     #[test]
@@ -77,6 +97,27 @@ mod tests {
         assert_eq!(&a, &expected);
     }
 
+    #[test]
+    fn test_insertion_sort_is_stable() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let n = rng.gen_range(0, 50);
+            // (key, the original index)
+            let a = (0..n)
+                .map(|i| (rng.gen_range(0, 10), i))
+                .collect::<Vec<(u32, usize)>>();
+            let mut expected = a.clone();
+            expected.sort_by_key(|&(x, _)| std::cmp::Reverse(x));
+
+            let mut result = a.clone();
+            insertion_sort_by(&mut result, |x, y| y.0.cmp(&x.0));
+            assert_eq!(&result, &expected);
+            let mut result = a.clone();
+            insertion_sort_by_key(&mut result, |&(x, _)| std::cmp::Reverse(x));
+            assert_eq!(&result, &expected);
+        }
+    }
+
     #[test]
     fn test_counting_sort() {
         let mut a = [
@@ -89,6 +130,48 @@ mod tests {
         assert_eq!(&a, &expected);
     }
 
+    #[test]
+    fn test_counting_sort_by_key() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let n = rng.gen_range(0, 100);
+            let max_key = rng.gen_range(0, 10);
+            // (key, the original index)
+            let a = (0..n)
+                .map(|i| (rng.gen_range(0, max_key + 1), i))
+                .collect::<Vec<(usize, usize)>>();
+            let mut expected = a.clone();
+            expected.sort_by_key(|&(k, _)| k);
+            let mut result = a.clone();
+            let offsets = counting_sort_by_key(&mut result, |&(k, _)| k, max_key);
+            assert_eq!(result, expected);
+            assert_eq!(offsets.len(), max_key + 2);
+            for k in 0..=max_key {
+                assert!(result[offsets[k]..offsets[k + 1]]
+                    .iter()
+                    .all(|&(x, _)| x == k));
+            }
+            assert_eq!(offsets[max_key + 1], n);
+        }
+    }
+
+    #[test]
+    fn test_counting_sort_by_key_csr() {
+        // The adjacency lists of a graph in the compressed sparse row format.
+        let mut edges = vec![(2, 0), (0, 1), (3, 2), (0, 3), (2, 3), (0, 2)];
+        let offsets = counting_sort_by_key(&mut edges, |&(u, _)| u, 3);
+        let adj = |u: usize| {
+            edges[offsets[u]..offsets[u + 1]]
+                .iter()
+                .map(|&(_, v)| v)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(adj(0), vec![1, 3, 2]);
+        assert_eq!(adj(1), vec![]);
+        assert_eq!(adj(2), vec![0, 3]);
+        assert_eq!(adj(3), vec![2]);
+    }
+
     #[test]
     fn test_radix_sort() {
         let n = 20;
@@ -122,5 +205,10 @@ mod tests {
         let mut a = a;
         backet_sort(&mut a, lim);
         assert_eq!(&a, &expected);
+
+        // too small `lim`
+        let mut a = vec![u32::MAX, 7, 3, u32::MAX - 1, 0];
+        backet_sort(&mut a, 2);
+        assert_eq!(&a, &[0, 3, 7, u32::MAX - 1, u32::MAX]);
     }
 }
@@ -0,0 +1,87 @@
+use {
+    super::RadixKey,
+    std::{mem::take, thread},
+};
+
+// `radix_sort` with `threads` threads. In each pass, the threads count the digits of their chunks,
+// the prefix sums of the counts in the (digit, chunk) order give the destination of each chunk,
+// and then the threads scatter their chunks. It is stable, so the result is the same. The passes go
+// back and forth between `a` and a buffer, and the result is copied back at most once.
+pub fn par_radix_sort<K: RadixKey + Send + Sync>(a: &mut [K], r: usize, threads: usize) {
+    assert!(threads >= 1);
+    assert!((1..=16).contains(&r));
+    let chunk_len = a.len().div_ceil(threads).max(1);
+    let mut swp = a.to_vec();
+    let passes = K::BITS.div_ceil(r);
+    for i in 0..passes {
+        let (from, to): (&[K], &mut [K]) = if i % 2 == 0 { (a, &mut swp) } else { (&swp, a) };
+        let counts = thread::scope(|s| {
+            from.chunks(chunk_len)
+                .map(|chunk| {
+                    s.spawn(move || {
+                        let mut c = vec![0; 1 << r];
+                        chunk.iter().for_each(|&x| c[x.digit(i, r)] += 1);
+                        c
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        // dest[t][key]: where the elements of the `t`-th chunk with the digit `key` go
+        let mut dest = counts
+            .iter()
+            .map(|_| Vec::with_capacity(1 << r))
+            .collect::<Vec<Vec<&mut [K]>>>();
+        let mut rest = to;
+        for key in 0..1 << r {
+            for (t, c) in counts.iter().enumerate() {
+                let (head, tail) = take(&mut rest).split_at_mut(c[key]);
+                dest[t].push(head);
+                rest = tail;
+            }
+        }
+
+        thread::scope(|s| {
+            for (chunk, mut dest) in from.chunks(chunk_len).zip(dest) {
+                s.spawn(move || {
+                    let mut pos = vec![0; 1 << r];
+                    for &x in chunk {
+                        let key = x.digit(i, r);
+                        dest[key][pos[key]] = x;
+                        pos[key] += 1;
+                    }
+                });
+            }
+        });
+    }
+    if passes % 2 == 1 {
+        a.copy_from_slice(&swp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::par_radix_sort, crate::radix_sort, rand::prelude::*, std::iter::repeat_with};
+
+    #[test]
+    fn test_same_as_sequential() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for &n in [0, 1, 2, 1000, 100_000].iter() {
+            let a = repeat_with(|| rng.gen_range(0, u32::MAX))
+                .take(n)
+                .collect::<Vec<_>>();
+            for &r in [1, 3, 8, 11].iter() {
+                let mut expected = a.clone();
+                radix_sort(&mut expected, r);
+                for &threads in [1, 2, 3, 8].iter() {
+                    let mut result = a.clone();
+                    par_radix_sort(&mut result, r, threads);
+                    assert_eq!(result, expected);
+                }
+            }
+        }
+    }
+}
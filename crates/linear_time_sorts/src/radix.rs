@@ -0,0 +1,325 @@
+use {super::insertion_sort_by, std::mem::size_of};
+
+// Keys that the radix sorts can split into `r`-bit digits, in the same order as the keys.
+pub trait RadixKey: Copy {
+    const BITS: usize;
+    // the `i`-th digit from the least significant one
+    fn digit(self, i: usize, r: usize) -> usize;
+}
+
+macro_rules! impl_radix_key_unsigned {
+    ($($t:ty),*) => {$(
+        impl RadixKey for $t {
+            const BITS: usize = size_of::<$t>() * 8;
+            fn digit(self, i: usize, r: usize) -> usize {
+                (self >> (i * r)) as usize & ((1 << r) - 1)
+            }
+        }
+    )*};
+}
+impl_radix_key_unsigned!(u8, u16, u32, u64, u128, usize);
+
+// Flipping the sign bit maps the two's complements to the unsigned integers in the same order.
+macro_rules! impl_radix_key_signed {
+    ($(($t:ty, $u:ty)),*) => {$(
+        impl RadixKey for $t {
+            const BITS: usize = size_of::<$t>() * 8;
+            fn digit(self, i: usize, r: usize) -> usize {
+                (self as $u ^ 1 << (<Self as RadixKey>::BITS - 1)).digit(i, r)
+            }
+        }
+    )*};
+}
+impl_radix_key_signed!(
+    (i8, u8),
+    (i16, u16),
+    (i32, u32),
+    (i64, u64),
+    (i128, u128),
+    (isize, usize)
+);
+
+// IEEE 754 total order: flip all the bits of the negative numbers, and the sign bit of the others.
+// -NaN < -inf < ... < -0.0 < 0.0 < ... < inf < NaN
+macro_rules! impl_radix_key_float {
+    ($(($t:ty, $u:ty)),*) => {$(
+        impl RadixKey for $t {
+            const BITS: usize = size_of::<$t>() * 8;
+            fn digit(self, i: usize, r: usize) -> usize {
+                let bits = self.to_bits();
+                let sign = 1 << (<Self as RadixKey>::BITS - 1);
+                let key: $u = if bits & sign == 0 { bits | sign } else { !bits };
+                key.digit(i, r)
+            }
+        }
+    )*};
+}
+impl_radix_key_float!((f32, u32), (f64, u64));
+
+// LSD radix sort with `r`-bit digits. It is stable.
+pub fn radix_sort<K: RadixKey>(a: &mut [K], r: usize) {
+    assert!((1..=16).contains(&r));
+    for i in 0..K::BITS.div_ceil(r) {
+        let mut c = vec![0; 1 << r];
+        a.iter().map(|&x| x.digit(i, r)).for_each(|key| c[key] += 1);
+        for i in 1..1 << r {
+            c[i] += c[i - 1];
+        }
+
+        let mut swp = a.to_vec();
+        for &x in a.iter().rev() {
+            let key = x.digit(i, r);
+            c[key] -= 1;
+            swp[c[key]] = x;
+        }
+        a.copy_from_slice(&swp);
+    }
+}
+
+// Sort the records by their keys, which are computed once each. It is stable.
+pub fn radix_sort_by_key<T, K: RadixKey>(a: &mut [T], r: usize, mut f: impl FnMut(&T) -> K) {
+    let mut keys = a
+        .iter()
+        .enumerate()
+        .map(|(i, x)| Index(f(x), i))
+        .collect::<Vec<_>>();
+    radix_sort(&mut keys, r);
+    permute(a, keys.into_iter().map(|Index(_, i)| i).collect());
+}
+
+// Slices up to this length are left to the insertion sort.
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+// MSD radix sort of byte strings, one byte at a time. A string comes before its extensions, and it
+// is stable.
+pub fn msd_radix_sort<S: AsRef<[u8]>>(a: &mut [S]) {
+    let mut idx = (0..a.len()).collect::<Vec<_>>();
+    let mut swp = vec![0; a.len()];
+    msd(a, &mut idx, &mut swp);
+    permute(a, idx);
+}
+
+// Sort `idx` by the strings. The ranges still to split are kept in a stack instead of the call
+// stack, since the depth is as large as the longest common prefix.
+fn msd<S: AsRef<[u8]>>(a: &[S], idx: &mut [usize], swp: &mut [usize]) {
+    // (l, r, d): the strings of `idx[l..r]` have the same prefixes of length `d`.
+    let mut stack = vec![(0, idx.len(), 0)];
+    let mut c = [0; 258];
+    while let Some((l, r, d)) = stack.pop() {
+        let (idx, swp) = (&mut idx[l..r], &mut swp[l..r]);
+        if idx.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort_by(idx, |&i, &j| a[i].as_ref()[d..].cmp(&a[j].as_ref()[d..]));
+            continue;
+        }
+        // 0 for the strings of length `d`, and `b + 1` for the byte `b`.
+        let key = |i: usize| a[i].as_ref().get(d).map_or(0, |&b| b as usize + 1);
+        c.fill(0);
+        idx.iter().for_each(|&i| c[key(i) + 1] += 1);
+        for k in 1..258 {
+            c[k] += c[k - 1];
+        }
+        for &i in idx.iter() {
+            swp[c[key(i)]] = i;
+            c[key(i)] += 1;
+        }
+        idx.copy_from_slice(swp);
+        // Now `c[k]` is the end of the bucket `k`.
+        for k in 1..257 {
+            if c[k] - c[k - 1] > 1 {
+                stack.push((l + c[k - 1], l + c[k], d + 1));
+            }
+        }
+    }
+}
+
+// Rearrange `a` so that `a[k]` becomes the old `a[perm[k]]`.
+pub fn permute<T>(a: &mut [T], mut perm: Vec<usize>) {
+    for i in 0..a.len() {
+        let mut j = i;
+        while perm[j] != i {
+            let k = perm[j];
+            a.swap(j, k);
+            perm[j] = j;
+            j = k;
+        }
+        perm[j] = j;
+    }
+}
+
+// A key with the index of its record, compared only by the key.
+#[derive(Clone, Copy)]
+struct Index<K>(K, usize);
+impl<K: RadixKey> RadixKey for Index<K> {
+    const BITS: usize = K::BITS;
+    fn digit(self, i: usize, r: usize) -> usize {
+        self.0.digit(i, r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{msd_radix_sort, radix_sort, radix_sort_by_key, RadixKey},
+        rand::{distributions::Standard, prelude::*},
+        std::{fmt::Debug, iter::repeat_with},
+    };
+
+    #[test]
+    fn test_integers() {
+        let mut rng = StdRng::seed_from_u64(42);
+        test_ord::<u8>(&mut rng);
+        test_ord::<u64>(&mut rng);
+        test_ord::<u128>(&mut rng);
+        test_ord::<i8>(&mut rng);
+        test_ord::<i32>(&mut rng);
+        test_ord::<i64>(&mut rng);
+        test_ord::<i128>(&mut rng);
+    }
+
+    fn test_ord<K: RadixKey + Ord + Debug>(rng: &mut StdRng)
+    where
+        Standard: Distribution<K>,
+    {
+        for _ in 0..20 {
+            let n = rng.gen_range(0, 200);
+            let a = repeat_with(|| rng.gen()).take(n).collect::<Vec<K>>();
+            let mut expected = a.clone();
+            expected.sort();
+            for &r in [1, 5, 8, 16].iter() {
+                let mut result = a.clone();
+                radix_sort(&mut result, r);
+                assert_eq!(result, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_floats() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let specials = [
+            0.0,
+            -0.0,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::MIN,
+        ];
+        for _ in 0..20 {
+            let n = rng.gen_range(0, 200);
+            let a = repeat_with(|| match rng.gen_range(0, 3) {
+                0 => *specials.choose(&mut rng).unwrap(),
+                1 => rng.gen_range(-10.0, 10.0),
+                _ => f64::from_bits(rng.gen()),
+            })
+            .take(n)
+            .collect::<Vec<f64>>();
+            let mut expected = a.clone();
+            expected.sort_by(f64::total_cmp);
+            let mut result = a.clone();
+            radix_sort(&mut result, 8);
+            assert_eq!(
+                result.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+                expected.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+            );
+
+            let a = a.iter().map(|&x| x as f32).collect::<Vec<_>>();
+            let mut expected = a.clone();
+            expected.sort_by(f32::total_cmp);
+            let mut result = a.clone();
+            radix_sort(&mut result, 8);
+            assert_eq!(
+                result.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+                expected.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_by_key_is_stable() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let n = rng.gen_range(0, 200);
+            // (key, the original index as a record that is not `Copy`)
+            let a = (0..n)
+                .map(|i| (rng.gen_range(-10, 10), i.to_string()))
+                .collect::<Vec<(i32, String)>>();
+            let mut expected = a.clone();
+            expected.sort_by_key(|&(x, _)| x);
+            let mut result = a.clone();
+            radix_sort_by_key(&mut result, 4, |&(x, _)| x);
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn test_msd_radix_sort() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let n = rng.gen_range(0, 200);
+            // Small alphabets make many common prefixes.
+            let a = repeat_with(|| {
+                let len = rng.gen_range(0, 8);
+                repeat_with(|| rng.gen_range(b'a', b'd'))
+                    .take(len)
+                    .collect::<Vec<u8>>()
+            })
+            .take(n)
+            .collect::<Vec<_>>();
+            let mut expected = a.clone();
+            expected.sort();
+            let mut result = a.clone();
+            msd_radix_sort(&mut result);
+            assert_eq!(result, expected);
+
+            let mut result = a
+                .iter()
+                .map(|s| String::from_utf8(s.clone()).unwrap())
+                .collect::<Vec<_>>();
+            msd_radix_sort(&mut result);
+            assert!(result
+                .iter()
+                .map(|s| s.as_bytes())
+                .eq(expected.iter().map(|s| s.as_slice())));
+
+            // `Record`s with the same name keep their order.
+            let a = a
+                .into_iter()
+                .enumerate()
+                .map(|(i, s)| Record(s, i))
+                .collect::<Vec<_>>();
+            let mut expected = a.clone();
+            expected.sort_by(|x, y| x.0.cmp(&y.0));
+            let mut result = a;
+            msd_radix_sort(&mut result);
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn test_msd_radix_sort_long_common_prefix() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let prefix = vec![b'a'; 100_000];
+        let a = repeat_with(|| {
+            let len = rng.gen_range(0, 4);
+            let mut s = prefix.clone();
+            s.extend(repeat_with(|| rng.gen_range(b'a', b'd')).take(len));
+            s
+        })
+        .take(40)
+        .collect::<Vec<_>>();
+        let mut expected = a.clone();
+        expected.sort();
+        let mut result = a;
+        msd_radix_sort(&mut result);
+        assert_eq!(result, expected);
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Record(Vec<u8>, usize);
+    impl AsRef<[u8]> for Record {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+}
@@ -1,35 +1,64 @@
+// Records an event with the `trace` feature, and does nothing without it.
+macro_rules! trace {
+    ($($event:tt)*) => {
+        #[cfg(feature = "trace")]
+        {
+            if trace::is_recording() {
+                trace::record(trace::Event::$($event)*);
+            }
+        }
+    };
+}
+
+mod memory;
 mod paren;
 mod validate;
 
 pub use validate::Validate;
 use {
+    node_pool::NodePool,
     std::{cmp::Ordering, fmt::Debug, mem::replace},
-    yansi::Paint,
 };
 
-pub struct LLRB<K, V>(BoxNode<K, V>);
+pub struct LLRB<K, V>(BoxNode<K, V>, NodePool<Node<K, V>>);
 impl<K: Ord + Debug, V: Debug> LLRB<K, V> {
     pub fn new() -> Self {
-        Self(BoxNode::nil())
+        Self(BoxNode::nil(), NodePool::disabled())
+    }
+    // Recycles the deleted nodes through `NodePool::with_capacity(capacity)`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(BoxNode::nil(), NodePool::with_capacity(capacity))
     }
     pub fn insert(&mut self, key: K, value: V) {
-        self.0.insert(key, value);
+        trace!(Begin {
+            op: "insert".to_owned(),
+            key: trace::key(&key),
+        });
+        let node = self.1.alloc(Node::new(key, value, Color::Red));
+        self.0.insert(node);
         self.0.set_color(Color::Black);
     }
     pub fn delete(&mut self, key: &K) -> Option<(K, V)> {
-        let root = &mut self.0;
+        trace!(Begin {
+            op: "delete".to_owned(),
+            key: trace::key(key),
+        });
+        let Self(root, pool) = self;
         if root.is_two() {
             root.set_color(Color::Red)
         }
         let res = root
             .delete(key)
-            .map(|node| node.0.unwrap())
+            .map(|node| pool.free(node.0.unwrap()))
             .map(|node| (node.key, node.value));
         if !root.is_nil() {
             root.set_color(Color::Black);
         }
         res
     }
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.0.get(key)
+    }
     pub fn collect_vec(&self) -> Vec<(K, V)>
     where
         K: Clone,
@@ -48,12 +77,15 @@ impl<K: Ord + Debug, V: Debug> BoxNode<K, V> {
     fn is_nil(&self) -> bool {
         self.0.is_none()
     }
-    fn insert(&mut self, key: K, value: V) {
+    fn insert(&mut self, node: Box<Node<K, V>>) {
         if self.is_nil() {
-            *self = Node::new(key, value, Color::Red).boxed();
+            trace!(Attach {
+                label: format!("{:?}:R", node.key),
+            });
+            *self = BoxNode(Some(node));
         } else {
-            self.child_mut(if key <= self.unwrap().key { 0 } else { 1 })
-                .insert(key, value);
+            self.child_mut(if node.key <= self.unwrap().key { 0 } else { 1 })
+                .insert(node);
             self.fixup();
         }
     }
@@ -74,6 +106,9 @@ impl<K: Ord + Debug, V: Debug> BoxNode<K, V> {
                     self.rotate(0);
                 }
                 if cmp == Ordering::Equal && self.child(1).is_nil() {
+                    trace!(Splice {
+                        key: trace::key(&self.unwrap().key),
+                    });
                     return Some(replace(self, Self::nil()));
                 }
                 // Merge 2-nodes
@@ -83,6 +118,10 @@ impl<K: Ord + Debug, V: Debug> BoxNode<K, V> {
                 if key == &self.unwrap().key {
                     let mut rem = self.child_mut(1).delete_first();
                     (0..2).for_each(|i| rem.init_child(i, self.take_child(i)));
+                    trace!(Replace {
+                        key: trace::key(&self.unwrap().key),
+                        by: trace::key(&rem.unwrap().key),
+                    });
                     rem.set_color(self.color());
                     Some(replace(self, rem))
                 } else {
@@ -95,6 +134,9 @@ impl<K: Ord + Debug, V: Debug> BoxNode<K, V> {
     }
     fn delete_first(&mut self) -> Self {
         if self.child(0).is_nil() {
+            trace!(Splice {
+                key: trace::key(&self.unwrap().key),
+            });
             replace(self, Self::nil())
         } else {
             // Merge 2-nodes
@@ -121,6 +163,14 @@ impl<K: Ord + Debug, V: Debug> BoxNode<K, V> {
         }
     }
 
+    fn get(&self, key: &K) -> Option<&V> {
+        let me = self.0.as_ref()?;
+        match key.cmp(&me.key) {
+            Ordering::Less => me.child[0].get(key),
+            Ordering::Greater => me.child[1].get(key),
+            Ordering::Equal => Some(&me.value),
+        }
+    }
     fn collect_vec(&self, vec: &mut Vec<(K, V)>)
     where
         K: Clone,
@@ -152,7 +202,17 @@ impl<K: Ord + Debug, V: Debug> BoxNode<K, V> {
         self.0.as_mut().unwrap()
     }
     fn set_color(&mut self, color: Color) {
-        self.unwrap_mut().color = color
+        let node = self.unwrap_mut();
+        #[cfg(feature = "trace")]
+        {
+            if node.color != color {
+                trace!(SetColor {
+                    key: trace::key(&node.key),
+                    color: color.into(),
+                });
+            }
+        }
+        node.color = color
     }
     fn is_two(&self) -> bool {
         !self.is_nil() && self.is_black() && self.child(0).is_black() && self.child(1).is_black()
@@ -175,6 +235,15 @@ impl<K: Ord + Debug, V: Debug> BoxNode<K, V> {
 
     // -- rotate
     fn rotate(&mut self, i: usize) {
+        if i == 1 {
+            trace!(RotateLeft {
+                key: trace::key(&self.unwrap().key),
+            });
+        } else {
+            trace!(RotateRight {
+                key: trace::key(&self.unwrap().key),
+            });
+        }
         let mut x = replace(self, Self::nil());
         let mut y = x.take_child(i);
         assert!(y.is_red());
@@ -207,12 +276,18 @@ impl<K: Ord + Debug, V: Debug> BoxNode<K, V> {
     }
 
     fn merge_node(&mut self) {
+        trace!(MergeNode {
+            key: trace::key(&self.unwrap().key),
+        });
         assert!(self.is_red());
         (0..2).for_each(|i| assert!(self.child(i).is_black()));
         self.set_color(Color::Black);
         (0..2).for_each(|i| self.child_mut(i).set_color(Color::Red));
     }
     fn split_node(&mut self) {
+        trace!(SplitNode {
+            key: trace::key(&self.unwrap().key),
+        });
         assert!(self.is_black());
         (0..2).for_each(|i| assert!(self.child(i).is_red()));
         self.set_color(Color::Red);
@@ -243,21 +318,180 @@ enum Color {
     Red,
     Black,
 }
-impl Color {
-    fn paint<T>(&self, x: T) -> Paint<T> {
-        match self {
-            Self::Red => Paint::red(x),
-            Self::Black => Paint::blue(x),
-        }
-        .bold()
-    }
-}
 
 #[cfg(test)]
 mod tests {
     use super::Validate;
-    use super::LLRB;
+    use super::{Node, LLRB};
+    use counting_allocator::{count, CountingAllocator, Counts};
+    use invariant::Property;
+    use memory_usage::MemoryUsage;
+    use paren::{FromParen, Paren, Visualize};
     use rand::prelude::*;
+    use std::mem::size_of;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_paren() {
+        let mut llrb = LLRB::new();
+        (0..6).for_each(|i| llrb.insert(i, ()));
+        let expected = "(((0:B)1:R(2:B))3:B((4:R)5:B))";
+        assert_eq!(llrb.to_paren(), expected);
+        let parsed = LLRB::<u32, ()>::from_paren(expected).unwrap();
+        assert_eq!(parsed.validate(), 2);
+        assert_eq!(parsed.to_paren(), expected);
+    }
+
+    #[test]
+    fn test_pretty() {
+        let llrb = LLRB::<u32, ()>::from_paren("(((0:B)1:R(2:B))3:B((4:R)5:B))").unwrap();
+        let expected = [
+            "/-- 5:B",
+            "|   \\-- 4:R",
+            "3:B",
+            "|   /-- 2:B",
+            "\\-- 1:R",
+            "    \\-- 0:B",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(llrb.pretty(), expected);
+        let dot = llrb.to_dot();
+        assert!(dot.contains("n0 [label=\"3\", style=filled, fillcolor=black, fontcolor=white];"));
+        assert!(dot.contains("[label=\"1\", style=filled, fillcolor=red, fontcolor=white];"));
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_trace() {
+        use {paren::Color, trace::Event};
+        let mut llrb = LLRB::<u32, ()>::from_paren("((1:R)2:B)").unwrap();
+        let ((), events) = trace::capture(|| llrb.insert(3, ()));
+        let key = |key: u32| key.to_string();
+        let set_color = |k: u32, color: Color| Event::SetColor { key: key(k), color };
+        let expected = vec![
+            Event::Begin {
+                op: "insert".to_owned(),
+                key: key(3),
+            },
+            Event::Attach {
+                label: "3:R".to_owned(),
+            },
+            Event::SplitNode { key: key(2) },
+            set_color(2, Color::Red),
+            set_color(1, Color::Black),
+            set_color(3, Color::Black),
+            set_color(2, Color::Black),
+        ];
+        assert_eq!(events, expected);
+        assert_eq!(llrb.to_paren(), "((1:B)2:B(3:B))");
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_trace_replay() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut llrb = LLRB::<u32, ()>::new();
+        let mut keys = std::collections::BTreeSet::new();
+        for _ in 0..1000 {
+            let key = rng.gen_range(0, 40);
+            let before = llrb.to_paren();
+            let ((), events) = if keys.insert(key) {
+                trace::capture(|| llrb.insert(key, ()))
+            } else {
+                keys.remove(&key);
+                trace::capture(|| {
+                    llrb.delete(&key);
+                })
+            };
+            let snapshots = trace::replay::<u32>(&before, &events).unwrap();
+            assert_eq!(snapshots.last().unwrap(), &llrb.to_paren());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "black height is violated")]
+    fn test_validate_black_height() {
+        LLRB::<u32, ()>::from_paren("((1:B)2:B)")
+            .unwrap()
+            .validate();
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let check = |s: &str| {
+            LLRB::<u32, ()>::from_paren(s)
+                .unwrap()
+                .check_invariants()
+                .map_err(|e| (e.property, e.path))
+        };
+        assert_eq!(check("(((0:B)1:R(2:B))3:B((4:R)5:B))"), Ok(()));
+        assert_eq!(check("(1:R)"), Err((Property::BlackRoot, vec![])));
+        assert_eq!(
+            check("(((1:R)2:R)3:B)"),
+            Err((Property::RedRed, vec![0, 0]))
+        );
+        assert_eq!(check("(1:B(2:R))"), Err((Property::LeftLeaning, vec![1])));
+        assert_eq!(check("((1:B)2:B)"), Err((Property::BlackHeight, vec![])));
+        assert_eq!(check("((3:B)2:B(1:B))"), Err((Property::BstOrder, vec![])));
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let node = size_of::<Node<u32, String>>();
+        let mut llrb = LLRB::new();
+        assert_eq!(llrb.heap_size_bytes(), 0);
+        for k in 0..10 {
+            llrb.insert(k, "x".repeat(k as usize));
+        }
+        assert_eq!(llrb.heap_size_bytes(), 10 * node + 45);
+        for k in 0..4 {
+            llrb.delete(&k);
+        }
+        assert_eq!(llrb.heap_size_bytes(), 6 * node + 39);
+    }
+
+    #[test]
+    fn test_allocations() {
+        // a box for every node, and nothing else
+        let mut llrb = LLRB::<u32, ()>::new();
+        let ((), counts) = count(|| (0..10).for_each(|k| llrb.insert(k, ())));
+        assert_eq!(
+            counts,
+            Counts {
+                allocs: 10,
+                ..Counts::default()
+            }
+        );
+        let ((), counts) = count(|| {
+            (0..10).for_each(|k| {
+                llrb.delete(&k);
+            })
+        });
+        assert_eq!(
+            counts,
+            Counts {
+                deallocs: 10,
+                ..Counts::default()
+            }
+        );
+
+        // none in the churn, with a pool
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut llrb = LLRB::<u32, ()>::with_capacity(100);
+        let ((), counts) = count(|| {
+            (0..100).for_each(|k| llrb.insert(k, ()));
+            for _ in 0..1000 {
+                if llrb.delete(&rng.gen_range(0, 200)).is_some() {
+                    llrb.insert(rng.gen_range(0, 200), ());
+                }
+            }
+        });
+        assert_eq!(counts, Counts::default());
+        llrb.validate();
+    }
 
     #[test]
     fn test_hand_insert() {
@@ -362,6 +596,9 @@ mod tests {
                     .collect::<Vec<_>>(),
                 &self.vec
             );
+            for x in 0..30 {
+                assert_eq!(self.llrb.get(&x).is_some(), self.vec.contains(&x));
+            }
         }
     }
 }
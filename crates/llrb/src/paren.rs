@@ -1,9 +1,12 @@
-use super::{BoxNode, Node, LLRB};
-use std::fmt::{self, Debug};
-
-pub trait Paren {
-    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result;
-}
+use {
+    super::{BoxNode, Color, Node, LLRB},
+    node_pool::NodePool,
+    paren::{FromParen, Painted, Paren, ParseError, Shape, Visualize},
+    std::{
+        fmt::{self, Debug},
+        str::FromStr,
+    },
+};
 
 impl<K: Ord + Debug, V: Debug> Paren for LLRB<K, V> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
@@ -19,31 +22,89 @@ impl<K: Ord + Debug, V: Debug> Paren for Node<K, V> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
         write!(w, "(")?;
         self.child[0].paren(w)?;
-        write!(w, "{:?}", self.color.paint(&self.key))?;
+        write!(
+            w,
+            "{:?}:{}",
+            paren::paint(w, self.color.into(), &self.key),
+            paren::Color::from(self.color)
+        )?;
         self.child[1].paren(w)?;
         write!(w, ")")
     }
 }
 
-struct ParenWrapper<'a, T>(&'a T);
-impl<'a, T: Paren> Debug for ParenWrapper<'a, T> {
-    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        self.0.paren(w)
+impl<K: Ord + Debug, V: Debug> Visualize for LLRB<K, V> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.0.shape().into_iter().collect()
+    }
+}
+impl<K: Ord + Debug, V: Debug> BoxNode<K, V> {
+    fn shape(&self) -> Option<Shape> {
+        self.0.as_ref().map(|x| {
+            Shape::new(&x.key)
+                .color(x.color)
+                .child(x.child[0].shape())
+                .child(x.child[1].shape())
+        })
+    }
+}
+
+// The values are left default.
+impl<K: Ord + Debug + FromStr, V: Debug + Default> FromParen for LLRB<K, V> {
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        BoxNode::from_parsed(paren::parse(s)?).map(|root| Self(root, NodePool::disabled()))
+    }
+}
+impl<K: Ord + Debug + FromStr, V: Debug + Default> BoxNode<K, V> {
+    fn from_parsed(node: Option<Box<paren::Node>>) -> Result<Self, ParseError> {
+        match node {
+            None => Ok(Self::nil()),
+            Some(node) => {
+                let paren::Node {
+                    label,
+                    child: [left, right],
+                } = *node;
+                Ok(Node {
+                    child: [Self::from_parsed(left)?, Self::from_parsed(right)?],
+                    key: label.key()?,
+                    value: V::default(),
+                    color: label.color()?.into(),
+                }
+                .boxed())
+            }
+        }
+    }
+}
+
+impl From<Color> for paren::Color {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Red => paren::Color::Red,
+            Color::Black => paren::Color::Black,
+        }
+    }
+}
+impl From<paren::Color> for Color {
+    fn from(color: paren::Color) -> Self {
+        match color {
+            paren::Color::Red => Color::Red,
+            paren::Color::Black => Color::Black,
+        }
     }
 }
 
 impl<K: Ord + Debug, V: Debug> Debug for LLRB<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("LLRB").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("LLRB").field(&Painted(self)).finish()
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for BoxNode<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("BoxNode").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("BoxNode").field(&Painted(self)).finish()
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for Node<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("Node").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("Node").field(&Painted(self)).finish()
     }
 }
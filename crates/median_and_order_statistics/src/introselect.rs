@@ -0,0 +1,110 @@
+use {
+    linear_time_sorts::insertion_sort_by,
+    quick_sort::{median_of_three, partition3},
+    std::cmp::Ordering,
+};
+
+// Slices up to this length are left to the insertion sort.
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+pub fn select_nth<T: Ord>(a: &mut [T], k: usize) -> &T {
+    select_nth_by(a, k, T::cmp)
+}
+pub fn select_nth_by_key<T, K: Ord>(a: &mut [T], k: usize, mut f: impl FnMut(&T) -> K) -> &T {
+    select_nth_by(a, k, |x, y| f(x).cmp(&f(y)))
+}
+// The k-th smallest element (0-indexed) with respect to `compare`. It reorders `a`.
+pub fn select_nth_by<T>(a: &mut [T], k: usize, compare: impl FnMut(&T, &T) -> Ordering) -> &T {
+    select_nth_unstable_by(a, k, compare).1
+}
+
+pub fn select_nth_unstable<T: Ord>(a: &mut [T], k: usize) -> (&mut [T], &mut T, &mut [T]) {
+    select_nth_unstable_by(a, k, T::cmp)
+}
+pub fn select_nth_unstable_by_key<T, K: Ord>(
+    a: &mut [T],
+    k: usize,
+    mut f: impl FnMut(&T) -> K,
+) -> (&mut [T], &mut T, &mut [T]) {
+    select_nth_unstable_by(a, k, |x, y| f(x).cmp(&f(y)))
+}
+// Reorder `a` so that `a[k]` is the k-th smallest element, and the ones before and after it are
+// not greater and not less than it respectively, and return the three parts.
+pub fn select_nth_unstable_by<T>(
+    a: &mut [T],
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+) -> (&mut [T], &mut T, &mut [T]) {
+    assert!(k < a.len(), "the index {} is out of 0..{}", k, a.len());
+    introselect(a, k, &mut compare);
+    let (left, rest) = a.split_at_mut(k);
+    let (nth, right) = rest.split_first_mut().unwrap();
+    (left, nth, right)
+}
+
+// Quickselect with the median of three pivots, which falls back to the median of medians once the
+// partitions have scanned 4n elements in total, so it takes O(n) in the worst case.
+pub fn introselect<T>(mut a: &mut [T], mut k: usize, compare: &mut impl FnMut(&T, &T) -> Ordering) {
+    let mut budget = 4 * a.len();
+    loop {
+        if a.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort_by(a, &mut *compare);
+            return;
+        }
+        if budget < a.len() {
+            median_of_medians_select(a, k, compare);
+            return;
+        }
+        budget -= a.len();
+        median_of_three(a, compare);
+        let (lt, gt) = partition3(a, compare);
+        if k < lt {
+            a = &mut a[..lt];
+        } else if gt <= k {
+            a = &mut a[gt..];
+            k -= gt;
+        } else {
+            return;
+        }
+    }
+}
+
+// The same as `introselect` but always with the median of medians pivots.
+pub fn median_of_medians_select<T>(
+    mut a: &mut [T],
+    mut k: usize,
+    compare: &mut impl FnMut(&T, &T) -> Ordering,
+) {
+    loop {
+        if a.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort_by(a, &mut *compare);
+            return;
+        }
+        median_of_medians(a, compare);
+        let (lt, gt) = partition3(a, compare);
+        if k < lt {
+            a = &mut a[..lt];
+        } else if gt <= k {
+            a = &mut a[gt..];
+            k -= gt;
+        } else {
+            return;
+        }
+    }
+}
+
+// Move the median of the medians of the groups of five to the front. At least 3/10 of the
+// elements are not greater, and as many are not less than it.
+pub fn median_of_medians<T>(a: &mut [T], compare: &mut impl FnMut(&T, &T) -> Ordering) {
+    assert!(!a.is_empty());
+    let m = a.len() / 5;
+    for i in 0..m {
+        insertion_sort_by(&mut a[5 * i..5 * i + 5], &mut *compare);
+        // The group of `i` has already been visited.
+        a.swap(i, 5 * i + 2);
+    }
+    if m != 0 {
+        median_of_medians_select(&mut a[..m], m / 2, compare);
+        a.swap(0, m / 2);
+    }
+}
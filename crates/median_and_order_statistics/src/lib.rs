@@ -1,80 +1,74 @@
-use rand::prelude::*;
-use std::cmp::Ordering;
-use std::sync::Mutex;
+mod introselect;
 
-lazy_static::lazy_static! {
-    static ref RNG: Mutex<StdRng> = Mutex::new(StdRng::seed_from_u64(42));
-}
+pub use introselect::{
+    select_nth, select_nth_by, select_nth_by_key, select_nth_unstable, select_nth_unstable_by,
+    select_nth_unstable_by_key,
+};
+
+use {quick_sort::partition3, rand::prelude::*, std::cmp::Ordering};
 
-pub fn median_of_median_select(a: &mut [u32], k: usize) -> u32 {
+// O(n) in the worst case.
+pub fn median_of_median_select<T: Ord>(a: &mut [T], k: usize) -> &T {
     assert!(k < a.len());
-    let mid = median_of_medians_partition(a);
-    match k.cmp(&mid) {
-        Ordering::Equal => a[k],
-        Ordering::Less => randomized_select(&mut a[..mid], k),
-        Ordering::Greater => randomized_select(&mut a[mid + 1..], k - mid - 1),
-    }
+    introselect::median_of_medians_select(a, k, &mut T::cmp);
+    &a[k]
 }
 
-pub fn median_of_medians_partition(a: &mut [u32]) -> usize {
-    let mut b = a
-        .chunks_mut(5)
-        .map(|v| {
-            v.sort();
-            v[(v.len() - 1) / 2]
-        })
-        .collect::<Vec<_>>();
-    let m = b.len();
-    let median = median_of_median_select(&mut b, (m - 1) / 2);
-    a.iter().position(|&x| x == median).unwrap()
+// Partition `a` around the median of medians, and return its index.
+pub fn median_of_medians_partition<T: Ord>(a: &mut [T]) -> usize {
+    introselect::median_of_medians(a, &mut T::cmp);
+    partition3(a, &mut T::cmp).0
 }
 
-pub fn randomized_select(a: &mut [u32], k: usize) -> u32 {
+pub fn randomized_select<'a, T: Ord>(a: &'a mut [T], k: usize, rng: &mut impl Rng) -> &'a T {
     assert!(k < a.len());
-    let mid = randomized_partition(a);
+    let mid = randomized_partition(a, rng);
     match k.cmp(&mid) {
-        Ordering::Equal => a[k],
-        Ordering::Less => randomized_select(&mut a[..mid], k),
-        Ordering::Greater => randomized_select(&mut a[mid + 1..], k - mid - 1),
+        Ordering::Equal => &a[k],
+        Ordering::Less => randomized_select(&mut a[..mid], k, rng),
+        Ordering::Greater => randomized_select(&mut a[mid + 1..], k - mid - 1, rng),
     }
 }
 
-pub fn randomized_select_no_recursion(a: &mut [u32], k: usize) -> u32 {
+pub fn randomized_select_no_recursion<'a, T: Ord>(
+    a: &'a mut [T],
+    k: usize,
+    rng: &mut impl Rng,
+) -> &'a T {
     assert!(k < a.len());
     let mut l = 0;
     let mut r = a.len();
     while l + 1 != r {
-        let mid = l + randomized_partition(&mut a[l..r]);
+        let mid = l + randomized_partition(&mut a[l..r], rng);
         match k.cmp(&mid) {
             Ordering::Equal => {
-                return a[k];
+                return &a[k];
             }
             Ordering::Less => r = mid,
             Ordering::Greater => l = mid + 1,
         }
     }
-    a[l]
+    &a[l]
 }
 
-pub fn randomized_partition(a: &mut [u32]) -> usize {
+pub fn randomized_partition<T: Ord>(a: &mut [T], rng: &mut impl Rng) -> usize {
     let n = a.len();
     if n == 1 {
         0
     } else {
-        a.swap(RNG.lock().unwrap().gen_range(0, n - 1), n - 1);
+        a.swap(rng.gen_range(0, n), n - 1);
         partition(a)
     }
 }
 
-pub fn partition(a: &mut [u32]) -> usize {
+pub fn partition<T: Ord>(a: &mut [T]) -> usize {
     let n = a.len();
     if n == 1 {
         0
     } else {
-        let key = a[n - 1];
         let mut i = 0;
         for j in 0..n - 1 {
-            if a[j] < key {
+            if a[j] < a[n - 1] {
                 a.swap(i, j);
                 i += 1;
             }
@@ -86,9 +80,17 @@ pub fn partition(a: &mut [u32]) -> usize {
 
 #[cfg(test)]
 mod tests {
-    use super::{randomized_select, randomized_select_no_recursion};
+    use {
+        super::{
+            median_of_median_select, median_of_medians_partition, randomized_select,
+            randomized_select_no_recursion, select_nth, select_nth_by, select_nth_by_key,
+            select_nth_unstable, select_nth_unstable_by_key,
+        },
+        rand::prelude::*,
+        std::{cmp::Reverse, iter::repeat_with},
+    };
 
-    fn tester(select: impl Fn(&mut [u32], usize) -> u32) {
+    fn tester(mut select: impl FnMut(&mut [u32], usize) -> u32) {
         let a = vec![3, 2, 9, 0, 7, 5, 4, 8, 6, 1];
         let mut sorted = a.clone();
         sorted.sort();
@@ -103,16 +105,99 @@ mod tests {
 
     #[test]
     fn test_randomized_select() {
-        tester(randomized_select);
+        let mut rng = StdRng::seed_from_u64(42);
+        tester(|a, k| *randomized_select(a, k, &mut rng));
     }
 
     #[test]
     fn test_randomized_select_no_recursion() {
-        tester(randomized_select_no_recursion);
+        let mut rng = StdRng::seed_from_u64(42);
+        tester(|a, k| *randomized_select_no_recursion(a, k, &mut rng));
     }
 
     #[test]
     fn test_median_of_medians_select() {
-        tester(randomized_select_no_recursion);
+        tester(|a, k| *median_of_median_select(a, k));
+    }
+
+    #[test]
+    fn test_select_nth() {
+        tester(|a, k| *select_nth(a, k));
+    }
+
+    #[test]
+    fn test_rand() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..300 {
+            let n = rng.gen_range(1, 200);
+            let m = rng.gen_range(1, 300);
+            let a = repeat_with(|| (rng.gen_range(0, m), rng.gen_range(0, 10)))
+                .take(n)
+                .collect::<Vec<(u32, u32)>>();
+            let mut sorted = a.clone();
+            sorted.sort();
+            let k = rng.gen_range(0, n);
+
+            let mut b = a.clone();
+            let (left, &mut nth, right) = select_nth_unstable(&mut b, k);
+            assert_eq!(nth, sorted[k]);
+            assert!(left.iter().all(|&x| x <= nth));
+            assert!(right.iter().all(|&x| nth <= x));
+            b.sort();
+            assert_eq!(b, sorted);
+
+            // It is unstable, so only the keys are compared.
+            let mut b = a.clone();
+            let (left, &mut (nth, _), right) =
+                select_nth_unstable_by_key(&mut b, k, |&(x, _)| Reverse(x));
+            assert_eq!(nth, sorted[n - 1 - k].0);
+            assert!(left.iter().all(|&(x, _)| nth <= x));
+            assert!(right.iter().all(|&(x, _)| x <= nth));
+            let mut b = a.clone();
+            assert_eq!(select_nth_by_key(&mut b, k, |&(x, _)| x).0, sorted[k].0);
+            let mut b = a.clone();
+            assert_eq!(
+                select_nth_by(&mut b, k, |x, y| y.0.cmp(&x.0)).0,
+                sorted[n - 1 - k].0
+            );
+
+            let mut b = a.clone();
+            assert_eq!(*median_of_median_select(&mut b, k), sorted[k]);
+            let mut b = a.clone();
+            assert_eq!(*randomized_select(&mut b, k, &mut rng), sorted[k]);
+            let mut b = a.clone();
+            assert_eq!(
+                *randomized_select_no_recursion(&mut b, k, &mut rng),
+                sorted[k]
+            );
+
+            let mut b = a.clone();
+            let i = median_of_medians_partition(&mut b);
+            assert!(b[..i].iter().all(|&x| x < b[i]));
+            assert!(b[i + 1..].iter().all(|&x| b[i] <= x));
+        }
+    }
+
+    // They would take quadratic time with the first or the last element as the pivot.
+    #[test]
+    fn test_adversarial_inputs() {
+        let n = 100_000;
+        let inputs = vec![
+            (0..n).collect::<Vec<u32>>(),
+            (0..n).rev().collect(),
+            (0..n / 2).chain((0..n / 2).rev()).collect(),
+            (0..n).map(|x| x % 4).collect(),
+            vec![0; n as usize],
+        ];
+        for a in inputs {
+            let mut sorted = a.clone();
+            sorted.sort();
+            for &k in [0, n as usize / 2, n as usize - 1].iter() {
+                let mut b = a.clone();
+                assert_eq!(*select_nth(&mut b, k), sorted[k]);
+                let mut b = a.clone();
+                assert_eq!(*median_of_median_select(&mut b, k), sorted[k]);
+            }
+        }
     }
 }
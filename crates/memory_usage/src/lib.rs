@@ -0,0 +1,216 @@
+use std::{
+    cell::RefCell,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    mem::{align_of, size_of},
+    rc::{Rc, Weak},
+};
+
+// The bytes which a value owns on the heap, so that the structures can be compared by their space
+// as well as by their time.
+//
+// They are the bytes requested from the allocator, which may round them up. `size_of::<Self>()`
+// is not counted, since it is inline in whatever holds the value, e.g. a node or a `Vec`.
+pub trait MemoryUsage {
+    fn heap_size_bytes(&self) -> usize;
+}
+
+macro_rules! inline {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl MemoryUsage for $t {
+                fn heap_size_bytes(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+inline!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+);
+
+impl<T: MemoryUsage> MemoryUsage for Option<T> {
+    fn heap_size_bytes(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size_bytes)
+    }
+}
+impl<A: MemoryUsage, B: MemoryUsage> MemoryUsage for (A, B) {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes() + self.1.heap_size_bytes()
+    }
+}
+impl MemoryUsage for String {
+    fn heap_size_bytes(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: MemoryUsage> MemoryUsage for Box<T> {
+    fn heap_size_bytes(&self) -> usize {
+        size_of::<T>() + (**self).heap_size_bytes()
+    }
+}
+// Counted at every `Rc`, so an allocation is counted as many times as it is shared. The structures
+// sharing their nodes count them once by their addresses, with `rc_bytes`.
+impl<T: MemoryUsage> MemoryUsage for Rc<T> {
+    fn heap_size_bytes(&self) -> usize {
+        rc_bytes::<T>() + (**self).heap_size_bytes()
+    }
+}
+// The allocation belongs to the `Rc`s.
+impl<T> MemoryUsage for Weak<T> {
+    fn heap_size_bytes(&self) -> usize {
+        0
+    }
+}
+impl<T: MemoryUsage> MemoryUsage for RefCell<T> {
+    fn heap_size_bytes(&self) -> usize {
+        self.borrow().heap_size_bytes()
+    }
+}
+
+// The spare capacity is counted as well as the elements.
+impl<T: MemoryUsage> MemoryUsage for Vec<T> {
+    fn heap_size_bytes(&self) -> usize {
+        self.capacity() * size_of::<T>() + sum(self)
+    }
+}
+impl<T: MemoryUsage> MemoryUsage for VecDeque<T> {
+    fn heap_size_bytes(&self) -> usize {
+        self.capacity() * size_of::<T>() + sum(self)
+    }
+}
+impl<T: MemoryUsage> MemoryUsage for BinaryHeap<T> {
+    fn heap_size_bytes(&self) -> usize {
+        self.capacity() * size_of::<T>() + sum(self)
+    }
+}
+impl<K: MemoryUsage, V: MemoryUsage, S> MemoryUsage for HashMap<K, V, S> {
+    fn heap_size_bytes(&self) -> usize {
+        hash_table_bytes::<(K, V)>(self.capacity())
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size_bytes() + v.heap_size_bytes())
+                .sum::<usize>()
+    }
+}
+impl<T: MemoryUsage, S> MemoryUsage for HashSet<T, S> {
+    fn heap_size_bytes(&self) -> usize {
+        hash_table_bytes::<T>(self.capacity()) + sum(self)
+    }
+}
+
+fn sum<'a, T: MemoryUsage + 'a>(iter: impl IntoIterator<Item = &'a T>) -> usize {
+    iter.into_iter().map(T::heap_size_bytes).sum()
+}
+
+// the allocation of an `Rc<T>`, which has the strong and the weak counts ahead of the value
+pub fn rc_bytes<T>() -> usize {
+    let value = round_up(2 * size_of::<usize>(), align_of::<T>());
+    round_up(
+        value + size_of::<T>(),
+        align_of::<T>().max(align_of::<usize>()),
+    )
+}
+// `align` is a power of two.
+fn round_up(x: usize, align: usize) -> usize {
+    (x + align - 1) & !(align - 1)
+}
+
+// The control bytes scanned at once: an SSE2 register, or a `usize` elsewhere, where `hashbrown`
+// falls back to the generic implementation (NEON also takes 8 bytes).
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2"
+))]
+const GROUP_WIDTH: usize = 16;
+#[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2"
+)))]
+const GROUP_WIDTH: usize = size_of::<usize>();
+
+// The buckets of a `HashMap` or a `HashSet` of `capacity`, in the layout of `hashbrown`, which std
+// uses: a power of two, at least 4, buckets at most 7/8 full, with a control byte each and
+// `GROUP_WIDTH` more.
+//
+// `capacity` may be less than 7/8 of the buckets after the removals, which the rounding up covers.
+pub fn hash_table_bytes<T>(capacity: usize) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+    let buckets = (capacity * 8 / 7).next_power_of_two().max(4);
+    buckets * size_of::<T>() + buckets + GROUP_WIDTH
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{hash_table_bytes, rc_bytes, MemoryUsage},
+        std::{
+            cell::RefCell,
+            collections::{HashMap, VecDeque},
+            mem::size_of,
+            rc::Rc,
+        },
+    };
+
+    #[test]
+    fn test_std() {
+        assert_eq!(42u32.heap_size_bytes(), 0);
+        assert_eq!(Some(Box::new(42u32)).heap_size_bytes(), 4);
+        assert_eq!(Vec::<u32>::with_capacity(10).heap_size_bytes(), 40);
+        assert_eq!(
+            vec![vec![0u8; 3], Vec::new()].heap_size_bytes(),
+            2 * size_of::<Vec<u8>>() + 3
+        );
+        assert!(VecDeque::<u64>::with_capacity(8).heap_size_bytes() >= 64);
+        assert_eq!(String::from("abc").heap_size_bytes(), 3);
+
+        assert_eq!(rc_bytes::<usize>(), 3 * size_of::<usize>());
+        assert_eq!(rc_bytes::<u8>(), 3 * size_of::<usize>());
+        let rc = Rc::new(RefCell::new(vec![0u8; 5]));
+        assert_eq!(rc.heap_size_bytes(), rc_bytes::<RefCell<Vec<u8>>>() + 5);
+        assert_eq!(Rc::downgrade(&rc).heap_size_bytes(), 0);
+    }
+
+    #[test]
+    fn test_hash_table_bytes() {
+        assert_eq!(HashMap::<u32, u32>::new().heap_size_bytes(), 0);
+        assert_eq!(hash_table_bytes::<(u32, u32)>(3), 4 * 8 + 4 + 16);
+        assert_eq!(hash_table_bytes::<(u32, u32)>(7), 8 * 8 + 8 + 16);
+        assert_eq!(hash_table_bytes::<(u32, u32)>(112), 128 * 8 + 128 + 16);
+
+        // The buckets do not shrink by the removals.
+        let mut map = HashMap::<u32, u32>::new();
+        for i in 0..100 {
+            map.insert(i, i);
+        }
+        let bytes = map.heap_size_bytes();
+        assert_eq!(bytes, 128 * 8 + 128 + 16);
+        for i in 0..50 {
+            map.remove(&i);
+        }
+        assert_eq!(map.heap_size_bytes(), bytes);
+        let map = (0..100)
+            .map(|i| (i, vec![0u8; 10]))
+            .collect::<HashMap<u32, _>>();
+        assert!(map.heap_size_bytes() >= 100 * (size_of::<(u32, Vec<u8>)>() + 10));
+    }
+}
@@ -1,6 +1,7 @@
 mod color;
+mod memory;
 mod paren;
-mod validate;
+pub mod validate;
 
 use color::Color;
 use std::{cmp::Ordering, fmt::Debug};
@@ -338,9 +339,28 @@ pub struct Leaf<K> {
 #[cfg(test)]
 mod tests {
     use super::{validate, RBTree};
+    use paren::{FromParen, Paren};
     use rand::prelude::*;
     use std::{collections::LinkedList, iter::once};
 
+    #[test]
+    fn test_paren() {
+        let mut rbt = RBTree::<u32>::from_paren("(((1)R(2))B(3))").unwrap();
+        validate::all(&rbt);
+        assert_eq!(rbt.to_paren(), "(((1)1:R(2))1:B(3))");
+        rbt.append(&mut RBTree::from_paren("(4)").unwrap());
+        let expected = "(((1)1:R(2))1:B((3)1:R(4)))";
+        assert_eq!(rbt.to_paren(), expected);
+        validate::all(&rbt);
+        let other = rbt.split_off(2);
+        assert_eq!(rbt.to_paren(), "((1)1:B(2))");
+        assert_eq!(other.to_paren(), "((3)1:B(4))");
+        validate::all(&rbt);
+        validate::all(&other);
+        // An internal node has two children.
+        assert!(RBTree::<u32>::from_paren("((1)B)").is_err());
+    }
+
     #[test]
     fn test_rand_small() {
         test_rand(20, 200, 10, 42);
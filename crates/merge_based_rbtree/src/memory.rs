@@ -0,0 +1,24 @@
+use {
+    super::{BoxNode, Node, RBTree},
+    memory_usage::MemoryUsage,
+};
+
+impl<K: MemoryUsage> MemoryUsage for RBTree<K> {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes()
+    }
+}
+// The leaves are inline, so a tree of `n` keys has `n - 1` boxes.
+impl<K: MemoryUsage> MemoryUsage for BoxNode<K> {
+    fn heap_size_bytes(&self) -> usize {
+        match self {
+            BoxNode::Internal(node) => node.heap_size_bytes(),
+            BoxNode::Leaf(leaf) => leaf.key.heap_size_bytes(),
+        }
+    }
+}
+impl<K: MemoryUsage> MemoryUsage for Node<K> {
+    fn heap_size_bytes(&self) -> usize {
+        self.child[0].heap_size_bytes() + self.child[1].heap_size_bytes()
+    }
+}
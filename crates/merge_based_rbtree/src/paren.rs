@@ -1,36 +1,34 @@
-use super::{color::Color, BoxNode, Node, RBTree};
-use std::fmt::{self, Debug};
-use yansi::Paint;
+use {
+    super::{color::Color, BoxNode, Node, RBTree},
+    paren::{FromParen, Painted, Paren, ParseError, Shape, Visualize},
+    std::{
+        fmt::{self, Debug},
+        str::FromStr,
+    },
+    yansi::Paint,
+};
 
 impl<K: Ord + Debug> Debug for RBTree<K> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("RBTree").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("RBTree").field(&Painted(self)).finish()
     }
 }
 impl<K: Ord + Debug> Debug for BoxNode<K> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("BoxNode").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("BoxNode").field(&Painted(self)).finish()
     }
 }
 impl<K: Ord + Debug> Debug for Node<K> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("Node").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("Node").field(&Painted(self)).finish()
     }
 }
-struct ParenWrapper<'a, T>(&'a T);
-impl<'a, T: Paren> Debug for ParenWrapper<'a, T> {
-    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        self.0.paren(w)
-    }
-}
-pub trait Paren {
-    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result;
-}
 impl<K: Ord + Debug> Paren for RBTree<K> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
         self.0.as_ref().iter().map(|x| x.paren(w)).collect()
     }
 }
+// The keys are in the leaves, and the internal nodes are labeled with the black heights.
 impl<K: Ord + Debug> Paren for BoxNode<K> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -38,19 +36,95 @@ impl<K: Ord + Debug> Paren for BoxNode<K> {
             Self::Leaf(leaf) => write!(
                 w,
                 "{}{:?}{}",
-                Color::Black.paint("("),
-                Paint::yellow(&leaf.key),
-                Color::Black.paint(")")
+                paren::paint(w, paren::Color::Black, "("),
+                if w.alternate() {
+                    Paint::yellow(&leaf.key)
+                } else {
+                    Paint::new(&leaf.key)
+                },
+                paren::paint(w, paren::Color::Black, ")")
             ),
         }
     }
 }
 impl<K: Ord + Debug> Paren for Node<K> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        write!(w, "{}", self.color.paint("("))?;
+        write!(w, "{}", paren::paint(w, self.color.into(), "("))?;
         self.child[0].paren(w)?;
-        write!(w, "{}", self.color.paint(self.bh))?;
+        write!(
+            w,
+            "{}:{}",
+            paren::paint(w, self.color.into(), self.bh),
+            paren::Color::from(self.color)
+        )?;
         self.child[1].paren(w)?;
-        write!(w, "{}", self.color.paint(")"))
+        write!(w, "{}", paren::paint(w, self.color.into(), ")"))
+    }
+}
+
+impl<K: Ord + Debug> Visualize for RBTree<K> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.0.iter().map(BoxNode::shape).collect()
+    }
+}
+impl<K: Ord + Debug> BoxNode<K> {
+    fn shape(&self) -> Shape {
+        match self {
+            Self::Internal(internal) => Shape::new(internal.bh)
+                .color(internal.color)
+                .attr("size", internal.size)
+                .child(Some(internal.child[0].shape()))
+                .child(Some(internal.child[1].shape())),
+            Self::Leaf(leaf) => Shape::new(&leaf.key).color(Color::Black),
+        }
+    }
+}
+
+// A node without children is a leaf, and the others are internal nodes of two children. The black
+// heights and the sizes are counted, so an internal node may be labeled with the color only, e.g.
+// `((1)B(2))`.
+impl<K: Ord + Debug + FromStr> FromParen for RBTree<K> {
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        paren::parse(s)?
+            .map(|root| BoxNode::from_parsed(*root))
+            .transpose()
+            .map(Self)
+    }
+}
+impl<K: Ord + Debug + FromStr> BoxNode<K> {
+    fn from_parsed(node: paren::Node) -> Result<Self, ParseError> {
+        let paren::Node { label, child } = node;
+        match child {
+            [None, None] => Ok(Self::leaf(label.key()?)),
+            [Some(left), Some(right)] => {
+                let child = [Self::from_parsed(*left)?, Self::from_parsed(*right)?];
+                // The color is the last field, after the black height if any.
+                let color = label.field::<paren::Color>(label.0.len() - 1)?.into();
+                Ok(Self::Internal(Box::new(Node {
+                    color,
+                    bh: child[0].bh_aug(),
+                    size: child[0].size() + child[1].size(),
+                    child,
+                })))
+            }
+            _ => Err(ParseError::InvalidLabel(label.0.join(":"))),
+        }
+    }
+}
+
+impl From<Color> for paren::Color {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Red => paren::Color::Red,
+            Color::Black => paren::Color::Black,
+        }
+    }
+}
+impl From<paren::Color> for Color {
+    fn from(color: paren::Color) -> Self {
+        match color {
+            paren::Color::Red => Color::Red,
+            paren::Color::Black => Color::Black,
+        }
     }
 }
@@ -1,78 +1,58 @@
-use super::{color::Color, BoxNode, Node, RBTree};
+use super::{color::Color, BoxNode, RBTree};
+use invariant::{ensure, InvariantViolation, Property};
 use std::fmt::Debug;
 
-impl<K: Ord + Debug> Validate for RBTree<K> {
-    fn no_double_red(&self) {
-        self.0.as_ref().iter().for_each(|root| root.no_double_red());
-    }
-    fn correct_black_height(&self) {
-        self.0
-            .as_ref()
-            .iter()
-            .for_each(|root| root.correct_black_height());
-    }
-    fn correct_size(&self) {
-        self.0.as_ref().iter().for_each(|root| root.correct_size());
-    }
-    fn root_is_black(&self) {
-        assert!(self.0.as_ref().map_or(true, |root| root.is_black()));
-    }
+pub fn all<T: Validate>(x: &T) {
+    x.validate()
 }
 
-impl<K: Ord + Debug> Validate for BoxNode<K> {
-    fn no_double_red(&self) {
-        self.as_node().iter().for_each(|node| node.no_double_red());
-    }
-    fn correct_black_height(&self) {
-        self.as_node()
-            .iter()
-            .for_each(|node| node.correct_black_height());
-    }
-    fn correct_size(&self) {
-        self.as_node().iter().for_each(|node| node.correct_size());
+// `check_invariants` that panics.
+pub trait Validate {
+    fn validate(&self);
+}
+impl<K: Ord + Debug> Validate for RBTree<K> {
+    fn validate(&self) {
+        invariant::assert_holds(self.check_invariants())
     }
 }
 
-impl<K: Ord + Debug> Validate for Node<K> {
-    fn no_double_red(&self) {
-        self.child.iter().for_each(|child| {
-            assert!(
-                child.is_black() || self.color == Color::Black,
-                "Double red: self = {:?}, child ={:?}",
-                &self,
-                child
-            );
-            child.no_double_red();
-        });
-    }
-    fn correct_black_height(&self) {
-        self.child.iter().for_each(|child| {
-            assert_eq!(
-                child.bh_aug(),
-                self.bh,
-                "Incorrect black height: self = {:?}, child ={:?}",
-                &self,
-                child
-            );
-            child.correct_black_height();
-        });
-    }
-    fn correct_size(&self) {
-        assert_eq!(
-            self.child.iter().map(|child| child.size()).sum::<usize>(),
-            self.size,
-            "Incorrect size: self = {:?}",
-            &self,
-        );
-        self.child.iter().for_each(|child| {
-            child.correct_size();
-        });
+impl<K: Ord + Debug> RBTree<K> {
+    // the colors, the black heights and the sizes; the leaves are in the order of the sequence,
+    // so there is no key order to check
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        if let Some(root) = &self.0 {
+            ensure(root.is_black(), Property::BlackRoot, &[])?;
+            check(root, &mut Vec::new())?;
+        }
+        Ok(())
     }
 }
-
-pub trait Validate {
-    fn no_double_red(&self);
-    fn correct_black_height(&self);
-    fn correct_size(&self);
-    fn root_is_black(&self) {}
+// Returns the black height, counting the leaves.
+fn check<K: Ord + Debug>(x: &BoxNode<K>, path: &mut Vec<usize>) -> Result<u32, InvariantViolation> {
+    let node = match x.as_node() {
+        None => return Ok(1),
+        Some(node) => node,
+    };
+    let mut bh = [0; 2];
+    for (i, child) in node.child.iter().enumerate() {
+        path.push(i);
+        ensure(
+            node.color == Color::Black || child.is_black(),
+            Property::RedRed,
+            path,
+        )?;
+        bh[i] = check(child, path)?;
+        path.pop();
+    }
+    ensure(bh[0] == bh[1], Property::BlackHeight, path)?;
+    ensure(node.bh == bh[0], Property::BlackHeightCache, path)?;
+    ensure(
+        node.size == node.child.iter().map(|child| child.size()).sum::<usize>(),
+        Property::SizeCache,
+        path,
+    )?;
+    Ok(match node.color {
+        Color::Black => bh[0] + 1,
+        Color::Red => bh[0],
+    })
 }
@@ -0,0 +1,119 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use fibonacci_heap::arena::FibonacciHeap;
+use mergeable_heaps::{AddressableHeap, BinomialHeap, LeftistHeap, PairingHeap};
+use rand::prelude::*;
+use std::iter::repeat_with;
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+fn gen_heap<H: AddressableHeap<u32, ()>>(pool: &mut H::Pool, n: usize, rng: &mut StdRng) -> H {
+    let mut heap = H::default();
+    repeat_with(|| rng.gen_range(0, u32::MAX))
+        .take(n)
+        .for_each(|x| {
+            heap.push(pool, x, ());
+        });
+    heap
+}
+
+fn bench_append<H: AddressableHeap<u32, ()>>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(format!("Append/{}", name));
+    let mut rng = StdRng::seed_from_u64(42);
+    for &n in SIZES.iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter_batched_ref(
+                || {
+                    let mut pool = H::Pool::default();
+                    let heap = gen_heap::<H>(&mut pool, n, &mut rng);
+                    let other = gen_heap::<H>(&mut pool, n, &mut rng);
+                    (pool, heap, other)
+                },
+                |(pool, heap, other)| heap.append(pool, other),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+fn bench_pop<H: AddressableHeap<u32, ()>>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(format!("Push and pop all/{}", name));
+    let mut rng = StdRng::seed_from_u64(42);
+    for &n in SIZES.iter() {
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter_batched_ref(
+                || {
+                    let mut pool = H::Pool::default();
+                    let heap = gen_heap::<H>(&mut pool, n, &mut rng);
+                    (pool, heap)
+                },
+                |(pool, heap)| while heap.pop(pool).is_some() {},
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+// Dijkstra-like: every `pop` is followed by a few `decrease_key`s on the remaining elements.
+fn bench_decrease_key<H: AddressableHeap<u32, ()>>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(format!("Pop and decrease key/{}", name));
+    let mut rng = StdRng::seed_from_u64(42);
+    for &n in SIZES.iter() {
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter_batched_ref(
+                || {
+                    let mut pool = H::Pool::default();
+                    let mut heap = H::default();
+                    let handles = (0..n)
+                        .map(|_| heap.push(&mut pool, rng.gen_range(u32::MAX / 2, u32::MAX), ()))
+                        .collect::<Vec<_>>();
+                    let queries = repeat_with(|| rng.gen_range(0, n))
+                        .take(4 * n)
+                        .collect::<Vec<_>>();
+                    (pool, heap, handles, queries)
+                },
+                |(pool, heap, handles, queries)| {
+                    let mut key = u32::MAX / 2;
+                    for chunk in queries.chunks(4) {
+                        heap.pop(pool);
+                        // The handles of the popped elements are rejected, which is fine here.
+                        for &i in chunk {
+                            key -= 1;
+                            let _ = heap.decrease_key(pool, &handles[i], key);
+                        }
+                    }
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+fn bench_all<H: AddressableHeap<u32, ()>>(c: &mut Criterion, name: &str) {
+    bench_append::<H>(c, name);
+    bench_pop::<H>(c, name);
+    bench_decrease_key::<H>(c, name);
+}
+
+fn bench_fibonacci_heap(c: &mut Criterion) {
+    bench_all::<FibonacciHeap<u32, ()>>(c, "Fibonacci");
+}
+fn bench_pairing_heap(c: &mut Criterion) {
+    bench_all::<PairingHeap<u32, ()>>(c, "Pairing");
+}
+fn bench_binomial_heap(c: &mut Criterion) {
+    bench_all::<BinomialHeap<u32, ()>>(c, "Binomial");
+}
+fn bench_leftist_heap(c: &mut Criterion) {
+    bench_all::<LeftistHeap<u32, ()>>(c, "Leftist");
+}
+
+criterion_group!(
+    benches,
+    bench_fibonacci_heap,
+    bench_pairing_heap,
+    bench_binomial_heap,
+    bench_leftist_heap
+);
+criterion_main!(benches);
@@ -0,0 +1,343 @@
+use {
+    super::{AddressableHeap, HandleError},
+    heap_arena::{Arena, Handle, HeapId, Heaps},
+    memory_usage::MemoryUsage,
+    std::{
+        marker::PhantomData,
+        mem::{replace, swap},
+    },
+};
+
+// A binomial heap in CLRS 2nd edition, chapter 19.
+//
+// `decrease_key` bubbles the elements up by exchanging them with their parents, so the handles
+// point to `entries`, which in turn remember the nodes that hold the elements. The nodes are in a
+// `Pool` shared by the heaps that may be appended to each other.
+#[derive(Debug)]
+pub struct BinomialHeap<K, V> {
+    id: HeapId,
+    len: usize,
+    // the root list in the increasing order of the degrees
+    head: Option<usize>,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+impl<K: Ord, V> BinomialHeap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            id: HeapId::new(),
+            len: 0,
+            head: None,
+            marker: PhantomData,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    // O(lg n)
+    pub fn push(&mut self, pool: &mut Pool<K, V>, key: K, value: V) -> Handle {
+        let entry = pool.entries.insert(0);
+        let x = pool.nodes.insert(Node {
+            key,
+            value,
+            heap: self.id,
+            entry,
+            degree: 0,
+            parent: None,
+            child: None,
+            sibling: None,
+        });
+        pool.entries[entry] = x;
+        pool.heaps.join(self.id);
+        self.head = pool.union(self.head, Some(x));
+        self.len += 1;
+        pool.entries.handle(self.id, entry)
+    }
+    // O(lg n + lg h) for the h heaps appended so far, which only link their ids
+    pub fn append(&mut self, pool: &mut Pool<K, V>, other: &mut Self) {
+        self.head = pool.union(self.head, other.head.take());
+        self.id = pool.heaps.union(self.id, other.id);
+        other.id = HeapId::new();
+        self.len += other.len;
+        other.len = 0;
+    }
+    // O(lg n)
+    pub fn peek<'a>(&self, pool: &'a Pool<K, V>) -> Option<&'a K> {
+        pool.min(self.head).map(|(_, x)| &pool.nodes[x].key)
+    }
+    // O(lg n)
+    pub fn pop(&mut self, pool: &mut Pool<K, V>) -> Option<(K, V)> {
+        let (prev, z) = pool.min(self.head)?;
+        self.len -= 1;
+        let Node {
+            key,
+            value,
+            heap,
+            entry,
+            child,
+            sibling,
+            ..
+        } = pool.nodes.remove(z);
+        pool.entries.remove(entry);
+        pool.heaps.leave(heap);
+        match prev {
+            None => self.head = sibling,
+            Some(prev) => pool.nodes[prev].sibling = sibling,
+        }
+        // The children are in the decreasing order of the degrees.
+        let mut reversed = None;
+        let mut x = child;
+        while let Some(y) = x {
+            x = replace(&mut pool.nodes[y].sibling, reversed);
+            pool.nodes[y].parent = None;
+            reversed = Some(y);
+        }
+        self.head = pool.union(self.head, reversed);
+        Some((key, value))
+    }
+    pub fn is_live(&self, pool: &Pool<K, V>, handle: &Handle) -> bool {
+        pool.entries.resolve(&pool.heaps, self.id, handle).is_ok()
+    }
+    pub fn get<'a>(
+        &self,
+        pool: &'a Pool<K, V>,
+        handle: &Handle,
+    ) -> Result<(&'a K, &'a V), HandleError> {
+        let node = &pool.nodes[pool.entries[pool.entries.resolve(&pool.heaps, self.id, handle)?]];
+        Ok((&node.key, &node.value))
+    }
+    // O(lg n)
+    pub fn decrease_key(
+        &mut self,
+        pool: &mut Pool<K, V>,
+        handle: &Handle,
+        key: K,
+    ) -> Result<(), HandleError> {
+        let mut x = pool.entries[pool.entries.resolve(&pool.heaps, self.id, handle)?];
+        if key > pool.nodes[x].key {
+            return Err(HandleError::KeyIncreased);
+        }
+        pool.nodes[x].key = key;
+        while let Some(p) = pool.nodes[x].parent {
+            if pool.nodes[p].key <= pool.nodes[x].key {
+                break;
+            }
+            pool.exchange(x, p);
+            x = p;
+        }
+        Ok(())
+    }
+}
+impl<K: Ord, V> Default for BinomialHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The nodes of the binomial heaps, the entries which the handles point to, and the ids of the
+// heaps that hold them.
+#[derive(Debug)]
+pub struct Pool<K, V> {
+    nodes: Arena<Node<K, V>>,
+    entries: Arena<usize>,
+    heaps: Heaps,
+}
+impl<K, V> Pool<K, V> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Arena::new(),
+            entries: Arena::new(),
+            heaps: Heaps::new(),
+        }
+    }
+}
+impl<K, V> Default for Pool<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<K: Ord, V> Pool<K, V> {
+    // (the root before the minimum, the minimum)
+    fn min(&self, head: Option<usize>) -> Option<(Option<usize>, usize)> {
+        let mut result = (None, head?);
+        let mut prev = result.1;
+        while let Some(x) = self.nodes[prev].sibling {
+            if self.nodes[x].key < self.nodes[result.1].key {
+                result = (Some(prev), x);
+            }
+            prev = x;
+        }
+        Some(result)
+    }
+    // Exchange the elements of `x` and `y` leaving the shape as it is.
+    fn exchange(&mut self, x: usize, y: usize) {
+        let (a, b) = self.nodes.pair_mut(x, y);
+        swap(&mut a.key, &mut b.key);
+        swap(&mut a.value, &mut b.value);
+        swap(&mut a.heap, &mut b.heap);
+        swap(&mut a.entry, &mut b.entry);
+        let (a, b) = (a.entry, b.entry);
+        self.entries[a] = x;
+        self.entries[b] = y;
+    }
+    // Make `y` the leftmost child of `z`.
+    fn link(&mut self, y: usize, z: usize) {
+        self.nodes[y].parent = Some(z);
+        self.nodes[y].sibling = self.nodes[z].child.replace(y);
+        self.nodes[z].degree += 1;
+    }
+    // Merge the root lists `x` and `y` sorted by the degrees.
+    fn merge(&mut self, mut x: Option<usize>, mut y: Option<usize>) -> Option<usize> {
+        let mut head = None;
+        let mut tail: Option<usize> = None;
+        while let (Some(a), Some(b)) = (x, y) {
+            let z = if self.nodes[a].degree <= self.nodes[b].degree {
+                x = self.nodes[a].sibling;
+                a
+            } else {
+                y = self.nodes[b].sibling;
+                b
+            };
+            match tail {
+                None => head = Some(z),
+                Some(tail) => self.nodes[tail].sibling = Some(z),
+            }
+            tail = Some(z);
+        }
+        match tail {
+            None => x.or(y),
+            Some(tail) => {
+                self.nodes[tail].sibling = x.or(y);
+                head
+            }
+        }
+    }
+    // Merge the root lists `x` and `y`, and link the roots of the same degrees. Return the head.
+    fn union(&mut self, x: Option<usize>, y: Option<usize>) -> Option<usize> {
+        let mut head = self.merge(x, y);
+        let mut prev: Option<usize> = None;
+        let mut x = head?;
+        while let Some(next) = self.nodes[x].sibling {
+            let degree = self.nodes[x].degree;
+            if degree != self.nodes[next].degree
+                || self.nodes[next]
+                    .sibling
+                    .is_some_and(|y| self.nodes[y].degree == degree)
+            {
+                prev = Some(x);
+                x = next;
+            } else if self.nodes[x].key <= self.nodes[next].key {
+                self.nodes[x].sibling = self.nodes[next].sibling;
+                self.link(next, x);
+            } else {
+                match prev {
+                    None => head = Some(next),
+                    Some(prev) => self.nodes[prev].sibling = Some(next),
+                }
+                self.link(x, next);
+                x = next;
+            }
+        }
+        head
+    }
+}
+impl<K: Ord, V> AddressableHeap<K, V> for BinomialHeap<K, V> {
+    type Handle = Handle;
+    type Pool = Pool<K, V>;
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn push(&mut self, pool: &mut Pool<K, V>, key: K, value: V) -> Handle {
+        self.push(pool, key, value)
+    }
+    fn peek<'a>(&self, pool: &'a Pool<K, V>) -> Option<&'a K> {
+        self.peek(pool)
+    }
+    fn pop(&mut self, pool: &mut Pool<K, V>) -> Option<(K, V)> {
+        self.pop(pool)
+    }
+    fn append(&mut self, pool: &mut Pool<K, V>, other: &mut Self) {
+        self.append(pool, other)
+    }
+    fn is_live(&self, pool: &Pool<K, V>, handle: &Handle) -> bool {
+        self.is_live(pool, handle)
+    }
+    fn decrease_key(
+        &mut self,
+        pool: &mut Pool<K, V>,
+        handle: &Handle,
+        key: K,
+    ) -> Result<(), HandleError> {
+        self.decrease_key(pool, handle, key)
+    }
+}
+
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Pool<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.nodes.heap_size_bytes() + self.entries.heap_size_bytes() + self.heaps.heap_size_bytes()
+    }
+}
+
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // the heap which the element was pushed to
+    heap: HeapId,
+    entry: usize,
+    degree: usize,
+    parent: Option<usize>,
+    // the leftmost child, whose degree is the largest
+    child: Option<usize>,
+    sibling: Option<usize>,
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Node<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.key.heap_size_bytes() + self.value.heap_size_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{BinomialHeap, Pool},
+        crate::tests::Validate,
+    };
+
+    impl Validate for BinomialHeap<u32, usize> {
+        fn validate(&self, pool: &Pool<u32, usize>) {
+            let mut count = 0;
+            let mut degree = None;
+            let mut x = self.head;
+            while let Some(y) = x {
+                assert_eq!(pool.nodes[y].parent, None);
+                assert!(degree < Some(pool.nodes[y].degree));
+                degree = Some(pool.nodes[y].degree);
+                self.validate_subtree(pool, y, &mut count);
+                x = pool.nodes[y].sibling;
+            }
+            assert_eq!(count, self.len);
+        }
+    }
+    impl BinomialHeap<u32, usize> {
+        // The children of a node of degree k have the degrees k - 1, k - 2, ..., 0.
+        fn validate_subtree(&self, pool: &Pool<u32, usize>, x: usize, count: &mut usize) {
+            *count += 1;
+            assert_eq!(pool.entries[pool.nodes[x].entry], x);
+            assert_eq!(pool.heaps.find(pool.nodes[x].heap), self.id);
+            let mut degree = pool.nodes[x].degree;
+            let mut child = pool.nodes[x].child;
+            while let Some(y) = child {
+                assert_eq!(pool.nodes[y].parent, Some(x));
+                assert!(pool.nodes[x].key <= pool.nodes[y].key);
+                degree -= 1;
+                assert_eq!(pool.nodes[y].degree, degree);
+                self.validate_subtree(pool, y, count);
+                child = pool.nodes[y].sibling;
+            }
+            assert_eq!(degree, 0);
+        }
+    }
+}
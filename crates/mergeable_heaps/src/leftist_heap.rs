@@ -0,0 +1,278 @@
+use {
+    super::{AddressableHeap, HandleError},
+    heap_arena::{Arena, Handle, HeapId, Heaps},
+    memory_usage::MemoryUsage,
+    std::{marker::PhantomData, mem::swap},
+};
+
+// A leftist heap: the rank of a right child, i.e. the length of its right spine, is at most that
+// of its left sibling, so every right spine has O(lg n) nodes.
+//
+// `decrease_key` cuts the node out and melds it with the root, and then repairs the ranks of the
+// ancestors of the cut, which also takes O(lg n). The nodes are in a `Pool` shared by the heaps that
+// may be appended to each other.
+#[derive(Debug)]
+pub struct LeftistHeap<K, V> {
+    id: HeapId,
+    len: usize,
+    root: Option<usize>,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+impl<K: Ord, V> LeftistHeap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            id: HeapId::new(),
+            len: 0,
+            root: None,
+            marker: PhantomData,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    // O(lg n)
+    pub fn push(&mut self, pool: &mut Pool<K, V>, key: K, value: V) -> Handle {
+        let x = pool.nodes.insert(Node {
+            key,
+            value,
+            heap: self.id,
+            rank: 1,
+            parent: None,
+            left: None,
+            right: None,
+        });
+        pool.heaps.join(self.id);
+        self.root = pool.meld(self.root, Some(x));
+        self.len += 1;
+        pool.nodes.handle(self.id, x)
+    }
+    // O(lg n + lg h) for the h heaps appended so far, which only link their ids
+    pub fn append(&mut self, pool: &mut Pool<K, V>, other: &mut Self) {
+        self.root = pool.meld(self.root, other.root.take());
+        self.id = pool.heaps.union(self.id, other.id);
+        other.id = HeapId::new();
+        self.len += other.len;
+        other.len = 0;
+    }
+    pub fn peek<'a>(&self, pool: &'a Pool<K, V>) -> Option<&'a K> {
+        self.root.map(|x| &pool.nodes[x].key)
+    }
+    // O(lg n)
+    pub fn pop(&mut self, pool: &mut Pool<K, V>) -> Option<(K, V)> {
+        let z = self.root?;
+        self.len -= 1;
+        let Node {
+            key,
+            value,
+            heap,
+            left,
+            right,
+            ..
+        } = pool.nodes.remove(z);
+        pool.heaps.leave(heap);
+        for &x in left.iter().chain(right.iter()) {
+            pool.nodes[x].parent = None;
+        }
+        self.root = pool.meld(left, right);
+        Some((key, value))
+    }
+    pub fn is_live(&self, pool: &Pool<K, V>, handle: &Handle) -> bool {
+        pool.nodes.resolve(&pool.heaps, self.id, handle).is_ok()
+    }
+    pub fn get<'a>(
+        &self,
+        pool: &'a Pool<K, V>,
+        handle: &Handle,
+    ) -> Result<(&'a K, &'a V), HandleError> {
+        let node = &pool.nodes[pool.nodes.resolve(&pool.heaps, self.id, handle)?];
+        Ok((&node.key, &node.value))
+    }
+    // O(lg n)
+    pub fn decrease_key(
+        &mut self,
+        pool: &mut Pool<K, V>,
+        handle: &Handle,
+        key: K,
+    ) -> Result<(), HandleError> {
+        let x = pool.nodes.resolve(&pool.heaps, self.id, handle)?;
+        if key > pool.nodes[x].key {
+            return Err(HandleError::KeyIncreased);
+        }
+        pool.nodes[x].key = key;
+        if let Some(p) = pool.nodes[x].parent {
+            if pool.nodes[x].key < pool.nodes[p].key {
+                pool.cut(x, p);
+                self.root = pool.meld(self.root, Some(x));
+            }
+        }
+        Ok(())
+    }
+}
+impl<K: Ord, V> Default for LeftistHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The nodes of the leftist heaps, and the ids of the heaps that hold them.
+#[derive(Debug)]
+pub struct Pool<K, V> {
+    nodes: Arena<Node<K, V>>,
+    heaps: Heaps,
+}
+impl<K, V> Pool<K, V> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Arena::new(),
+            heaps: Heaps::new(),
+        }
+    }
+}
+impl<K, V> Default for Pool<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<K: Ord, V> Pool<K, V> {
+    fn rank(&self, x: Option<usize>) -> usize {
+        x.map_or(0, |x| self.nodes[x].rank)
+    }
+    // Swap the children of `x` if needed and recompute the rank.
+    fn update(&mut self, x: usize) {
+        if self.rank(self.nodes[x].left) < self.rank(self.nodes[x].right) {
+            let node = &mut self.nodes[x];
+            swap(&mut node.left, &mut node.right);
+        }
+        self.nodes[x].rank = 1 + self.rank(self.nodes[x].right);
+    }
+    // Meld two trees along their right spines.
+    fn meld(&mut self, x: Option<usize>, y: Option<usize>) -> Option<usize> {
+        let (mut x, mut y) = match (x, y) {
+            (None, z) | (z, None) => return z,
+            (Some(x), Some(y)) => (x, y),
+        };
+        if self.nodes[y].key < self.nodes[x].key {
+            swap(&mut x, &mut y);
+        }
+        let right = self.nodes[x].right;
+        let right = self.meld(right, Some(y));
+        self.nodes[x].right = right;
+        if let Some(right) = right {
+            self.nodes[right].parent = Some(x);
+        }
+        self.update(x);
+        Some(x)
+    }
+    // Cut the subtree of `x` out of its parent `p`.
+    fn cut(&mut self, x: usize, p: usize) {
+        self.nodes[x].parent = None;
+        if self.nodes[p].left == Some(x) {
+            self.nodes[p].left = self.nodes[p].right.take();
+        } else {
+            self.nodes[p].right = None;
+        }
+        // The rank can only shrink, and stops changing at the first ancestor that keeps it.
+        let mut y = p;
+        loop {
+            let rank = self.nodes[y].rank;
+            self.update(y);
+            if self.nodes[y].rank == rank {
+                break;
+            }
+            match self.nodes[y].parent {
+                None => break,
+                Some(parent) => y = parent,
+            }
+        }
+    }
+}
+impl<K: Ord, V> AddressableHeap<K, V> for LeftistHeap<K, V> {
+    type Handle = Handle;
+    type Pool = Pool<K, V>;
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn push(&mut self, pool: &mut Pool<K, V>, key: K, value: V) -> Handle {
+        self.push(pool, key, value)
+    }
+    fn peek<'a>(&self, pool: &'a Pool<K, V>) -> Option<&'a K> {
+        self.peek(pool)
+    }
+    fn pop(&mut self, pool: &mut Pool<K, V>) -> Option<(K, V)> {
+        self.pop(pool)
+    }
+    fn append(&mut self, pool: &mut Pool<K, V>, other: &mut Self) {
+        self.append(pool, other)
+    }
+    fn is_live(&self, pool: &Pool<K, V>, handle: &Handle) -> bool {
+        self.is_live(pool, handle)
+    }
+    fn decrease_key(
+        &mut self,
+        pool: &mut Pool<K, V>,
+        handle: &Handle,
+        key: K,
+    ) -> Result<(), HandleError> {
+        self.decrease_key(pool, handle, key)
+    }
+}
+
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Pool<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.nodes.heap_size_bytes() + self.heaps.heap_size_bytes()
+    }
+}
+
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // the heap which the node was pushed to
+    heap: HeapId,
+    // the number of the nodes on the right spine
+    rank: usize,
+    parent: Option<usize>,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Node<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.key.heap_size_bytes() + self.value.heap_size_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{LeftistHeap, Pool},
+        crate::tests::Validate,
+    };
+
+    impl Validate for LeftistHeap<u32, usize> {
+        fn validate(&self, pool: &Pool<u32, usize>) {
+            let mut count = 0;
+            if let Some(root) = self.root {
+                assert_eq!(pool.nodes[root].parent, None);
+                self.validate_subtree(pool, root, &mut count);
+            }
+            assert_eq!(count, self.len);
+        }
+    }
+    impl LeftistHeap<u32, usize> {
+        fn validate_subtree(&self, pool: &Pool<u32, usize>, x: usize, count: &mut usize) {
+            *count += 1;
+            let node = &pool.nodes[x];
+            assert_eq!(pool.heaps.find(node.heap), self.id);
+            for &y in node.left.iter().chain(node.right.iter()) {
+                assert_eq!(pool.nodes[y].parent, Some(x));
+                assert!(node.key <= pool.nodes[y].key);
+                self.validate_subtree(pool, y, count);
+            }
+            assert!(pool.rank(node.left) >= pool.rank(node.right));
+            assert_eq!(node.rank, 1 + pool.rank(node.right));
+        }
+    }
+}
@@ -0,0 +1,352 @@
+pub mod binomial_heap;
+pub mod leftist_heap;
+pub mod pairing_heap;
+
+pub use {
+    binomial_heap::BinomialHeap,
+    heap_arena::{Handle, HandleError},
+    leftist_heap::LeftistHeap,
+    pairing_heap::PairingHeap,
+};
+
+use {fibonacci_heap::arena::FibonacciHeap, std::fmt::Debug};
+
+// The operations shared by the min-heaps whose elements can be addressed through the handles
+// returned by `push`.
+//
+// The nodes are in a `Pool`, which every operation borrows, so that `append` links the roots of
+// two heaps sharing it without moving the nodes. A handle stays valid across `append`, and becomes
+// `HandleError::Removed` once its element is popped.
+pub trait AddressableHeap<K, V>: Default {
+    type Handle;
+    type Pool: Default;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn push(&mut self, pool: &mut Self::Pool, key: K, value: V) -> Self::Handle;
+    fn peek<'a>(&self, pool: &'a Self::Pool) -> Option<&'a K>;
+    fn pop(&mut self, pool: &mut Self::Pool) -> Option<(K, V)>;
+    // Move all the elements of `other` into `self`, leaving `other` empty.
+    fn append(&mut self, pool: &mut Self::Pool, other: &mut Self);
+    fn is_live(&self, pool: &Self::Pool, handle: &Self::Handle) -> bool;
+    fn decrease_key(
+        &mut self,
+        pool: &mut Self::Pool,
+        handle: &Self::Handle,
+        key: K,
+    ) -> Result<(), HandleError>;
+}
+
+impl<K: Ord + Debug, V: Debug> AddressableHeap<K, V> for FibonacciHeap<K, V> {
+    type Handle = fibonacci_heap::arena::Handle;
+    type Pool = fibonacci_heap::arena::Pool<K, V>;
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn push(&mut self, pool: &mut Self::Pool, key: K, value: V) -> Self::Handle {
+        self.push(pool, key, value)
+    }
+    fn peek<'a>(&self, pool: &'a Self::Pool) -> Option<&'a K> {
+        self.peek(pool)
+    }
+    fn pop(&mut self, pool: &mut Self::Pool) -> Option<(K, V)> {
+        self.pop(pool)
+    }
+    fn append(&mut self, pool: &mut Self::Pool, other: &mut Self) {
+        self.append(pool, other)
+    }
+    fn is_live(&self, pool: &Self::Pool, handle: &Self::Handle) -> bool {
+        self.is_live(pool, handle)
+    }
+    fn decrease_key(
+        &mut self,
+        pool: &mut Self::Pool,
+        handle: &Self::Handle,
+        key: K,
+    ) -> Result<(), HandleError> {
+        self.decrease_key(pool, handle, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            AddressableHeap, BinomialHeap, FibonacciHeap, HandleError, LeftistHeap, PairingHeap,
+        },
+        memory_usage::MemoryUsage,
+        rand::prelude::*,
+        std::fmt::Debug,
+        yansi::Paint,
+    };
+
+    #[test]
+    fn test_rand_fibonacci_heap() {
+        test_rand::<FibonacciHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_rand_pairing_heap() {
+        test_rand::<PairingHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_rand_binomial_heap() {
+        test_rand::<BinomialHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_rand_leftist_heap() {
+        test_rand::<LeftistHeap<u32, usize>>();
+    }
+
+    #[test]
+    fn test_handle_misuse_fibonacci_heap() {
+        test_handle_misuse::<FibonacciHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_handle_misuse_pairing_heap() {
+        test_handle_misuse::<PairingHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_handle_misuse_binomial_heap() {
+        test_handle_misuse::<BinomialHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_handle_misuse_leftist_heap() {
+        test_handle_misuse::<LeftistHeap<u32, usize>>();
+    }
+
+    fn test_handle_misuse<H: AddressableHeap<u32, usize>>() {
+        let mut pool = H::Pool::default();
+        let mut heap = H::default();
+        let mut other = H::default();
+        let x = heap.push(&mut pool, 10, 0);
+        let y = other.push(&mut pool, 20, 1);
+        assert_eq!(
+            heap.decrease_key(&mut pool, &y, 0),
+            Err(HandleError::ForeignHeap)
+        );
+        assert_eq!(
+            heap.decrease_key(&mut pool, &x, 11),
+            Err(HandleError::KeyIncreased)
+        );
+        heap.append(&mut pool, &mut other);
+        assert!(heap.is_live(&pool, &y));
+        assert!(!other.is_live(&pool, &y));
+        assert_eq!(heap.decrease_key(&mut pool, &y, 5), Ok(()));
+        assert_eq!(heap.pop(&mut pool), Some((5, 1)));
+        assert!(!heap.is_live(&pool, &y));
+        assert_eq!(
+            heap.decrease_key(&mut pool, &y, 0),
+            Err(HandleError::Removed)
+        );
+        assert_eq!(heap.pop(&mut pool), Some((10, 0)));
+        assert_eq!(heap.pop(&mut pool), None);
+    }
+
+    #[test]
+    fn test_heap_size_bytes_fibonacci_heap() {
+        test_heap_size_bytes::<FibonacciHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_heap_size_bytes_pairing_heap() {
+        test_heap_size_bytes::<PairingHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_heap_size_bytes_binomial_heap() {
+        test_heap_size_bytes::<BinomialHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_heap_size_bytes_leftist_heap() {
+        test_heap_size_bytes::<LeftistHeap<u32, usize>>();
+    }
+
+    fn test_heap_size_bytes<H>()
+    where
+        H: AddressableHeap<u32, usize>,
+        H::Pool: MemoryUsage,
+    {
+        let mut pool = H::Pool::default();
+        let mut heap = H::default();
+        assert_eq!(pool.heap_size_bytes(), 0);
+        (0..10).for_each(|i| {
+            heap.push(&mut pool, i, 0);
+        });
+        while heap.pop(&mut pool).is_some() {}
+        // The slots are kept, and reused.
+        let bytes = pool.heap_size_bytes();
+        assert!(bytes > 0);
+        (0..10).for_each(|i| {
+            heap.push(&mut pool, i, 0);
+        });
+        assert_eq!(pool.heap_size_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_append_many_fibonacci_heap() {
+        test_append_many::<FibonacciHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_append_many_pairing_heap() {
+        test_append_many::<PairingHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_append_many_binomial_heap() {
+        test_append_many::<BinomialHeap<u32, usize>>();
+    }
+    #[test]
+    fn test_append_many_leftist_heap() {
+        test_append_many::<LeftistHeap<u32, usize>>();
+    }
+
+    // The ids of the heaps appended are forgotten along with their elements, so the pool does not
+    // grow in the second round.
+    fn test_append_many<H>()
+    where
+        H: AddressableHeap<u32, usize>,
+        H::Pool: MemoryUsage,
+    {
+        let mut pool = H::Pool::default();
+        let mut heap = H::default();
+        let mut bytes = Vec::new();
+        for _ in 0..2 {
+            let handles = (0..1000)
+                .map(|i| {
+                    let mut other = H::default();
+                    let handle = other.push(&mut pool, i, 0);
+                    heap.append(&mut pool, &mut other);
+                    handle
+                })
+                .collect::<Vec<_>>();
+            assert!(handles.iter().all(|handle| heap.is_live(&pool, handle)));
+            for i in 0..1000 {
+                assert_eq!(heap.pop(&mut pool), Some((i, 0)));
+            }
+            bytes.push(pool.heap_size_bytes());
+        }
+        assert_eq!(bytes[0], bytes[1]);
+    }
+
+    // Checks the shape of a heap apart from the results of the operations.
+    pub trait Validate: AddressableHeap<u32, usize> {
+        fn validate(&self, pool: &Self::Pool);
+    }
+    impl Validate for FibonacciHeap<u32, usize> {
+        // tested in `fibonacci_heap`
+        fn validate(&self, _pool: &Self::Pool) {}
+    }
+
+    fn test_rand<H>()
+    where
+        H: Validate + Debug,
+        H::Handle: Debug,
+        H::Pool: Debug,
+    {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let mut test = Test::<H>::new();
+            for _ in 0..rng.gen_range(1, 200) {
+                match rng.gen_range(0, 8) {
+                    0..=2 => test.push(rng.gen_range(0, 1000)),
+                    3 | 4 => test.pop(),
+                    5 | 6 if !test.handles.is_empty() => {
+                        let i = rng.gen_range(0, test.handles.len());
+                        let key = test.keys[i].map_or(1000, |key| rng.gen_range(0, key + 1));
+                        test.decrease_key(i, key);
+                    }
+                    7 => {
+                        let keys = (0..rng.gen_range(0, 20))
+                            .map(|_| rng.gen_range(0, 1000))
+                            .collect::<Vec<_>>();
+                        test.append(&keys);
+                    }
+                    _ => (),
+                }
+            }
+            while !test.heap.is_empty() {
+                test.pop();
+            }
+        }
+    }
+
+    // Checks a heap against the keys of its elements, indexed by the value pushed with them.
+    struct Test<H: AddressableHeap<u32, usize>> {
+        pool: H::Pool,
+        heap: H,
+        handles: Vec<H::Handle>,
+        keys: Vec<Option<u32>>,
+    }
+    impl<H> Test<H>
+    where
+        H: Validate + Debug,
+        H::Handle: Debug,
+        H::Pool: Debug,
+    {
+        fn new() -> Self {
+            Self {
+                pool: H::Pool::default(),
+                heap: H::default(),
+                handles: Vec::new(),
+                keys: Vec::new(),
+            }
+        }
+        fn push(&mut self, key: u32) {
+            println!("{}", Paint::red(format!("Push {}", key)).bold());
+            self.handles
+                .push(self.heap.push(&mut self.pool, key, self.keys.len()));
+            self.keys.push(Some(key));
+            self.postprocess();
+        }
+        fn pop(&mut self) {
+            println!("{}", Paint::blue("Pop").bold());
+            let expected = self.keys.iter().filter_map(|&key| key).min();
+            let result = self.heap.pop(&mut self.pool);
+            assert_eq!(result.map(|(key, _)| key), expected);
+            if let Some((key, i)) = result {
+                assert_eq!(self.keys[i], Some(key));
+                self.keys[i] = None;
+                assert!(!self.heap.is_live(&self.pool, &self.handles[i]));
+            }
+            self.postprocess();
+        }
+        fn decrease_key(&mut self, i: usize, key: u32) {
+            println!(
+                "{}",
+                Paint::yellow(format!("Decrease key {:?} to {}", &self.handles[i], key)).bold()
+            );
+            let result = self
+                .heap
+                .decrease_key(&mut self.pool, &self.handles[i], key);
+            match self.keys[i] {
+                Some(_) => {
+                    assert_eq!(result, Ok(()));
+                    self.keys[i] = Some(key);
+                }
+                None => assert_eq!(result, Err(HandleError::Removed)),
+            }
+            self.postprocess();
+        }
+        fn append(&mut self, keys: &[u32]) {
+            println!("{}", Paint::green(format!("Append {:?}", keys)).bold());
+            let mut other = H::default();
+            for &key in keys {
+                self.handles
+                    .push(other.push(&mut self.pool, key, self.keys.len()));
+                self.keys.push(Some(key));
+            }
+            self.heap.append(&mut self.pool, &mut other);
+            assert!(other.is_empty());
+            self.postprocess();
+        }
+        fn postprocess(&self) {
+            println!("heap = {:?}", &self.heap);
+            println!("pool = {:?}", &self.pool);
+            self.heap.validate(&self.pool);
+            let live = self.keys.iter().filter_map(|&key| key).collect::<Vec<_>>();
+            assert_eq!(self.heap.len(), live.len());
+            assert_eq!(self.heap.peek(&self.pool), live.iter().min());
+            for (handle, key) in self.handles.iter().zip(self.keys.iter()) {
+                assert_eq!(self.heap.is_live(&self.pool, handle), key.is_some());
+            }
+        }
+    }
+}
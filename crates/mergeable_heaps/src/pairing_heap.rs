@@ -0,0 +1,277 @@
+use {
+    super::{AddressableHeap, HandleError},
+    heap_arena::{Arena, Handle, HeapId, Heaps},
+    memory_usage::MemoryUsage,
+    std::{marker::PhantomData, mem::swap},
+};
+
+// A pairing heap with the two-pass pop.
+//
+// The children of a node form a doubly-linked list, and `prev` of the leftmost child points to
+// the parent so that a node can be cut out in O(1). The nodes are in a `Pool` shared by the heaps
+// that may be appended to each other.
+#[derive(Debug)]
+pub struct PairingHeap<K, V> {
+    id: HeapId,
+    len: usize,
+    root: Option<usize>,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+impl<K: Ord, V> PairingHeap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            id: HeapId::new(),
+            len: 0,
+            root: None,
+            marker: PhantomData,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    // O(1)
+    pub fn push(&mut self, pool: &mut Pool<K, V>, key: K, value: V) -> Handle {
+        let x = pool.nodes.insert(Node {
+            key,
+            value,
+            heap: self.id,
+            child: None,
+            prev: None,
+            next: None,
+        });
+        pool.heaps.join(self.id);
+        self.root = Some(pool.meld(self.root, x));
+        self.len += 1;
+        pool.nodes.handle(self.id, x)
+    }
+    // O(lg h) for the h heaps appended so far, which only link their ids
+    pub fn append(&mut self, pool: &mut Pool<K, V>, other: &mut Self) {
+        if let Some(root) = other.root.take() {
+            self.root = Some(pool.meld(self.root, root));
+        }
+        self.id = pool.heaps.union(self.id, other.id);
+        other.id = HeapId::new();
+        self.len += other.len;
+        other.len = 0;
+    }
+    pub fn peek<'a>(&self, pool: &'a Pool<K, V>) -> Option<&'a K> {
+        self.root.map(|x| &pool.nodes[x].key)
+    }
+    // amortized O(lg n)
+    pub fn pop(&mut self, pool: &mut Pool<K, V>) -> Option<(K, V)> {
+        let z = self.root.take()?;
+        self.len -= 1;
+        let Node {
+            key,
+            value,
+            heap,
+            child,
+            ..
+        } = pool.nodes.remove(z);
+        pool.heaps.leave(heap);
+        if let Some(child) = child {
+            pool.nodes[child].prev = None;
+        }
+        // First pass: meld the children in pairs from left to right.
+        let mut pairs = Vec::new();
+        let mut x = child;
+        while let Some(a) = x {
+            let b = pool.nodes[a].next;
+            x = b.and_then(|b| pool.nodes[b].next);
+            pool.detach(a);
+            pairs.push(match b {
+                Some(b) => {
+                    pool.detach(b);
+                    pool.meld(Some(a), b)
+                }
+                None => a,
+            });
+        }
+        // Second pass: meld the pairs from right to left.
+        self.root = pairs
+            .into_iter()
+            .rev()
+            .fold(None, |acc, x| Some(pool.meld(acc, x)));
+        Some((key, value))
+    }
+    pub fn is_live(&self, pool: &Pool<K, V>, handle: &Handle) -> bool {
+        pool.nodes.resolve(&pool.heaps, self.id, handle).is_ok()
+    }
+    pub fn get<'a>(
+        &self,
+        pool: &'a Pool<K, V>,
+        handle: &Handle,
+    ) -> Result<(&'a K, &'a V), HandleError> {
+        let node = &pool.nodes[pool.nodes.resolve(&pool.heaps, self.id, handle)?];
+        Ok((&node.key, &node.value))
+    }
+    // O(1), but it is believed to slow down the later `pop`s by o(lg n).
+    pub fn decrease_key(
+        &mut self,
+        pool: &mut Pool<K, V>,
+        handle: &Handle,
+        key: K,
+    ) -> Result<(), HandleError> {
+        let x = pool.nodes.resolve(&pool.heaps, self.id, handle)?;
+        if key > pool.nodes[x].key {
+            return Err(HandleError::KeyIncreased);
+        }
+        pool.nodes[x].key = key;
+        if self.root != Some(x) {
+            pool.detach(x);
+            self.root = Some(pool.meld(self.root, x));
+        }
+        Ok(())
+    }
+}
+impl<K: Ord, V> Default for PairingHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The nodes of the pairing heaps, and the ids of the heaps that hold them.
+#[derive(Debug)]
+pub struct Pool<K, V> {
+    nodes: Arena<Node<K, V>>,
+    heaps: Heaps,
+}
+impl<K, V> Pool<K, V> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Arena::new(),
+            heaps: Heaps::new(),
+        }
+    }
+}
+impl<K, V> Default for Pool<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<K: Ord, V> Pool<K, V> {
+    // Make the root with the larger key the leftmost child of the other, and return the new root.
+    fn meld(&mut self, x: Option<usize>, y: usize) -> usize {
+        let mut x = match x {
+            None => return y,
+            Some(x) => x,
+        };
+        let mut y = y;
+        if self.nodes[y].key < self.nodes[x].key {
+            swap(&mut x, &mut y);
+        }
+        let child = self.nodes[x].child.replace(y);
+        if let Some(child) = child {
+            self.nodes[child].prev = Some(y);
+        }
+        self.nodes[y].prev = Some(x);
+        self.nodes[y].next = child;
+        x
+    }
+    // Cut `x` with its subtree out of the list of its siblings.
+    fn detach(&mut self, x: usize) {
+        let prev = self.nodes[x].prev.take();
+        let next = self.nodes[x].next.take();
+        if let Some(p) = prev {
+            if self.nodes[p].child == Some(x) {
+                self.nodes[p].child = next;
+            } else {
+                self.nodes[p].next = next;
+            }
+        }
+        if let Some(next) = next {
+            self.nodes[next].prev = prev;
+        }
+    }
+}
+impl<K: Ord, V> AddressableHeap<K, V> for PairingHeap<K, V> {
+    type Handle = Handle;
+    type Pool = Pool<K, V>;
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn push(&mut self, pool: &mut Pool<K, V>, key: K, value: V) -> Handle {
+        self.push(pool, key, value)
+    }
+    fn peek<'a>(&self, pool: &'a Pool<K, V>) -> Option<&'a K> {
+        self.peek(pool)
+    }
+    fn pop(&mut self, pool: &mut Pool<K, V>) -> Option<(K, V)> {
+        self.pop(pool)
+    }
+    fn append(&mut self, pool: &mut Pool<K, V>, other: &mut Self) {
+        self.append(pool, other)
+    }
+    fn is_live(&self, pool: &Pool<K, V>, handle: &Handle) -> bool {
+        self.is_live(pool, handle)
+    }
+    fn decrease_key(
+        &mut self,
+        pool: &mut Pool<K, V>,
+        handle: &Handle,
+        key: K,
+    ) -> Result<(), HandleError> {
+        self.decrease_key(pool, handle, key)
+    }
+}
+
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Pool<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.nodes.heap_size_bytes() + self.heaps.heap_size_bytes()
+    }
+}
+
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // the heap which the node was pushed to
+    heap: HeapId,
+    child: Option<usize>,
+    // the left sibling, or the parent if this is the leftmost child
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Node<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.key.heap_size_bytes() + self.value.heap_size_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{PairingHeap, Pool},
+        crate::tests::Validate,
+    };
+
+    impl Validate for PairingHeap<u32, usize> {
+        fn validate(&self, pool: &Pool<u32, usize>) {
+            let mut count = 0;
+            if let Some(root) = self.root {
+                assert_eq!(pool.nodes[root].prev, None);
+                assert_eq!(pool.nodes[root].next, None);
+                self.validate_subtree(pool, root, &mut count);
+            }
+            assert_eq!(count, self.len);
+        }
+    }
+    impl PairingHeap<u32, usize> {
+        fn validate_subtree(&self, pool: &Pool<u32, usize>, x: usize, count: &mut usize) {
+            *count += 1;
+            assert_eq!(pool.heaps.find(pool.nodes[x].heap), self.id);
+            let mut prev = x;
+            let mut child = pool.nodes[x].child;
+            while let Some(y) = child {
+                assert_eq!(pool.nodes[y].prev, Some(prev));
+                assert!(pool.nodes[x].key <= pool.nodes[y].key);
+                self.validate_subtree(pool, y, count);
+                prev = y;
+                child = pool.nodes[y].next;
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 mod color;
+mod memory;
 mod paren;
 pub mod validate;
 
@@ -16,11 +17,25 @@ impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
         self.0.set_color(Color::Black);
         self.0.update();
     }
-    pub fn delete(&mut self, k: K) {
-        self.0.delete(k);
-        if let Some(me) = self.0.0.as_mut() {
-            me.color = Color::Black;
-            self.0.update();
+    pub fn delete(&mut self, k: K) -> bool {
+        let deleted = self.0.delete(k).is_some();
+        self.0.blacken();
+        deleted
+    }
+    // Moves the keys not less than `k` to the returned tree.
+    pub fn split_off(&mut self, k: &K) -> Self {
+        let (mut l, mut r) = self.0.take().split(k);
+        l.blacken();
+        r.blacken();
+        self.0 = l;
+        Self(r)
+    }
+    // The keys in `other` must not be less than the ones in `self`.
+    pub fn append(&mut self, other: &mut Self) {
+        if let Some((mid, _)) = other.0.delete_first() {
+            other.0.blacken();
+            self.0 = BoxedNode::join(self.0.take(), mid, other.0.take());
+            self.0.blacken();
         }
     }
     pub fn collect_vec(&self) -> Vec<(K, V)>
@@ -146,6 +161,11 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
     fn bh(&self) -> u32 {
         self.0.as_ref().map_or(0, |me| me.bh)
     }
+    fn blacken(&mut self) {
+        if self.0.is_some() {
+            self.set_color(Color::Black).update();
+        }
+    }
 
     // rb ops
     fn insert(&mut self, k: K, v: V) -> Option<DoubleRed> {
@@ -204,6 +224,7 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
                     rem.update();
                     (0..2).for_each(|i| rem.replace_empty_child(i, self.take_child(i)).finish());
                     let e = rem.delete_and_then(1, e);
+                    rem.update();
                     (self.replace(rem), e)
                 } else {
                     assert!(self.child(1).is_nil());
@@ -259,9 +280,18 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
                 lg!("foo");
                     self.assert_black().child(1 - i).child(0).assert_black();
                     self.assert_black().child(1 - i).child(1).assert_black();
-                    self.swap_color_rotate(1 - i);
-                    let e = self.child_mut(i).delete_fixup(i);
-                    self.delete_and_then(i, e)
+                    // `swap_color_rotate(1 - i)`, except that the old root is updated only after
+                    // the fixup, since its child `i` is still short.
+                    self.swap_color_with_child(1 - i);
+                    let mut x = self.take();
+                    let mut y = x.take_child(1 - i);
+                    x.replace_empty_child(1 - i, y.take_child(i));
+                    let e = x.delete_fixup(i);
+                    y.replace_empty_child(i, x);
+                    *self = y;
+                    let e = self.delete_and_then(i, e);
+                    self.update();
+                    e
                 }
                 Color::Black => match (
                     self.child(1 - i).child(i).color(),
@@ -291,6 +321,51 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
             },
         }
     }
+
+    // split and join
+    fn split(mut self, k: &K) -> (Self, Self) {
+        if self.is_nil() {
+            return (Self(None), Self(None));
+        }
+        let l = self.take_child(0);
+        let r = self.take_child(1);
+        if k <= &self.unwrap().key {
+            let (ll, lr) = l.split(k);
+            (ll, Self::join(lr, self, r))
+        } else {
+            let (rl, rr) = r.split(k);
+            (Self::join(l, self, rl), rr)
+        }
+    }
+    // Joins `l`, the isolated node `mid` and `r` in this order; the root may be red.
+    fn join(mut l: Self, mut mid: Self, mut r: Self) -> Self {
+        l.blacken();
+        r.blacken();
+        mid.set_color(Color::Red).update();
+        if l.bh() >= r.bh() {
+            l.join_descend(1, mid, r);
+            l
+        } else {
+            r.join_descend(0, mid, l);
+            r
+        }
+    }
+    // Hangs `other`, which is not higher than `self`, under `mid` on the spine `i` of `self`.
+    fn join_descend(&mut self, i: usize, mut mid: Self, other: Self) -> Option<DoubleRed> {
+        msg!("join_descend", (&self, i));
+        if self.is_black() && self.bh() == other.bh() {
+            let me = self.take();
+            mid.replace_empty_child(1 - i, me)
+                .replace_empty_child(i, other)
+                .update();
+            *self = mid;
+            Some(DoubleRed::Me)
+        } else {
+            let e = self.child_mut(i).join_descend(i, mid, other);
+            self.insert_and_then(i, e)
+        }
+    }
+
     fn collect_vec(&self, vec: &mut Vec<(K, V)>)
     where
         K: Clone,
@@ -321,8 +396,64 @@ struct Charge();
 
 #[cfg(test)]
 mod tests {
-    use super::{validate, RBTree};
+    use super::{validate, Color, Node, RBTree};
+    use invariant::Property;
+    use memory_usage::MemoryUsage;
+    use paren::{FromParen, Paren};
     use rand::prelude::*;
+    use std::mem::size_of;
+
+    #[test]
+    fn test_paren() {
+        // CLRS Figure 13.4
+        let mut rbt =
+            RBTree::<u32, ()>::from_paren("(((1:B)2:R((5:R)7:B(8:R)))11:B(14:B(15:R)))").unwrap();
+        validate::all(&rbt);
+        rbt.insert(4, ());
+        let expected = "(((1:B:1)2:R:1((4:R:0)5:B:1))7:B:2((8:B:1)11:R:1(14:B:1(15:R:0))))";
+        assert_eq!(rbt.to_paren(), expected);
+        validate::all(&rbt);
+        assert_eq!(RBTree::<u32, ()>::from_paren(expected).unwrap().to_paren(), expected);
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut rbt = RBTree::new();
+        (0..4).for_each(|i| rbt.insert(i, ()));
+        assert_eq!(rbt.check_invariants(), Ok(()));
+        let check = |rbt: &RBTree<u32, ()>| {
+            rbt.check_invariants()
+                .map_err(|e| (e.property, e.path))
+                .unwrap_err()
+        };
+        // ((0:B)1:B(2:B(3:R)))
+        let root = rbt.0 .0.as_mut().unwrap();
+        root.child[1].0.as_mut().unwrap().color = Color::Red;
+        assert_eq!(check(&rbt), (Property::RedRed, vec![1, 1]));
+        let root = rbt.0 .0.as_mut().unwrap();
+        root.child[1].0.as_mut().unwrap().color = Color::Black;
+        root.child[0].0.as_mut().unwrap().color = Color::Red;
+        // The cached black height of 0 goes wrong first.
+        assert_eq!(check(&rbt), (Property::BlackHeightCache, vec![0]));
+        rbt.0 .0.as_mut().unwrap().color = Color::Red;
+        assert_eq!(check(&rbt), (Property::BlackRoot, vec![]));
+
+        let mut rbt = RBTree::new();
+        (0..4).for_each(|i| rbt.insert(i, ()));
+        rbt.0 .0.as_mut().unwrap().bh = 3;
+        assert_eq!(check(&rbt), (Property::BlackHeightCache, vec![]));
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let node = size_of::<Node<u32, ()>>();
+        let mut rbt = RBTree::<u32, ()>::new();
+        assert_eq!(rbt.heap_size_bytes(), 0);
+        for k in 0..10 {
+            rbt.insert(k, ());
+        }
+        assert_eq!(rbt.heap_size_bytes(), 10 * node);
+    }
 
     #[test]
     fn test_rand_small() {
@@ -360,9 +491,10 @@ mod tests {
         for _ in 0..t {
             let mut test = Test::new();
             for _ in 0..q {
-                match rng.gen_range(0, 3) {
+                match rng.gen_range(0, 4) {
                     0 => test.insert(rng.gen_range(0, 10)),
                     1 | 2 => test.delete(rng.gen_range(0, 10)),
+                    3 => test.split_off_and_append(rng.gen_range(0, 11)),
                     _ => unreachable!(),
                 }
             }
@@ -392,13 +524,32 @@ mod tests {
         }
         fn delete(&mut self, k: u32) {
             println!("Delete {:?}.", &k);
-            self.rbt.delete(k);
-            match self.vec.binary_search(&k) {
+            let deleted = match self.vec.binary_search(&k) {
                 Ok(i) => {
                     self.vec.remove(i);
+                    true
                 }
-                Err(_) => (),
+                Err(_) => false,
+            };
+            assert_eq!(self.rbt.delete(k), deleted);
+            self.postprocess();
+        }
+        fn split_off_and_append(&mut self, k: u32) {
+            println!("Split off {:?} and append.", &k);
+            let mut right = self.rbt.split_off(&k);
+            validate::all(&self.rbt);
+            validate::all(&right);
+            let lb = self.vec.iter().filter(|&&x| x < k).count();
+            let keys = |rbt: &RBTree<u32, ()>| {
+                rbt.collect_vec()
+                    .into_iter()
+                    .map(|(k, ())| k)
+                    .collect::<Vec<_>>()
             };
+            assert_eq!(keys(&self.rbt), &self.vec[..lb]);
+            assert_eq!(keys(&right), &self.vec[lb..]);
+            self.rbt.append(&mut right);
+            assert!(right.collect_vec().is_empty());
             self.postprocess();
         }
         fn postprocess(&self) {
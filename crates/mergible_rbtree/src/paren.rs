@@ -1,9 +1,11 @@
-use super::{BoxedNode, Node, RBTree};
-use std::fmt::{self, Debug};
-
-pub trait Paren {
-    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result;
-}
+use {
+    super::{color::Color, BoxedNode, Node, RBTree},
+    paren::{FromParen, Painted, Paren, ParseError, Shape, Visualize},
+    std::{
+        fmt::{self, Debug},
+        str::FromStr,
+    },
+};
 
 impl<K: Ord + Debug, V: Debug> Paren for RBTree<K, V> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
@@ -19,31 +21,98 @@ impl<K: Ord + Debug, V: Debug> Paren for Node<K, V> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
         write!(w, "(")?;
         self.child[0].paren(w)?;
-        write!(w, "{:?}:{}", self.color.paint(&self.key), self.bh)?;
+        write!(
+            w,
+            "{:?}:{}:{}",
+            paren::paint(w, self.color.into(), &self.key),
+            paren::Color::from(self.color),
+            self.bh
+        )?;
         self.child[1].paren(w)?;
         write!(w, ")")
     }
 }
-struct ParenWrapper<'a, T>(&'a T);
-impl<'a, T: Paren> Debug for ParenWrapper<'a, T> {
-    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        self.0.paren(w)
+
+impl<K: Ord + Debug, V: Debug> Visualize for RBTree<K, V> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.0.shape().into_iter().collect()
+    }
+}
+impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
+    fn shape(&self) -> Option<Shape> {
+        self.0.as_ref().map(|x| {
+            Shape::new(&x.key)
+                .color(x.color)
+                .attr("bh", x.bh)
+                .child(x.child[0].shape())
+                .child(x.child[1].shape())
+        })
+    }
+}
+
+// The values are left default. The black heights are counted on the left children, so the third
+// field may be left out, e.g. `((1:R)2:B)`, and a shape of uneven black heights is read as it is.
+impl<K: Ord + Debug + FromStr, V: Debug + Default> FromParen for RBTree<K, V> {
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        BoxedNode::from_parsed(paren::parse(s)?).map(Self)
+    }
+}
+impl<K: Ord + Debug + FromStr, V: Debug + Default> BoxedNode<K, V> {
+    fn from_parsed(node: Option<Box<paren::Node>>) -> Result<Self, ParseError> {
+        match node {
+            None => Ok(Self(None)),
+            Some(node) => {
+                let paren::Node {
+                    label,
+                    child: [left, right],
+                } = *node;
+                let child = [Self::from_parsed(left)?, Self::from_parsed(right)?];
+                let color = label.color()?.into();
+                let bh = match color {
+                    Color::Red => child[0].bh(),
+                    Color::Black => child[0].bh() + 1,
+                };
+                Ok(Self(Some(Box::new(Node {
+                    child,
+                    key: label.key()?,
+                    value: V::default(),
+                    color,
+                    bh,
+                }))))
+            }
+        }
     }
 }
+
+impl From<Color> for paren::Color {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Red => paren::Color::Red,
+            Color::Black => paren::Color::Black,
+        }
+    }
+}
+impl From<paren::Color> for Color {
+    fn from(color: paren::Color) -> Self {
+        match color {
+            paren::Color::Red => Color::Red,
+            paren::Color::Black => Color::Black,
+        }
+    }
+}
+
 impl<K: Ord + Debug, V: Debug> Debug for RBTree<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("RBTree").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("RBTree").field(&Painted(self)).finish()
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for BoxedNode<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("BoxedNode")
-            .field(&ParenWrapper(self))
-            .finish()
+        w.debug_tuple("BoxedNode").field(&Painted(self)).finish()
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for Node<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("Node").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("Node").field(&Painted(self)).finish()
     }
 }
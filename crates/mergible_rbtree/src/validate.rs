@@ -1,58 +1,62 @@
-use super::{color::Color, BoxedNode, Node, RBTree};
+use super::{color::Color, BoxedNode, RBTree};
+use invariant::{ensure, InvariantViolation, Property};
 use std::fmt::Debug;
 
-pub trait Validate {
-    fn no_double_red(&self);
-    fn consistent_black_height(&self) -> u32;
-    fn root_is_black(&self) {}
-}
-
 pub fn all<T: Validate>(x: &T) {
-    x.no_double_red();
-    x.consistent_black_height();
-    x.root_is_black();
+    x.validate()
 }
 
+// `check_invariants` that panics.
+pub trait Validate {
+    fn validate(&self);
+}
 impl<K: Ord + Debug, V: Debug> Validate for RBTree<K, V> {
-    fn no_double_red(&self) {
-        self.0.no_double_red();
-    }
-    fn consistent_black_height(&self) -> u32 {
-        self.0.consistent_black_height()
-    }
-    fn root_is_black(&self) {
-        assert!(self.0.is_black(), "Root is not black: {:?}", self);
+    fn validate(&self) {
+        invariant::assert_holds(self.check_invariants())
     }
 }
-impl<K: Ord + Debug, V: Debug> Validate for BoxedNode<K, V> {
-    fn no_double_red(&self) {
-        self.0.as_ref().iter().for_each(|x| x.no_double_red())
-    }
-    fn consistent_black_height(&self) -> u32 {
-        self.0.as_ref().map_or(0, |x| x.consistent_black_height())
+
+impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
+    // the BST order and the colors, and the black heights
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        ensure(self.0.is_black(), Property::BlackRoot, &[])?;
+        check(&self.0, &mut Vec::new(), &mut None).map(|_| ())
     }
 }
-impl<K: Ord + Debug, V: Debug> Validate for Node<K, V> {
-    fn no_double_red(&self) {
-        for child in &self.child {
-            assert!(
-                self.color == Color::Black || child.is_black(),
-                "Double red: self = {:?}, child = {:?}",
-                &self,
-                &child
-            );
-            child.no_double_red();
-        }
+// Returns the black height, with `prev` the last key in the in-order.
+fn check<'a, K: Ord + Debug, V: Debug>(
+    x: &'a BoxedNode<K, V>,
+    path: &mut Vec<usize>,
+    prev: &mut Option<&'a K>,
+) -> Result<u32, InvariantViolation> {
+    let node = match x.0.as_ref() {
+        None => return Ok(0),
+        Some(node) => node,
+    };
+    for (i, child) in node.child.iter().enumerate() {
+        path.push(i);
+        ensure(
+            node.color == Color::Black || child.is_black(),
+            Property::RedRed,
+            path,
+        )?;
+        path.pop();
     }
-    fn consistent_black_height(&self) -> u32 {
-        let x = self.child[0].consistent_black_height();
-        let y = self.child[1].consistent_black_height();
-        assert_eq!(x, y, "Inconsistent black height: {:?}", &self);
-        let res = match self.color {
-            Color::Red => x,
-            Color::Black => x + 1,
-        };
-        assert_eq!(res, self.bh, "Recorded black height is wrong: {:?}", &self);
-        res
+    path.push(0);
+    let left = check(&node.child[0], path, prev)?;
+    path.pop();
+    if let Some(prev) = prev {
+        ensure(*prev <= &node.key, Property::BstOrder, path)?;
     }
+    *prev = Some(&node.key);
+    path.push(1);
+    let right = check(&node.child[1], path, prev)?;
+    path.pop();
+    ensure(left == right, Property::BlackHeight, path)?;
+    let bh = match node.color {
+        Color::Black => left + 1,
+        Color::Red => left,
+    };
+    ensure(node.bh == bh, Property::BlackHeightCache, path)?;
+    Ok(bh)
 }
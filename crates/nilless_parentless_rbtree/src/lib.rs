@@ -1,8 +1,8 @@
+mod memory;
 mod paren;
 pub mod validate;
 
 use std::{cmp::Ordering, fmt::Debug, mem::replace};
-use yansi::Paint;
 
 pub struct RBTree<K, V>(BoxedNode<K, V>);
 impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
@@ -16,6 +16,9 @@ impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
     pub fn delete(&mut self, k: K) -> bool {
         self.0.delete(k).is_some()
     }
+    pub fn get(&self, k: &K) -> Option<&V> {
+        self.0.get(k)
+    }
     pub fn collect_vec(&self) -> Vec<(K, V)>
     where
         K: Clone,
@@ -223,6 +226,14 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
                 Some((self.transplant_child(1).0.unwrap(), charge))
             })
     }
+    fn get(&self, k: &K) -> Option<&V> {
+        let internal = self.0.as_ref()?;
+        match k.cmp(&internal.key) {
+            Ordering::Less => internal.child[0].get(k),
+            Ordering::Greater => internal.child[1].get(k),
+            Ordering::Equal => Some(&internal.value),
+        }
+    }
     fn collect_vec(&self, vec: &mut Vec<(K, V)>)
     where
         K: Clone,
@@ -247,15 +258,6 @@ enum Color {
     Red,
     Black,
 }
-impl Color {
-    fn paint<T>(&self, x: T) -> Paint<T> {
-        match self {
-            Color::Red => Paint::red(x),
-            Color::Black => Paint::blue(x),
-        }
-        .bold()
-    }
-}
 enum DoubleRed {
     Me,
     Child(usize),
@@ -266,8 +268,59 @@ struct Charge();
 #[cfg(test)]
 mod tests {
     use super::validate;
-    use super::RBTree;
+    use super::{Color, Node, RBTree};
+    use invariant::Property;
+    use memory_usage::MemoryUsage;
+    use paren::{FromParen, Paren};
     use rand::prelude::*;
+    use std::mem::size_of;
+
+    #[test]
+    fn test_paren() {
+        // CLRS Figure 13.4
+        let mut rbt =
+            RBTree::<u32, ()>::from_paren("(((1:B)2:R((5:R)7:B(8:R)))11:B(14:B(15:R)))").unwrap();
+        validate::all(&rbt);
+        rbt.insert(4, ());
+        let expected = "(((1:B)2:R((4:R)5:B))7:B((8:B)11:R(14:B(15:R))))";
+        assert_eq!(rbt.to_paren(), expected);
+        validate::all(&rbt);
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut rbt = RBTree::new();
+        (0..4).for_each(|i| rbt.insert(i, ()));
+        assert_eq!(rbt.check_invariants(), Ok(()));
+        let check = |rbt: &RBTree<u32, ()>| {
+            rbt.check_invariants()
+                .map_err(|e| (e.property, e.path))
+                .unwrap_err()
+        };
+        // ((0:B)1:B(2:B(3:R)))
+        let root = rbt.0 .0.as_mut().unwrap();
+        root.child[1].0.as_mut().unwrap().color = Color::Red;
+        assert_eq!(check(&rbt), (Property::RedRed, vec![1, 1]));
+        let root = rbt.0 .0.as_mut().unwrap();
+        root.child[1].0.as_mut().unwrap().color = Color::Black;
+        root.child[0].0.as_mut().unwrap().color = Color::Red;
+        assert_eq!(check(&rbt), (Property::BlackHeight, vec![]));
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let node = size_of::<Node<u32, ()>>();
+        let mut rbt = RBTree::<u32, ()>::new();
+        assert_eq!(rbt.heap_size_bytes(), 0);
+        for k in 0..10 {
+            rbt.insert(k, ());
+        }
+        assert_eq!(rbt.heap_size_bytes(), 10 * node);
+        for k in 0..4 {
+            rbt.delete(k);
+        }
+        assert_eq!(rbt.heap_size_bytes(), 6 * node);
+    }
 
     #[test]
     fn test_hand() {
@@ -340,6 +393,9 @@ mod tests {
                     .collect::<Vec<_>>(),
                 &self.vec,
             );
+            for x in 0..30 {
+                assert_eq!(self.rbt.get(&x).is_some(), self.vec.contains(&x));
+            }
         }
         fn insert(&mut self, k: u32) {
             println!("Insert {:?}", &k);
@@ -1,15 +1,27 @@
-use super::{BoxedNode, Node, RBTree};
-use std::fmt::{self, Debug};
+use {
+    super::{BoxedNode, Color, Node, RBTree},
+    paren::{FromParen, Painted, Paren, ParseError, Shape, Visualize},
+    std::{
+        fmt::{self, Debug},
+        str::FromStr,
+    },
+};
 
-pub trait Paren {
-    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result;
+impl<K: Ord + Debug, V: Debug> Paren for RBTree<K, V> {
+    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        self.0.paren(w)
+    }
 }
-
 impl<K: Ord + Debug, V: Debug> Paren for Node<K, V> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
         write!(w, "(")?;
         self.child[0].paren(w)?;
-        write!(w, "{:?}", self.color.paint(&self.key))?;
+        write!(
+            w,
+            "{:?}:{}",
+            paren::paint(w, self.color.into(), &self.key),
+            paren::Color::from(self.color)
+        )?;
         self.child[1].paren(w)?;
         write!(w, ")")
     }
@@ -20,24 +32,83 @@ impl<K: Ord + Debug, V: Debug> Paren for BoxedNode<K, V> {
     }
 }
 
+impl<K: Ord + Debug, V: Debug> Visualize for RBTree<K, V> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.0.shape().into_iter().collect()
+    }
+}
+impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
+    fn shape(&self) -> Option<Shape> {
+        self.0.as_ref().map(|x| {
+            Shape::new(&x.key)
+                .color(x.color)
+                .child(x.child[0].shape())
+                .child(x.child[1].shape())
+        })
+    }
+}
+
+// The values are left default.
+impl<K: Ord + Debug + FromStr, V: Debug + Default> FromParen for RBTree<K, V> {
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        BoxedNode::from_parsed(paren::parse(s)?).map(Self)
+    }
+}
+impl<K: Ord + Debug + FromStr, V: Debug + Default> BoxedNode<K, V> {
+    fn from_parsed(node: Option<Box<paren::Node>>) -> Result<Self, ParseError> {
+        match node {
+            None => Ok(Self(None)),
+            Some(node) => {
+                let paren::Node {
+                    label,
+                    child: [left, right],
+                } = *node;
+                Ok(Self(Some(Box::new(Node {
+                    child: [Self::from_parsed(left)?, Self::from_parsed(right)?],
+                    key: label.key()?,
+                    value: V::default(),
+                    color: label.color()?.into(),
+                }))))
+            }
+        }
+    }
+}
+
+impl From<Color> for paren::Color {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Red => paren::Color::Red,
+            Color::Black => paren::Color::Black,
+        }
+    }
+}
+impl From<paren::Color> for Color {
+    fn from(color: paren::Color) -> Self {
+        match color {
+            paren::Color::Red => Color::Red,
+            paren::Color::Black => Color::Black,
+        }
+    }
+}
+
 impl<K: Ord + Debug, V: Debug> Debug for Node<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
         write!(w, "Node {{")?;
-        self.paren(w)?;
+        write!(w, "{:?}", Painted(self))?;
         write!(w, " }}")
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for BoxedNode<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
         write!(w, "BoxedNode {{")?;
-        self.paren(w)?;
+        write!(w, "{:?}", Painted(self))?;
         write!(w, " }}")
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for RBTree<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
         write!(w, "RBTree {{")?;
-        self.0.paren(w)?;
+        write!(w, "{:?}", Painted(self))?;
         write!(w, " }}")
     }
 }
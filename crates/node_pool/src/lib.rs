@@ -0,0 +1,134 @@
+// Keeps the boxes of the deleted nodes and gives them back to the insertions, so that a tree with
+// as many insertions as deletions stops calling the allocator. A disabled pool, which is the
+// default, allocates and frees every box as usual.
+use {
+    memory_usage::MemoryUsage,
+    std::mem::{size_of, MaybeUninit},
+};
+
+pub struct NodePool<T> {
+    free: Vec<Box<MaybeUninit<T>>>,
+    enabled: bool,
+}
+impl<T> NodePool<T> {
+    pub fn disabled() -> Self {
+        Self {
+            free: Vec::new(),
+            enabled: false,
+        }
+    }
+    // `capacity` boxes allocated ahead, in a `Vec` with room for exactly as many. While at most
+    // `capacity` values are live at once, neither `alloc` nor `free` calls the allocator. Beyond
+    // that `alloc` allocates new boxes, and `free` keeps them too, which may grow the `Vec`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            free: (0..capacity)
+                .map(|_| Box::new(MaybeUninit::uninit()))
+                .collect(),
+            enabled: true,
+        }
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    // the boxes to be reused
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+    pub fn alloc(&mut self, value: T) -> Box<T> {
+        match self.free.pop() {
+            Some(mut node) => {
+                *node = MaybeUninit::new(value);
+                // SAFETY: It has just been initialized, and `MaybeUninit<T>` has the layout of `T`.
+                unsafe { Box::from_raw(Box::into_raw(node).cast::<T>()) }
+            }
+            None => Box::new(value),
+        }
+    }
+    // Moves the value out of `node`, and keeps the box if enabled.
+    pub fn free(&mut self, node: Box<T>) -> T {
+        if !self.enabled {
+            return *node;
+        }
+        let node = Box::into_raw(node);
+        // SAFETY: The value is read once, and the box is left uninitialized, which is never read
+        // until `alloc` writes into it.
+        unsafe {
+            let value = node.read();
+            self.free.push(Box::from_raw(node.cast::<MaybeUninit<T>>()));
+            value
+        }
+    }
+}
+impl<T> Default for NodePool<T> {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+// The spare boxes are uninitialized, so only their sizes are counted.
+impl<T> MemoryUsage for NodePool<T> {
+    fn heap_size_bytes(&self) -> usize {
+        self.free.capacity() * size_of::<Box<MaybeUninit<T>>>() + self.free.len() * size_of::<T>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::NodePool,
+        memory_usage::MemoryUsage,
+        std::{mem::size_of, rc::Rc},
+    };
+
+    #[test]
+    fn test_reuse() {
+        let mut pool = NodePool::with_capacity(2);
+        assert!(pool.is_enabled());
+        assert_eq!(pool.len(), 2);
+        let x = pool.alloc([1u64; 4]);
+        let y = pool.alloc([2u64; 4]);
+        let z = pool.alloc([3u64; 4]);
+        assert!(pool.is_empty());
+        let address = &*y as *const [u64; 4];
+        assert_eq!(pool.free(y), [2; 4]);
+        assert_eq!(pool.len(), 1);
+        let w = pool.alloc([4u64; 4]);
+        assert_eq!(&*w as *const [u64; 4], address);
+        assert_eq!((*x, *z, *w), ([1; 4], [3; 4], [4; 4]));
+        assert_eq!(pool.heap_size_bytes(), 2 * size_of::<Box<[u64; 4]>>());
+        pool.free(x);
+        assert_eq!(
+            pool.heap_size_bytes(),
+            2 * size_of::<Box<[u64; 4]>>() + size_of::<[u64; 4]>()
+        );
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut pool = NodePool::disabled();
+        let x = pool.alloc(42);
+        assert_eq!(pool.free(x), 42);
+        assert!(pool.is_empty());
+        assert_eq!(pool.heap_size_bytes(), 0);
+    }
+
+    // The values are moved out, so they are dropped exactly once, and none are in the pool.
+    #[test]
+    fn test_drop() {
+        let rc = Rc::new(());
+        let mut pool = NodePool::with_capacity(1);
+        let x = pool.alloc(Rc::clone(&rc));
+        let y = pool.alloc(Rc::clone(&rc));
+        assert_eq!(Rc::strong_count(&rc), 3);
+        drop(pool.free(x));
+        assert_eq!(Rc::strong_count(&rc), 2);
+        drop(pool);
+        assert_eq!(Rc::strong_count(&rc), 2);
+        drop(y);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+}
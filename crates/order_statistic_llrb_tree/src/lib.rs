@@ -1,11 +1,9 @@
+mod memory;
 mod paren;
 mod validate;
 
+use std::{cmp::Ordering, fmt::Debug, mem::replace};
 pub use validate::Validate;
-use {
-    std::{cmp::Ordering, fmt::Debug, mem::replace},
-    yansi::Paint,
-};
 
 pub struct LLRB<K, V>(BoxNode<K, V>);
 impl<K: Ord + Debug, V: Debug> LLRB<K, V> {
@@ -19,6 +17,9 @@ impl<K: Ord + Debug, V: Debug> LLRB<K, V> {
     pub fn get(&self, i: usize) -> Option<&Node<K, V>> {
         self.0.get(i)
     }
+    pub fn find(&self, key: &K) -> Option<&Node<K, V>> {
+        self.0.find(key)
+    }
     // NOTE: 重複なしです。
     pub fn insert(&mut self, key: K, value: V) {
         self.0.insert(key, value);
@@ -60,11 +61,18 @@ impl<K: Ord + Debug, V: Debug> BoxNode<K, V> {
         self.0.as_ref().map_or(0, |node| node.size)
     }
     fn update(&mut self) {
-        println!("update {:?}", &self);
         if let Some(me) = self.0.as_mut() {
             me.size = 1 + me.child.iter().map(|child| child.size()).sum::<usize>();
         }
     }
+    fn find(&self, key: &K) -> Option<&Node<K, V>> {
+        let me = self.0.as_ref()?;
+        match key.cmp(&me.key) {
+            Ordering::Less => me.child[0].find(key),
+            Ordering::Greater => me.child[1].find(key),
+            Ordering::Equal => Some(me),
+        }
+    }
     fn get(&self, i: usize) -> Option<&Node<K, V>> {
         let me = self.0.as_ref()?;
         let lsize = me.child[0].size();
@@ -278,21 +286,16 @@ enum Color {
     Red,
     Black,
 }
-impl Color {
-    fn paint<T>(&self, x: T) -> Paint<T> {
-        match self {
-            Self::Red => Paint::red(x),
-            Self::Black => Paint::blue(x),
-        }
-        .bold()
-    }
-}
 
 #[cfg(test)]
 mod tests {
     use super::Validate;
-    use super::LLRB;
+    use super::{Node, LLRB};
+    use invariant::Property;
+    use memory_usage::MemoryUsage;
+    use paren::{FromParen, Paren};
     use rand::prelude::*;
+    use std::mem::size_of;
 
     #[test]
     fn test_hand_insert() {
@@ -329,6 +332,42 @@ mod tests {
         (0..10).for_each(|i| test.get(i));
     }
 
+    #[test]
+    fn test_paren() {
+        let mut llrb = LLRB::<u32, ()>::from_paren("((1:B)2:B(3:B))").unwrap();
+        assert_eq!(llrb.validate(), 2);
+        assert_eq!(llrb.get(2).map(|node| node.key), Some(3));
+        llrb.insert(4, ());
+        assert_eq!(llrb.to_paren(), "((1:B:():1)2:B:():4((3:R:():1)4:B:():2))");
+        assert_eq!(llrb.validate(), 2);
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut llrb = LLRB::new();
+        (0..4).for_each(|i| llrb.insert(i, 42));
+        assert_eq!(llrb.check_invariants(), Ok(()));
+        let root = llrb.0 .0.as_mut().unwrap();
+        root.child[0].0.as_mut().unwrap().size += 1;
+        let e = llrb.check_invariants().unwrap_err();
+        assert_eq!((e.property, e.path), (Property::SizeCache, vec![0]));
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let node = size_of::<Node<u32, u32>>();
+        let mut llrb = LLRB::<u32, u32>::new();
+        assert_eq!(llrb.heap_size_bytes(), 0);
+        for k in 0..10 {
+            llrb.insert(k, k);
+        }
+        assert_eq!(llrb.heap_size_bytes(), 10 * node);
+        for k in 0..4 {
+            llrb.delete(&k);
+        }
+        assert_eq!(llrb.heap_size_bytes(), 6 * node);
+    }
+
     #[test]
     fn test_rand_small() {
         test_rand(10, 50, 42);
@@ -418,6 +457,11 @@ mod tests {
                 &self.vec
             );
             assert_eq!(self.llrb.len(), self.vec.len());
+            for x in 0..30 {
+                let result = self.llrb.find(&x).map(|node| (node.key, node.value));
+                let expected = self.vec.iter().find(|&&(key, _)| key == x).copied();
+                assert_eq!(result, expected, "Failed in `find`");
+            }
         }
     }
 }
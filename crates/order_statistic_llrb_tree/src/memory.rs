@@ -0,0 +1,23 @@
+use {
+    super::{BoxNode, Node, LLRB},
+    memory_usage::MemoryUsage,
+};
+
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for LLRB<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes()
+    }
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for BoxNode<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes()
+    }
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Node<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.key.heap_size_bytes()
+            + self.value.heap_size_bytes()
+            + self.child[0].heap_size_bytes()
+            + self.child[1].heap_size_bytes()
+    }
+}
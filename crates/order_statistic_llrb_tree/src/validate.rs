@@ -1,39 +1,65 @@
-use super::{BoxNode, Color, Node, LLRB};
+use super::{BoxNode, Color, LLRB};
+use invariant::{ensure, InvariantViolation, Property};
 use std::fmt::Debug;
 
+// `check_invariants` that panics, and returns the black height.
 pub trait Validate {
     fn validate(&self) -> u32;
 }
 impl<K: Ord + Debug, V: Debug> Validate for LLRB<K, V> {
     fn validate(&self) -> u32 {
-        self.0.validate()
+        invariant::assert_holds(self.check_black_height())
     }
 }
-impl<K: Ord + Debug, V: Debug> Validate for BoxNode<K, V> {
-    fn validate(&self) -> u32 {
-        self.0.as_ref().map_or(0, |x| x.validate())
+
+impl<K: Ord + Debug, V: Debug> LLRB<K, V> {
+    // the BST order, the colors and the black heights, and the sizes
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_black_height().map(|_| ())
+    }
+    fn check_black_height(&self) -> Result<u32, InvariantViolation> {
+        if let Some(root) = &self.0 .0 {
+            ensure(root.color == Color::Black, Property::BlackRoot, &[])?;
+        }
+        check(&self.0, &mut Vec::new(), &mut None)
     }
 }
-impl<K: Ord + Debug, V: Debug> Validate for Node<K, V> {
-    fn validate(&self) -> u32 {
-        self.child.iter().for_each(|child| {
-            assert!(
-                self.color == Color::Black || child.is_black(),
-                "Double red: {:?}",
-                &self
-            );
-        });
-        assert!(
-            self.color == Color::Black || self.child[0].is_red() || self.child[1].is_black(),
-            "Right leaning 3-node: {:?}",
-            &self
-        );
-        let x = self.child[0].validate();
-        let y = self.child[1].validate();
-        assert_eq!(x, y, "Inconsistent black height: self = {:?}", &self);
-        match self.color {
-            Color::Black => x + 1,
-            Color::Red => x,
+
+// Returns the black height, with `prev` the last key in the in-order.
+fn check<'a, K: Ord + Debug, V: Debug>(
+    x: &'a BoxNode<K, V>,
+    path: &mut Vec<usize>,
+    prev: &mut Option<&'a K>,
+) -> Result<u32, InvariantViolation> {
+    let node = match &x.0 {
+        None => return Ok(0),
+        Some(node) => node,
+    };
+    for (i, child) in node.child.iter().enumerate() {
+        if let Some(child) = &child.0 {
+            path.push(i);
+            if child.color == Color::Red {
+                ensure(node.color == Color::Black, Property::RedRed, path)?;
+                ensure(i == 0, Property::LeftLeaning, path)?;
+            }
+            path.pop();
         }
     }
+    path.push(0);
+    let left = check(&node.child[0], path, prev)?;
+    path.pop();
+    if let Some(prev) = prev {
+        ensure(*prev <= &node.key, Property::BstOrder, path)?;
+    }
+    *prev = Some(&node.key);
+    path.push(1);
+    let right = check(&node.child[1], path, prev)?;
+    path.pop();
+    ensure(left == right, Property::BlackHeight, path)?;
+    let size = 1 + node.child.iter().map(|child| child.size()).sum::<usize>();
+    ensure(node.size == size, Property::SizeCache, path)?;
+    Ok(match node.color {
+        Color::Black => left + 1,
+        Color::Red => left,
+    })
 }
@@ -1,5 +1,21 @@
-use std::fmt::{Debug, Formatter, Result};
+mod parse;
+mod visualize;
 
+pub use {
+    parse::{parse, parse_btree, BNode, Label, Node, ParseError, MAX_DEPTH},
+    visualize::{pretty, to_dot, Shape, Visualize},
+};
+
+use {
+    std::{
+        fmt::{self, Debug, Display, Formatter, Result},
+        str::FromStr,
+    },
+    yansi::Paint,
+};
+
+// A tree that can be written in the parenthesized form, e.g. `((1)2(3))`, or `[[1,2]3[4]]` for a
+// B-tree.
 pub struct Wrapper<'a, T>(pub &'a T);
 
 pub trait Paren: Sized {
@@ -13,3 +29,52 @@ impl<'a, T: Paren> Debug for Wrapper<'a, T> {
         self.0.paren(w)
     }
 }
+
+// `Paren` in the alternate form, in which `paint` colors the keys, for the `Debug` of the trees.
+// `to_paren` stays plain so that `from_paren` can read it back.
+pub struct Painted<'a, T>(pub &'a T);
+impl<'a, T: Paren> Debug for Painted<'a, T> {
+    fn fmt(&self, w: &mut Formatter) -> Result {
+        write!(w, "{:#?}", Wrapper(self.0))
+    }
+}
+
+// Red in red and black in blue, but only in the alternate form.
+pub fn paint<T>(w: &Formatter, color: Color, x: T) -> Paint<T> {
+    match color {
+        _ if !w.alternate() => Paint::new(x),
+        Color::Red => Paint::red(x).bold(),
+        Color::Black => Paint::blue(x).bold(),
+    }
+}
+
+// The inverse of `Paren`. The shape is taken as it is, so the invariants of the tree are left to
+// the caller to check.
+pub trait FromParen: Sized {
+    fn from_paren(s: &str) -> std::result::Result<Self, ParseError>;
+}
+
+// The color annotation of the red-black trees, e.g. `((1:R)2:B(3:R))`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Black,
+}
+impl Display for Color {
+    fn fmt(&self, w: &mut Formatter) -> fmt::Result {
+        match self {
+            Color::Red => write!(w, "R"),
+            Color::Black => write!(w, "B"),
+        }
+    }
+}
+impl FromStr for Color {
+    type Err = ();
+    fn from_str(s: &str) -> std::result::Result<Self, ()> {
+        match s {
+            "R" => Ok(Color::Red),
+            "B" => Ok(Color::Black),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use {
+    super::Color,
+    std::{
+        error::Error,
+        fmt::{self, Display, Formatter},
+        str::FromStr,
+    },
+};
+
+// A node of a binary tree `(left label right)`. The empty string is the empty tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub label: Label,
+    pub child: [Option<Box<Node>>; 2],
+}
+
+// A node of a B-tree `[child label child ... label child]`, or `[label,label,...]` if it is a
+// leaf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BNode {
+    pub keys: Vec<Label>,
+    pub child: Vec<BNode>,
+}
+impl BNode {
+    pub fn is_leaf(&self) -> bool {
+        self.child.is_empty()
+    }
+}
+
+// The fields of a label separated by `:`, e.g. `1:R` for the key 1 of a red node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label(pub Vec<String>);
+impl Label {
+    pub fn field<T: FromStr>(&self, i: usize) -> Result<T, ParseError> {
+        self.0
+            .get(i)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| ParseError::InvalidLabel(self.0.join(":")))
+    }
+    pub fn key<K: FromStr>(&self) -> Result<K, ParseError> {
+        self.field(0)
+    }
+    pub fn color(&self) -> Result<Color, ParseError> {
+        self.field(1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    // the byte offset and the character
+    Unexpected(usize, char),
+    UnexpectedEnd,
+    InvalidLabel(String),
+    // the byte offset of the node nested deeper than `MAX_DEPTH`
+    TooDeep(usize),
+}
+impl Display for ParseError {
+    fn fmt(&self, w: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseError::Unexpected(i, c) => write!(w, "unexpected {:?} at {}", c, i),
+            ParseError::UnexpectedEnd => write!(w, "unexpected end of input"),
+            ParseError::InvalidLabel(s) => write!(w, "invalid label {:?}", s),
+            ParseError::TooDeep(i) => write!(w, "nested deeper than {} at {}", MAX_DEPTH, i),
+        }
+    }
+}
+impl Error for ParseError {}
+
+// The trees are built and dropped recursively, so a deeper input would overflow the stack there
+// if not here.
+pub const MAX_DEPTH: usize = 1000;
+
+pub fn parse(s: &str) -> Result<Option<Box<Node>>, ParseError> {
+    let mut parser = Parser { s, i: 0 };
+    let root = parser.binary(0)?;
+    parser.end()?;
+    Ok(root)
+}
+
+pub fn parse_btree(s: &str) -> Result<BNode, ParseError> {
+    let mut parser = Parser { s, i: 0 };
+    let root = parser.bnode(0)?;
+    parser.end()?;
+    Ok(root)
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    i: usize,
+}
+impl<'a> Parser<'a> {
+    // Skip the whitespaces and peek the next character.
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.s[self.i..];
+        self.i += rest.len() - rest.trim_start().len();
+        self.s[self.i..].chars().next()
+    }
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(d) if d == c => {
+                self.i += c.len_utf8();
+                Ok(())
+            }
+            Some(d) => Err(ParseError::Unexpected(self.i, d)),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+    fn end(&mut self) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(c) => Err(ParseError::Unexpected(self.i, c)),
+            None => Ok(()),
+        }
+    }
+    fn label(&mut self) -> Result<Label, ParseError> {
+        let mut fields = Vec::new();
+        loop {
+            self.peek();
+            let rest = &self.s[self.i..];
+            let len = rest
+                .find(|c: char| "()[],:".contains(c) || c.is_whitespace())
+                .unwrap_or(rest.len());
+            if len == 0 {
+                return Err(match rest.chars().next() {
+                    Some(c) => ParseError::Unexpected(self.i, c),
+                    None => ParseError::UnexpectedEnd,
+                });
+            }
+            fields.push(rest[..len].to_owned());
+            self.i += len;
+            if self.peek() != Some(':') {
+                return Ok(Label(fields));
+            }
+            self.i += 1;
+        }
+    }
+    fn binary(&mut self, depth: usize) -> Result<Option<Box<Node>>, ParseError> {
+        if self.peek() != Some('(') {
+            return Ok(None);
+        }
+        if depth == MAX_DEPTH {
+            return Err(ParseError::TooDeep(self.i));
+        }
+        self.i += 1;
+        let left = self.binary(depth + 1)?;
+        let label = self.label()?;
+        let right = self.binary(depth + 1)?;
+        self.expect(')')?;
+        Ok(Some(Box::new(Node {
+            label,
+            child: [left, right],
+        })))
+    }
+    fn bnode(&mut self, depth: usize) -> Result<BNode, ParseError> {
+        if depth == MAX_DEPTH {
+            return Err(ParseError::TooDeep(self.i));
+        }
+        self.expect('[')?;
+        let mut keys = Vec::new();
+        let mut child = Vec::new();
+        // The children and the keys alternate in an internal node.
+        loop {
+            match self.peek() {
+                Some(']') => break,
+                Some('[') if child.len() == keys.len() => child.push(self.bnode(depth + 1)?),
+                Some(c) if c != '[' && (child.is_empty() || child.len() == keys.len() + 1) => {
+                    // The keys of a leaf are separated by commas.
+                    if child.is_empty() && !keys.is_empty() {
+                        self.expect(',')?;
+                    }
+                    keys.push(self.label()?)
+                }
+                Some(c) => return Err(ParseError::Unexpected(self.i, c)),
+                None => return Err(ParseError::UnexpectedEnd),
+            }
+        }
+        if !child.is_empty() && child.len() != keys.len() + 1 {
+            return Err(ParseError::Unexpected(self.i, ']'));
+        }
+        self.i += 1;
+        Ok(BNode { keys, child })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, parse_btree, BNode, Label, Node, ParseError, MAX_DEPTH};
+
+    fn leaf(label: &str) -> Option<Box<Node>> {
+        node(None, label, None)
+    }
+    fn node(left: Option<Box<Node>>, label: &str, right: Option<Box<Node>>) -> Option<Box<Node>> {
+        Some(Box::new(Node {
+            label: Label(label.split(':').map(str::to_owned).collect()),
+            child: [left, right],
+        }))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(""), Ok(None));
+        assert_eq!(parse("(1)"), Ok(leaf("1")));
+        assert_eq!(parse("((1)2(3))"), Ok(node(leaf("1"), "2", leaf("3"))));
+        assert_eq!(
+            parse(" ( (1:R) 2:B ((3:B)4:R) ) "),
+            Ok(node(leaf("1:R"), "2:B", node(leaf("3:B"), "4:R", None)))
+        );
+        assert_eq!(parse("(1"), Err(ParseError::UnexpectedEnd));
+        assert_eq!(parse("(1))"), Err(ParseError::Unexpected(3, ')')));
+        assert_eq!(parse("((1))"), Err(ParseError::Unexpected(4, ')')));
+        assert_eq!(parse("(1:)"), Err(ParseError::Unexpected(3, ')')));
+    }
+
+    // The nesting is bounded, so that neither this nor the trees built from it overflow the stack.
+    #[test]
+    fn test_too_deep() {
+        let nested = |depth: usize| "(".repeat(depth) + "1" + &")1".repeat(depth - 1) + ")";
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            parse(&nested(MAX_DEPTH + 1)),
+            Err(ParseError::TooDeep(MAX_DEPTH))
+        );
+        assert_eq!(
+            parse(&"(".repeat(1_000_000)),
+            Err(ParseError::TooDeep(MAX_DEPTH))
+        );
+        let nested = |depth: usize| "[".repeat(depth) + &"]1[]".repeat(depth - 1) + "]";
+        assert!(parse_btree(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            parse_btree(&nested(MAX_DEPTH + 1)),
+            Err(ParseError::TooDeep(MAX_DEPTH))
+        );
+    }
+
+    #[test]
+    fn test_label() {
+        let root = parse("(-1:R:42)").unwrap().unwrap();
+        assert_eq!(root.label.key::<i32>(), Ok(-1));
+        assert_eq!(root.label.color(), Ok(super::Color::Red));
+        assert_eq!(root.label.field::<u32>(2), Ok(42));
+        assert_eq!(
+            root.label.field::<u32>(3),
+            Err(ParseError::InvalidLabel("-1:R:42".to_owned()))
+        );
+        assert!(root.label.key::<u32>().is_err());
+    }
+
+    #[test]
+    fn test_parse_btree() {
+        let leaf = |keys: &[&str]| BNode {
+            keys: keys.iter().map(|&s| Label(vec![s.to_owned()])).collect(),
+            child: Vec::new(),
+        };
+        assert_eq!(parse_btree("[]"), Ok(leaf(&[])));
+        assert_eq!(parse_btree("[1,2,3]"), Ok(leaf(&["1", "2", "3"])));
+        assert_eq!(
+            parse_btree("[[1,2]3[4][5,6]]"),
+            Err(ParseError::Unexpected(10, '['))
+        );
+        assert_eq!(
+            parse_btree("[[1,2]3[4]5[6]]"),
+            Ok(BNode {
+                keys: vec![Label(vec!["3".to_owned()]), Label(vec!["5".to_owned()])],
+                child: vec![leaf(&["1", "2"]), leaf(&["4"]), leaf(&["6"])],
+            })
+        );
+        assert_eq!(parse_btree("[[1]2]"), Err(ParseError::Unexpected(5, ']')));
+        assert_eq!(parse_btree("[1,,2]"), Err(ParseError::Unexpected(3, ',')));
+        assert_eq!(parse_btree("[1]]"), Err(ParseError::Unexpected(3, ']')));
+        assert_eq!(parse_btree("[1 2]"), Err(ParseError::Unexpected(3, '2')));
+        assert_eq!(parse_btree("[1[2]]"), Err(ParseError::Unexpected(2, '[')));
+    }
+}
@@ -0,0 +1,214 @@
+use {
+    super::Color,
+    std::fmt::{Debug, Write},
+};
+
+// A node to draw: the label, the color if any, the other attributes such as the height, the size
+// or the priority, and the children. The children of a binary tree are `[left, right]` with
+// `None` for the nil.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shape {
+    pub label: String,
+    pub color: Option<Color>,
+    pub attrs: Vec<(&'static str, String)>,
+    pub child: Vec<Option<Shape>>,
+}
+impl Shape {
+    pub fn new(label: impl Debug) -> Self {
+        Self {
+            label: format!("{:?}", label),
+            color: None,
+            attrs: Vec::new(),
+            child: Vec::new(),
+        }
+    }
+    pub fn color(mut self, color: impl Into<Color>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+    pub fn attr(mut self, name: &'static str, value: impl Debug) -> Self {
+        self.attrs.push((name, format!("{:?}", value)));
+        self
+    }
+    pub fn child(mut self, child: Option<Shape>) -> Self {
+        self.child.push(child);
+        self
+    }
+
+    // `7:R h=2`
+    fn line(&self) -> String {
+        let mut s = self.label.clone();
+        if let Some(color) = self.color {
+            write!(s, ":{}", color).unwrap();
+        }
+        for (name, value) in &self.attrs {
+            write!(s, " {}={}", name, value).unwrap();
+        }
+        s
+    }
+}
+
+// Draws the trees for debugging, which is easier to read than the parenthesized form when the
+// trees are large.
+pub trait Visualize {
+    // more than one for a forest, e.g. the root list of a Fibonacci heap
+    fn shapes(&self) -> Vec<Shape>;
+    // Graphviz
+    fn to_dot(&self) -> String {
+        to_dot(&self.shapes())
+    }
+    // The tree is drawn sideways: turn the page clockwise to see the root at the top.
+    fn pretty(&self) -> String {
+        pretty(&self.shapes())
+    }
+}
+
+pub fn to_dot(shapes: &[Shape]) -> String {
+    fn dfs(x: &Shape, id: &mut usize, s: &mut String) -> usize {
+        let me = *id;
+        *id += 1;
+        let mut label = x.label.replace('\\', "\\\\").replace('"', "\\\"");
+        for (name, value) in &x.attrs {
+            write!(label, "\\n{}={}", name, value.replace('"', "\\\"")).unwrap();
+        }
+        let style = match x.color {
+            None => String::new(),
+            Some(Color::Red) => ", style=filled, fillcolor=red, fontcolor=white".to_owned(),
+            Some(Color::Black) => ", style=filled, fillcolor=black, fontcolor=white".to_owned(),
+        };
+        writeln!(s, "    n{} [label=\"{}\"{}];", me, label, style).unwrap();
+        // The nil children of a binary tree are kept as invisible nodes so that a lone child
+        // still leans to its side.
+        let binary = x.child.len() == 2 && x.child.iter().any(Option::is_some);
+        for child in &x.child {
+            match child {
+                Some(child) => {
+                    let y = dfs(child, id, s);
+                    writeln!(s, "    n{} -> n{};", me, y).unwrap();
+                }
+                None if binary => {
+                    writeln!(s, "    n{} [style=invis];", *id).unwrap();
+                    writeln!(s, "    n{} -> n{} [style=invis];", me, *id).unwrap();
+                    *id += 1;
+                }
+                None => (),
+            }
+        }
+        me
+    }
+    let mut s = "digraph {\n".to_owned();
+    let mut id = 0;
+    for x in shapes {
+        dfs(x, &mut id, &mut s);
+    }
+    s.push_str("}\n");
+    s
+}
+
+pub fn pretty(shapes: &[Shape]) -> String {
+    // `above`, `mid` and `below` are the prefixes of the lines above `x`, of `x` and below `x`.
+    fn dfs(x: &Shape, above: &str, mid: &str, below: &str, s: &mut String) {
+        // The latter half of the children goes above, the right child of a binary tree in
+        // particular.
+        let child = x.child.iter().flatten().collect::<Vec<_>>();
+        let half = x.child.iter().take(x.child.len() / 2).flatten().count();
+        let (lower, upper) = child.split_at(half);
+        for (i, y) in upper.iter().rev().enumerate() {
+            let (a, m) = if i == 0 {
+                ("    ", "/-- ")
+            } else {
+                ("|   ", "|-- ")
+            };
+            dfs(
+                y,
+                &format!("{}{}", above, a),
+                &format!("{}{}", above, m),
+                &format!("{}|   ", above),
+                s,
+            );
+        }
+        writeln!(s, "{}{}", mid, x.line()).unwrap();
+        for (i, y) in lower.iter().rev().enumerate() {
+            let (m, b) = if i + 1 == lower.len() {
+                ("\\-- ", "    ")
+            } else {
+                ("|-- ", "|   ")
+            };
+            dfs(
+                y,
+                &format!("{}|   ", below),
+                &format!("{}{}", below, m),
+                &format!("{}{}", below, b),
+                s,
+            );
+        }
+    }
+    let mut s = String::new();
+    for x in shapes {
+        dfs(x, "", "", "", &mut s);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pretty, to_dot, Shape};
+    use crate::Color;
+
+    fn leaf(key: u32) -> Option<Shape> {
+        Some(Shape::new(key).child(None).child(None))
+    }
+
+    #[test]
+    fn test_pretty() {
+        let tree = Shape::new(4)
+            .color(Color::Black)
+            .attr("h", 3)
+            .child(Some(
+                Shape::new(2)
+                    .color(Color::Red)
+                    .child(leaf(1))
+                    .child(leaf(3)),
+            ))
+            .child(Some(Shape::new(6).child(None).child(leaf(7))));
+        let expected = [
+            "    /-- 7",
+            "/-- 6",
+            "4:B h=3",
+            "|   /-- 3",
+            "\\-- 2:R",
+            "    \\-- 1",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(pretty(&[tree]), expected);
+
+        // a B-tree
+        let tree = Shape::new(vec![4, 8])
+            .child(Some(Shape::new(vec![1, 2])))
+            .child(Some(Shape::new(vec![5])))
+            .child(Some(Shape::new(vec![9])));
+        let expected = ["/-- [9]", "|-- [5]", "[4, 8]", "\\-- [1, 2]", ""].join("\n");
+        assert_eq!(pretty(&[tree]), expected);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let tree = Shape::new(2)
+            .color(Color::Black)
+            .child(None)
+            .child(Some(Shape::new(3).color(Color::Red).attr("size", 1)));
+        let expected = [
+            "digraph {",
+            "    n0 [label=\"2\", style=filled, fillcolor=black, fontcolor=white];",
+            "    n1 [style=invis];",
+            "    n0 -> n1 [style=invis];",
+            "    n2 [label=\"3\\nsize=1\", style=filled, fillcolor=red, fontcolor=white];",
+            "    n0 -> n2;",
+            "}",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(to_dot(&[tree]), expected);
+    }
+}
@@ -1,8 +1,8 @@
+mod memory;
 mod paren;
 pub mod validate;
 
 use std::{cmp::Ordering, fmt::Debug, mem::replace};
-use yansi::Paint;
 
 pub struct RBTree<K, V>(BoxedNode<K, V>);
 impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
@@ -15,9 +15,14 @@ impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
     }
     pub fn remove(&mut self, k: K) -> bool {
         let res = self.0.remove(k).is_some();
-        self.0.set_color(Color::Black);
+        if !self.0.is_nil() {
+            self.0.set_color(Color::Black);
+        }
         res
     }
+    pub fn get(&self, k: &K) -> Option<&V> {
+        self.0.get(k)
+    }
     pub fn collect(&self) -> Vec<(K, V)>
     where
         K: Clone,
@@ -133,6 +138,14 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
     }
 
     // -- collect
+    fn get(&self, k: &K) -> Option<&V> {
+        let internal = self.as_internal()?;
+        match k.cmp(&internal.key) {
+            Ordering::Less => internal.child[0].get(k),
+            Ordering::Greater => internal.child[1].get(k),
+            Ordering::Equal => Some(&internal.value),
+        }
+    }
     fn collect(&self, vec: &mut Vec<(K, V)>)
     where
         K: Clone,
@@ -288,15 +301,6 @@ enum Color {
     Red,
     Black,
 }
-impl Color {
-    fn paint<T>(&self, x: T) -> Paint<T> {
-        match self {
-            Color::Red => Paint::red(x),
-            Color::Black => Paint::blue(x),
-        }
-        .bold()
-    }
-}
 enum DoubleRed {
     Me,
     Child(usize),
@@ -305,9 +309,63 @@ struct Charge();
 
 #[cfg(test)]
 mod tests {
-    use super::{validate, RBTree};
+    use super::{validate, Color, Node, RBTree};
+    use invariant::Property;
+    use memory_usage::MemoryUsage;
+    use paren::{FromParen, Paren};
     use rand::prelude::*;
     use span::Span;
+    use std::mem::size_of;
+
+    #[test]
+    fn test_paren() {
+        // CLRS Figure 13.4
+        let mut rbt =
+            RBTree::<u32, ()>::from_paren("(((1:B)2:R((5:R)7:B(8:R)))11:B(14:B(15:R)))").unwrap();
+        validate::all(&rbt);
+        rbt.insert(4, ());
+        let expected = "(((1:B)2:R((4:R)5:B))7:B((8:B)11:R(14:B(15:R))))";
+        assert_eq!(rbt.to_paren(), expected);
+        validate::all(&rbt);
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut rbt = RBTree::new();
+        (0..4).for_each(|i| rbt.insert(i, ()));
+        assert_eq!(rbt.check_invariants(), Ok(()));
+        let check = |rbt: &RBTree<u32, ()>| {
+            rbt.check_invariants()
+                .map_err(|e| (e.property, e.path))
+                .unwrap_err()
+        };
+        // ((0:B)1:B(2:B(3:R)))
+        let root = rbt.0.as_internal_mut().unwrap();
+        root.child[1].as_internal_mut().unwrap().color = Color::Red;
+        assert_eq!(check(&rbt), (Property::RedRed, vec![1, 1]));
+        let root = rbt.0.as_internal_mut().unwrap();
+        root.child[1].as_internal_mut().unwrap().color = Color::Black;
+        root.child[0].as_internal_mut().unwrap().color = Color::Red;
+        assert_eq!(check(&rbt), (Property::BlackHeight, vec![]));
+        rbt.0.as_internal_mut().unwrap().color = Color::Red;
+        assert_eq!(check(&rbt), (Property::BlackRoot, vec![]));
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        // the internal nodes and one more nils
+        let node = size_of::<Node<u32, ()>>();
+        let mut rbt = RBTree::new();
+        assert_eq!(rbt.heap_size_bytes(), node);
+        for k in 0..10 {
+            rbt.insert(k, ());
+        }
+        assert_eq!(rbt.heap_size_bytes(), 21 * node);
+        for k in 0..4 {
+            rbt.remove(k);
+        }
+        assert_eq!(rbt.heap_size_bytes(), 13 * node);
+    }
 
     #[test]
     fn test_hand_insert_delete() {
@@ -325,6 +383,11 @@ mod tests {
         remove(10, &mut rbt, &mut vec);
         remove(12, &mut rbt, &mut vec);
         remove(13, &mut rbt, &mut vec);
+        remove(9, &mut rbt, &mut vec);
+        remove(7, &mut rbt, &mut vec);
+        remove(8, &mut rbt, &mut vec);
+        remove(11, &mut rbt, &mut vec);
+        remove(11, &mut rbt, &mut vec);
     }
 
     #[test]
@@ -421,6 +484,9 @@ mod tests {
                 .as_slice(),
             vec
         );
+        for x in 0..30 {
+            assert_eq!(rbt.get(&x).is_some(), vec.contains(&x));
+        }
     }
     fn insert(k: u32, rbt: &mut RBTree<u32, ()>, vec: &mut Vec<u32>) {
         println!("Insert {:?}.", &k);
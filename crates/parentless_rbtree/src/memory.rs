@@ -0,0 +1,29 @@
+use {
+    super::{BoxedNode, Node, RBTree},
+    memory_usage::MemoryUsage,
+};
+
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for RBTree<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes()
+    }
+}
+// Every nil is boxed as well as the internal nodes.
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for BoxedNode<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes()
+    }
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Node<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        match self {
+            Node::Internal(internal) => {
+                internal.key.heap_size_bytes()
+                    + internal.value.heap_size_bytes()
+                    + internal.child[0].heap_size_bytes()
+                    + internal.child[1].heap_size_bytes()
+            }
+            Node::Nil => 0,
+        }
+    }
+}
@@ -1,8 +1,16 @@
-use super::{BoxedNode, Node, RBTree};
-use std::fmt::{self, Debug};
+use {
+    super::{BoxedNode, Color, Node, RBTree},
+    paren::{FromParen, Painted, Paren, ParseError, Shape, Visualize},
+    std::{
+        fmt::{self, Debug},
+        str::FromStr,
+    },
+};
 
-pub trait Paren {
-    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result;
+impl<K: Ord + Debug, V: Debug> Paren for RBTree<K, V> {
+    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        self.0.paren(w)
+    }
 }
 impl<K: Ord + Debug, V: Debug> Paren for BoxedNode<K, V> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
@@ -11,24 +19,91 @@ impl<K: Ord + Debug, V: Debug> Paren for BoxedNode<K, V> {
             Node::Internal(internal) => {
                 write!(w, "(")?;
                 internal.child[0].paren(w)?;
-                write!(w, "{:?}", internal.color.paint(&internal.key))?;
+                write!(
+                    w,
+                    "{:?}:{}",
+                    paren::paint(w, internal.color.into(), &internal.key),
+                    paren::Color::from(internal.color)
+                )?;
                 internal.child[1].paren(w)?;
                 write!(w, ")")
             }
         }
     }
 }
+
+impl<K: Ord + Debug, V: Debug> Visualize for RBTree<K, V> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.0.shape().into_iter().collect()
+    }
+}
+impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
+    fn shape(&self) -> Option<Shape> {
+        match &*self.0 {
+            Node::Nil => None,
+            Node::Internal(x) => Some(
+                Shape::new(&x.key)
+                    .color(x.color)
+                    .child(x.child[0].shape())
+                    .child(x.child[1].shape()),
+            ),
+        }
+    }
+}
+
+// The values are left default.
+impl<K: Ord + Debug + FromStr, V: Debug + Default> FromParen for RBTree<K, V> {
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        BoxedNode::from_parsed(paren::parse(s)?).map(Self)
+    }
+}
+impl<K: Ord + Debug + FromStr, V: Debug + Default> BoxedNode<K, V> {
+    fn from_parsed(node: Option<Box<paren::Node>>) -> Result<Self, ParseError> {
+        match node {
+            None => Ok(Self::nil()),
+            Some(node) => {
+                let paren::Node {
+                    label,
+                    child: [left, right],
+                } = *node;
+                let mut x = Self::new(label.key()?, V::default());
+                let internal = x.as_internal_mut().unwrap();
+                internal.color = label.color()?.into();
+                internal.child = [Self::from_parsed(left)?, Self::from_parsed(right)?];
+                Ok(x)
+            }
+        }
+    }
+}
+
+impl From<Color> for paren::Color {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Red => paren::Color::Red,
+            Color::Black => paren::Color::Black,
+        }
+    }
+}
+impl From<paren::Color> for Color {
+    fn from(color: paren::Color) -> Self {
+        match color {
+            paren::Color::Red => Color::Red,
+            paren::Color::Black => Color::Black,
+        }
+    }
+}
+
 impl<K: Ord + Debug, V: Debug> Debug for BoxedNode<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
         write!(w, "BoxedNode {{ ")?;
-        self.paren(w)?;
+        write!(w, "{:?}", Painted(self))?;
         write!(w, " }}")
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for RBTree<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
         write!(w, "RBTree {{ ")?;
-        self.0.paren(w)?;
+        write!(w, "{:?}", Painted(self))?;
         write!(w, " }}")
     }
 }
@@ -1,53 +1,61 @@
 use super::{BoxedNode, Color, RBTree};
+use invariant::{ensure, InvariantViolation, Property};
 use std::fmt::Debug;
 
-pub trait Validate {
-    fn no_double_red(&self);
-    fn consistent_black_height(&self) -> u32;
+pub fn all<T: Validate>(x: &T) {
+    x.validate()
 }
 
-impl<K: Ord + Debug, V: Debug> Validate for BoxedNode<K, V> {
-    fn no_double_red(&self) {
-        if let Some(internal) = self.as_internal() {
-            for i in 0..2 {
-                let child = &internal.child[i];
-                assert!(
-                    self.is_black() || child.is_black(),
-                    "Double red: self = {:?}, child = {:?}",
-                    self,
-                    child,
-                );
-                child.no_double_red()
-            }
-        }
-    }
-    fn consistent_black_height(&self) -> u32 {
-        if let Some(internal) = self.as_internal() {
-            let [x, y] = [
-                internal.child[0].consistent_black_height(),
-                internal.child[1].consistent_black_height(),
-            ];
-            assert!(x == y, "Inconsistent black height: self = {:?}", self,);
-            match internal.color {
-                Color::Black => x + 1,
-                Color::Red => x,
-            }
-        } else {
-            0
-        }
-    }
+// `check_invariants` that panics.
+pub trait Validate {
+    fn validate(&self);
 }
-
 impl<K: Ord + Debug, V: Debug> Validate for RBTree<K, V> {
-    fn no_double_red(&self) {
-        self.0.no_double_red()
-    }
-    fn consistent_black_height(&self) -> u32 {
-        self.0.consistent_black_height()
+    fn validate(&self) {
+        invariant::assert_holds(self.check_invariants())
     }
 }
 
-pub fn all<T: Validate>(x: &T) {
-    x.no_double_red();
-    x.consistent_black_height();
+impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
+    // the BST order and the colors
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        ensure(self.0.is_black(), Property::BlackRoot, &[])?;
+        check(&self.0, &mut Vec::new(), &mut None).map(|_| ())
+    }
+}
+// Returns the black height, with `prev` the last key in the in-order.
+fn check<'a, K: Ord + Debug, V: Debug>(
+    x: &'a BoxedNode<K, V>,
+    path: &mut Vec<usize>,
+    prev: &mut Option<&'a K>,
+) -> Result<u32, InvariantViolation> {
+    let node = match x.as_internal() {
+        None => return Ok(0),
+        Some(node) => node,
+    };
+    for (i, child) in node.child.iter().enumerate() {
+        path.push(i);
+        ensure(
+            node.color == Color::Black || child.is_black(),
+            Property::RedRed,
+            path,
+        )?;
+        path.pop();
+    }
+    path.push(0);
+    let left = check(&node.child[0], path, prev)?;
+    path.pop();
+    if let Some(prev) = prev {
+        ensure(*prev <= &node.key, Property::BstOrder, path)?;
+    }
+    *prev = Some(&node.key);
+    path.push(1);
+    let right = check(&node.child[1], path, prev)?;
+    path.pop();
+    ensure(left == right, Property::BlackHeight, path)?;
+    let bh = match node.color {
+        Color::Black => left + 1,
+        Color::Red => left,
+    };
+    Ok(bh)
 }
@@ -1,4 +1,5 @@
 mod color;
+mod memory;
 mod paren;
 pub mod validate;
 
@@ -30,6 +31,9 @@ impl<K: Ord + Debug, V: Debug> PersistentRBTree<K, V> {
             None
         }
     }
+    pub fn get(&self, i: usize, k: &K) -> Option<&V> {
+        self.0[i].get(k)
+    }
     pub fn collect_vec(&self, i: usize) -> Vec<(K, V)>
     where
         K: Clone,
@@ -301,6 +305,14 @@ impl<K: Ord + Debug, V: Debug> RcNode<K, V> {
             },
         }
     }
+    fn get(&self, k: &K) -> Option<&V> {
+        let me = self.0.as_ref()?;
+        match k.cmp(&me.kv.0) {
+            Ordering::Less => me.child[0].get(k),
+            Ordering::Greater => me.child[1].get(k),
+            Ordering::Equal => Some(&me.kv.1),
+        }
+    }
     fn collect_vec(&self, vec: &mut Vec<(K, V)>)
     where
         K: Clone,
@@ -352,8 +364,12 @@ struct Charge();
 
 #[cfg(test)]
 mod tests {
-    use super::{validate, PersistentRBTree};
+    use super::{validate, Color, Node, PersistentRBTree, RcNode};
+    use invariant::Property;
+    use memory_usage::{rc_bytes, MemoryUsage};
+    use paren::{FromParen, Paren};
     use rand::prelude::*;
+    use std::mem::size_of;
 
     #[test]
     fn test_rand_small() {
@@ -384,6 +400,59 @@ mod tests {
         test.delete(10);
     }
 
+    #[test]
+    fn test_paren() {
+        // CLRS Figure 13.4
+        let before = "(((1:B)2:R((5:R)7:B(8:R)))11:B(14:B(15:R)))";
+        let mut rbt = PersistentRBTree::<u32, ()>::from_paren(before).unwrap();
+        validate::all(&rbt);
+        rbt.insert(4, ());
+        validate::all(&rbt);
+        let after = "(((1:B)2:R((4:R)5:B))7:B((8:B)11:R(14:B(15:R))))";
+        assert_eq!(rbt.0[1].to_paren(), after);
+        assert_eq!(rbt.0[0].to_paren(), before);
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut rbt = PersistentRBTree::new();
+        (0..4).for_each(|i| rbt.insert(i, ()));
+        assert_eq!(rbt.check_invariants(), Ok(()));
+        let check = |rbt: &PersistentRBTree<u32, ()>| {
+            rbt.check_invariants()
+                .map_err(|e| (e.property, e.path))
+                .unwrap_err()
+        };
+        // ((0:B)1:B(2:B(3:R))), and the corrupted versions are pushed as the 5th.
+        let root = rbt.0[4].clone_node();
+        let child = root.child[1].clone_node().with_color(Color::Red).finish();
+        rbt.0.push(root.with_child(1, child).finish());
+        assert_eq!(check(&rbt), (Property::RedRed, vec![5, 1, 1]));
+        rbt.0[5] = rbt.0[4].clone_node().with_color(Color::Red).finish();
+        assert_eq!(check(&rbt), (Property::BlackRoot, vec![5]));
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let mut rbt = PersistentRBTree::<u32, ()>::new();
+        let roots =
+            |rbt: &PersistentRBTree<u32, ()>| rbt.0.capacity() * size_of::<RcNode<u32, ()>>();
+        assert_eq!(rbt.heap_size_bytes(), roots(&rbt));
+        for k in 0..10 {
+            rbt.insert(k, ());
+        }
+        // The copies of the paths are counted, but not the shared nodes nor the keys.
+        let kvs = 10 * rc_bytes::<(u32, ())>();
+        let nodes = rbt.heap_size_bytes() - roots(&rbt) - kvs;
+        assert_eq!(nodes % rc_bytes::<Node<u32, ()>>(), 0);
+        let nodes = nodes / rc_bytes::<Node<u32, ()>>();
+        assert!(10 < nodes && nodes < (1..=10).sum());
+        // The old versions keep the deleted key.
+        rbt.delete(5);
+        let nodes = rbt.heap_size_bytes() - roots(&rbt) - kvs;
+        assert_eq!(nodes % rc_bytes::<Node<u32, ()>>(), 0);
+    }
+
     fn test_rand(t: u32, q: u32, seed: u64) {
         let mut rng = StdRng::seed_from_u64(seed);
         for _ in 0..t {
@@ -427,6 +496,9 @@ mod tests {
                     .collect::<Vec<_>>();
                 let expected = self.vec[i].clone();
                 assert_eq!(result, expected, "Time = {}/{}", i, self.time);
+                for x in 0..10 {
+                    assert_eq!(self.rbt.get(i, &x).is_some(), expected.contains(&x));
+                }
             }
         }
         fn insert(&mut self, k: u32) {
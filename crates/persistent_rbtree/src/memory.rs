@@ -0,0 +1,37 @@
+use {
+    super::{Node, PersistentRBTree, RcNode},
+    memory_usage::{rc_bytes, MemoryUsage},
+    std::{collections::HashSet, mem::size_of, rc::Rc},
+};
+
+// The versions share their nodes, and the copies of a node share its key and value, so each of the
+// allocations is counted once by its address.
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for PersistentRBTree<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        let mut visited = Visited {
+            nodes: HashSet::new(),
+            kvs: HashSet::new(),
+        };
+        self.0.capacity() * size_of::<RcNode<K, V>>()
+            + self.0.iter().map(|root| visited.count(root)).sum::<usize>()
+    }
+}
+
+struct Visited<K, V> {
+    nodes: HashSet<*const Node<K, V>>,
+    kvs: HashSet<*const (K, V)>,
+}
+impl<K: MemoryUsage, V: MemoryUsage> Visited<K, V> {
+    // the allocations under `x` not visited yet
+    fn count(&mut self, x: &RcNode<K, V>) -> usize {
+        let node = match &x.0 {
+            Some(node) if self.nodes.insert(Rc::as_ptr(node)) => node,
+            _ => return 0,
+        };
+        let mut bytes = rc_bytes::<Node<K, V>>();
+        if self.kvs.insert(Rc::as_ptr(&node.kv)) {
+            bytes += rc_bytes::<(K, V)>() + node.kv.heap_size_bytes();
+        }
+        bytes + self.count(&node.child[0]) + self.count(&node.child[1])
+    }
+}
@@ -1,14 +1,23 @@
-use super::{Node, PersistentRBTree, RcNode};
-use std::fmt::{self, Debug};
+use {
+    super::{color::Color, Node, PersistentRBTree, RcNode},
+    paren::{FromParen, Painted, Paren, ParseError, Shape, Visualize},
+    std::{
+        fmt::{self, Debug},
+        rc::Rc,
+        str::FromStr,
+    },
+};
 
-pub trait Paren {
-    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result;
-}
 impl<K: Ord + Debug, V: Debug> Paren for Node<K, V> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
         write!(w, "(")?;
         self.child[0].paren(w)?;
-        write!(w, "{:?}", self.color.paint(&self.kv.0))?;
+        write!(
+            w,
+            "{:?}:{}",
+            paren::paint(w, self.color.into(), &self.kv.0),
+            paren::Color::from(self.color)
+        )?;
         self.child[1].paren(w)?;
         write!(w, ")")
     }
@@ -22,27 +31,78 @@ impl<K: Ord + Debug, V: Debug> Paren for RcNode<K, V> {
             .collect()
     }
 }
-struct ParenWrapper<'a, T>(&'a T);
-impl<'a, T: Paren> Debug for ParenWrapper<'a, T> {
-    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        self.0.paren(w)
+
+// One tree for each version.
+impl<K: Ord + Debug, V: Debug> Visualize for PersistentRBTree<K, V> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.0.iter().flat_map(RcNode::shape).collect()
+    }
+}
+impl<K: Ord + Debug, V: Debug> RcNode<K, V> {
+    fn shape(&self) -> Option<Shape> {
+        self.0.as_ref().map(|x| {
+            Shape::new(&x.kv.0)
+                .color(x.color)
+                .child(x.child[0].shape())
+                .child(x.child[1].shape())
+        })
+    }
+}
+
+// The tree of a single version. The values are left default.
+impl<K: Ord + Debug + FromStr, V: Debug + Default> FromParen for PersistentRBTree<K, V> {
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        RcNode::from_parsed(paren::parse(s)?).map(|root| Self(vec![root]))
+    }
+}
+impl<K: Ord + Debug + FromStr, V: Debug + Default> RcNode<K, V> {
+    fn from_parsed(node: Option<Box<paren::Node>>) -> Result<Self, ParseError> {
+        match node {
+            None => Ok(Self(None)),
+            Some(node) => {
+                let paren::Node {
+                    label,
+                    child: [left, right],
+                } = *node;
+                Ok(Self::from_node(Node {
+                    child: [Self::from_parsed(left)?, Self::from_parsed(right)?],
+                    kv: Rc::new((label.key()?, V::default())),
+                    color: label.color()?.into(),
+                }))
+            }
+        }
+    }
+}
+
+impl From<Color> for paren::Color {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Red => paren::Color::Red,
+            Color::Black => paren::Color::Black,
+        }
+    }
+}
+impl From<paren::Color> for Color {
+    fn from(color: paren::Color) -> Self {
+        match color {
+            paren::Color::Red => Color::Red,
+            paren::Color::Black => Color::Black,
+        }
     }
 }
 
 impl<K: Ord + Debug, V: Debug> Debug for Node<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("Node").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("Node").field(&Painted(self)).finish()
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for RcNode<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("RcNode").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("RcNode").field(&Painted(self)).finish()
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for PersistentRBTree<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_list()
-            .entries(self.0.iter().map(|x| ParenWrapper(x)))
-            .finish()
+        w.debug_list().entries(self.0.iter().map(Painted)).finish()
     }
 }
@@ -1,61 +1,63 @@
-use super::{Color, Node, PersistentRBTree, RcNode};
+use super::{Color, PersistentRBTree, RcNode};
+use invariant::{ensure, InvariantViolation, Property};
 use std::fmt::Debug;
 
-pub trait Validate {
-    fn root_is_black(&self) {}
-    fn no_double_red(&self);
-    fn consistent_black_height(&self) -> u32;
-}
-
 pub fn all<T: Validate>(x: &T) {
-    x.root_is_black();
-    x.no_double_red();
-    x.consistent_black_height();
+    x.validate()
 }
 
-impl<K: Ord + Debug, V: Debug> Validate for Node<K, V> {
-    fn no_double_red(&self) {
-        for child in self.child.iter() {
-            child.no_double_red();
-            assert!(
-                self.color == Color::Black || child.is_black(),
-                "Double red: self = {:?}, child = {:?}",
-                self,
-                child
-            );
-        }
-    }
-    fn consistent_black_height(&self) -> u32 {
-        let x = self.child[0].consistent_black_height();
-        let y = self.child[1].consistent_black_height();
-        assert_eq!(x, y, "self = {:?}", &self);
-        x
-    }
+// `check_invariants` that panics.
+pub trait Validate {
+    fn validate(&self);
 }
-impl<K: Ord + Debug, V: Debug> Validate for RcNode<K, V> {
-    fn no_double_red(&self) {
-        self.0.as_ref().iter().for_each(|x| x.no_double_red())
-    }
-    fn consistent_black_height(&self) -> u32 {
-        self.0.as_ref().map_or(0, |x| x.consistent_black_height())
+impl<K: Ord + Debug, V: Debug> Validate for PersistentRBTree<K, V> {
+    fn validate(&self) {
+        invariant::assert_holds(self.check_invariants())
     }
 }
-impl<K: Ord + Debug, V: Debug> Validate for PersistentRBTree<K, V> {
-    fn no_double_red(&self) {
-        self.0.iter().for_each(Validate::no_double_red)
+
+impl<K: Ord + Debug, V: Debug> PersistentRBTree<K, V> {
+    // the BST order and the colors of every version, with the version at the head of the path
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        for (i, root) in self.0.iter().enumerate() {
+            ensure(root.is_black(), Property::BlackRoot, &[i])?;
+            check(root, &mut vec![i], &mut None)?;
+        }
+        Ok(())
     }
-    fn consistent_black_height(&self) -> u32 {
-        self.0
-            .iter()
-            .map(Validate::consistent_black_height)
-            .for_each(|_| {});
-        0
+}
+// Returns the black height, with `prev` the last key in the in-order.
+fn check<'a, K: Ord + Debug, V: Debug>(
+    x: &'a RcNode<K, V>,
+    path: &mut Vec<usize>,
+    prev: &mut Option<&'a K>,
+) -> Result<u32, InvariantViolation> {
+    let node = match x.0.as_ref() {
+        None => return Ok(0),
+        Some(node) => node,
+    };
+    for (i, child) in node.child.iter().enumerate() {
+        path.push(i);
+        ensure(
+            node.color == Color::Black || child.is_black(),
+            Property::RedRed,
+            path,
+        )?;
+        path.pop();
     }
-    fn root_is_black(&self) {
-        assert!(
-            self.0.iter().all(|root| root.is_black()),
-            "Root is not black: {:?}",
-            &self
-        )
+    path.push(0);
+    let left = check(&node.child[0], path, prev)?;
+    path.pop();
+    if let Some(prev) = prev {
+        ensure(*prev <= &node.kv.0, Property::BstOrder, path)?;
     }
+    *prev = Some(&node.kv.0);
+    path.push(1);
+    let right = check(&node.child[1], path, prev)?;
+    path.pop();
+    ensure(left == right, Property::BlackHeight, path)?;
+    Ok(match node.color {
+        Color::Black => left + 1,
+        Color::Red => left,
+    })
 }
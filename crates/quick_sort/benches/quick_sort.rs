@@ -0,0 +1,93 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use rand::prelude::*;
+use std::iter::repeat_with;
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+fn sorted(n: usize, _rng: &mut StdRng) -> Vec<u32> {
+    (0..n as u32).collect()
+}
+fn reverse_sorted(n: usize, _rng: &mut StdRng) -> Vec<u32> {
+    (0..n as u32).rev().collect()
+}
+fn organ_pipe(n: usize, _rng: &mut StdRng) -> Vec<u32> {
+    (0..n as u32 / 2)
+        .chain((0..(n - n / 2) as u32).rev())
+        .collect()
+}
+fn few_unique(n: usize, rng: &mut StdRng) -> Vec<u32> {
+    repeat_with(|| rng.gen_range(0, 4)).take(n).collect()
+}
+fn random(n: usize, rng: &mut StdRng) -> Vec<u32> {
+    repeat_with(|| rng.gen_range(0, u32::MAX))
+        .take(n)
+        .collect()
+}
+
+type Input = fn(usize, &mut StdRng) -> Vec<u32>;
+
+// O(n lg n) except that `quick_sort` is quadratic on all but `random`, which is why it is left
+// out of the others.
+fn bench_sorts(c: &mut Criterion) {
+    let inputs: [(&str, Input); 5] = [
+        ("Sorted", sorted),
+        ("Reverse sorted", reverse_sorted),
+        ("Organ pipe", organ_pipe),
+        ("Few unique", few_unique),
+        ("Random", random),
+    ];
+    let mut rng = StdRng::seed_from_u64(42);
+    for &(name, input) in inputs.iter() {
+        let mut group = c.benchmark_group(name);
+        for &n in SIZES.iter() {
+            group.throughput(Throughput::Elements(n as u64));
+            let mut bench = |id: &str, sort: fn(&mut [u32])| {
+                group.bench_with_input(BenchmarkId::new(id, n), &n, |b, &n| {
+                    b.iter_batched_ref(|| input(n, &mut rng), |a| sort(a), BatchSize::LargeInput)
+                });
+            };
+            bench("introsort", quick_sort::introsort);
+            bench("randomized_quick_sort", quick_sort::randomized_quick_sort);
+            bench("heap_sort", heap_sort::heap_sort);
+            bench("slice::sort_unstable", <[u32]>::sort_unstable);
+            if name == "Random" {
+                bench("quick_sort", quick_sort::quick_sort);
+            }
+        }
+    }
+}
+
+// The speedup should approach the number of the threads as long as there are enough cores.
+fn bench_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Parallel");
+    let mut rng = StdRng::seed_from_u64(42);
+    let n = 1_000_000;
+    group.throughput(Throughput::Elements(n as u64));
+    for &threads in [1, 2, 4, 8].iter() {
+        group.bench_with_input(
+            BenchmarkId::new("par_introsort", threads),
+            &threads,
+            |b, &threads| {
+                b.iter_batched_ref(
+                    || random(n, &mut rng),
+                    |a| quick_sort::par_introsort(a, threads),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("par_radix_sort", threads),
+            &threads,
+            |b, &threads| {
+                b.iter_batched_ref(
+                    || random(n, &mut rng),
+                    |a| linear_time_sorts::par_radix_sort(a, 8, threads),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+}
+
+criterion_group!(benches, bench_sorts, bench_parallel);
+criterion_main!(benches);
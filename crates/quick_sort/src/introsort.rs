@@ -0,0 +1,143 @@
+use {
+    super::partition3,
+    heap_sort::heap_sort_by,
+    linear_time_sorts::insertion_sort_by,
+    std::{cmp::Ordering, mem::size_of},
+};
+
+// Slices up to this length are left to the insertion sort.
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+pub fn introsort<T: Ord>(a: &mut [T]) {
+    introsort_by(a, T::cmp);
+}
+pub fn introsort_by_key<T, K: Ord>(a: &mut [T], mut f: impl FnMut(&T) -> K) {
+    introsort_by(a, |x, y| f(x).cmp(&f(y)));
+}
+// Quicksort that switches to the heap sort when the recursion gets deeper than 2 lg n, so it
+// takes O(n lg n) in the worst case. It is not stable.
+pub fn introsort_by<T>(a: &mut [T], mut compare: impl FnMut(&T, &T) -> Ordering) {
+    dfs(a, depth_limit(a.len()), &mut compare);
+}
+
+// 2 lg n
+pub fn depth_limit(n: usize) -> u32 {
+    2 * (size_of::<usize>() as u32 * 8 - n.leading_zeros())
+}
+
+pub fn dfs<T>(mut a: &mut [T], mut depth: u32, compare: &mut impl FnMut(&T, &T) -> Ordering) {
+    loop {
+        if a.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort_by(a, &mut *compare);
+            return;
+        }
+        if depth == 0 {
+            heap_sort_by(a, &mut *compare);
+            return;
+        }
+        depth -= 1;
+        median_of_three(a, compare);
+        let (lt, gt) = partition3(a, compare);
+        // Recurse into the shorter side and loop on the longer one, so that the stack depth is
+        // O(lg n).
+        let (left, rest) = a.split_at_mut(lt);
+        let right = &mut rest[gt - lt..];
+        if left.len() < right.len() {
+            dfs(left, depth, compare);
+            a = right;
+        } else {
+            dfs(right, depth, compare);
+            a = left;
+        }
+    }
+}
+
+// Move the median of the first, middle and last elements to the front.
+pub fn median_of_three<T>(a: &mut [T], compare: &mut impl FnMut(&T, &T) -> Ordering) {
+    let (i, j, k) = (0, a.len() / 2, a.len() - 1);
+    let mut less = |x: usize, y: usize| compare(&a[x], &a[y]) == Ordering::Less;
+    let median = if less(i, j) {
+        if less(j, k) {
+            j
+        } else if less(i, k) {
+            k
+        } else {
+            i
+        }
+    } else if less(i, k) {
+        i
+    } else if less(j, k) {
+        k
+    } else {
+        j
+    };
+    a.swap(0, median);
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{dfs, introsort, introsort_by, introsort_by_key},
+        rand::prelude::*,
+        std::{cmp::Reverse, iter::repeat_with},
+    };
+
+    #[test]
+    fn test_rand() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let n = rng.gen_range(0, 200);
+            let m = rng.gen_range(1, 300);
+            let a = repeat_with(|| (rng.gen_range(0, m), rng.gen_range(0, 10)))
+                .take(n)
+                .collect::<Vec<(u32, u32)>>();
+            let mut expected = a.clone();
+            expected.sort();
+            let mut result = a.clone();
+            introsort(&mut result);
+            assert_eq!(&result, &expected);
+
+            // It is unstable, so only the keys are compared.
+            let keys = |a: &[(u32, u32)]| a.iter().map(|&(x, _)| Reverse(x)).collect::<Vec<_>>();
+            let mut expected = keys(&a);
+            expected.sort();
+            let mut result = a.clone();
+            introsort_by(&mut result, |x, y| y.0.cmp(&x.0));
+            assert_eq!(&keys(&result), &expected);
+            let mut result = a.clone();
+            introsort_by_key(&mut result, |&(x, _)| Reverse(x));
+            assert_eq!(&keys(&result), &expected);
+        }
+    }
+
+    // They would take quadratic time and overflow the stack with the Lomuto partition.
+    #[test]
+    fn test_adversarial_inputs() {
+        let n = 100_000;
+        let inputs = vec![
+            (0..n).collect::<Vec<u32>>(),
+            (0..n).rev().collect(),
+            (0..n / 2).chain((0..n / 2).rev()).collect(),
+            (0..n).map(|x| x % 4).collect(),
+            vec![0; n as usize],
+        ];
+        for mut a in inputs {
+            let mut expected = a.clone();
+            expected.sort();
+            introsort(&mut a);
+            assert_eq!(a, expected);
+        }
+    }
+
+    #[test]
+    fn test_heap_sort_fallback() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut a = repeat_with(|| rng.gen_range(0, 100))
+            .take(1000)
+            .collect::<Vec<u32>>();
+        let mut expected = a.clone();
+        expected.sort();
+        dfs(&mut a, 0, &mut u32::cmp);
+        assert_eq!(a, expected);
+    }
+}
@@ -1,34 +1,71 @@
-pub fn quick_sort(a: &mut [u32]) {
-    if 2 <= a.len() {
-        let i = partition(a);
-        quick_sort(&mut a[..i]);
-        quick_sort(&mut a[i + 1..]);
+mod introsort;
+mod parallel;
+
+pub use {
+    introsort::{introsort, introsort_by, introsort_by_key, median_of_three},
+    parallel::{par_introsort, par_introsort_by, par_introsort_by_key},
+};
+
+use {rand::prelude::*, std::cmp::Ordering};
+
+pub fn quick_sort<T: Ord>(a: &mut [T]) {
+    quick_sort_by(a, T::cmp);
+}
+pub fn quick_sort_by_key<T, K: Ord>(a: &mut [T], mut f: impl FnMut(&T) -> K) {
+    quick_sort_by(a, |x, y| f(x).cmp(&f(y)));
+}
+// It takes O(n^2) on a sorted input, but the stack depth is O(lg n), since it recurses into the
+// shorter side and loops on the longer one as `introsort_by` does.
+pub fn quick_sort_by<T>(a: &mut [T], mut compare: impl FnMut(&T, &T) -> Ordering) {
+    fn dfs<T>(mut a: &mut [T], compare: &mut impl FnMut(&T, &T) -> Ordering) {
+        while 2 <= a.len() {
+            let i = partition(a, compare);
+            let (left, rest) = a.split_at_mut(i);
+            let right = &mut rest[1..];
+            if left.len() < right.len() {
+                dfs(left, compare);
+                a = right;
+            } else {
+                dfs(right, compare);
+                a = left;
+            }
+        }
     }
+    dfs(a, &mut compare);
 }
 
-pub fn randomized_quick_sort(a: &mut [u32]) {
-    use rand::prelude::*;
-    let mut rng = StdRng::seed_from_u64(42);
-    pub fn dfs(a: &mut [u32], rng: &mut StdRng) {
-        let n = a.len();
-        if 2 <= n {
-            let i = rng.gen_range(0, n);
-            a.swap(i, n - 1);
-            let i = partition(a);
-            dfs(&mut a[..i], rng);
-            dfs(&mut a[i + 1..], rng);
+pub fn randomized_quick_sort<T: Ord>(a: &mut [T]) {
+    randomized_quick_sort_by(a, T::cmp);
+}
+pub fn randomized_quick_sort_by_key<T, K: Ord>(a: &mut [T], mut f: impl FnMut(&T) -> K) {
+    randomized_quick_sort_by(a, |x, y| f(x).cmp(&f(y)));
+}
+pub fn randomized_quick_sort_by<T>(a: &mut [T], mut compare: impl FnMut(&T, &T) -> Ordering) {
+    fn dfs<T>(mut a: &mut [T], compare: &mut impl FnMut(&T, &T) -> Ordering, rng: &mut ThreadRng) {
+        while 2 <= a.len() {
+            a.swap(0, rng.gen_range(0, a.len()));
+            let (lt, gt) = partition3(a, compare);
+            // the same as `quick_sort_by`
+            let (left, rest) = a.split_at_mut(lt);
+            let right = &mut rest[gt - lt..];
+            if left.len() < right.len() {
+                dfs(left, compare, rng);
+                a = right;
+            } else {
+                dfs(right, compare, rng);
+                a = left;
+            }
         }
     }
-    dfs(a, &mut rng);
+    dfs(a, &mut compare, &mut thread_rng());
 }
 
-fn partition(a: &mut [u32]) -> usize {
+fn partition<T>(a: &mut [T], compare: &mut impl FnMut(&T, &T) -> Ordering) -> usize {
     let n = a.len();
     assert!(2 <= n);
-    let x = a[n - 1];
     let mut i = 0;
     for j in 0..n - 1 {
-        if a[j] < x {
+        if compare(&a[j], &a[n - 1]) == Ordering::Less {
             a.swap(i, j);
             i += 1;
         }
@@ -37,9 +74,41 @@ fn partition(a: &mut [u32]) -> usize {
     i
 }
 
+// Dutch national flag partitioning around `a[0]`: returns `(lt, gt)` such that `a[..lt]`,
+// `a[lt..gt]` and `a[gt..]` are less than, equal to and greater than the pivot respectively.
+pub fn partition3<T>(a: &mut [T], compare: &mut impl FnMut(&T, &T) -> Ordering) -> (usize, usize) {
+    assert!(!a.is_empty());
+    // The pivot is kept at `a[lt]`, the head of the equal block `a[lt..i]`.
+    let mut lt = 0;
+    let mut i = 1;
+    let mut gt = a.len();
+    while i < gt {
+        match compare(&a[i], &a[lt]) {
+            Ordering::Less => {
+                a.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Equal => i += 1,
+            Ordering::Greater => {
+                gt -= 1;
+                a.swap(i, gt);
+            }
+        }
+    }
+    (lt, gt)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{quick_sort, randomized_quick_sort};
+    use {
+        super::{
+            quick_sort, quick_sort_by, quick_sort_by_key, randomized_quick_sort,
+            randomized_quick_sort_by, randomized_quick_sort_by_key,
+        },
+        rand::prelude::*,
+        std::cmp::Reverse,
+    };
 
     #[test]
     fn test_quick_sort() {
@@ -64,4 +133,75 @@ mod tests {
         ];
         assert_eq!(&a, &expected);
     }
+
+    type Sort = fn(&mut [(u32, u32)]);
+
+    #[test]
+    fn test_rand_by() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let n = rng.gen_range(0, 50);
+            // (key, payload): the payloads are not compared, and the sorts are unstable.
+            let a = std::iter::repeat_with(|| (rng.gen_range(0, 10), rng.gen_range(0, 10)))
+                .take(n)
+                .collect::<Vec<(u32, u32)>>();
+            let mut expected = a.clone();
+            expected.sort_by_key(|&(x, _)| Reverse(x));
+            let mut expected_multiset = a.clone();
+            expected_multiset.sort();
+
+            let sorts: [Sort; 4] = [
+                |a| quick_sort_by(a, |x, y| y.0.cmp(&x.0)),
+                |a| quick_sort_by_key(a, |&(x, _)| Reverse(x)),
+                |a| randomized_quick_sort_by(a, |x, y| y.0.cmp(&x.0)),
+                |a| randomized_quick_sort_by_key(a, |&(x, _)| Reverse(x)),
+            ];
+            for sort in sorts.iter() {
+                let mut result = a.clone();
+                sort(&mut result);
+                assert!(result
+                    .iter()
+                    .zip(expected.iter())
+                    .all(|(&(x, _), &(y, _))| x == y));
+                result.sort();
+                assert_eq!(&result, &expected_multiset);
+            }
+        }
+    }
+
+    // The sorted inputs make the deepest partitions, which would overflow the small stack if both
+    // sides were recursed into.
+    #[test]
+    fn test_sorted_inputs() {
+        std::thread::Builder::new()
+            .stack_size(1 << 16)
+            .spawn(|| {
+                let a = (0..5_000).collect::<Vec<u32>>();
+                let mut result = a.clone();
+                quick_sort(&mut result);
+                assert_eq!(&result, &a);
+                let mut result = a.iter().rev().copied().collect::<Vec<_>>();
+                quick_sort(&mut result);
+                assert_eq!(&result, &a);
+                let a = (0..100_000).collect::<Vec<u32>>();
+                let mut result = a.clone();
+                randomized_quick_sort(&mut result);
+                assert_eq!(&result, &a);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_strings() {
+        let mut a = ["banana", "apple", "cherry", "apple"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        quick_sort(&mut a);
+        assert_eq!(&a, &["apple", "apple", "banana", "cherry"]);
+        quick_sort_by_key(&mut a, |s| Reverse(s.len()));
+        assert!(a[..2].iter().all(|s| s.len() == 6));
+    }
 }
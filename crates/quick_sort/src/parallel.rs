@@ -0,0 +1,84 @@
+use {
+    super::{
+        introsort::{depth_limit, dfs, median_of_three},
+        partition3,
+    },
+    std::{cmp::Ordering, thread},
+};
+
+// Slices up to this length are sorted in the current thread.
+const PARALLEL_THRESHOLD: usize = 1 << 14;
+
+pub fn par_introsort<T: Ord + Send>(a: &mut [T], threads: usize) {
+    par_introsort_by(a, threads, T::cmp);
+}
+pub fn par_introsort_by_key<T: Send, K: Ord>(
+    a: &mut [T],
+    threads: usize,
+    f: impl Fn(&T) -> K + Sync,
+) {
+    par_introsort_by(a, threads, |x, y| f(x).cmp(&f(y)));
+}
+// `introsort_by` that sorts the two sides of each partition in parallel, using at most `threads`
+// threads. It makes the same partitions as `introsort_by`, so the results are identical.
+pub fn par_introsort_by<T: Send>(
+    a: &mut [T],
+    threads: usize,
+    compare: impl Fn(&T, &T) -> Ordering + Sync,
+) {
+    assert!(threads >= 1);
+    par_dfs(a, depth_limit(a.len()), threads, &compare);
+}
+
+fn par_dfs<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(
+    a: &mut [T],
+    depth: u32,
+    threads: usize,
+    compare: &F,
+) {
+    if threads == 1 || a.len() <= PARALLEL_THRESHOLD || depth == 0 {
+        dfs(a, depth, &mut |x, y| compare(x, y));
+        return;
+    }
+    median_of_three(a, &mut |x, y| compare(x, y));
+    let (lt, gt) = partition3(a, &mut |x, y| compare(x, y));
+    let (left, rest) = a.split_at_mut(lt);
+    let right = &mut rest[gt - lt..];
+    thread::scope(|s| {
+        s.spawn(|| par_dfs(left, depth - 1, threads / 2, compare));
+        par_dfs(right, depth - 1, threads - threads / 2, compare);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{par_introsort, par_introsort_by_key},
+        crate::{introsort, introsort_by_key},
+        rand::prelude::*,
+        std::iter::repeat_with,
+    };
+
+    #[test]
+    fn test_same_as_sequential() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for &n in [0, 1, 1000, 100_000, 300_000].iter() {
+            let a = repeat_with(|| (rng.gen_range(0, 100), rng.gen_range(0, 100)))
+                .take(n)
+                .collect::<Vec<(u32, u32)>>();
+            let mut expected = a.clone();
+            introsort(&mut expected);
+            let mut expected_by_key = a.clone();
+            introsort_by_key(&mut expected_by_key, |&(x, _)| x);
+            for &threads in [1, 2, 3, 8].iter() {
+                let mut result = a.clone();
+                par_introsort(&mut result, threads);
+                assert_eq!(result, expected);
+                // The ties are broken in the same way as well.
+                let mut result = a.clone();
+                par_introsort_by_key(&mut result, threads, |&(x, _)| x);
+                assert_eq!(result, expected_by_key);
+            }
+        }
+    }
+}
@@ -0,0 +1,641 @@
+use {
+    super::color::Color,
+    invariant::{ensure, InvariantViolation, Property},
+    memory_usage::MemoryUsage,
+    paren::{FromParen, Painted, Paren, ParseError, Shape, Visualize},
+    std::{
+        fmt::{self, Debug},
+        str::FromStr,
+    },
+};
+
+// the sentinel of CLRS, which is the parent of the root and the child of the leaves
+const NIL: u32 = 0;
+
+// The same as `super::RBTree` except that the nodes are stored in a `Vec` and linked by their
+// `u32` indices, with the nil sentinel at `NIL`. No link needs a dynamic borrow, and it is `Send`.
+//
+// The slots of the deleted nodes are reused by the next insertions.
+//
+// It does not reach 3x the speed of `super::RBTree` in `delete` and `mixed` of the trees bench on
+// the random keys: it is 2.4-4.1x up to 10^5 keys, 2.0x at 10^6 and 1.3-1.5x at 10^7, where both
+// trees wait for a cache miss at every hop and the links no longer make the difference.
+pub struct RBTree<K, V> {
+    root: u32,
+    nodes: Vec<Node<K, V>>,
+    free: Vec<u32>,
+    len: usize,
+}
+struct Node<K, V> {
+    parent: u32,
+    child: [u32; 2],
+    color: Color,
+    // `None` for the sentinel and the free slots
+    entry: Option<(K, V)>,
+}
+impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+    // `capacity` nodes allocated ahead
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut nodes = Vec::with_capacity(capacity + 1);
+        nodes.push(Node {
+            parent: NIL,
+            child: [NIL; 2],
+            color: Color::Black,
+            entry: None,
+        });
+        Self {
+            root: NIL,
+            nodes,
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn insert(&mut self, k: K, v: V) {
+        trace!(Begin {
+            op: "insert".to_owned(),
+            key: trace::key(&k),
+        });
+        trace!(Attach {
+            label: format!("{:?}:R", k),
+        });
+        let mut y = NIL;
+        let mut x = self.root;
+        let mut i = 0;
+        while x != NIL {
+            y = x;
+            i = if &k <= self.key(x) { 0 } else { 1 };
+            x = self.node(x).child[i];
+        }
+        let z = self.alloc(y, k, v);
+        if y == NIL {
+            self.root = z;
+        } else {
+            self.node_mut(y).child[i] = z;
+        }
+        self.insert_fixup(z);
+        self.len += 1;
+    }
+    pub fn delete(&mut self, k: &K) -> Option<(K, V)> {
+        trace!(Begin {
+            op: "delete".to_owned(),
+            key: trace::key(k),
+        });
+        let z = self.find_node(k)?;
+        let child = self.node(z).child;
+        let (x, color) = if let Some(i) = child.iter().position(|&c| c == NIL) {
+            trace!(Splice {
+                key: trace::key(self.key(z)),
+            });
+            self.transplant(z, child[1 - i]);
+            (child[1 - i], self.node(z).color)
+        } else {
+            let y = self.minimum(child[1]);
+            let x = self.node(y).child[1];
+            trace!(Splice {
+                key: trace::key(self.key(y)),
+            });
+            self.transplant(y, x);
+            trace!(Replace {
+                key: trace::key(self.key(z)),
+                by: trace::key(self.key(y)),
+            });
+            self.transplant(z, y);
+            self.swap_color(y, z);
+            let color = self.node(z).color;
+            // The right child of `z` is `x` now if it has been `y`, whose parent is to be `y`
+            // even if `x` is the sentinel.
+            let child = self.node(z).child;
+            self.connect(y, 0, child[0]);
+            self.connect(y, 1, child[1]);
+            (x, color)
+        };
+        if self.root != NIL && color == Color::Black {
+            self.delete_fixup(x);
+        }
+        self.len -= 1;
+        self.free.push(z);
+        self.node_mut(z).entry.take()
+    }
+    pub fn get(&self, k: &K) -> Option<&V> {
+        self.find_node(k)
+            .map(|x| &self.node(x).entry.as_ref().unwrap().1)
+    }
+    pub fn collect_vec(&self) -> Vec<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut vec = Vec::with_capacity(self.len);
+        self.collect(self.root, &mut vec);
+        vec
+    }
+    fn collect(&self, x: u32, vec: &mut Vec<(K, V)>)
+    where
+        K: Clone,
+        V: Clone,
+    {
+        if x != NIL {
+            self.collect(self.node(x).child[0], vec);
+            vec.push(self.node(x).entry.clone().unwrap());
+            self.collect(self.node(x).child[1], vec);
+        }
+    }
+    // The side is taken by a comparison instead of a `match`, which compiles to a `setcc`. A branch
+    // there would be mispredicted at half the hops for random keys.
+    fn find_node(&self, k: &K) -> Option<u32> {
+        let mut x = self.root;
+        while x != NIL {
+            let key = self.key(x);
+            if k == key {
+                return Some(x);
+            }
+            x = self.node(x).child[(k > key) as usize];
+        }
+        None
+    }
+    fn insert_fixup(&mut self, mut x: u32) {
+        while self.node(self.node(x).parent).color == Color::Red {
+            let i = self.index(x);
+            let p = self.node(x).parent;
+            let j = self.index(p);
+            let pp = self.node(p).parent;
+            assert_ne!(pp, NIL);
+            assert_eq!(self.node(pp).color, Color::Black);
+            let y = self.node(pp).child[1 - j];
+            if self.node(y).color == Color::Red {
+                trace!(Case {
+                    fixup: "insert_fixup".to_owned(),
+                    case: 1,
+                });
+                self.set_color(p, Color::Black);
+                self.set_color(y, Color::Black);
+                self.set_color(pp, Color::Red);
+                x = pp;
+            } else if i == j {
+                trace!(Case {
+                    fixup: "insert_fixup".to_owned(),
+                    case: 3,
+                });
+                self.set_color(p, Color::Black);
+                self.set_color(pp, Color::Red);
+                self.rotate(pp, j);
+            } else {
+                trace!(Case {
+                    fixup: "insert_fixup".to_owned(),
+                    case: 2,
+                });
+                self.rotate(p, i);
+                x = p;
+            }
+        }
+        self.set_color(self.root, Color::Black);
+    }
+    // `x` may be the sentinel, whose parent has been set by `transplant` or `connect`.
+    fn delete_fixup(&mut self, mut x: u32) {
+        while x != self.root && self.node(x).color == Color::Black {
+            let i = self.index(x);
+            let p = self.node(x).parent;
+            let y = self.node(p).child[1 - i];
+            assert_ne!(y, NIL);
+            if self.node(y).color == Color::Red {
+                trace!(Case {
+                    fixup: "delete_fixup".to_owned(),
+                    case: 1,
+                });
+                self.swap_color(p, y);
+                self.rotate(p, 1 - i);
+            } else {
+                let child = self.node(y).child;
+                if self.node(child[0]).color == Color::Black
+                    && self.node(child[1]).color == Color::Black
+                {
+                    trace!(Case {
+                        fixup: "delete_fixup".to_owned(),
+                        case: 2,
+                    });
+                    self.set_color(y, Color::Red);
+                    x = p;
+                } else if self.node(child[1 - i]).color == Color::Black {
+                    trace!(Case {
+                        fixup: "delete_fixup".to_owned(),
+                        case: 3,
+                    });
+                    self.swap_color(y, child[i]);
+                    self.rotate(y, i);
+                } else {
+                    trace!(Case {
+                        fixup: "delete_fixup".to_owned(),
+                        case: 4,
+                    });
+                    self.swap_color(p, y);
+                    self.set_color(child[1 - i], Color::Black);
+                    self.rotate(p, 1 - i);
+                    x = self.root;
+                }
+            }
+        }
+        self.set_color(x, Color::Black);
+    }
+    fn rotate(&mut self, x: u32, i: usize) {
+        assert_ne!(x, NIL);
+        let y = self.node(x).child[i];
+        assert_ne!(y, NIL);
+        let z = self.node(y).child[1 - i];
+        if i == 1 {
+            trace!(RotateLeft {
+                key: trace::key(self.key(x)),
+            });
+        } else {
+            trace!(RotateRight {
+                key: trace::key(self.key(x)),
+            });
+        }
+        self.transplant(x, y);
+        self.connect(y, 1 - i, x);
+        self.node_mut(x).child[i] = z;
+        if z != NIL {
+            self.node_mut(z).parent = x;
+        }
+    }
+    // Replaces the subtree `x` by `y`, which may be the sentinel.
+    fn transplant(&mut self, x: u32, y: u32) {
+        let p = self.node(x).parent;
+        if p == NIL {
+            assert_eq!(self.root, x);
+            self.root = y;
+        } else {
+            let i = self.index(x);
+            self.node_mut(p).child[i] = y;
+        }
+        self.node_mut(y).parent = p;
+    }
+    fn connect(&mut self, x: u32, i: usize, y: u32) {
+        self.node_mut(x).child[i] = y;
+        self.node_mut(y).parent = x;
+    }
+    fn minimum(&self, mut x: u32) -> u32 {
+        while self.node(x).child[0] != NIL {
+            x = self.node(x).child[0];
+        }
+        x
+    }
+    fn alloc(&mut self, parent: u32, k: K, v: V) -> u32 {
+        let node = Node {
+            parent,
+            child: [NIL; 2],
+            color: Color::Red,
+            entry: Some((k, v)),
+        };
+        match self.free.pop() {
+            Some(x) => {
+                *self.node_mut(x) = node;
+                x
+            }
+            None => {
+                assert!(self.nodes.len() < u32::MAX as usize);
+                self.nodes.push(node);
+                (self.nodes.len() - 1) as u32
+            }
+        }
+    }
+    // The sentinel is black all the time.
+    fn set_color(&mut self, x: u32, color: Color) {
+        if x == NIL {
+            assert_eq!(color, Color::Black);
+            return;
+        }
+        #[cfg(feature = "trace")]
+        {
+            if self.node(x).color != color {
+                trace!(SetColor {
+                    key: trace::key(self.key(x)),
+                    color: color.into(),
+                });
+            }
+        }
+        self.node_mut(x).color = color;
+    }
+    fn swap_color(&mut self, x: u32, y: u32) {
+        let color = self.node(x).color;
+        self.set_color(x, self.node(y).color);
+        self.set_color(y, color);
+    }
+    // the side of `x` in its parent
+    fn index(&self, x: u32) -> usize {
+        let p = self.node(x).parent;
+        if self.node(p).child[0] == x {
+            0
+        } else {
+            assert_eq!(self.node(p).child[1], x);
+            1
+        }
+    }
+    fn key(&self, x: u32) -> &K {
+        &self.node(x).entry.as_ref().unwrap().0
+    }
+    fn node(&self, x: u32) -> &Node<K, V> {
+        &self.nodes[x as usize]
+    }
+    fn node_mut(&mut self, x: u32) -> &mut Node<K, V> {
+        &mut self.nodes[x as usize]
+    }
+}
+impl<K: Ord + Debug, V: Debug> Default for RBTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
+    // the BST order, the colors and the parent pointers
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        ensure(
+            self.node(self.root).parent == NIL,
+            Property::ParentPointer,
+            &[],
+        )?;
+        ensure(
+            self.node(self.root).color == Color::Black,
+            Property::BlackRoot,
+            &[],
+        )?;
+        self.check(self.root, &mut Vec::new(), &mut None)
+            .map(|_| ())
+    }
+    // Returns the black height, with `prev` the last key in the in-order.
+    fn check<'a>(
+        &'a self,
+        x: u32,
+        path: &mut Vec<usize>,
+        prev: &mut Option<&'a K>,
+    ) -> Result<u32, InvariantViolation> {
+        if x == NIL {
+            return Ok(0);
+        }
+        let child = self.node(x).child;
+        for (i, &c) in child.iter().enumerate() {
+            if c != NIL {
+                path.push(i);
+                ensure(self.node(c).parent == x, Property::ParentPointer, path)?;
+                ensure(
+                    self.node(x).color == Color::Black || self.node(c).color == Color::Black,
+                    Property::RedRed,
+                    path,
+                )?;
+                path.pop();
+            }
+        }
+        path.push(0);
+        let left = self.check(child[0], path, prev)?;
+        path.pop();
+        if let Some(prev) = prev {
+            ensure(*prev <= self.key(x), Property::BstOrder, path)?;
+        }
+        *prev = Some(self.key(x));
+        path.push(1);
+        let right = self.check(child[1], path, prev)?;
+        path.pop();
+        ensure(left == right, Property::BlackHeight, path)?;
+        Ok(match self.node(x).color {
+            Color::Black => left + 1,
+            Color::Red => left,
+        })
+    }
+}
+
+impl<K: Ord + Debug, V: Debug> Paren for RBTree<K, V> {
+    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        self.paren_node(self.root, w)
+    }
+}
+impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
+    fn paren_node(&self, x: u32, w: &mut fmt::Formatter) -> fmt::Result {
+        if x != NIL {
+            let node = self.node(x);
+            write!(w, "(")?;
+            self.paren_node(node.child[0], w)?;
+            write!(
+                w,
+                "{:?}:{}",
+                paren::paint(w, node.color.into(), self.key(x)),
+                paren::Color::from(node.color)
+            )?;
+            self.paren_node(node.child[1], w)?;
+            write!(w, ")")?;
+        }
+        Ok(())
+    }
+    fn shape(&self, x: u32) -> Option<Shape> {
+        if x == NIL {
+            None
+        } else {
+            let node = self.node(x);
+            Some(
+                Shape::new(self.key(x))
+                    .color(node.color)
+                    .child(self.shape(node.child[0]))
+                    .child(self.shape(node.child[1])),
+            )
+        }
+    }
+}
+impl<K: Ord + Debug, V: Debug> Visualize for RBTree<K, V> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.shape(self.root).into_iter().collect()
+    }
+}
+
+// The values are left default.
+impl<K: Ord + Debug + FromStr, V: Debug + Default> FromParen for RBTree<K, V> {
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        let mut rbt = Self::new();
+        rbt.root = rbt.build(paren::parse(s)?, NIL)?;
+        Ok(rbt)
+    }
+}
+impl<K: Ord + Debug + FromStr, V: Debug + Default> RBTree<K, V> {
+    fn build(&mut self, node: Option<Box<paren::Node>>, p: u32) -> Result<u32, ParseError> {
+        match node {
+            None => Ok(NIL),
+            Some(node) => {
+                let paren::Node {
+                    label,
+                    child: [left, right],
+                } = *node;
+                let x = self.alloc(p, label.key()?, V::default());
+                self.node_mut(x).color = label.color()?.into();
+                self.len += 1;
+                let left = self.build(left, x)?;
+                let right = self.build(right, x)?;
+                self.node_mut(x).child = [left, right];
+                Ok(x)
+            }
+        }
+    }
+}
+
+impl<K: Ord + Debug, V: Debug> Debug for RBTree<K, V> {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        write!(w, "RBTree {{ ")?;
+        write!(w, "{:?}", Painted(self))?;
+        write!(w, " }}")
+    }
+}
+
+// The free slots are counted with their capacity.
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for RBTree<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.nodes.heap_size_bytes() + self.free.heap_size_bytes()
+    }
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Node<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.entry.heap_size_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Node, RBTree},
+        invariant::Property,
+        memory_usage::MemoryUsage,
+        paren::{FromParen, Paren},
+        rand::prelude::*,
+        std::{collections::BTreeMap, mem::size_of},
+    };
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<RBTree<u32, String>>();
+    }
+
+    #[test]
+    fn test_paren() {
+        // CLRS Figure 13.4
+        let mut rbt =
+            RBTree::<u32, ()>::from_paren("(((1:B)2:R((5:R)7:B(8:R)))11:B(14:B(15:R)))").unwrap();
+        rbt.check_invariants().unwrap();
+        rbt.insert(4, ());
+        let expected = "(((1:B)2:R((4:R)5:B))7:B((8:B)11:R(14:B(15:R))))";
+        assert_eq!(rbt.to_paren(), expected);
+        rbt.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let check = |s: &str| {
+            RBTree::<u32, ()>::from_paren(s)
+                .unwrap()
+                .check_invariants()
+                .map_err(|e| (e.property, e.path))
+        };
+        assert_eq!(check("((1:B(2:R))3:B(4:B))"), Ok(()));
+        assert_eq!(check("(1:R)"), Err((Property::BlackRoot, vec![])));
+        assert_eq!(
+            check("((1:R(2:R))3:B(4:R))"),
+            Err((Property::RedRed, vec![0, 1]))
+        );
+        assert_eq!(check("((1:B)2:B)"), Err((Property::BlackHeight, vec![])));
+        assert_eq!(check("((3:B)2:B(1:B))"), Err((Property::BstOrder, vec![])));
+
+        let mut rbt = RBTree::<u32, ()>::from_paren("((1:B(2:R))3:B(4:B))").unwrap();
+        let x = rbt.node(rbt.root).child[0];
+        rbt.node_mut(x).parent = x;
+        assert_eq!(
+            rbt.check_invariants().map_err(|e| (e.property, e.path)),
+            Err((Property::ParentPointer, vec![0]))
+        );
+    }
+
+    // the same rotations and recolorings as the one with `Rc`
+    #[test]
+    fn test_same_as_rc() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut arena = RBTree::<u32, ()>::new();
+        let mut rc = crate::RBTree::<u32, ()>::new();
+        for _ in 0..2000 {
+            let key = rng.gen_range(0, 40);
+            if rng.gen_bool(0.5) {
+                arena.insert(key, ());
+                rc.insert(key, ());
+            } else {
+                assert_eq!(arena.delete(&key).is_some(), rc.delete(key).is_some());
+            }
+            assert_eq!(arena.to_paren(), rc.to_paren());
+        }
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..20 {
+            let mut rbt = RBTree::<u32, u32>::new();
+            let mut map = BTreeMap::<u32, u32>::new();
+            for _ in 0..2000 {
+                let key = rng.gen_range(0, 40);
+                if rng.gen_bool(0.5) {
+                    map.entry(key).or_insert_with(|| {
+                        let value = rng.gen();
+                        rbt.insert(key, value);
+                        value
+                    });
+                } else {
+                    assert_eq!(rbt.delete(&key), map.remove_entry(&key));
+                }
+                assert_eq!(rbt.get(&key), map.get(&key));
+                assert_eq!(rbt.len(), map.len());
+                rbt.check_invariants().unwrap();
+            }
+            assert_eq!(rbt.collect_vec(), map.into_iter().collect::<Vec<_>>());
+        }
+    }
+
+    // The deleted slots are reused, and there is no other allocation.
+    #[test]
+    fn test_heap_size_bytes() {
+        let node = size_of::<Node<u32, ()>>();
+        let mut rbt = RBTree::<u32, ()>::with_capacity(10);
+        assert_eq!(rbt.heap_size_bytes(), 11 * node);
+        (0..10).for_each(|k| rbt.insert(k, ()));
+        assert_eq!(rbt.heap_size_bytes(), 11 * node);
+        (0..4).for_each(|k| {
+            rbt.delete(&k);
+        });
+        let bytes = rbt.heap_size_bytes();
+        (0..4).for_each(|k| rbt.insert(k, ()));
+        assert_eq!(rbt.heap_size_bytes(), bytes);
+        assert_eq!(rbt.nodes.len(), 11);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_trace_same_as_rc() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut arena = RBTree::<u32, ()>::new();
+        let mut rc = crate::RBTree::<u32, ()>::new();
+        for _ in 0..1000 {
+            let key = rng.gen_range(0, 40);
+            let (expected, events) = if rng.gen_bool(0.5) {
+                let ((), expected) = trace::capture(|| rc.insert(key, ()));
+                let ((), events) = trace::capture(|| arena.insert(key, ()));
+                (expected, events)
+            } else {
+                let (_, expected) = trace::capture(|| rc.delete(key));
+                let (_, events) = trace::capture(|| arena.delete(&key));
+                (expected, events)
+            };
+            assert_eq!(events, expected);
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use {
+    super::{
+        node::{Node, RcNode},
+        RBTree,
+    },
+    std::{
+        cell::{Ref, RefMut},
+        fmt::Debug,
+        mem::replace,
+    },
+};
+
+// A position in the in-order, which is a node or the ghost between the last and the first. It walks
+// by the parent pointers, so that a walk through all the `n` nodes takes O(n) in total.
+pub struct Cursor<'a, K, V> {
+    tree: &'a RBTree<K, V>,
+    // `None` at the ghost
+    current: Option<RcNode<K, V>>,
+}
+impl<'a, K: Ord + Debug, V: Debug> Cursor<'a, K, V> {
+    // From the ghost to the first.
+    pub fn move_next(&mut self) {
+        self.current = step(&self.tree.root, self.current.take(), 1);
+    }
+    // From the ghost to the last.
+    pub fn move_prev(&mut self) {
+        self.current = step(&self.tree.root, self.current.take(), 0);
+    }
+    pub fn key(&self) -> Option<Ref<'_, K>> {
+        self.current.as_ref().map(key)
+    }
+    pub fn value(&self) -> Option<Ref<'_, V>> {
+        self.current
+            .as_ref()
+            .map(|x| Ref::map(x.as_ref(), |x| x.as_internal().unwrap().value()))
+    }
+}
+
+// A cursor which can change the tree. The nodes are never moved to the other keys by the
+// rebalancing, so it stays at the same node. The rebalancing after an insertion or a deletion takes
+// amortized O(1) rotations and recolorings.
+//
+// It keeps the next node as well as the ends, so that `insert_after` attaches the new node
+// without searching. The next one is found by walking one step ahead, which takes amortized O(1)
+// in a walk as `Cursor` does.
+pub struct CursorMut<'a, K, V> {
+    tree: &'a mut RBTree<K, V>,
+    // `None` at the ghost
+    current: Option<RcNode<K, V>>,
+    // the one after `current`, which is the first at the ghost, or `None` if it is the ghost
+    next: Option<RcNode<K, V>>,
+    // the first and the last nodes, which are next to the ghost
+    ends: [Option<RcNode<K, V>>; 2],
+}
+impl<'a, K: Ord + Debug, V: Debug> CursorMut<'a, K, V> {
+    pub fn move_next(&mut self) {
+        self.current = self.next.take();
+        self.next = self.after(&self.current);
+    }
+    pub fn move_prev(&mut self) {
+        let current = match &self.current {
+            None => self.ends[1].clone(),
+            Some(x) => x.neighbor(0),
+        };
+        self.next = replace(&mut self.current, current);
+    }
+    pub fn key(&self) -> Option<Ref<'_, K>> {
+        self.current.as_ref().map(key)
+    }
+    pub fn value_mut(&mut self) -> Option<RefMut<'_, V>> {
+        self.current
+            .as_ref()
+            .map(|x| RefMut::map(x.as_mut(), |x| x.as_internal_mut().unwrap().value_mut()))
+    }
+    // Deletes the current node, which is returned as `RBTree::delete` does, and moves to the next.
+    pub fn remove_current(&mut self) -> Option<RcNode<K, V>> {
+        let x = self.current.take()?;
+        trace!(Begin {
+            op: "delete".to_owned(),
+            key: x.trace_key(),
+        });
+        self.current = self.next.take();
+        if is(&self.ends[0], &x) {
+            self.ends[0] = self.current.clone();
+        }
+        if is(&self.ends[1], &x) {
+            self.ends[1] = x.neighbor(0);
+        }
+        let x = self.tree.delete_node(x);
+        self.next = self.after(&self.current);
+        Some(x)
+    }
+    // Inserts a node right after the current one, or at the front at the ghost, and stays. `k` has
+    // to be between the keys around.
+    //
+    // The new node goes to the right of the current one if it is a nil, and otherwise to the left of
+    // the next one, which is a nil then. The rebalancing takes amortized O(1).
+    pub fn insert_after(&mut self, k: K, v: V) {
+        if let Some(x) = &self.current {
+            assert!(*key(x) <= k, "the order of the keys is broken");
+        }
+        if let Some(x) = &self.next {
+            assert!(k <= *key(x), "the order of the keys is broken");
+        }
+        let nil = match (&self.current, &self.next) {
+            (Some(x), _) if x.clone_child(1).unwrap().is_nil() => x.clone_child(1).unwrap(),
+            (_, Some(y)) => y.clone_child(0).unwrap(),
+            (_, None) => RcNode::clone(&self.tree.root),
+        };
+        let x = self.tree.insert_at(nil, k, v);
+        if self.current.is_none() {
+            self.ends[0] = Some(RcNode::clone(&x));
+        }
+        if self.next.is_none() {
+            self.ends[1] = Some(RcNode::clone(&x));
+        }
+        self.next = Some(x);
+    }
+    // the one after `x`, where the first is after the ghost
+    fn after(&self, x: &Option<RcNode<K, V>>) -> Option<RcNode<K, V>> {
+        match x {
+            None => self.ends[0].clone(),
+            Some(x) => x.neighbor(1),
+        }
+    }
+}
+
+impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
+    pub fn cursor_first(&self) -> Cursor<'_, K, V> {
+        let mut cursor = self.cursor_ghost();
+        cursor.move_next();
+        cursor
+    }
+    pub fn cursor_last(&self) -> Cursor<'_, K, V> {
+        let mut cursor = self.cursor_ghost();
+        cursor.move_prev();
+        cursor
+    }
+    // at the first key not less than `k`, or at the ghost
+    pub fn cursor_at(&self, k: &K) -> Cursor<'_, K, V> {
+        Cursor {
+            current: lower_bound(&self.root, k),
+            tree: self,
+        }
+    }
+    pub fn cursor_first_mut(&mut self) -> CursorMut<'_, K, V> {
+        let mut cursor = self.cursor_ghost_mut();
+        cursor.move_next();
+        cursor
+    }
+    pub fn cursor_last_mut(&mut self) -> CursorMut<'_, K, V> {
+        let mut cursor = self.cursor_ghost_mut();
+        cursor.move_prev();
+        cursor
+    }
+    pub fn cursor_at_mut(&mut self, k: &K) -> CursorMut<'_, K, V> {
+        let mut cursor = self.cursor_ghost_mut();
+        cursor.current = lower_bound(&cursor.tree.root, k);
+        cursor.next = cursor.after(&cursor.current);
+        cursor
+    }
+    fn cursor_ghost(&self) -> Cursor<'_, K, V> {
+        Cursor {
+            tree: self,
+            current: None,
+        }
+    }
+    fn cursor_ghost_mut(&mut self) -> CursorMut<'_, K, V> {
+        let first = self.root.tree_non_null_extremum(0);
+        CursorMut {
+            next: first.clone(),
+            ends: [first, self.root.tree_non_null_extremum(1)],
+            tree: self,
+            current: None,
+        }
+    }
+}
+
+// the next one to the side `i`, where the ghost is next to both the ends
+fn step<K: Ord + Debug, V: Debug>(
+    root: &RcNode<K, V>,
+    current: Option<RcNode<K, V>>,
+    i: usize,
+) -> Option<RcNode<K, V>> {
+    match current {
+        None => root.tree_non_null_extremum(1 - i),
+        Some(x) => x.neighbor(i),
+    }
+}
+fn is<K: Ord + Debug, V: Debug>(x: &Option<RcNode<K, V>>, y: &RcNode<K, V>) -> bool {
+    x.as_ref().is_some_and(|x| RcNode::ptr_eq(x, y))
+}
+fn key<K: Ord + Debug, V: Debug>(x: &RcNode<K, V>) -> Ref<'_, K> {
+    Ref::map(x.as_ref(), |x| x.as_internal().unwrap().key())
+}
+fn lower_bound<K: Ord + Debug, V: Debug>(root: &RcNode<K, V>, k: &K) -> Option<RcNode<K, V>> {
+    let mut x = RcNode::clone(root);
+    let mut found = None;
+    loop {
+        let swp = match &*x.as_ref() {
+            Node::Internal(internal) => {
+                if k <= internal.key() {
+                    found = Some(RcNode::clone(&x));
+                    RcNode::clone(internal.child(0))
+                } else {
+                    RcNode::clone(internal.child(1))
+                }
+            }
+            Node::Nil(_) => break,
+        };
+        x = swp;
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{super::RBTree, key},
+        rand::prelude::*,
+    };
+
+    fn keys(rbt: &RBTree<u32, u32>) -> Vec<u32> {
+        rbt.collect_vec().into_iter().map(|(k, _)| k).collect()
+    }
+
+    #[test]
+    fn test_move() {
+        let mut rbt = RBTree::new();
+        assert!(rbt.cursor_first().key().is_none());
+        for k in [5, 2, 8, 2, 9, 1].iter() {
+            rbt.insert(*k, *k * 10);
+        }
+        let mut cursor = rbt.cursor_first();
+        let mut forward = Vec::new();
+        while let Some(k) = cursor.key().map(|k| *k) {
+            assert_eq!(*cursor.value().unwrap(), k * 10);
+            forward.push(k);
+            cursor.move_next();
+        }
+        assert_eq!(forward, vec![1, 2, 2, 5, 8, 9]);
+        // past the ghost
+        cursor.move_next();
+        assert_eq!(cursor.key().map(|k| *k), Some(1));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.key().map(|k| *k), Some(9));
+
+        let mut cursor = rbt.cursor_last();
+        let mut backward = Vec::new();
+        while let Some(k) = cursor.key().map(|k| *k) {
+            backward.push(k);
+            cursor.move_prev();
+        }
+        backward.reverse();
+        assert_eq!(backward, forward);
+
+        let at = |k| rbt.cursor_at(&k).key().map(|k| *k);
+        assert_eq!(at(0), Some(1));
+        assert_eq!(at(2), Some(2));
+        assert_eq!(at(6), Some(8));
+        assert_eq!(at(10), None);
+        let mut cursor = rbt.cursor_at(&2);
+        cursor.move_prev();
+        assert_eq!(cursor.key().map(|k| *k), Some(1));
+    }
+
+    #[test]
+    fn test_cursor_mut() {
+        let mut rbt = RBTree::new();
+        // at the front from the ghost, and right after the current one
+        let mut cursor = rbt.cursor_first_mut();
+        cursor.insert_after(4, 0);
+        cursor.insert_after(0, 0);
+        cursor.move_next();
+        assert_eq!(cursor.key().map(|k| *k), Some(0));
+        for k in 1..4 {
+            cursor.insert_after(k, 0);
+            cursor.move_next();
+        }
+        *cursor.value_mut().unwrap() = 30;
+        // It keeps the next, the first and the last nodes.
+        drop(cursor);
+        assert_eq!(keys(&rbt), vec![0, 1, 2, 3, 4]);
+        assert_eq!(
+            rbt.get(&3).unwrap().as_ref().as_internal().unwrap().value(),
+            &30
+        );
+        rbt.check_invariants().unwrap();
+
+        // every even key
+        let mut cursor = rbt.cursor_first_mut();
+        while let Some(k) = cursor.key().map(|k| *k) {
+            if k % 2 == 0 {
+                let x = cursor.remove_current().unwrap();
+                assert_eq!(x.try_unwrap().ok().unwrap(), Some((k, 0)));
+            } else {
+                cursor.move_next();
+            }
+        }
+        assert_eq!(keys(&rbt), vec![1, 3]);
+        rbt.check_invariants().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "the order of the keys is broken")]
+    fn test_insert_after_unordered() {
+        let mut rbt = RBTree::new();
+        rbt.insert(1, ());
+        rbt.insert(3, ());
+        rbt.cursor_first_mut().insert_after(4, ());
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..20 {
+            let mut rbt = RBTree::new();
+            let mut vec = Vec::new();
+            let mut cursor = rbt.cursor_first_mut();
+            // `None` at the ghost
+            let mut i = None;
+            for _ in 0..200 {
+                match rng.gen_range(0, 4) {
+                    0 => {
+                        cursor.move_next();
+                        i = match i {
+                            None if vec.is_empty() => None,
+                            None => Some(0),
+                            Some(i) if i + 1 == vec.len() => None,
+                            Some(i) => Some(i + 1),
+                        };
+                    }
+                    1 => {
+                        cursor.move_prev();
+                        i = match i {
+                            None => vec.len().checked_sub(1),
+                            Some(0) => None,
+                            Some(i) => Some(i - 1),
+                        };
+                    }
+                    2 => {
+                        let j = i.map_or(0, |i| i + 1);
+                        let lo = i.map_or(0, |i| vec[i]);
+                        let hi = vec.get(j).copied().unwrap_or(100);
+                        let k = rng.gen_range(lo, hi + 1);
+                        cursor.insert_after(k, k);
+                        vec.insert(j, k);
+                    }
+                    3 => {
+                        assert_eq!(cursor.remove_current().is_some(), i.is_some());
+                        if let Some(j) = i {
+                            vec.remove(j);
+                            if j == vec.len() {
+                                i = None;
+                            }
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+                assert_eq!(cursor.key().map(|k| *k), i.map(|i| vec[i]));
+                assert_eq!(cursor.value_mut().map(|v| *v), i.map(|i| vec[i]));
+                assert_eq!(
+                    cursor.next.as_ref().map(|x| *key(x)),
+                    vec.get(i.map_or(0, |i| i + 1)).copied()
+                );
+            }
+            drop(cursor);
+            assert_eq!(keys(&rbt), vec);
+            rbt.check_invariants().unwrap();
+        }
+    }
+}
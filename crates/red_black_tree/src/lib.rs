@@ -1,11 +1,28 @@
+// Records an event with the `trace` feature, and does nothing without it.
+macro_rules! trace {
+    ($($event:tt)*) => {
+        #[cfg(feature = "trace")]
+        {
+            if trace::is_recording() {
+                trace::record(trace::Event::$($event)*);
+            }
+        }
+    };
+}
+
+pub mod arena;
 mod color;
+mod cursor;
 mod node;
 mod paren;
 mod validate;
 
 use color::Color;
+pub use cursor::{Cursor, CursorMut};
+use memory_usage::MemoryUsage;
 use node::{Node, RcNode, WeakNode};
 use std::{cmp::Ordering, fmt::Debug};
+pub use validate::Validate;
 
 pub struct RBTree<K, V> {
     root: RcNode<K, V>,
@@ -17,22 +34,38 @@ impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
         }
     }
     pub fn insert(&mut self, k: K, v: V) {
-        let mut nil = self.find_insert_position(&k);
-        let mut node = RcNode::new(k, v);
-        self.transplant(&mut nil, &mut node);
-        self.insert_fixup(node);
+        let nil = self.find_insert_position(&k);
+        self.insert_at(nil, k, v);
     }
     pub fn delete(&mut self, k: K) -> Option<RcNode<K, V>> {
-        let mut found = self.find_node(&k)?;
+        trace!(Begin {
+            op: "delete".to_owned(),
+            key: trace::key(&k),
+        });
+        let found = self.find_node(&k)?;
+        Some(self.delete_node(found))
+    }
+    // Splices the node `found` out, which is returned.
+    fn delete_node(&mut self, mut found: RcNode<K, V>) -> RcNode<K, V> {
         let mut child = found.clone_children().unwrap();
         let charged = if let Some(i) = child.iter().position(|child| child.is_nil()) {
+            trace!(Splice {
+                key: found.trace_key(),
+            });
             self.transplant(&mut found, &mut child[1 - i]);
             RcNode::clone(&child[1 - i])
         } else {
             let mut next = child[1].tree_non_null_extremum(0).unwrap();
             assert!(!RcNode::ptr_eq(&found, &next));
             let mut next1 = next.clone_child(1).unwrap();
+            trace!(Splice {
+                key: next.trace_key(),
+            });
             self.transplant(&mut next, &mut next1);
+            trace!(Replace {
+                key: found.trace_key(),
+                by: next.trace_key(),
+            });
             self.transplant(&mut found, &mut next);
             next.swap_color(&mut found);
             let mut found_mut = found.as_mut();
@@ -44,7 +77,10 @@ impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
         if !self.root.is_nil() && found.is_black() {
             self.delete_fixup(charged);
         }
-        Some(RcNode::clone(&found))
+        found
+    }
+    pub fn get(&self, k: &K) -> Option<RcNode<K, V>> {
+        self.find_node(k)
     }
     pub fn collect_vec(&self) -> Vec<(K, V)>
     where
@@ -85,15 +121,27 @@ impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
             assert!(pp.is_black());
             let mut y = pp.clone_child(1 - j).unwrap();
             if y.is_red() {
+                trace!(Case {
+                    fixup: "insert_fixup".to_owned(),
+                    case: 1,
+                });
                 p.set_color(Color::Black);
                 y.set_color(Color::Black);
                 pp.set_color(Color::Red);
                 x = pp;
             } else if i == j {
+                trace!(Case {
+                    fixup: "insert_fixup".to_owned(),
+                    case: 3,
+                });
                 p.set_color(Color::Black);
                 pp.set_color(Color::Red);
                 self.rotate(&mut pp, j);
             } else {
+                trace!(Case {
+                    fixup: "insert_fixup".to_owned(),
+                    case: 2,
+                });
                 self.rotate(&mut p, i);
                 x = p;
             }
@@ -106,6 +154,10 @@ impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
             assert!(!p.is_nil());
             let mut y = p.clone_child(1 - i).unwrap();
             if y.is_red() {
+                trace!(Case {
+                    fixup: "delete_fixup".to_owned(),
+                    case: 1,
+                });
                 p.swap_color(&mut y);
                 self.rotate(&mut p, 1 - i);
             } else {
@@ -113,13 +165,25 @@ impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
                 assert!(!y.is_nil());
                 let mut child = y.clone_children().unwrap();
                 if child[0].is_black() && child[1].is_black() {
+                    trace!(Case {
+                        fixup: "delete_fixup".to_owned(),
+                        case: 2,
+                    });
                     y.set_color(Color::Red);
                     x = p;
                 } else if child[1 - i].is_black() {
                     assert!(child[i].is_red());
+                    trace!(Case {
+                        fixup: "delete_fixup".to_owned(),
+                        case: 3,
+                    });
                     y.swap_color(&mut child[i]);
                     self.rotate(&mut y, i);
                 } else {
+                    trace!(Case {
+                        fixup: "delete_fixup".to_owned(),
+                        case: 4,
+                    });
                     p.swap_color(&mut y);
                     child[1 - i].set_color(Color::Black);
                     self.rotate(&mut p, 1 - i);
@@ -130,6 +194,21 @@ impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
         assert!(x.is_red() || self.is_root(&x));
         x.set_color(Color::Black);
     }
+    // Attaches a new node at the nil `nil`, where the order of the keys has to be kept, and returns
+    // it.
+    fn insert_at(&mut self, mut nil: RcNode<K, V>, k: K, v: V) -> RcNode<K, V> {
+        trace!(Begin {
+            op: "insert".to_owned(),
+            key: trace::key(&k),
+        });
+        trace!(Attach {
+            label: format!("{:?}:R", k),
+        });
+        let mut node = RcNode::new(k, v);
+        self.transplant(&mut nil, &mut node);
+        self.insert_fixup(RcNode::clone(&node));
+        node
+    }
     fn find_insert_position(&self, k: &K) -> RcNode<K, V> {
         let mut x = RcNode::clone(&self.root);
         loop {
@@ -148,6 +227,11 @@ impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
         let mut y = x.clone_child(i).unwrap();
         assert!(!y.is_nil());
         let mut z = y.clone_child(1 - i).unwrap();
+        if i == 1 {
+            trace!(RotateLeft { key: x.trace_key() });
+        } else {
+            trace!(RotateRight { key: x.trace_key() });
+        }
         if let Some((h, mut p)) = x.index_parent() {
             assert!(!self.is_root(&x));
             p.connect(h, &mut y);
@@ -178,12 +262,27 @@ impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
     }
 }
 
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for RBTree<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.root.heap_size_bytes()
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::node::Node;
     use super::validate::Validate;
     use super::RBTree;
+    use counting_allocator::{count, CountingAllocator, Counts};
+    use invariant::Property;
+    use memory_usage::{rc_bytes, MemoryUsage};
+    use paren::{FromParen, Paren};
     use rand::prelude::*;
     use span::Span;
+    use std::cell::RefCell;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
 
     fn insert(key: u32, rbt: &mut RBTree<u32, ()>, vec: &mut Vec<u32>) {
         rbt.insert(key, ());
@@ -208,6 +307,7 @@ mod tests {
         if vec.get(lb).map_or(false, |x| x == &key) {
             vec.remove(lb);
         }
+        assert_eq!(rbt.get(&key).is_some(), vec.contains(&key));
         println!("Delete {:?}.", key);
         println!("vec = {:?}.", &vec);
         println!("rbt = {:?}.", &rbt);
@@ -238,6 +338,150 @@ mod tests {
         delete(18, &mut rbt, &mut vec);
     }
 
+    #[test]
+    fn test_paren() {
+        // CLRS Figure 13.4
+        let mut rbt =
+            RBTree::<u32, ()>::from_paren("(((1:B)2:R((5:R)7:B(8:R)))11:B(14:B(15:R)))").unwrap();
+        Validate::all(&rbt);
+        rbt.insert(4, ());
+        let expected = "(((1:B)2:R((4:R)5:B))7:B((8:B)11:R(14:B(15:R))))";
+        assert_eq!(rbt.to_paren(), expected);
+        Validate::all(&rbt);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_trace_delete_fixup() {
+        use trace::Event;
+        // Delete 1 from the trees of CLRS Figure 13.7 in small.
+        for &(tree, cases, expected) in [
+            ("((1:B)2:B(3:B))", &[2][..], "(2:B(3:R))"),
+            ("((1:B)2:B((3:B)4:R(5:B)))", &[1, 2], "((2:B(3:R))4:B(5:B))"),
+            ("((1:B)2:B((3:R)4:B))", &[3, 4], "((2:B)3:B(4:B))"),
+            ("((1:B)2:B((3:R)4:B(5:R)))", &[4], "((2:B(3:R))4:B(5:B))"),
+        ]
+        .iter()
+        {
+            let mut rbt = RBTree::<u32, ()>::from_paren(tree).unwrap();
+            let (_, events) = trace::capture(|| rbt.delete(1));
+            Validate::all(&rbt);
+            assert_eq!(rbt.to_paren(), expected);
+            let result = events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Case { fixup, case } if fixup == "delete_fixup" => Some(*case),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(result, cases);
+
+            let events = trace::from_json_lines(&trace::to_json_lines(&events)).unwrap();
+            let snapshots = trace::replay::<u32>(tree, &events).unwrap();
+            assert_eq!(snapshots.last().unwrap(), expected);
+        }
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_trace_replay() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rbt = RBTree::<u32, ()>::new();
+        let mut keys = std::collections::BTreeSet::new();
+        for _ in 0..1000 {
+            let key = rng.gen_range(0, 40);
+            let before = rbt.to_paren();
+            let ((), events) = if keys.insert(key) {
+                trace::capture(|| rbt.insert(key, ()))
+            } else {
+                keys.remove(&key);
+                trace::capture(|| {
+                    rbt.delete(key);
+                })
+            };
+            let snapshots = trace::replay::<u32>(&before, &events).unwrap();
+            assert_eq!(snapshots.last().unwrap(), &rbt.to_paren());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "no red-red is violated")]
+    fn test_validate_double_red() {
+        Validate::all(&RBTree::<u32, ()>::from_paren("((1:R(2:R))3:B(4:R))").unwrap());
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let check = |s: &str| {
+            RBTree::<u32, ()>::from_paren(s)
+                .unwrap()
+                .check_invariants()
+                .map_err(|e| (e.property, e.path))
+        };
+        assert_eq!(check("((1:B(2:R))3:B(4:B))"), Ok(()));
+        assert_eq!(check("(1:R)"), Err((Property::BlackRoot, vec![])));
+        assert_eq!(
+            check("((1:R(2:R))3:B(4:R))"),
+            Err((Property::RedRed, vec![0, 1]))
+        );
+        assert_eq!(check("((1:B)2:B)"), Err((Property::BlackHeight, vec![])));
+        assert_eq!(check("((3:B)2:B(1:B))"), Err((Property::BstOrder, vec![])));
+
+        let rbt = RBTree::<u32, ()>::from_paren("((1:B(2:R))3:B(4:B))").unwrap();
+        rbt.root.clone_child(0).unwrap().take_parent();
+        assert_eq!(
+            rbt.check_invariants().map_err(|e| (e.property, e.path)),
+            Err((Property::ParentPointer, vec![0]))
+        );
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        // the internal nodes and one more nils
+        let node = rc_bytes::<RefCell<Node<u32, ()>>>();
+        let mut rbt = RBTree::new();
+        assert_eq!(rbt.heap_size_bytes(), node);
+        for k in 0..10 {
+            rbt.insert(k, ());
+        }
+        assert_eq!(rbt.heap_size_bytes(), 21 * node);
+        for k in 0..4 {
+            rbt.delete(k);
+        }
+        assert_eq!(rbt.heap_size_bytes(), 13 * node);
+    }
+
+    #[test]
+    fn test_allocations() {
+        // An insertion replaces a nil by a node with two nils, and a deletion frees the node and
+        // one of its nils. A node with two children is given two nils to be taken from, which are
+        // freed with it.
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rbt = RBTree::<u32, ()>::new();
+        let mut keys = (0..100).collect::<Vec<_>>();
+        keys.shuffle(&mut rng);
+        for &k in &keys {
+            let ((), counts) = count(|| rbt.insert(k, ()));
+            assert_eq!(
+                counts,
+                Counts {
+                    allocs: 3,
+                    deallocs: 1,
+                    reallocs: 0
+                }
+            );
+        }
+        let (_, counts) = count(|| rbt.get(&42).is_some());
+        assert_eq!(counts, Counts::default());
+        keys.shuffle(&mut rng);
+        for &k in &keys {
+            let ((), counts) = count(|| drop(rbt.delete(k)));
+            assert!(counts.allocs == 0 || counts.allocs == 2, "{:?}", counts);
+            assert_eq!(counts.deallocs, counts.allocs + 2);
+            assert_eq!(counts.reallocs, 0);
+        }
+    }
+
     #[test]
     fn test_random() {
         let mut rng = StdRng::seed_from_u64(42);
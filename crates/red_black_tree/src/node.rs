@@ -1,4 +1,5 @@
 use super::color::Color;
+use memory_usage::MemoryUsage;
 use std::{
     cell::{Ref, RefCell, RefMut},
     fmt::Debug,
@@ -76,7 +77,21 @@ impl<K: Ord + Debug, V: Debug> RcNode<K, V> {
     }
     pub fn set_color(&mut self, color: Color) {
         let mut x = self.as_mut();
-        x.as_internal_mut().unwrap().color = color;
+        let internal = x.as_internal_mut().unwrap();
+        #[cfg(feature = "trace")]
+        {
+            if internal.color != color {
+                trace!(SetColor {
+                    key: trace::key(&internal.key),
+                    color: color.into(),
+                });
+            }
+        }
+        internal.color = color;
+    }
+    #[cfg(feature = "trace")]
+    pub fn trace_key(&self) -> String {
+        trace::key(self.as_ref().as_internal().unwrap().key())
     }
     pub fn swap_color(&mut self, x: &mut RcNode<K, V>) {
         let self_color = self.color();
@@ -157,6 +172,22 @@ impl<K: Ord + Debug, V: Debug> RcNode<K, V> {
         }
     }
 
+    // -- in-order
+    // the next internal node to the side `i`, i.e. the successor if `i == 1`
+    pub fn neighbor(&self, i: usize) -> Option<Self> {
+        if let Some(x) = self.clone_child(i).unwrap().tree_non_null_extremum(1 - i) {
+            return Some(x);
+        }
+        let mut x = RcNode::clone(self);
+        loop {
+            let (j, p) = x.index_parent()?;
+            if j != i {
+                return Some(p);
+            }
+            x = p;
+        }
+    }
+
     // -- deformation
     pub fn take_parent(&mut self) -> Option<WeakNode<K, V>> {
         match &mut *self.as_mut() {
@@ -255,6 +286,12 @@ impl<K: Ord + Debug, V: Debug> Internal<K, V> {
     pub fn key(&self) -> &K {
         &self.key
     }
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+    pub fn value_mut(&mut self) -> &mut V {
+        &mut self.value
+    }
     pub fn color(&self) -> Color {
         self.color
     }
@@ -279,3 +316,24 @@ impl<K: Ord + Debug, V: Debug> Internal<K, V> {
 pub struct Nil<K, V> {
     parent: Option<WeakNode<K, V>>,
 }
+
+// Every nil is allocated as well as the internal nodes, and the parents are weak, so each
+// allocation is counted once from its parent.
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for RcNode<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes()
+    }
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Node<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        match self {
+            Node::Internal(internal) => {
+                internal.key.heap_size_bytes()
+                    + internal.value.heap_size_bytes()
+                    + internal.child[0].heap_size_bytes()
+                    + internal.child[1].heap_size_bytes()
+            }
+            Node::Nil(_) => 0,
+        }
+    }
+}
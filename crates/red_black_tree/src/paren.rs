@@ -1,21 +1,34 @@
-use super::{
-    node::{Node, RcNode, WeakNode},
-    RBTree,
+use {
+    super::{
+        color::Color,
+        node::{Node, RcNode, WeakNode},
+        RBTree,
+    },
+    paren::{FromParen, Painted, Paren, ParseError, Shape, Visualize},
+    std::{
+        fmt::{self, Debug},
+        str::FromStr,
+    },
 };
-use std::fmt::{self, Debug};
 
-pub trait ParenInternal {
-    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result;
+impl<K: Ord + Debug, V: Debug> Paren for RBTree<K, V> {
+    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        self.root.paren(w)
+    }
 }
-
-impl<K: Ord + Debug, V: Debug> ParenInternal for RcNode<K, V> {
+impl<K: Ord + Debug, V: Debug> Paren for RcNode<K, V> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
         match &*self.as_ref() {
             Node::Nil(_) => (),
             Node::Internal(internal) => {
                 write!(w, "(")?;
                 internal.child(0).paren(w)?;
-                write!(w, "{:?}", internal.color().paint(internal.key()))?;
+                write!(
+                    w,
+                    "{:?}:{}",
+                    paren::paint(w, internal.color().into(), internal.key()),
+                    paren::Color::from(internal.color())
+                )?;
                 internal.child(1).paren(w)?;
                 write!(w, ")")?;
             }
@@ -24,10 +37,70 @@ impl<K: Ord + Debug, V: Debug> ParenInternal for RcNode<K, V> {
     }
 }
 
+impl<K: Ord + Debug, V: Debug> Visualize for RBTree<K, V> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.root.shape().into_iter().collect()
+    }
+}
+impl<K: Ord + Debug, V: Debug> RcNode<K, V> {
+    fn shape(&self) -> Option<Shape> {
+        self.as_ref().as_internal().map(|x| {
+            Shape::new(x.key())
+                .color(x.color())
+                .child(x.child(0).shape())
+                .child(x.child(1).shape())
+        })
+    }
+}
+
+// The values are left default.
+impl<K: Ord + Debug + FromStr, V: Debug + Default> FromParen for RBTree<K, V> {
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            root: RcNode::from_parsed(paren::parse(s)?)?,
+        })
+    }
+}
+impl<K: Ord + Debug + FromStr, V: Debug + Default> RcNode<K, V> {
+    fn from_parsed(node: Option<Box<paren::Node>>) -> Result<Self, ParseError> {
+        match node {
+            None => Ok(Self::nil()),
+            Some(node) => {
+                let paren::Node {
+                    label,
+                    child: [left, right],
+                } = *node;
+                let mut x = Self::new(label.key()?, V::default());
+                x.set_color(label.color()?.into());
+                x.connect(0, &mut Self::from_parsed(left)?);
+                x.connect(1, &mut Self::from_parsed(right)?);
+                Ok(x)
+            }
+        }
+    }
+}
+
+impl From<Color> for paren::Color {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Red => paren::Color::Red,
+            Color::Black => paren::Color::Black,
+        }
+    }
+}
+impl From<paren::Color> for Color {
+    fn from(color: paren::Color) -> Self {
+        match color {
+            paren::Color::Red => Color::Red,
+            paren::Color::Black => Color::Black,
+        }
+    }
+}
+
 impl<K: Ord + Debug, V: Debug> Debug for RBTree<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
         write!(w, "RBTree {{ ")?;
-        ParenInternal::paren(&self.root, w)?;
+        write!(w, "{:?}", Painted(&self.root))?;
         write!(w, " }}")
     }
 }
@@ -35,7 +108,7 @@ impl<K: Ord + Debug, V: Debug> Debug for RBTree<K, V> {
 impl<K: Ord + Debug, V: Debug> Debug for RcNode<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
         write!(w, "RcNode {{ tree: ")?;
-        ParenInternal::paren(self, w)?;
+        write!(w, "{:?}", Painted(self))?;
         write!(w, ", parent:")?;
         if let Some(p) = self
             .as_ref()
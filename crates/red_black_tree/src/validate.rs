@@ -3,84 +3,69 @@ use super::{
     node::{RcNode, WeakNode},
     RBTree,
 };
+use invariant::{ensure, InvariantViolation, Property};
 use std::fmt::Debug;
 
+// `check_invariants` that panics.
 pub trait Validate {
-    fn reflexive_parent(&self);
-    fn no_double_red(&self);
-    fn consistent_black_height(&self) -> u32;
+    fn all(&self);
+}
+impl<K: Ord + Debug, V: Debug> Validate for RBTree<K, V> {
     fn all(&self) {
-        self.reflexive_parent();
-        self.no_double_red();
-        self.consistent_black_height();
+        invariant::assert_holds(self.check_invariants())
     }
 }
 
-impl<K: Ord + Debug, V: Debug> Validate for RcNode<K, V> {
-    fn reflexive_parent(&self) {
-        let self_ref = self.as_ref();
-        if let Some(internal) = self_ref.as_internal() {
-            for i in 0..2 {
-                let child = RcNode::clone(&internal.child(i));
-                Validate::reflexive_parent(&child);
-                assert!(
-                    child.as_ref().parent().is_some(),
-                    "No parent: self = {:?}, child = {:?}",
-                    &self,
-                    &child,
-                );
-                assert!(
-                    WeakNode::ptr_eq(&RcNode::downgrade(&self), child.as_ref().parent().unwrap()),
-                    "Non reflexive parent: self = {:?}, child = {:?}",
-                    &self,
-                    &child,
-                );
-            }
-        }
-    }
-    fn no_double_red(&self) {
-        let self_ref = self.as_ref();
-        if let Some(internal) = self_ref.as_internal() {
-            for i in 0..2 {
-                let child = RcNode::clone(&internal.child(i));
-                Validate::no_double_red(&child);
-                assert!(
-                    self.is_black() || child.is_black(),
-                    "Double red: self = {:?}, child = {:?}",
-                    &self,
-                    &child,
-                );
-            }
-        }
-    }
-    fn consistent_black_height(&self) -> u32 {
-        let self_ref = self.as_ref();
-        if let Some(internal) = self_ref.as_internal() {
-            let x = internal.child(0).consistent_black_height();
-            let y = internal.child(1).consistent_black_height();
-            assert_eq!(x, y, "Inconsistent black height: self = {:?}", &self,);
-            match internal.color() {
-                Color::Black => x + 1,
-                Color::Red => x,
-            }
-        } else {
-            0
-        }
+impl<K: Ord + Debug, V: Debug> RBTree<K, V> {
+    // the BST order, the colors and the parent pointers
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        ensure(
+            self.root.as_ref().parent().is_none(),
+            Property::ParentPointer,
+            &[],
+        )?;
+        ensure(self.root.is_black(), Property::BlackRoot, &[])?;
+        check(&self.root, &mut Vec::new(), &mut None).map(|_| ())
     }
 }
-impl<K: Ord + Debug, V: Debug> Validate for RBTree<K, V> {
-    fn reflexive_parent(&self) {
-        assert!(
-            self.root.index_parent().is_none(),
-            "Root has a parent: {:?}",
-            &self
-        );
-        Validate::reflexive_parent(&self.root)
-    }
-    fn no_double_red(&self) {
-        self.root.no_double_red()
+// Returns the black height, with `prev` the last node in the in-order.
+fn check<K: Ord + Debug, V: Debug>(
+    x: &RcNode<K, V>,
+    path: &mut Vec<usize>,
+    prev: &mut Option<RcNode<K, V>>,
+) -> Result<u32, InvariantViolation> {
+    let child = match x.clone_children() {
+        None => return Ok(0),
+        Some(child) => child,
+    };
+    for (i, child) in child.iter().enumerate() {
+        path.push(i);
+        ensure(
+            matches!(child.as_ref().parent(), Some(p) if WeakNode::ptr_eq(p, &x.downgrade())),
+            Property::ParentPointer,
+            path,
+        )?;
+        ensure(x.is_black() || child.is_black(), Property::RedRed, path)?;
+        path.pop();
     }
-    fn consistent_black_height(&self) -> u32 {
-        self.root.consistent_black_height()
+    path.push(0);
+    let left = check(&child[0], path, prev)?;
+    path.pop();
+    if let Some(prev) = prev {
+        let (p, k) = (prev.as_ref(), x.as_ref());
+        ensure(
+            p.as_internal().unwrap().key() <= k.as_internal().unwrap().key(),
+            Property::BstOrder,
+            path,
+        )?;
     }
+    *prev = Some(RcNode::clone(x));
+    path.push(1);
+    let right = check(&child[1], path, prev)?;
+    path.pop();
+    ensure(left == right, Property::BlackHeight, path)?;
+    Ok(match x.color() {
+        Color::Black => left + 1,
+        Color::Red => left,
+    })
 }
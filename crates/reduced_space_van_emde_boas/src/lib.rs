@@ -1,6 +1,11 @@
-use std::{
-    collections::HashMap,
-    mem::{size_of, swap},
+use {
+    memory_usage::MemoryUsage,
+    paren::{Shape, Visualize},
+    std::{
+        collections::HashMap,
+        iter::repeat_n,
+        mem::{size_of, swap},
+    },
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -83,6 +88,131 @@ impl Veb {
         }
     }
 }
+#[derive(Debug, Clone, PartialEq)]
+pub struct VebMultiset {
+    veb: Veb,
+    count: HashMap<usize, usize>,
+    len: usize,
+}
+impl VebMultiset {
+    pub fn new(lg: u32) -> Self {
+        Self {
+            veb: Veb::new(lg),
+            count: HashMap::new(),
+            len: 0,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn count(&self, x: usize) -> usize {
+        self.count.get(&x).copied().unwrap_or(0)
+    }
+    pub fn contains(&self, x: usize) -> bool {
+        self.count.contains_key(&x)
+    }
+    pub fn min(&self) -> Option<usize> {
+        self.veb.min()
+    }
+    pub fn max(&self) -> Option<usize> {
+        self.veb.max()
+    }
+    // カウントが 0 になったキーは `veb` から消えているので、そのまま飛ばされます。
+    pub fn prev(&self, x: usize) -> Option<usize> {
+        self.veb.prev(x)
+    }
+    pub fn succ(&self, x: usize) -> Option<usize> {
+        self.veb.succ(x)
+    }
+    pub fn insert(&mut self, x: usize) {
+        let c = self.count.entry(x).or_insert(0);
+        if *c == 0 {
+            self.veb.insert(x);
+        }
+        *c += 1;
+        self.len += 1;
+    }
+    pub fn remove_one(&mut self, x: usize) -> bool {
+        if let Some(c) = self.count.get_mut(&x) {
+            *c -= 1;
+            if *c == 0 {
+                self.count.remove(&x);
+                self.veb.delete(x);
+            }
+            self.len -= 1;
+            true
+        } else {
+            false
+        }
+    }
+    pub fn remove_all(&mut self, x: usize) -> usize {
+        if let Some(c) = self.count.remove(&x) {
+            self.veb.delete(x);
+            self.len -= c;
+            c
+        } else {
+            0
+        }
+    }
+    pub fn collect_vec(&self) -> Vec<usize> {
+        self.veb
+            .collect_vec()
+            .into_iter()
+            .flat_map(|x| repeat_n(x, self.count[&x]))
+            .collect()
+    }
+}
+// The children of a recursive node are the summary and then the clusters, with `None` for the
+// empty or absent clusters. The clusters are labeled with their indices.
+impl Visualize for Veb {
+    fn shapes(&self) -> Vec<Shape> {
+        vec![self.shape()]
+    }
+}
+impl Veb {
+    fn shape(&self) -> Shape {
+        match self {
+            Veb::Base(base) => Shape::new(
+                (0..base.len())
+                    .filter(|&x| base.contains(x))
+                    .collect::<Vec<_>>(),
+            ),
+            Veb::Rec(rec) => (0..1 << (rec.lg - rec.lower)).fold(
+                Shape::new(rec.minmax)
+                    .attr("lg", rec.lg)
+                    .child(Some(rec.summary.shape())),
+                |shape, i| {
+                    shape.child(
+                        rec.cluster
+                            .get(&i)
+                            .filter(|c| !c.is_empty())
+                            .map(|c| c.shape().attr("cluster", i)),
+                    )
+                },
+            ),
+        }
+    }
+}
+
+// The clusters are allocated on their first keys and freed when they are empty, but a summary is
+// allocated with its node.
+impl MemoryUsage for Veb {
+    fn heap_size_bytes(&self) -> usize {
+        match self {
+            Veb::Base(_) => 0,
+            Veb::Rec(rec) => rec.summary.heap_size_bytes() + rec.cluster.heap_size_bytes(),
+        }
+    }
+}
+impl MemoryUsage for VebMultiset {
+    fn heap_size_bytes(&self) -> usize {
+        self.veb.heap_size_bytes() + self.count.heap_size_bytes()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Base {
     len: usize,
@@ -344,9 +474,14 @@ fn decompose(x: usize, lower: u32) -> (usize, usize) {
 #[cfg(test)]
 mod test {
     use {
-        super::Veb,
+        super::{Veb, VebMultiset},
+        memory_usage::MemoryUsage,
         rand::prelude::*,
-        std::{collections::BTreeSet, time::Instant},
+        std::{
+            collections::{BTreeMap, BTreeSet},
+            iter::repeat_n,
+            time::Instant,
+        },
         yansi::Paint,
     };
 
@@ -355,6 +490,45 @@ mod test {
         assert_eq!(super::decompose(10, 2), (2, 2));
     }
 
+    // In the universe of 2^32 keys, `van_emde_boas` would take hundreds of gigabytes.
+    #[test]
+    fn test_heap_size_bytes() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut veb = Veb::new(32);
+        let empty = veb.heap_size_bytes();
+        assert!(empty < 1024);
+        let mut keys = Vec::new();
+        // the bytes per key for 16, 64, ..., 16384 keys, as fewer keys take no allocation
+        let mut per_key = Vec::new();
+        for k in 0..8 {
+            while keys.len() < 1 << (2 * k) {
+                let x = rng.gen_range(0, 1 << 32);
+                veb.insert(x);
+                keys.push(x);
+            }
+            if 2 <= k {
+                per_key.push((veb.heap_size_bytes() - empty) as f64 / keys.len() as f64);
+            }
+        }
+        // O(n): the bytes per key stay within a constant factor from the smallest to the largest.
+        let min = per_key.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = per_key.iter().copied().fold(0.0, f64::max);
+        assert!(max <= 2.0 * min, "{:?} bytes per key", per_key);
+
+        // The empty clusters are freed, but the `HashMap`s keep their capacities.
+        let bytes = veb.heap_size_bytes();
+        for &x in &keys {
+            veb.delete(x);
+        }
+        assert!(veb.heap_size_bytes() < bytes);
+
+        let mut ms = VebMultiset::new(32);
+        ms.insert(42);
+        let bytes = ms.heap_size_bytes();
+        ms.insert(42);
+        assert_eq!(ms.heap_size_bytes(), bytes);
+    }
+
     #[test]
     fn test_insert() {
         let mut test = Test::new(3);
@@ -384,6 +558,31 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_rand_multiset() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for lg in 3..18 {
+            let mut test = MultisetTest::new(lg);
+            let len = 1 << lg;
+            for _ in 0..200 {
+                match rng.gen_range(0, 6) {
+                    0 => test.count(rng.gen_range(0, len)),
+                    1 => test.prev(rng.gen_range(0, len)),
+                    2 => test.succ(rng.gen_range(0, len)),
+                    3 | 4 => test.insert(rng.gen_range(0, len.min(8))),
+                    5 => {
+                        if rng.gen_ratio(1, 4) {
+                            test.remove_all(rng.gen_range(0, len.min(8)))
+                        } else {
+                            test.remove_one(rng.gen_range(0, len.min(8)))
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_speed_veb() {
         let mut rng = StdRng::seed_from_u64(42);
@@ -393,7 +592,7 @@ mod test {
         let end = Instant::now();
         println!("Construction: {:?}", end - start);
 
-        let len = 1 << lg;
+        let len = 1usize << lg;
         let q = 1_000_000;
         let start = Instant::now();
         for _ in 0..q {
@@ -435,7 +634,7 @@ mod test {
         let end = Instant::now();
         println!("Construction: {:?}", end - start);
 
-        let len = 1 << lg;
+        let len = 1usize << lg;
         let q = 1_000_000;
         let start = Instant::now();
         for _ in 0..q {
@@ -526,4 +725,83 @@ mod test {
             assert_eq!(&result, &expected);
         }
     }
+
+    struct MultisetTest {
+        veb: VebMultiset,
+        map: BTreeMap<usize, usize>,
+    }
+    impl MultisetTest {
+        fn new(lg: u32) -> Self {
+            let res = Self {
+                veb: VebMultiset::new(lg),
+                map: BTreeMap::new(),
+            };
+            res.postproces();
+            res
+        }
+        fn count(&self, x: usize) {
+            println!("{}: {:?}", Paint::yellow("Count").bold(), x);
+            assert_eq!(self.veb.count(x), self.map.get(&x).copied().unwrap_or(0));
+            self.postproces();
+        }
+        fn prev(&self, x: usize) {
+            println!("{}: {:?}", Paint::yellow("Prev").bold(), x);
+            assert_eq!(
+                self.veb.prev(x),
+                self.map.range(..x).next_back().map(|(&x, _)| x)
+            );
+            self.postproces();
+        }
+        fn succ(&self, x: usize) {
+            println!("{}: {:?}", Paint::yellow("Succ").bold(), x);
+            assert_eq!(
+                self.veb.succ(x),
+                self.map.range(x + 1..).next().map(|(&x, _)| x)
+            );
+            self.postproces();
+        }
+        fn insert(&mut self, x: usize) {
+            println!("{}: {:?}", Paint::green("Insert").bold(), x);
+            self.veb.insert(x);
+            *self.map.entry(x).or_insert(0) += 1;
+            self.postproces();
+        }
+        fn remove_one(&mut self, x: usize) {
+            println!("{}: {:?}", Paint::cyan("Remove one").bold(), x);
+            let result = self.veb.remove_one(x);
+            let expected = match self.map.get_mut(&x) {
+                Some(c) => {
+                    *c -= 1;
+                    if *c == 0 {
+                        self.map.remove(&x);
+                    }
+                    true
+                }
+                None => false,
+            };
+            assert_eq!(result, expected);
+            self.postproces();
+        }
+        fn remove_all(&mut self, x: usize) {
+            println!("{}: {:?}", Paint::cyan("Remove all").bold(), x);
+            let result = self.veb.remove_all(x);
+            let expected = self.map.remove(&x).unwrap_or(0);
+            assert_eq!(result, expected);
+            self.postproces();
+        }
+        fn postproces(&self) {
+            println!("map = {:?}", &self.map);
+            let result = self.veb.collect_vec();
+            let expected = self
+                .map
+                .iter()
+                .flat_map(|(&x, &c)| repeat_n(x, c))
+                .collect::<Vec<_>>();
+            assert_eq!(&result, &expected);
+            assert_eq!(self.veb.len(), expected.len());
+            assert_eq!(self.veb.is_empty(), expected.is_empty());
+            assert_eq!(self.veb.min(), expected.first().copied());
+            assert_eq!(self.veb.max(), expected.last().copied());
+        }
+    }
 }
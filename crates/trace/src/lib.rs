@@ -0,0 +1,216 @@
+mod replay;
+
+pub use replay::{replay, ReplayError};
+
+use {
+    paren::Color,
+    serde::{Deserialize, Serialize},
+    std::{cell::RefCell, fmt::Debug},
+};
+
+// A structural change of a tree, recorded by the trees built with the `trace` feature. The nodes
+// are named by their keys formatted with `Debug`, so the keys should be distinct to replay the
+// events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    // the start of an operation, e.g. `insert` or `delete` of `key`
+    Begin {
+        op: String,
+        key: String,
+    },
+    // a new leaf, labeled as in the parenthesized form
+    Attach {
+        label: String,
+    },
+    // A node with at most one child is removed, and the child takes its place.
+    Splice {
+        key: String,
+    },
+    // The node `by`, which has been spliced out, takes the place of `key`, e.g. the successor of
+    // a deleted node.
+    Replace {
+        key: String,
+        by: String,
+    },
+    // `key` goes down to the left and its right child goes up.
+    RotateLeft {
+        key: String,
+    },
+    RotateRight {
+        key: String,
+    },
+    SetColor {
+        key: String,
+        #[serde(with = "color")]
+        color: Color,
+    },
+    // The 4-node of `key` is split, or the 2-nodes of the children of `key` are merged, by
+    // flipping the colors. The colors themselves are recorded as `SetColor`.
+    SplitNode {
+        key: String,
+    },
+    MergeNode {
+        key: String,
+    },
+    // the case of a fixup, numbered as in CLRS
+    Case {
+        fixup: String,
+        case: u32,
+    },
+}
+
+pub fn key(key: &impl Debug) -> String {
+    format!("{:?}", key)
+}
+
+thread_local! {
+    static LOG: RefCell<Option<Vec<Event>>> = const { RefCell::new(None) };
+}
+
+// Runs `f`, and returns the events recorded in the meantime on this thread.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<Event>) {
+    let restore = Restore(LOG.with(|log| log.replace(Some(Vec::new()))));
+    let res = f();
+    let events = LOG.with(|log| log.borrow_mut().take()).unwrap();
+    drop(restore);
+    (res, events)
+}
+// Puts back the log of the outer `capture` if any, even when `f` panics.
+struct Restore(Option<Vec<Event>>);
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let old = self.0.take();
+        LOG.with(|log| log.replace(old));
+    }
+}
+pub fn is_recording() -> bool {
+    LOG.with(|log| log.borrow().is_some())
+}
+pub fn record(event: Event) {
+    LOG.with(|log| {
+        if let Some(log) = log.borrow_mut().as_mut() {
+            log.push(event);
+        }
+    });
+}
+
+// one JSON object per line, e.g. `{"event":"rotate_left","key":"3"}`
+pub fn to_json_lines(events: &[Event]) -> String {
+    events
+        .iter()
+        .map(|event| serde_json::to_string(event).unwrap() + "\n")
+        .collect()
+}
+pub fn from_json_lines(s: &str) -> serde_json::Result<Vec<Event>> {
+    s.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+// `R` or `B` as in the parenthesized form
+mod color {
+    use {
+        paren::Color,
+        serde::{de::Error, Deserialize, Deserializer, Serializer},
+    };
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(color)
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|()| D::Error::custom(format!("invalid color {:?}", s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{capture, from_json_lines, is_recording, record, to_json_lines, Event},
+        paren::Color,
+        std::panic,
+    };
+
+    #[test]
+    fn test_capture() {
+        record(Event::Splice {
+            key: "0".to_owned(),
+        });
+        assert!(!is_recording());
+        let ((), events) = capture(|| {
+            assert!(is_recording());
+            record(Event::Splice {
+                key: "1".to_owned(),
+            });
+        });
+        assert!(!is_recording());
+        assert_eq!(
+            events,
+            vec![Event::Splice {
+                key: "1".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_capture_panic() {
+        let ((), events) = capture(|| {
+            let result = panic::catch_unwind(|| {
+                capture(|| {
+                    record(Event::Splice {
+                        key: "1".to_owned(),
+                    });
+                    panic!("in capture");
+                })
+            });
+            assert!(result.is_err());
+            assert!(is_recording());
+            record(Event::Splice {
+                key: "2".to_owned(),
+            });
+        });
+        assert!(!is_recording());
+        assert_eq!(
+            events,
+            vec![Event::Splice {
+                key: "2".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let events = vec![
+            Event::Begin {
+                op: "delete".to_owned(),
+                key: "3".to_owned(),
+            },
+            Event::RotateLeft {
+                key: "1".to_owned(),
+            },
+            Event::SetColor {
+                key: "2".to_owned(),
+                color: Color::Red,
+            },
+            Event::Case {
+                fixup: "delete_fixup".to_owned(),
+                case: 4,
+            },
+        ];
+        let s = to_json_lines(&events);
+        let expected = [
+            r#"{"event":"begin","op":"delete","key":"3"}"#,
+            r#"{"event":"rotate_left","key":"1"}"#,
+            r#"{"event":"set_color","key":"2","color":"R"}"#,
+            r#"{"event":"case","fixup":"delete_fixup","case":4}"#,
+            "",
+        ]
+        .join("\n");
+        assert_eq!(s, expected);
+        assert_eq!(from_json_lines(&s).unwrap(), events);
+        assert!(from_json_lines(r#"{"event":"set_color","key":"2","color":"G"}"#).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use {
+    super::Event,
+    paren::{parse, Color, Label, Node, ParseError},
+    std::{
+        collections::HashMap,
+        error::Error,
+        fmt::{self, Display, Formatter},
+        mem::replace,
+        str::FromStr,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    Parse(ParseError),
+    // the index of the event that cannot be applied
+    InvalidEvent(usize, Event),
+}
+impl Display for ReplayError {
+    fn fmt(&self, w: &mut Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Parse(e) => write!(w, "{}", e),
+            ReplayError::InvalidEvent(i, event) => {
+                write!(w, "cannot apply the event {}: {:?}", i, event)
+            }
+        }
+    }
+}
+impl Error for ReplayError {}
+impl From<ParseError> for ReplayError {
+    fn from(e: ParseError) -> Self {
+        ReplayError::Parse(e)
+    }
+}
+
+// Applies the events to the tree `initial` in the parenthesized form, and returns the tree after
+// each event in the same form. The keys are parsed as `K` to find the positions of new leaves.
+pub fn replay<K: FromStr + Ord>(
+    initial: &str,
+    events: &[Event],
+) -> Result<Vec<String>, ReplayError> {
+    let mut replay = Replay {
+        root: parse(initial)?,
+        detached: HashMap::new(),
+    };
+    events
+        .iter()
+        .enumerate()
+        .map(|(i, event)| {
+            replay
+                .apply::<K>(event)
+                .ok_or_else(|| ReplayError::InvalidEvent(i, event.clone()))?;
+            let mut s = String::new();
+            write(&replay.root, &mut s);
+            Ok(s)
+        })
+        .collect()
+}
+
+struct Replay {
+    root: Option<Box<Node>>,
+    // the labels of the nodes spliced out or replaced, which may still be recolored or moved
+    detached: HashMap<String, Label>,
+}
+impl Replay {
+    fn apply<K: FromStr + Ord>(&mut self, event: &Event) -> Option<()> {
+        match event {
+            Event::Begin { .. }
+            | Event::SplitNode { .. }
+            | Event::MergeNode { .. }
+            | Event::Case { .. } => (),
+            Event::Attach { label } => {
+                let label = Label(label.split(':').map(str::to_owned).collect());
+                let k = label.key::<K>().ok()?;
+                let mut x = &mut self.root;
+                while let Some(node) = x {
+                    let i = if k <= node.label.key::<K>().ok()? {
+                        0
+                    } else {
+                        1
+                    };
+                    x = &mut node.child[i];
+                }
+                *x = Some(Box::new(Node {
+                    label,
+                    child: [None, None],
+                }));
+            }
+            Event::Splice { key } => {
+                let x = find(&mut self.root, key)?;
+                let node = x.as_mut()?;
+                let i = match &node.child {
+                    [Some(_), Some(_)] => return None,
+                    [Some(_), None] => 0,
+                    _ => 1,
+                };
+                let child = node.child[i].take();
+                let node = replace(x, child).unwrap();
+                self.detached.insert(key.clone(), node.label);
+            }
+            Event::Replace { key, by } => {
+                let label = self.detached.remove(by)?;
+                let node = find(&mut self.root, key)?.as_mut()?;
+                let old = replace(&mut node.label, label);
+                self.detached.insert(key.clone(), old);
+            }
+            Event::RotateLeft { key } => rotate(find(&mut self.root, key)?, 1)?,
+            Event::RotateRight { key } => rotate(find(&mut self.root, key)?, 0)?,
+            Event::SetColor { key, color } => {
+                let label = match find(&mut self.root, key) {
+                    Some(x) => &mut x.as_mut()?.label,
+                    None => self.detached.get_mut(key)?,
+                };
+                set_color(label, *color);
+            }
+        }
+        Some(())
+    }
+}
+
+// the place of the node labeled with `key`
+fn find<'a>(x: &'a mut Option<Box<Node>>, key: &str) -> Option<&'a mut Option<Box<Node>>> {
+    if x.as_ref()?.label.0[0] == key {
+        return Some(x);
+    }
+    let [left, right] = &mut x.as_mut().unwrap().child;
+    match find(left, key) {
+        Some(y) => Some(y),
+        None => find(right, key),
+    }
+}
+// The `i`-th child goes up.
+fn rotate(x: &mut Option<Box<Node>>, i: usize) -> Option<()> {
+    let node = x.as_mut()?;
+    let mut y = node.child[i].take()?;
+    node.child[i] = y.child[1 - i].take();
+    y.child[1 - i] = x.take();
+    *x = Some(y);
+    Some(())
+}
+fn set_color(label: &mut Label, color: Color) {
+    let color = color.to_string();
+    match label.0.get_mut(1) {
+        Some(field) => *field = color,
+        None => label.0.push(color),
+    }
+}
+fn write(x: &Option<Box<Node>>, s: &mut String) {
+    if let Some(node) = x {
+        s.push('(');
+        write(&node.child[0], s);
+        s.push_str(&node.label.0.join(":"));
+        write(&node.child[1], s);
+        s.push(')');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{replay, ReplayError},
+        crate::Event,
+        paren::Color,
+    };
+
+    fn key(key: u32) -> String {
+        key.to_string()
+    }
+
+    #[test]
+    fn test_replay() {
+        let events = vec![
+            Event::Attach {
+                label: "3:R".to_owned(),
+            },
+            Event::RotateLeft { key: key(2) },
+            Event::SetColor {
+                key: key(3),
+                color: Color::Black,
+            },
+            Event::Splice { key: key(1) },
+            Event::Splice { key: key(4) },
+            Event::Replace {
+                key: key(3),
+                by: key(4),
+            },
+            Event::SetColor {
+                key: key(3),
+                color: Color::Red,
+            },
+            Event::RotateRight { key: key(4) },
+        ];
+        let snapshots = replay::<u32>("((1:B)2:B(4:R))", &events[..7]).unwrap();
+        let expected = [
+            "((1:B)2:B((3:R)4:R))",
+            "(((1:B)2:B(3:R))4:R)",
+            "(((1:B)2:B(3:B))4:R)",
+            "((2:B(3:B))4:R)",
+            "(2:B(3:B))",
+            "(2:B(4:R))",
+            // 3 has been detached, but can still be recolored.
+            "(2:B(4:R))",
+        ];
+        assert_eq!(snapshots, expected);
+        // 4 has no left child to rotate.
+        assert_eq!(
+            replay::<u32>("((1:B)2:B(4:R))", &events),
+            Err(ReplayError::InvalidEvent(7, events[7].clone()))
+        );
+    }
+
+    #[test]
+    fn test_replay_errors() {
+        let splice = vec![Event::Splice { key: key(2) }];
+        assert_eq!(
+            replay::<u32>("((1)2(3))", &splice),
+            Err(ReplayError::InvalidEvent(0, splice[0].clone()))
+        );
+        assert_eq!(replay::<u32>("((1)2(3))", &[]), Ok(Vec::new()));
+        assert!(matches!(
+            replay::<u32>("((1)2", &[]),
+            Err(ReplayError::Parse(_))
+        ));
+    }
+}
@@ -1,20 +1,53 @@
+// Records an event with the `trace` feature, and does nothing without it.
+macro_rules! trace {
+    ($($event:tt)*) => {
+        #[cfg(feature = "trace")]
+        {
+            if trace::is_recording() {
+                trace::record(trace::Event::$($event)*);
+            }
+        }
+    };
+}
+
+mod memory;
 mod paren;
 pub mod validate;
 
+use node_pool::NodePool;
 use rand::prelude::*;
 use std::{cmp::Ordering, fmt::Debug, mem};
 
-pub struct Treap<K, V, R>(BoxedNode<K, V>, R);
+pub struct Treap<K, V, R>(BoxedNode<K, V>, R, NodePool<Node<K, V>>);
 impl<K: Ord + Debug, V: Debug, R: Rng> Treap<K, V, R> {
     pub fn new(rng: R) -> Self {
-        Self(BoxedNode::nil(), rng)
+        Self(BoxedNode::nil(), rng, NodePool::disabled())
+    }
+    // Recycles the deleted nodes through `NodePool::with_capacity(capacity)`.
+    pub fn with_capacity(rng: R, capacity: usize) -> Self {
+        Self(BoxedNode::nil(), rng, NodePool::with_capacity(capacity))
     }
     pub fn insert(&mut self, k: K, v: V) {
-        let node = Node::new(k, v, self.1.next_u64());
+        trace!(Begin {
+            op: "insert".to_owned(),
+            key: trace::key(&k),
+        });
+        let node = self.2.alloc(Node::new(k, v, self.1.next_u64()));
         self.0.insert(node);
     }
     pub fn delete(&mut self, k: &K) -> Option<(K, V)> {
-        self.0.delete(k).0.map(|node| (node.key, node.value))
+        trace!(Begin {
+            op: "delete".to_owned(),
+            key: trace::key(k),
+        });
+        let pool = &mut self.2;
+        self.0.delete(k).0.map(|node| {
+            let node = pool.free(node);
+            (node.key, node.value)
+        })
+    }
+    pub fn get(&self, k: &K) -> Option<&V> {
+        self.0.get(k)
     }
     pub fn collect_vec(&self) -> Vec<(K, V)>
     where
@@ -35,15 +68,15 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
     fn is_nil(&self) -> bool {
         self.0.is_none()
     }
-    fn new(node: Node<K, V>) -> Self {
-        Self(Some(Box::new(node)))
-    }
-    fn insert(&mut self, node: Node<K, V>) {
+    fn insert(&mut self, node: Box<Node<K, V>>) {
         if let Some(internal) = self.0.as_mut() {
             internal.child[if node.key <= internal.key { 0 } else { 1 }].insert(node);
             self.fixup();
         } else {
-            *self = Self::new(node);
+            trace!(Attach {
+                label: trace::key(&node.key),
+            });
+            *self = Self(Some(node));
         }
     }
     fn delete(&mut self, k: &K) -> Self {
@@ -56,6 +89,10 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
                         let mut rem = self.child_mut(1).delete_first();
                         rem.assert_isolated();
                         (0..2).for_each(|i| rem.replace_empty_child(i, self.take_child(i)));
+                        trace!(Replace {
+                            key: trace::key(&self.unwrap().key),
+                            by: trace::key(&rem.unwrap().key),
+                        });
                         mem::swap(&mut self.unwrap_mut().pri, &mut rem.unwrap_mut().pri);
                         rem.fixup();
                         mem::replace(self, rem)
@@ -91,6 +128,14 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
             self.rotate(i)
         }
     }
+    fn get(&self, k: &K) -> Option<&V> {
+        let internal = self.0.as_ref()?;
+        match k.cmp(&internal.key) {
+            Ordering::Less => internal.child[0].get(k),
+            Ordering::Greater => internal.child[1].get(k),
+            Ordering::Equal => Some(&internal.value),
+        }
+    }
     fn collect_vec(&self, vec: &mut Vec<(K, V)>)
     where
         K: Clone,
@@ -132,10 +177,22 @@ impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
     }
     fn replace_by_child(&mut self, i: usize) -> Self {
         assert!(self.child(1 - i).is_nil());
+        trace!(Splice {
+            key: trace::key(&self.unwrap().key),
+        });
         let x = self.take_child(i);
         mem::replace(self, x)
     }
     fn rotate(&mut self, i: usize) {
+        if i == 1 {
+            trace!(RotateLeft {
+                key: trace::key(&self.unwrap().key),
+            });
+        } else {
+            trace!(RotateRight {
+                key: trace::key(&self.unwrap().key),
+            });
+        }
         let mut x = self.take();
         let mut y = x.take_child(i);
         let z = y.take_child(1 - i);
@@ -163,8 +220,16 @@ impl<K: Ord + Debug, V: Debug> Node<K, V> {
 
 #[cfg(test)]
 mod tests {
-    use super::{validate, Treap};
+    use super::{validate, Node, Treap};
+    use counting_allocator::{count, CountingAllocator, Counts};
+    use invariant::Property;
+    use memory_usage::MemoryUsage;
+    use paren::{FromParen, Paren};
     use rand::prelude::*;
+    use std::mem::size_of;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
 
     #[test]
     fn test_hand() {
@@ -177,6 +242,110 @@ mod tests {
         test.delete(10);
     }
 
+    #[test]
+    fn test_paren() {
+        let mut treap = Treap::<u32, (), StdRng>::from_paren("(((1:3)2:1(3:4))4:0(5:2))").unwrap();
+        validate::all(&treap);
+        // The successor takes the place and the priority.
+        treap.delete(&2);
+        assert_eq!(treap.to_paren(), "(((1)3)4(5))");
+        validate::all(&treap);
+
+        // The priorities left out are the depths.
+        let treap = Treap::<u32, (), StdRng>::from_paren("((1)2((3)4))").unwrap();
+        validate::all(&treap);
+        let root = treap.0 .0.as_ref().unwrap();
+        let right = root.child[1].0.as_ref().unwrap();
+        assert_eq!((root.pri, right.pri), (0, 1));
+        assert_eq!(right.child[0].0.as_ref().unwrap().pri, 2);
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut treap = Treap::new(StdRng::seed_from_u64(42));
+        (0..8).for_each(|i| treap.insert(i, ()));
+        assert_eq!(treap.check_invariants(), Ok(()));
+        let root = treap.0 .0.as_mut().unwrap();
+        root.pri = u64::MAX;
+        let i = if root.child[0].0.is_some() { 0 } else { 1 };
+        let e = treap.check_invariants().unwrap_err();
+        assert_eq!((e.property, e.path), (Property::HeapPriority, vec![i]));
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        let node = size_of::<Node<u32, ()>>();
+        let mut treap = Treap::new(StdRng::seed_from_u64(42));
+        assert_eq!(treap.heap_size_bytes(), 0);
+        for k in 0..10 {
+            treap.insert(k, ());
+        }
+        assert_eq!(treap.heap_size_bytes(), 10 * node);
+        for k in 0..4 {
+            treap.delete(&k);
+        }
+        assert_eq!(treap.heap_size_bytes(), 6 * node);
+    }
+
+    #[test]
+    fn test_allocations() {
+        // a box for every node, and none for the random number generator
+        let mut treap = Treap::<u32, (), _>::new(StdRng::seed_from_u64(42));
+        let ((), counts) = count(|| treap.insert(0, ()));
+        assert_eq!(
+            counts,
+            Counts {
+                allocs: 1,
+                ..Counts::default()
+            }
+        );
+        let (_, counts) = count(|| treap.delete(&0));
+        assert_eq!(
+            counts,
+            Counts {
+                deallocs: 1,
+                ..Counts::default()
+            }
+        );
+
+        // none in the churn, with a pool
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut treap = Treap::<u32, (), _>::with_capacity(StdRng::seed_from_u64(42), 100);
+        let ((), counts) = count(|| {
+            (0..100).for_each(|k| treap.insert(k, ()));
+            for _ in 0..1000 {
+                if treap.delete(&rng.gen_range(0, 200)).is_some() {
+                    treap.insert(rng.gen_range(0, 200), ());
+                }
+            }
+        });
+        assert_eq!(counts, Counts::default());
+        validate::all(&treap);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_trace_replay() {
+        use paren::Paren;
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut treap = Treap::<u32, (), _>::new(StdRng::seed_from_u64(91));
+        let mut keys = std::collections::BTreeSet::new();
+        for _ in 0..1000 {
+            let key = rng.gen_range(0, 40);
+            let before = treap.to_paren();
+            let ((), events) = if keys.insert(key) {
+                trace::capture(|| treap.insert(key, ()))
+            } else {
+                keys.remove(&key);
+                trace::capture(|| {
+                    treap.delete(&key);
+                })
+            };
+            let snapshots = trace::replay::<u32>(&before, &events).unwrap();
+            assert_eq!(snapshots.last().unwrap(), &treap.to_paren());
+        }
+    }
+
     #[test]
     fn test_rand_small() {
         test_rand(10, 50, 42);
@@ -256,6 +425,9 @@ mod tests {
                     .collect::<Vec<_>>(),
                 &self.vec
             );
+            for x in 0..30 {
+                assert_eq!(self.treap.get(&x).is_some(), self.vec.contains(&x));
+            }
         }
     }
 }
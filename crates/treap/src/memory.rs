@@ -0,0 +1,25 @@
+use {
+    super::{BoxedNode, Node, Treap},
+    memory_usage::MemoryUsage,
+};
+
+// The random number generator is inline, so only the nodes are counted, including the spare ones
+// of the pool.
+impl<K: MemoryUsage, V: MemoryUsage, R> MemoryUsage for Treap<K, V, R> {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes() + self.2.heap_size_bytes()
+    }
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for BoxedNode<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.0.heap_size_bytes()
+    }
+}
+impl<K: MemoryUsage, V: MemoryUsage> MemoryUsage for Node<K, V> {
+    fn heap_size_bytes(&self) -> usize {
+        self.key.heap_size_bytes()
+            + self.value.heap_size_bytes()
+            + self.child[0].heap_size_bytes()
+            + self.child[1].heap_size_bytes()
+    }
+}
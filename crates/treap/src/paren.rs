@@ -1,10 +1,14 @@
-use super::{BoxedNode, Node, Treap};
-use rand::prelude::*;
-use std::fmt::{self, Debug};
+use {
+    super::{BoxedNode, Node, Treap},
+    node_pool::NodePool,
+    paren::{FromParen, Paren, ParseError, Shape, Visualize, Wrapper},
+    rand::prelude::*,
+    std::{
+        fmt::{self, Debug},
+        str::FromStr,
+    },
+};
 
-pub trait Paren {
-    fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result;
-}
 impl<K: Ord + Debug, V: Debug, R: Rng> Paren for Treap<K, V, R> {
     fn paren(&self, w: &mut fmt::Formatter) -> fmt::Result {
         self.0.paren(w)
@@ -24,15 +28,60 @@ impl<K: Ord + Debug, V: Debug> Paren for Node<K, V> {
         write!(w, ")")
     }
 }
-struct ParenWrapper<'a, T>(&'a T);
-impl<'a, T: Paren> Debug for ParenWrapper<'a, T> {
-    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        self.0.paren(w)
+impl<K: Ord + Debug, V: Debug, R: Rng> Visualize for Treap<K, V, R> {
+    fn shapes(&self) -> Vec<Shape> {
+        self.0.shape().into_iter().collect()
     }
 }
+impl<K: Ord + Debug, V: Debug> BoxedNode<K, V> {
+    fn shape(&self) -> Option<Shape> {
+        self.0.as_ref().map(|x| {
+            Shape::new(&x.key)
+                .attr("pri", x.pri)
+                .child(x.child[0].shape())
+                .child(x.child[1].shape())
+        })
+    }
+}
+
+// The priority is the optional second field of a label, e.g. `((1:5)2:3)`, and the depth if left
+// out, which keeps the heap order. The values are left default, and the random number generator
+// is seeded with 42.
+impl<K: Ord + Debug + FromStr, V: Debug + Default, R: Rng + SeedableRng> FromParen
+    for Treap<K, V, R>
+{
+    fn from_paren(s: &str) -> Result<Self, ParseError> {
+        BoxedNode::from_parsed(paren::parse(s)?, 0)
+            .map(|root| Self(root, R::seed_from_u64(42), NodePool::disabled()))
+    }
+}
+impl<K: Ord + Debug + FromStr, V: Debug + Default> BoxedNode<K, V> {
+    fn from_parsed(node: Option<Box<paren::Node>>, depth: u64) -> Result<Self, ParseError> {
+        match node {
+            None => Ok(Self::nil()),
+            Some(node) => {
+                let paren::Node {
+                    label,
+                    child: [left, right],
+                } = *node;
+                let pri = match label.0.len() {
+                    1 => depth,
+                    _ => label.field(1)?,
+                };
+                let mut node = Node::new(label.key()?, V::default(), pri);
+                node.child = [
+                    Self::from_parsed(left, depth + 1)?,
+                    Self::from_parsed(right, depth + 1)?,
+                ];
+                Ok(Self(Some(Box::new(node))))
+            }
+        }
+    }
+}
+
 impl<K: Ord + Debug, V: Debug, R: Rng> Debug for Treap<K, V, R> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("Treap").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("Treap").field(&Wrapper(self)).finish()
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for BoxedNode<K, V> {
@@ -44,12 +93,12 @@ impl<K: Ord + Debug, V: Debug> Debug for BoxedNode<K, V> {
                     .as_ref()
                     .map_or("nil".to_owned(), |x| x.pri.to_string()),
             )
-            .field(&ParenWrapper(self))
+            .field(&Wrapper(self))
             .finish()
     }
 }
 impl<K: Ord + Debug, V: Debug> Debug for Node<K, V> {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        w.debug_tuple("Node").field(&ParenWrapper(self)).finish()
+        w.debug_tuple("Node").field(&Wrapper(self)).finish()
     }
 }
@@ -1,4 +1,5 @@
-use super::{BoxedNode, Node, Treap};
+use super::{BoxedNode, Treap};
+use invariant::{ensure, InvariantViolation, Property};
 use rand::prelude::*;
 use std::fmt::Debug;
 
@@ -6,31 +7,48 @@ pub fn all<T: Validate>(x: &T) {
     x.heap()
 }
 
+// `check_invariants` that panics.
 pub trait Validate {
     fn heap(&self);
 }
 impl<K: Ord + Debug, V: Debug, R: Rng> Validate for Treap<K, V, R> {
     fn heap(&self) {
-        self.0.heap()
+        invariant::assert_holds(self.check_invariants())
     }
 }
-impl<K: Ord + Debug, V: Debug> Validate for BoxedNode<K, V> {
-    fn heap(&self) {
-        self.0.as_ref().iter().for_each(|x| x.heap())
+
+impl<K: Ord + Debug, V: Debug, R: Rng> Treap<K, V, R> {
+    // the BST order and the heap order of the priorities
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        check(&self.0, &mut Vec::new(), &mut None)
     }
 }
-impl<K: Ord + Debug, V: Debug> Validate for Node<K, V> {
-    fn heap(&self) {
-        self.child.iter().for_each(|child| {
-            if let Some(child) = child.0.as_ref() {
-                assert!(
-                    self.pri <= child.pri,
-                    "Broken heap condition: self = {:?}, child = {:?}",
-                    self,
-                    child
-                );
-            }
-            child.heap()
-        });
+// `prev` is the last key in the in-order.
+fn check<'a, K: Ord + Debug, V: Debug>(
+    x: &'a BoxedNode<K, V>,
+    path: &mut Vec<usize>,
+    prev: &mut Option<&'a K>,
+) -> Result<(), InvariantViolation> {
+    let node = match x.0.as_ref() {
+        None => return Ok(()),
+        Some(node) => node,
+    };
+    for (i, child) in node.child.iter().enumerate() {
+        if let Some(child) = child.0.as_ref() {
+            path.push(i);
+            ensure(node.pri <= child.pri, Property::HeapPriority, path)?;
+            path.pop();
+        }
+    }
+    path.push(0);
+    check(&node.child[0], path, prev)?;
+    path.pop();
+    if let Some(prev) = prev {
+        ensure(*prev <= &node.key, Property::BstOrder, path)?;
     }
+    *prev = Some(&node.key);
+    path.push(1);
+    check(&node.child[1], path, prev)?;
+    path.pop();
+    Ok(())
 }
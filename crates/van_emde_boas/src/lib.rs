@@ -1,6 +1,10 @@
-use std::{
-    iter::repeat_with,
-    mem::{size_of, swap},
+use {
+    memory_usage::MemoryUsage,
+    paren::{Shape, Visualize},
+    std::{
+        iter::{repeat_n, repeat_with},
+        mem::{replace, size_of, swap},
+    },
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -83,6 +87,127 @@ impl Veb {
         }
     }
 }
+#[derive(Debug, Clone, PartialEq)]
+pub struct VebMultiset {
+    veb: Veb,
+    count: Vec<usize>,
+    len: usize,
+}
+impl VebMultiset {
+    pub fn new(lg: u32) -> Self {
+        Self {
+            veb: Veb::new(lg),
+            count: vec![0; 1 << lg],
+            len: 0,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn count(&self, x: usize) -> usize {
+        self.count[x]
+    }
+    pub fn contains(&self, x: usize) -> bool {
+        self.count[x] != 0
+    }
+    pub fn min(&self) -> Option<usize> {
+        self.veb.min()
+    }
+    pub fn max(&self) -> Option<usize> {
+        self.veb.max()
+    }
+    // カウントが 0 になったキーは `veb` から消えているので、そのまま飛ばされます。
+    pub fn prev(&self, x: usize) -> Option<usize> {
+        self.veb.prev(x)
+    }
+    pub fn succ(&self, x: usize) -> Option<usize> {
+        self.veb.succ(x)
+    }
+    pub fn insert(&mut self, x: usize) {
+        if self.count[x] == 0 {
+            self.veb.insert(x);
+        }
+        self.count[x] += 1;
+        self.len += 1;
+    }
+    pub fn remove_one(&mut self, x: usize) -> bool {
+        match self.count[x] {
+            0 => false,
+            c => {
+                if c == 1 {
+                    self.veb.delete(x);
+                }
+                self.count[x] -= 1;
+                self.len -= 1;
+                true
+            }
+        }
+    }
+    pub fn remove_all(&mut self, x: usize) -> usize {
+        let c = replace(&mut self.count[x], 0);
+        if c != 0 {
+            self.veb.delete(x);
+            self.len -= c;
+        }
+        c
+    }
+    pub fn collect_vec(&self) -> Vec<usize> {
+        self.veb
+            .collect_vec()
+            .into_iter()
+            .flat_map(|x| repeat_n(x, self.count[x]))
+            .collect()
+    }
+}
+// The children of a recursive node are the summary and then the clusters, with `None` for the
+// empty clusters. The clusters are labeled with their indices.
+impl Visualize for Veb {
+    fn shapes(&self) -> Vec<Shape> {
+        vec![self.shape()]
+    }
+}
+impl Veb {
+    fn shape(&self) -> Shape {
+        match self {
+            Veb::Base(base) => Shape::new(
+                (0..base.len())
+                    .filter(|&x| base.contains(x))
+                    .collect::<Vec<_>>(),
+            ),
+            Veb::Rec(rec) => rec.cluster.iter().enumerate().fold(
+                Shape::new(rec.minmax)
+                    .attr("lg", rec.lg)
+                    .child(Some(rec.summary.shape())),
+                |shape, (i, cluster)| {
+                    shape.child(if cluster.is_empty() {
+                        None
+                    } else {
+                        Some(cluster.shape().attr("cluster", i))
+                    })
+                },
+            ),
+        }
+    }
+}
+
+// All the clusters are allocated by `new`, so this is O(u) whatever the keys are.
+impl MemoryUsage for Veb {
+    fn heap_size_bytes(&self) -> usize {
+        match self {
+            Veb::Base(_) => 0,
+            Veb::Rec(rec) => rec.summary.heap_size_bytes() + rec.cluster.heap_size_bytes(),
+        }
+    }
+}
+impl MemoryUsage for VebMultiset {
+    fn heap_size_bytes(&self) -> usize {
+        self.veb.heap_size_bytes() + self.count.heap_size_bytes()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Base {
     len: usize,
@@ -124,7 +249,7 @@ impl Base {
         if bit == 0 {
             None
         } else {
-            Some(bit.trailing_zeros() as usize)
+            Some(size_of::<u64>() as usize * 8 - bit.leading_zeros() as usize - 1)
         }
     }
     pub fn succ(&self, x: usize) -> Option<usize> {
@@ -135,7 +260,7 @@ impl Base {
             if bit == 0 {
                 None
             } else {
-                Some(size_of::<u64>() as usize * 8 - bit.leading_zeros() as usize - 1)
+                Some(bit.trailing_zeros() as usize)
             }
         }
     }
@@ -323,9 +448,16 @@ fn decompose(x: usize, lower: u32) -> (usize, usize) {
 #[cfg(test)]
 mod test {
     use {
-        super::Veb,
+        super::{Veb, VebMultiset},
+        memory_usage::MemoryUsage,
+        paren::Visualize,
         rand::prelude::*,
-        std::{collections::BTreeSet, time::Instant},
+        std::{
+            collections::{BTreeMap, BTreeSet},
+            iter::repeat_n,
+            mem::size_of,
+            time::Instant,
+        },
         yansi::Paint,
     };
 
@@ -334,6 +466,38 @@ mod test {
         assert_eq!(super::decompose(10, 2), (2, 2));
     }
 
+    #[test]
+    fn test_pretty() {
+        let mut veb = Veb::new(8);
+        for &x in [3, 20, 21, 200].iter() {
+            veb.insert(x);
+        }
+        // The minimum and the maximum are not stored in the clusters, and the summary is `[1]`.
+        let expected = [
+            "Some((3, 200)) lg=8",
+            "|-- [4, 5] cluster=1",
+            "\\-- [1]",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(veb.pretty(), expected);
+    }
+
+    #[test]
+    fn test_heap_size_bytes() {
+        // A base holds 64 keys, and all of them are allocated by `new`.
+        let mut veb = Veb::new(16);
+        let bytes = veb.heap_size_bytes();
+        assert!(bytes >= (1 << 16) / 64 * size_of::<Veb>());
+        for x in (0..1 << 16).step_by(7) {
+            veb.insert(x);
+        }
+        assert_eq!(veb.heap_size_bytes(), bytes);
+
+        let ms = VebMultiset::new(16);
+        assert!(ms.heap_size_bytes() >= bytes + (1 << 16) * size_of::<usize>());
+    }
+
     #[test]
     fn test_rand() {
         let mut rng = StdRng::seed_from_u64(42);
@@ -355,6 +519,31 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_rand_multiset() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for lg in 1..14 {
+            let mut test = MultisetTest::new(lg);
+            let len = 1 << lg;
+            for _ in 0..200 {
+                match rng.gen_range(0, 6) {
+                    0 => test.count(rng.gen_range(0, len)),
+                    1 => test.prev(rng.gen_range(0, len)),
+                    2 => test.succ(rng.gen_range(0, len)),
+                    3 | 4 => test.insert(rng.gen_range(0, len.min(8))),
+                    5 => {
+                        if rng.gen_ratio(1, 4) {
+                            test.remove_all(rng.gen_range(0, len.min(8)))
+                        } else {
+                            test.remove_one(rng.gen_range(0, len.min(8)))
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_speed() {
         let mut rng = StdRng::seed_from_u64(42);
@@ -497,4 +686,83 @@ mod test {
             assert_eq!(&result, &expected);
         }
     }
+
+    struct MultisetTest {
+        veb: VebMultiset,
+        map: BTreeMap<usize, usize>,
+    }
+    impl MultisetTest {
+        fn new(lg: u32) -> Self {
+            let res = Self {
+                veb: VebMultiset::new(lg),
+                map: BTreeMap::new(),
+            };
+            res.postproces();
+            res
+        }
+        fn count(&self, x: usize) {
+            println!("{}: {:?}", Paint::yellow("Count").bold(), x);
+            assert_eq!(self.veb.count(x), self.map.get(&x).copied().unwrap_or(0));
+            self.postproces();
+        }
+        fn prev(&self, x: usize) {
+            println!("{}: {:?}", Paint::yellow("Prev").bold(), x);
+            assert_eq!(
+                self.veb.prev(x),
+                self.map.range(..x).next_back().map(|(&x, _)| x)
+            );
+            self.postproces();
+        }
+        fn succ(&self, x: usize) {
+            println!("{}: {:?}", Paint::yellow("Succ").bold(), x);
+            assert_eq!(
+                self.veb.succ(x),
+                self.map.range(x + 1..).next().map(|(&x, _)| x)
+            );
+            self.postproces();
+        }
+        fn insert(&mut self, x: usize) {
+            println!("{}: {:?}", Paint::green("Insert").bold(), x);
+            self.veb.insert(x);
+            *self.map.entry(x).or_insert(0) += 1;
+            self.postproces();
+        }
+        fn remove_one(&mut self, x: usize) {
+            println!("{}: {:?}", Paint::cyan("Remove one").bold(), x);
+            let result = self.veb.remove_one(x);
+            let expected = match self.map.get_mut(&x) {
+                Some(c) => {
+                    *c -= 1;
+                    if *c == 0 {
+                        self.map.remove(&x);
+                    }
+                    true
+                }
+                None => false,
+            };
+            assert_eq!(result, expected);
+            self.postproces();
+        }
+        fn remove_all(&mut self, x: usize) {
+            println!("{}: {:?}", Paint::cyan("Remove all").bold(), x);
+            let result = self.veb.remove_all(x);
+            let expected = self.map.remove(&x).unwrap_or(0);
+            assert_eq!(result, expected);
+            self.postproces();
+        }
+        fn postproces(&self) {
+            println!("map = {:?}", &self.map);
+            let result = self.veb.collect_vec();
+            let expected = self
+                .map
+                .iter()
+                .flat_map(|(&x, &c)| repeat_n(x, c))
+                .collect::<Vec<_>>();
+            assert_eq!(&result, &expected);
+            assert_eq!(self.veb.len(), expected.len());
+            assert_eq!(self.veb.is_empty(), expected.is_empty());
+            assert_eq!(self.veb.min(), expected.first().copied());
+            assert_eq!(self.veb.max(), expected.last().copied());
+        }
+    }
 }